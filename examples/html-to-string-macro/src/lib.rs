@@ -3,7 +3,7 @@ use std::collections::HashSet;
 use proc_macro::TokenStream;
 use quote::{quote, quote_spanned, ToTokens};
 use rstml::{
-    node::{Node, NodeAttribute, NodeName},
+    node::{Namespace, Node, NodeAttribute, NodeName},
     visitor::{visit_attributes, visit_nodes, Visitor},
     Parser, ParserConfig,
 };
@@ -102,10 +102,11 @@ where
 
         self.output.static_format.push('>');
 
-        // Ignore childs of special Empty elements
-        if self
-            .empty_elements
-            .contains(element.open_tag.name.to_string().as_str())
+        // Ignore childs of special Empty elements, they exist only in html.
+        if element.namespace == Namespace::Html
+            && self
+                .empty_elements
+                .contains(element.open_tag.name.to_string().as_str())
         {
             self.output
                 .static_format
@@ -249,7 +250,7 @@ fn generate_tags_docs(elements: &[NodeName]) -> Vec<proc_macro2::TokenStream> {
         .collect();
 
     elements
-        .into_iter()
+        .iter()
         .map(|e| {
             if elements_as_type.contains(&*e.to_string()) {
                 let element = quote_spanned!(e.span() => enum);
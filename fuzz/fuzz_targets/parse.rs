@@ -0,0 +1,25 @@
+#![no_main]
+
+use std::str::FromStr;
+
+use libfuzzer_sys::fuzz_target;
+use proc_macro2::TokenStream;
+use rstml::{Parser, ParserConfig};
+
+// Parser should never panic, any input that is not valid rsx should be
+// reported as error.
+fuzz_target!(|data: &str| {
+    let Ok(tokens) = TokenStream::from_str(data) else {
+        return;
+    };
+
+    let _ = rstml::parse2(tokens.clone());
+
+    let config = ParserConfig::new()
+        .recover_block(true)
+        .always_self_closed_elements(["br", "img"].into())
+        .raw_text_elements(["script", "style"].into())
+        .element_close_wildcard(|_, close_tag| close_tag.name.is_wildcard());
+    let _ = Parser::new(config.clone()).parse_recoverable(tokens.clone());
+    let _ = Parser::new(config.flat_tree()).parse_recoverable(tokens);
+});
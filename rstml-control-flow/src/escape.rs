@@ -299,7 +299,7 @@ impl<T: ToTokens + Parse> ParseRecoverable for EscapeCode<T> {
 
 impl<T> CustomNode for EscapeCode<T>
 where
-    T: Parse + ToTokens,
+    T: Parse + ToTokens + 'static,
 {
     fn peek_element(input: syn::parse::ParseStream) -> bool {
         if input.parse::<T>().is_err() {
@@ -635,7 +635,7 @@ mod test_typed {
         };
 
         assert_eq!(expr.condition, parse_quote!(just && an || expression));
-        let node = expr.then_branch.body.iter().next().unwrap();
+        let node = expr.then_branch.body.first().unwrap();
         let Node::Custom(actual) = node else { panic!() };
         let EscapedExpr::If(expr) = &actual.expression else {
             panic!()
@@ -661,7 +661,7 @@ mod test_typed {
         };
 
         assert_eq!(expr.condition, parse_quote!(just && an || expression));
-        let node = expr.then_branch.body.iter().next().unwrap();
+        let node = expr.then_branch.body.first().unwrap();
         let Node::Custom(actual) = node else { panic!() };
         let EscapedExpr::For(expr) = &actual.expression else {
             panic!()
@@ -714,18 +714,13 @@ mod test_universal {
     fn parse_universal(input: TokenStream) -> ParsingResult<Vec<Node>> {
         use rstml::Parser;
 
-        let actual =
-            Parser::new(ParserConfig::new().custom_node::<EscapeCode>()).parse_recoverable(input);
-
-        return actual;
+        Parser::new(ParserConfig::new().custom_node::<EscapeCode>()).parse_recoverable(input)
     }
     #[cfg(feature = "extendable")]
     fn parse_universal(input: TokenStream) -> ParsingResult<Vec<Node>> {
         use crate::ExtendableCustomNode;
 
-        let result =
-            ExtendableCustomNode::parse2_with_config::<(EscapeCode,)>(ParserConfig::new(), input);
-        return result;
+        ExtendableCustomNode::parse2_with_config::<(EscapeCode,)>(ParserConfig::new(), input)
     }
 
     // For extendable custom node it is safe to use only after parsing.
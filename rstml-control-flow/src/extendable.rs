@@ -9,6 +9,7 @@
 //! steps:
 //!
 //! 1. type Node
+//!
 //! Type alias that change it's meaning depending on feature flag `extendable`.
 //! Example:
 //! ```no_compile
@@ -186,8 +187,6 @@ impl ExtendableCustomNode {
         tokens: proc_macro2::TokenStream,
     ) -> ParsingResult<Vec<Node<ExtendableCustomNode>>> {
         init_extendable_node::<E>();
-        let result =
-            Parser::new(config.custom_node::<ExtendableCustomNode>()).parse_recoverable(tokens);
-        result
+        Parser::new(config.custom_node::<ExtendableCustomNode>()).parse_recoverable(tokens)
     }
 }
@@ -1,4 +1,4 @@
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use proc_macro2::TokenStream;
 use quote::quote;
 
@@ -43,12 +43,77 @@ fn criterion_benchmark(c: &mut Criterion) {
                 .into_iter()
                 .collect(),
             );
-            rstml::Parser::new(config).parse_simple(tokens.clone())
+            rstml::Parser::new(config).parse_simple(RUST_SITE_SIMPLE.with(|t| t.clone()))
         })
     });
 }
 
-criterion_group!(benches, criterion_benchmark);
+/// Benchmarks of generated templates with different shapes, each in three
+/// sizes, so that parsing time can be compared to the number of elements.
+fn generated_benchmark(c: &mut Criterion) {
+    let sizes = [("small", 10), ("medium", 100), ("huge", 1000)];
+    let shapes = [
+        ("flat", flat_template as fn(usize) -> String),
+        ("deep", deep_template),
+        ("attributes", attributes_template),
+        ("values", values_template),
+    ];
+    for (shape, template) in shapes {
+        let mut group = c.benchmark_group(format!("rstml::parse2({shape})"));
+        for (size, n) in sizes {
+            let tokens: TokenStream = template(n).parse().unwrap();
+            group.throughput(Throughput::Elements(n as u64));
+            group.bench_with_input(BenchmarkId::from_parameter(size), &tokens, |b, tokens| {
+                b.iter(|| rstml::parse2(tokens.clone()))
+            });
+        }
+        group.finish();
+    }
+}
+
+/// `n` sibling elements with text and a few attributes.
+fn flat_template(n: usize) -> String {
+    (0..n)
+        .map(|i| format!(r#"<div class="item" id={i}><span>"text {i}"</span></div>"#))
+        .collect()
+}
+
+/// `n` nested elements.
+fn deep_template(n: usize) -> String {
+    let mut template = "<div>".repeat(n);
+    template.push_str(r#""leaf""#);
+    template.push_str(&"</div>".repeat(n));
+    template
+}
+
+/// Single element with `n` attributes of different kinds.
+fn attributes_template(n: usize) -> String {
+    let attributes: String = (0..n)
+        .map(|i| match i % 5 {
+            0 => format!(r#" key-{i}="value""#),
+            1 => format!(" num{i}={i}"),
+            2 => format!(" expr{i}=some::value({i})"),
+            3 => format!(" block{i}={{ {i} > 1 }}"),
+            _ => format!(" flag{i}"),
+        })
+        .collect();
+    format!("<input{attributes} />")
+}
+
+/// Single element with `n` attribute values that contain `>`, which is not the
+/// end of tag.
+fn values_template(n: usize) -> String {
+    let attributes: String = (0..n)
+        .map(|i| match i % 3 {
+            0 => format!(" new{i}=Vec::<u8>::with_capacity({i})"),
+            1 => format!(" on{i}=|e: Event<Click>| e.id() + {i}"),
+            _ => format!(" map{i}=HashMap::<u8, Vec<u8>>::with_capacity({i})"),
+        })
+        .collect();
+    format!("<input{attributes} />")
+}
+
+criterion_group!(benches, criterion_benchmark, generated_benchmark);
 criterion_main!(benches);
 
 thread_local! {
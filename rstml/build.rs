@@ -1,6 +1,7 @@
 use std::{env, process::Command, str::from_utf8};
 
 fn main() {
+    println!("cargo:rustc-check-cfg=cfg(rstml_signal_nightly)");
     if is_rustc_nightly() {
         println!("cargo:rustc-cfg=rstml_signal_nightly");
     }
@@ -0,0 +1,406 @@
+//! Analysis of rust code embedded in templates, available with `analysis`
+//! feature.
+//!
+//! [`referenced_idents`] returns identifiers used in blocks and attribute
+//! values, e.g. to generate `move` closure captures or component props:
+//!
+//! ```rust
+//! use rstml::{analysis::referenced_idents, tree};
+//!
+//! let nodes = tree! {
+//!     <ul class={class}>
+//!         {items.iter().map(|item| format!("{}", item.name)).collect::<Vec<_>>()}
+//!     </ul>
+//! };
+//! let idents: Vec<_> = referenced_idents(&nodes)
+//!     .iter()
+//!     .map(ToString::to_string)
+//!     .collect();
+//! assert_eq!(idents, ["class", "items"]);
+//! ```
+//!
+//! [`Skeleton`] splits tree into static html and dynamic holes, for renderers
+//! that clone templates and then patch only the holes:
+//!
+//! ```rust
+//! use rstml::{
+//!     analysis::{HoleKind, Skeleton},
+//!     tree,
+//! };
+//!
+//! let nodes = tree! { <p class="x" id={id}>"Hi, " {name} "!"</p> };
+//! let skeleton = Skeleton::new(&nodes);
+//! assert_eq!(skeleton.html, r#"<p class="x">Hi, <!>!</p>"#);
+//! assert_eq!(skeleton.holes[0].path, [0]);
+//! assert!(matches!(skeleton.holes[0].kind, HoleKind::Attribute(_)));
+//! assert_eq!(skeleton.holes[1].path, [0, 1]);
+//! assert!(matches!(skeleton.holes[1].kind, HoleKind::Node(_)));
+//! ```
+
+use std::collections::HashSet;
+
+use proc_macro2::Ident;
+use syn::{
+    punctuated::Punctuated,
+    visit::{self, Visit},
+    Arm, Block, Expr, ExprClosure, ExprForLoop, ExprPath, Item, Local, Macro, Pat, PatIdent, Token,
+};
+
+use crate::{
+    node::{
+        CustomNode, KVAttributeValue, KeyedAttribute, KeyedAttributeValue, Namespace, Node,
+        NodeAttribute, NodeBlock, NodeElement, RAW_TEXT_ELEMENTS, VOID_ELEMENTS,
+    },
+    Infallible,
+};
+
+/// Identifiers referenced from valid blocks and attribute expressions of
+/// `nodes`, without duplicates. Attributes of element (including special
+/// ones) are visited before its children.
+///
+/// Only single-segment paths, like `x` in `x.len()`, are collected, and
+/// names bound inside of the code (by `let`, closure arguments, loops and
+/// match arms) are skipped. Names starting with uppercase letter, like `None`
+/// or `MAX`, are treated as types, variants or constants and skipped too.
+/// Arguments of macros are visited if they are comma-separated expressions.
+/// Invalid blocks, function bindings and custom nodes are ignored.
+pub fn referenced_idents<C: CustomNode>(nodes: &[Node<C>]) -> Vec<Ident> {
+    let mut collector = Collector::default();
+    for (_, node) in Node::preorder(nodes) {
+        match node {
+            Node::Block(NodeBlock::ValidBlock(block)) => collector.visit_block(block),
+            Node::Element(element) => {
+                for attribute in element.attributes() {
+                    match attribute {
+                        NodeAttribute::Block(NodeBlock::ValidBlock(block)) => {
+                            collector.visit_block(block)
+                        }
+                        NodeAttribute::Block(NodeBlock::Invalid(_)) => {}
+                        NodeAttribute::Attribute(attribute) => collector.attribute(attribute),
+                    }
+                }
+                for attribute in &element.special_attributes {
+                    collector.attribute(attribute)
+                }
+            }
+            _ => {}
+        }
+    }
+    collector.found
+}
+
+#[derive(Default)]
+struct Collector {
+    /// Names bound in the enclosing scopes of visited code.
+    scopes: Vec<Vec<String>>,
+    seen: HashSet<String>,
+    found: Vec<Ident>,
+}
+
+impl Collector {
+    fn attribute(&mut self, attribute: &KeyedAttribute) {
+        if let KeyedAttributeValue::Value(value) = &attribute.possible_value {
+            if let KVAttributeValue::Expr(expr) = &value.value {
+                self.visit_expr(expr)
+            }
+        }
+    }
+
+    fn reference(&mut self, ident: &Ident) {
+        let name = ident.to_string();
+        let is_bound = self.scopes.iter().any(|scope| scope.contains(&name));
+        if is_bound || name.starts_with(char::is_uppercase) || self.seen.contains(&name) {
+            return;
+        }
+        self.seen.insert(name);
+        self.found.push(ident.clone());
+    }
+
+    /// Add names bound by `pat` to the current scope.
+    fn bind(&mut self, pat: &Pat) {
+        struct Bindings<'a>(&'a mut Vec<String>);
+        impl<'ast> Visit<'ast> for Bindings<'_> {
+            fn visit_pat_ident(&mut self, pat: &'ast PatIdent) {
+                self.0.push(pat.ident.to_string());
+                visit::visit_pat_ident(self, pat)
+            }
+        }
+        if self.scopes.is_empty() {
+            self.scopes.push(vec![]);
+        }
+        let scope = self.scopes.last_mut().expect("scope was added");
+        Bindings(scope).visit_pat(pat)
+    }
+
+    fn scoped(&mut self, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(vec![]);
+        f(self);
+        self.scopes.pop();
+    }
+}
+
+impl<'ast> Visit<'ast> for Collector {
+    fn visit_block(&mut self, block: &'ast Block) {
+        self.scoped(|this| visit::visit_block(this, block))
+    }
+
+    fn visit_local(&mut self, local: &'ast Local) {
+        if let Some(init) = &local.init {
+            self.visit_local_init(init)
+        }
+        self.bind(&local.pat)
+    }
+
+    fn visit_expr_closure(&mut self, closure: &'ast ExprClosure) {
+        self.scoped(|this| {
+            closure.inputs.iter().for_each(|input| this.bind(input));
+            this.visit_expr(&closure.body)
+        })
+    }
+
+    fn visit_expr_for_loop(&mut self, for_loop: &'ast ExprForLoop) {
+        self.visit_expr(&for_loop.expr);
+        self.scoped(|this| {
+            this.bind(&for_loop.pat);
+            this.visit_block(&for_loop.body)
+        })
+    }
+
+    fn visit_arm(&mut self, arm: &'ast Arm) {
+        self.scoped(|this| {
+            this.bind(&arm.pat);
+            if let Some((_, guard)) = &arm.guard {
+                this.visit_expr(guard)
+            }
+            this.visit_expr(&arm.body)
+        })
+    }
+
+    fn visit_expr_let(&mut self, expr: &'ast syn::ExprLet) {
+        self.visit_expr(&expr.expr);
+        self.bind(&expr.pat)
+    }
+
+    fn visit_expr_path(&mut self, expr: &'ast ExprPath) {
+        let path = &expr.path;
+        if expr.qself.is_none() && path.leading_colon.is_none() && path.segments.len() == 1 {
+            self.reference(&path.segments[0].ident)
+        }
+    }
+
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        let args = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated);
+        for arg in args.iter().flatten() {
+            self.visit_expr(arg)
+        }
+    }
+
+    // Nested items can't capture variables.
+    fn visit_item(&mut self, _item: &'ast Item) {}
+}
+
+/// Static html of tree, with a list of dynamic holes, see
+/// [module documentation](self).
+#[derive(Debug)]
+pub struct Skeleton<'a, C = Infallible> {
+    /// Html of static parts, every dynamic node is replaced with `<!>`
+    /// comment, and dynamic attributes are omitted.
+    pub html: String,
+    /// Dynamic parts, in order of appearance.
+    pub holes: Vec<Hole<'a, C>>,
+}
+
+/// Dynamic part of [`Skeleton`].
+#[derive(Debug)]
+pub struct Hole<'a, C = Infallible> {
+    /// Indices of DOM nodes from the top-level node down to the hole, as in
+    /// `childNodes` of parsed [`Skeleton::html`]. Adjacent text nodes are
+    /// counted as one, and fragments are flattened into their parent.
+    ///
+    /// For attributes it is the path of their element, for nodes it is the
+    /// path of `<!>` marker.
+    pub path: Vec<usize>,
+    pub kind: HoleKind<'a, C>,
+}
+
+#[derive(Debug)]
+pub enum HoleKind<'a, C = Infallible> {
+    /// Block, component, custom or incomplete node.
+    Node(&'a Node<C>),
+    /// Attribute which value is not a literal, or special attribute.
+    Attribute(&'a KeyedAttribute),
+    /// Attribute block, e.g. `{..rest}`.
+    Spread(&'a NodeBlock),
+}
+
+impl<'a, C: CustomNode> Skeleton<'a, C> {
+    /// Split `nodes` into static html and holes.
+    ///
+    /// Static parts are the ones that [`Node::is_static`] accepts. Text nodes
+    /// are escaped, except of children of `script` and `style`; raw text is
+    /// written as is.
+    pub fn new(nodes: &'a [Node<C>]) -> Self {
+        let mut skeleton = Self {
+            html: String::new(),
+            holes: vec![],
+        };
+        skeleton.children(nodes, &[], &mut DomIndex::default(), false);
+        skeleton
+    }
+
+    fn children(
+        &mut self,
+        nodes: &'a [Node<C>],
+        parent: &[usize],
+        index: &mut DomIndex,
+        raw: bool,
+    ) {
+        let path = |index: usize| {
+            let mut path = parent.to_vec();
+            path.push(index);
+            path
+        };
+        for node in nodes {
+            match node {
+                Node::Fragment(fragment) => self.children(&fragment.children, parent, index, raw),
+                Node::Text(text) => {
+                    let text = text.value_string();
+                    if !text.is_empty() {
+                        index.text();
+                        if raw {
+                            self.html.push_str(&text)
+                        } else {
+                            escape(&mut self.html, &text, false)
+                        }
+                    }
+                }
+                Node::RawText(text) => {
+                    let text = text.to_string_best();
+                    if !text.is_empty() {
+                        index.text();
+                        self.html.push_str(&text);
+                    }
+                }
+                Node::Comment(comment) => {
+                    index.node();
+                    self.html.push_str("<!--");
+                    self.html.push_str(&comment.value.value());
+                    self.html.push_str("-->");
+                }
+                Node::Doctype(doctype) => {
+                    index.node();
+                    self.html.push_str("<!DOCTYPE ");
+                    self.html.push_str(&doctype.value.to_string_best());
+                    self.html.push('>');
+                }
+                Node::Element(element) if !element.is_component() => {
+                    let path = path(index.node());
+                    self.element(element, path);
+                }
+                Node::Element(_) | Node::Block(_) | Node::Custom(_) | Node::Incomplete(_) => {
+                    let path = path(index.node());
+                    self.html.push_str("<!>");
+                    self.holes.push(Hole {
+                        path,
+                        kind: HoleKind::Node(node),
+                    });
+                }
+            }
+        }
+    }
+
+    fn element(&mut self, element: &'a NodeElement<C>, path: Vec<usize>) {
+        let name = element.name().to_string();
+        self.html.push('<');
+        self.html.push_str(&name);
+        for attribute in element.attributes() {
+            match attribute {
+                NodeAttribute::Attribute(attribute) if attribute.is_static() => {
+                    self.static_attribute(attribute)
+                }
+                NodeAttribute::Attribute(attribute) => self.holes.push(Hole {
+                    path: path.clone(),
+                    kind: HoleKind::Attribute(attribute),
+                }),
+                NodeAttribute::Block(block) => self.holes.push(Hole {
+                    path: path.clone(),
+                    kind: HoleKind::Spread(block),
+                }),
+            }
+        }
+        for attribute in &element.special_attributes {
+            self.holes.push(Hole {
+                path: path.clone(),
+                kind: HoleKind::Attribute(attribute),
+            })
+        }
+
+        let foreign = element.namespace != Namespace::Html;
+        if element.children.is_empty() && (foreign || VOID_ELEMENTS.contains(&name.as_str())) {
+            self.html.push_str(if foreign { "/>" } else { ">" });
+            return;
+        }
+        self.html.push('>');
+        let raw = RAW_TEXT_ELEMENTS.contains(&name.as_str());
+        self.children(&element.children, &path, &mut DomIndex::default(), raw);
+        self.html.push_str("</");
+        self.html.push_str(&name);
+        self.html.push('>');
+    }
+
+    fn static_attribute(&mut self, attribute: &KeyedAttribute) {
+        let value = match attribute.value() {
+            None => None,
+            Some(syn::Expr::Lit(lit)) => match &lit.lit {
+                syn::Lit::Bool(value) if !value.value => return,
+                syn::Lit::Bool(_) => None,
+                syn::Lit::Str(value) => Some(value.value()),
+                syn::Lit::Char(value) => Some(value.value().to_string()),
+                lit => Some(quote::ToTokens::to_token_stream(lit).to_string()),
+            },
+            Some(_) => unreachable!("attribute is static"),
+        };
+        self.html.push(' ');
+        self.html.push_str(&attribute.key.to_string());
+        if let Some(value) = value {
+            self.html.push_str("=\"");
+            escape(&mut self.html, &value, true);
+            self.html.push('"');
+        }
+    }
+}
+
+/// Index of the next DOM node among its siblings.
+#[derive(Default)]
+struct DomIndex {
+    next: usize,
+    after_text: bool,
+}
+
+impl DomIndex {
+    fn node(&mut self) -> usize {
+        self.after_text = false;
+        self.next += 1;
+        self.next - 1
+    }
+
+    /// Text is merged with the previous text node.
+    fn text(&mut self) {
+        if !self.after_text {
+            self.node();
+            self.after_text = true;
+        }
+    }
+}
+
+fn escape(out: &mut String, text: &str, attribute: bool) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' if attribute => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+}
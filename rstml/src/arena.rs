@@ -0,0 +1,198 @@
+//! Arena storage of parsed nodes, see [`NodeArena`].
+//!
+//! Nodes are kept in a flat vector, and refer to their parent and children by
+//! [`NodeRef`] handles, so tree can be traversed up and down, and nodes can be
+//! moved or detached without rebuilding nested vectors.
+//!
+//! ```rust
+//! use rstml::{arena::NodeArena, tree};
+//!
+//! let mut arena = NodeArena::from_nodes(tree! { <ul><li>"a"</li></ul> });
+//! let ul = arena.roots()[0];
+//! let li = arena.children(ul)[0];
+//! assert_eq!(arena.parent(li), Some(ul));
+//!
+//! arena.append_child(ul, rstml::node!(<li>"b"</li>));
+//! let nodes = arena.into_nodes();
+//! assert_eq!(nodes[0].children().unwrap().len(), 2);
+//! ```
+
+use crate::{
+    node::{CustomNode, Node},
+    Infallible,
+};
+
+/// Handle of node in [`NodeArena`].
+///
+/// Handles stay valid until arena is dropped, including handles of detached
+/// nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeRef(usize);
+
+impl NodeRef {
+    /// Index of node in arena, in order of insertion.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+#[derive(Debug)]
+struct Entry<C> {
+    node: Node<C>,
+    parent: Option<NodeRef>,
+    children: Vec<NodeRef>,
+}
+
+/// Flat storage of node tree, see [module documentation](self).
+///
+/// Nodes are stored without their children, [`Node::children`] of stored node
+/// is always empty, use [`NodeArena::children`] instead.
+#[derive(Debug)]
+pub struct NodeArena<C = Infallible> {
+    entries: Vec<Entry<C>>,
+    roots: Vec<NodeRef>,
+}
+
+impl<C: CustomNode> Default for NodeArena<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: CustomNode> NodeArena<C> {
+    /// Empty arena.
+    pub fn new() -> Self {
+        Self {
+            entries: vec![],
+            roots: vec![],
+        }
+    }
+
+    /// Arena with `nodes` as roots, nodes are added in preorder.
+    pub fn from_nodes(nodes: Vec<Node<C>>) -> Self {
+        let mut arena = Self::new();
+        for node in nodes {
+            arena.push_root(node);
+        }
+        arena
+    }
+
+    /// Number of nodes, including detached ones.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Top-level nodes.
+    pub fn roots(&self) -> &[NodeRef] {
+        &self.roots
+    }
+
+    /// Node without its children.
+    pub fn get(&self, node: NodeRef) -> &Node<C> {
+        &self.entries[node.0].node
+    }
+
+    /// Mutable node without its children.
+    pub fn get_mut(&mut self, node: NodeRef) -> &mut Node<C> {
+        &mut self.entries[node.0].node
+    }
+
+    /// Children of node, empty for nodes that can't have children.
+    pub fn children(&self, node: NodeRef) -> &[NodeRef] {
+        &self.entries[node.0].children
+    }
+
+    /// Parent of node, `None` for top-level and detached nodes.
+    pub fn parent(&self, node: NodeRef) -> Option<NodeRef> {
+        self.entries[node.0].parent
+    }
+
+    /// Iterate over all nodes, in order of insertion.
+    pub fn iter(&self) -> impl Iterator<Item = (NodeRef, &Node<C>)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (NodeRef(i), &entry.node))
+    }
+
+    /// Add `node` and its children as the last top-level node.
+    pub fn push_root(&mut self, node: Node<C>) -> NodeRef {
+        let node = self.insert(node, None);
+        self.roots.push(node);
+        node
+    }
+
+    /// Add `node` and its children as the last child of `parent`.
+    pub fn append_child(&mut self, parent: NodeRef, node: Node<C>) -> NodeRef {
+        let node = self.insert(node, Some(parent));
+        self.entries[parent.0].children.push(node);
+        node
+    }
+
+    /// Remove `node` from children of its parent, or from top-level nodes.
+    /// Node and its children are kept in arena, but are not returned by
+    /// [`NodeArena::into_nodes`].
+    pub fn detach(&mut self, node: NodeRef) {
+        let siblings = match self.entries[node.0].parent.take() {
+            Some(parent) => &mut self.entries[parent.0].children,
+            None => &mut self.roots,
+        };
+        siblings.retain(|sibling| *sibling != node);
+    }
+
+    /// Build node tree back from arena.
+    pub fn into_nodes(self) -> Vec<Node<C>> {
+        let mut children: Vec<_> = self.entries.iter().map(|e| e.children.clone()).collect();
+        let mut nodes: Vec<_> = self.entries.into_iter().map(|e| Some(e.node)).collect();
+        // Children are always added after their parent, so they are complete
+        // before the parent is.
+        for i in (0..nodes.len()).rev() {
+            let node_children: Vec<_> = std::mem::take(&mut children[i])
+                .into_iter()
+                .filter_map(|child| nodes[child.0].take())
+                .collect();
+            if let Some(slot) = nodes[i].as_mut().and_then(Node::children_mut) {
+                *slot = node_children;
+            }
+        }
+        self.roots
+            .into_iter()
+            .filter_map(|root| nodes[root.0].take())
+            .collect()
+    }
+
+    /// Add `node` with all its children, without explicit recursion.
+    fn insert(&mut self, node: Node<C>, parent: Option<NodeRef>) -> NodeRef {
+        let (root, children) = self.insert_entry(node, parent);
+        let mut stack = vec![(root, children.into_iter())];
+        while let Some((parent, children)) = stack.last_mut() {
+            let parent = *parent;
+            let Some(child) = children.next() else {
+                stack.pop();
+                continue;
+            };
+            let (child, grandchildren) = self.insert_entry(child, Some(parent));
+            self.entries[parent.0].children.push(child);
+            stack.push((child, grandchildren.into_iter()));
+        }
+        root
+    }
+
+    fn insert_entry(
+        &mut self,
+        mut node: Node<C>,
+        parent: Option<NodeRef>,
+    ) -> (NodeRef, Vec<Node<C>>) {
+        let children = node.children_mut().map(std::mem::take).unwrap_or_default();
+        self.entries.push(Entry {
+            node,
+            parent,
+            children: vec![],
+        });
+        (NodeRef(self.entries.len() - 1), children)
+    }
+}
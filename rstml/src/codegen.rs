@@ -0,0 +1,154 @@
+//! Generation of builder-pattern code from parsed nodes, available with
+//! `codegen` feature.
+//!
+//! [`Backend`] describes the shape of generated code, every method has a
+//! default, so small DSLs override only the parts that differ. The default
+//! shape of element is a chain of builder calls:
+//! `Element::new("div").attr("class", "x").child("text")`. Comments, doctypes
+//! and custom nodes produce no code.
+//!
+//! ```rust
+//! use quote::ToTokens;
+//! use rstml::{
+//!     codegen::{nodes_to_exprs, DefaultBackend},
+//!     tree,
+//! };
+//!
+//! let nodes = tree! { <div class="x" hidden>"Hi, " {name}</div> };
+//! let exprs = nodes_to_exprs(&DefaultBackend, &nodes);
+//! assert_eq!(
+//!     exprs[0].to_token_stream().to_string(),
+//!     quote::quote! {
+//!         Element::new("div")
+//!             .attr("class", "x")
+//!             .attr("hidden", true)
+//!             .child("Hi, ")
+//!             .child({ name })
+//!     }
+//!     .to_string()
+//! );
+//! ```
+
+use proc_macro2::{Ident, Span};
+use quote::format_ident;
+use syn::{parse_quote, Expr, LitStr};
+
+use crate::{
+    node::{CustomNode, KeyedAttributeValue, Node, NodeAttribute, NodeBlock, NodeElement},
+    Infallible,
+};
+
+/// Shape of generated code, see [module documentation](self).
+pub trait Backend<C: CustomNode = Infallible> {
+    /// Expression that creates builder of `element`, `Element::new("name")` by
+    /// default.
+    fn element_new(&self, element: &NodeElement<C>) -> Expr {
+        let name = element.name().to_string();
+        parse_quote!(Element::new(#name))
+    }
+
+    /// Method that sets single attribute, `attr` by default.
+    fn attribute_method(&self) -> Ident {
+        format_ident!("attr")
+    }
+
+    /// Method that sets attributes from block, like `{..rest}`, `attrs` by
+    /// default.
+    fn attributes_method(&self) -> Ident {
+        format_ident!("attrs")
+    }
+
+    /// Method that appends child, `child` by default.
+    fn child_method(&self) -> Ident {
+        format_ident!("child")
+    }
+
+    /// Expression of text node, string literal by default.
+    fn text(&self, text: &LitStr) -> Expr {
+        parse_quote!(#text)
+    }
+
+    /// Expression of block node, block itself by default.
+    fn block(&self, block: &NodeBlock) -> Expr {
+        parse_quote!(#block)
+    }
+
+    /// Expression of fragment, array of its children by default.
+    fn fragment(&self, children: Vec<Expr>) -> Expr {
+        parse_quote!([#(#children),*])
+    }
+
+    /// Expression of element with already generated `children`.
+    ///
+    /// By default it is [`element_new`](Self::element_new) followed by
+    /// attribute and child calls, including
+    /// [special attributes](NodeElement::special_attributes) in their source
+    /// order. Attributes without value are set to `true`, and function
+    /// bindings (`bind:value(x)`) are skipped.
+    fn element(&self, element: &NodeElement<C>, children: Vec<Expr>) -> Expr {
+        let mut expr = self.element_new(element);
+        let attr = self.attribute_method();
+        let attrs = self.attributes_method();
+        for attribute in element.all_attributes() {
+            expr = match &*attribute {
+                NodeAttribute::Block(block) => parse_quote!(#expr.#attrs(#block)),
+                NodeAttribute::Attribute(attribute) => {
+                    let key = attribute.key.to_string();
+                    match &attribute.possible_value {
+                        KeyedAttributeValue::Value(value) => {
+                            let value = &value.value;
+                            parse_quote!(#expr.#attr(#key, #value))
+                        }
+                        KeyedAttributeValue::Flag => parse_quote!(#expr.#attr(#key, true)),
+                        KeyedAttributeValue::Binding(_) => expr,
+                    }
+                }
+            };
+        }
+        let child = self.child_method();
+        for child_expr in children {
+            expr = parse_quote!(#expr.#child(#child_expr));
+        }
+        expr
+    }
+}
+
+/// [`Backend`] with all default methods.
+pub struct DefaultBackend;
+
+impl<C: CustomNode> Backend<C> for DefaultBackend {}
+
+/// Generate expression of `node`, or `None` if node produces no code.
+pub fn node_to_expr<C: CustomNode, B: Backend<C> + ?Sized>(
+    backend: &B,
+    node: &Node<C>,
+) -> Option<Expr> {
+    match node {
+        Node::Element(element) => {
+            let children = nodes_to_exprs(backend, &element.children);
+            Some(backend.element(element, children))
+        }
+        Node::Fragment(fragment) => {
+            let children = nodes_to_exprs(backend, &fragment.children);
+            Some(backend.fragment(children))
+        }
+        Node::Text(text) => Some(backend.text(&text.value)),
+        Node::RawText(text) => {
+            let text = LitStr::new(&text.to_string_best(), Span::call_site());
+            Some(backend.text(&text))
+        }
+        Node::Block(block) => Some(backend.block(block)),
+        Node::Comment(_) | Node::Doctype(_) | Node::Custom(_) | Node::Incomplete(_) => None,
+    }
+}
+
+/// Generate expressions of `nodes`, skipping ones that produce no code.
+pub fn nodes_to_exprs<C: CustomNode, B: Backend<C> + ?Sized>(
+    backend: &B,
+    nodes: &[Node<C>],
+) -> Vec<Expr> {
+    nodes
+        .iter()
+        .filter_map(|node| node_to_expr(backend, node))
+        .collect()
+}
@@ -1,18 +1,207 @@
-use std::{collections::HashSet, fmt::Debug, marker::PhantomData, rc::Rc};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Debug},
+    marker::PhantomData,
+    rc::Rc,
+};
 
-use proc_macro2::TokenStream;
+use proc_macro2::{Delimiter, TokenStream};
 use syn::{parse::ParseStream, Result};
 
 #[cfg(feature = "rawtext-stable-hack")]
 use crate::rawtext_stable_hack::MacroPattern;
 use crate::{
     atoms::{CloseTag, OpenTag},
-    node::{CustomNode, NodeType},
-    Infallible,
+    node::{CustomNode, ElementKind, Node, NodeElement, NodeName, NodeType},
+    Infallible, ParserError,
 };
 
 pub type TransformBlockFn = dyn Fn(ParseStream) -> Result<Option<TokenStream>>;
 pub type ElementWildcardFn = dyn Fn(&OpenTag, &CloseTag) -> bool;
+pub type ErrorMessageFn = dyn Fn(&ParserError) -> Option<String>;
+pub type ElementNameCheckFn = dyn Fn(&NodeName) -> std::result::Result<(), String>;
+pub type AttributeNameCheckFn = dyn Fn(&NodeName, &NodeName) -> std::result::Result<(), String>;
+pub type ElementClosedFn<C> = dyn Fn(&mut NodeElement<C>) -> Result<()>;
+pub type ChildrenParserFn<C> = dyn Fn(&OpenTag, ParseStream) -> Result<Vec<Node<C>>>;
+
+/// Rules used to tell components from html elements, see
+/// [`ParserConfig::component_rules`].
+///
+/// Element is a component if any of enabled rules matches its name. Names
+/// computed from blocks (`<{name}>`) are always html elements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ComponentRules {
+    /// Name starts with uppercase letter, e.g. `<Button>`. Enabled by default.
+    pub uppercase_first_letter: bool,
+    /// Name is a path of several segments, e.g. `<ui::button>`. Enabled by
+    /// default.
+    pub path_qualified: bool,
+    /// Name contains `-`, e.g. `<my-button>`.
+    pub contains_dash: bool,
+}
+
+impl Default for ComponentRules {
+    fn default() -> Self {
+        Self {
+            uppercase_first_letter: true,
+            path_qualified: true,
+            contains_dash: false,
+        }
+    }
+}
+
+impl ComponentRules {
+    /// Get kind of element with given `name`.
+    pub fn kind(&self, name: &NodeName) -> ElementKind {
+        let is_component = match name {
+            NodeName::Block(_) => false,
+            NodeName::Path(e) if self.path_qualified && e.path.segments.len() > 1 => true,
+            NodeName::Punctuated(p)
+                if self.contains_dash
+                    && p.pairs()
+                        .any(|p| p.punct().is_some_and(|p| p.as_char() == '-')) =>
+            {
+                true
+            }
+            _ => {
+                self.uppercase_first_letter
+                    && name.to_string().starts_with(|c: char| c.is_uppercase())
+            }
+        };
+        if is_component {
+            ElementKind::Component
+        } else {
+            ElementKind::HtmlElement
+        }
+    }
+}
+
+/// How expressions in attribute values are bounded, see
+/// [`ParserConfig::attribute_value_parsing`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AttributeValueParsing {
+    /// Stop at the first top-level `>`, so `<div cond=a > b>` has `cond=a`.
+    /// Comparisons should be wrapped in braces or parentheses, e.g.
+    /// `cond={a > b}`.
+    #[default]
+    Conservative,
+    /// Take the longest [`syn::Expr`] that ends right before `>` or `/>`,
+    /// so `<div cond=a > b>` has `cond=a > b`.
+    ///
+    /// Tokens are only scanned up to the first `<` after `>`, so
+    /// `<div attr=x>"text"</div>` still has `attr=x`.
+    Greedy,
+}
+
+/// Literals that can be used as text nodes, see
+/// [`ParserConfig::text_literals`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextLiterals {
+    /// String literals are text nodes, other literals, like `123` or `'c'`,
+    /// are parsed as a part of [`RawText`](crate::node::RawText).
+    #[default]
+    Any,
+    /// Only string literals, other literals are reported as
+    /// [`ParserError::UnquotedLiteral`](crate::ParserError::UnquotedLiteral).
+    Strings,
+}
+
+/// Characters allowed in names of elements and attributes, see
+/// [`ParserConfig::name_policy`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NamePolicy {
+    /// Any name that can be tokenized.
+    #[default]
+    Permissive,
+    /// Names from HTML spec: element names start with ASCII letter and
+    /// contain ASCII alphanumerics, `-`, `_`, `.` and `:`, names of custom
+    /// elements (with `-`) can also contain non-ASCII characters. Attribute
+    /// names can contain anything except of controls, spaces, quotes, `>`,
+    /// `/`, `=` and noncharacters.
+    Html,
+    /// `Name` production of XML 1.0 spec, for both elements and attributes.
+    Xml,
+}
+
+impl NamePolicy {
+    /// Returns true if `c` is allowed in name, `first` is true for the first
+    /// character of name, `custom` is true for names with `-`.
+    pub(crate) fn allows(self, c: char, first: bool, attribute: bool, custom: bool) -> bool {
+        match self {
+            Self::Permissive => true,
+            Self::Html if attribute => {
+                !c.is_control()
+                    && !c.is_whitespace()
+                    && !matches!(c, '"' | '\'' | '>' | '/' | '=')
+                    && !is_noncharacter(c)
+            }
+            Self::Html if first => c.is_ascii_alphabetic(),
+            Self::Html => {
+                c.is_ascii_alphanumeric()
+                    || matches!(c, '-' | '_' | '.' | ':')
+                    || (custom && is_pcen_char(c))
+            }
+            Self::Xml if first => is_xml_name_start_char(c),
+            Self::Xml => {
+                is_xml_name_start_char(c)
+                    || c.is_ascii_digit()
+                    || matches!(c, '-' | '.' | '\u{B7}' | '\u{300}'..='\u{36F}' | '\u{203F}'..='\u{2040}')
+            }
+        }
+    }
+}
+
+impl fmt::Display for NamePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Permissive => "permissive",
+            Self::Html => "HTML",
+            Self::Xml => "XML",
+        })
+    }
+}
+
+fn is_noncharacter(c: char) -> bool {
+    matches!(c, '\u{FDD0}'..='\u{FDEF}') || (c as u32 & 0xFFFE) == 0xFFFE
+}
+
+/// Non-ASCII part of `PCENChar` production of HTML spec.
+fn is_pcen_char(c: char) -> bool {
+    matches!(c,
+        '\u{B7}'
+        | '\u{C0}'..='\u{D6}'
+        | '\u{D8}'..='\u{F6}'
+        | '\u{F8}'..='\u{37D}'
+        | '\u{37F}'..='\u{1FFF}'
+        | '\u{200C}'..='\u{200D}'
+        | '\u{203F}'..='\u{2040}'
+        | '\u{2070}'..='\u{218F}'
+        | '\u{2C00}'..='\u{2FEF}'
+        | '\u{3001}'..='\u{D7FF}'
+        | '\u{F900}'..='\u{FDCF}'
+        | '\u{FDF0}'..='\u{FFFD}'
+        | '\u{10000}'..='\u{EFFFF}'
+    )
+}
+
+/// `NameStartChar` production of XML spec.
+fn is_xml_name_start_char(c: char) -> bool {
+    matches!(c,
+        ':' | 'A'..='Z' | '_' | 'a'..='z'
+        | '\u{C0}'..='\u{D6}'
+        | '\u{D8}'..='\u{F6}'
+        | '\u{F8}'..='\u{2FF}'
+        | '\u{370}'..='\u{37D}'
+        | '\u{37F}'..='\u{1FFF}'
+        | '\u{200C}'..='\u{200D}'
+        | '\u{2070}'..='\u{218F}'
+        | '\u{2C00}'..='\u{2FEF}'
+        | '\u{3001}'..='\u{D7FF}'
+        | '\u{F900}'..='\u{FDCF}'
+        | '\u{FDF0}'..='\u{FFFD}'
+        | '\u{10000}'..='\u{EFFFF}'
+    )
+}
 
 /// Configures the `Parser` behavior
 pub struct ParserConfig<C = Infallible> {
@@ -21,28 +210,126 @@ pub struct ParserConfig<C = Infallible> {
     pub(crate) type_of_top_level_nodes: Option<NodeType>,
     pub(crate) transform_block: Option<Rc<TransformBlockFn>>,
     pub(crate) recover_block: bool,
+    pub(crate) raw_blocks: bool,
+    pub(crate) recover_incomplete: bool,
+    pub(crate) block_delimiters: Vec<Delimiter>,
+    pub(crate) merge_adjacent_text: bool,
+    pub(crate) interpolate_text: bool,
+    pub(crate) tag_sugar: bool,
+    pub(crate) reject_flag_attributes: bool,
+    pub(crate) attribute_value_parsing: AttributeValueParsing,
+    pub(crate) unquoted_values: bool,
+    pub(crate) name_policy: NamePolicy,
+    pub(crate) text_literals: TextLiterals,
+    pub(crate) preserve_tokens: bool,
+    pub(crate) directive_prefix: Option<&'static str>,
+    pub(crate) case_insensitive_close_tags: bool,
+    pub(crate) component_rules: ComponentRules,
     pub(crate) always_self_closed_elements: HashSet<&'static str>,
+    pub(crate) strict_self_closing: bool,
+    pub(crate) reject_nested_fragments: bool,
+    pub(crate) reject_empty_fragments: bool,
+    pub(crate) reject_empty_blocks: bool,
     pub(crate) raw_text_elements: HashSet<&'static str>,
+    pub(crate) special_attributes: HashSet<&'static str>,
     pub(crate) element_close_wildcard: Option<Rc<ElementWildcardFn>>,
+    pub(crate) error_message: Option<Rc<ErrorMessageFn>>,
+    pub(crate) element_name_check: Option<Rc<ElementNameCheckFn>>,
+    pub(crate) attribute_name_check: Option<Rc<AttributeNameCheckFn>>,
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) on_element_closed: Option<Rc<ElementClosedFn<C>>>,
+    pub(crate) children_parsers: HashMap<&'static str, Rc<ChildrenParserFn<C>>>,
     #[cfg(feature = "rawtext-stable-hack")]
     pub(crate) macro_pattern: MacroPattern,
     custom_node: PhantomData<C>,
 }
 
+/// Options of [`ParserConfig`] that affect the shape of parsed tree, see
+/// [`ParserConfig::summary`].
+///
+/// Unlike config, summary has no callbacks, so it can be compared, and sent to
+/// passes that receive nodes from other crates. It contains every option of
+/// config, except of callbacks.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ConfigSummary {
+    pub flat_tree: bool,
+    pub number_of_top_level_nodes: Option<usize>,
+    pub type_of_top_level_nodes: Option<NodeType>,
+    pub recover_block: bool,
+    pub raw_blocks: bool,
+    pub recover_incomplete: bool,
+    pub block_delimiters: Vec<Delimiter>,
+    pub merge_adjacent_text: bool,
+    pub interpolate_text: bool,
+    pub tag_sugar: bool,
+    pub reject_flag_attributes: bool,
+    pub attribute_value_parsing: AttributeValueParsing,
+    pub unquoted_values: bool,
+    pub name_policy: NamePolicy,
+    pub text_literals: TextLiterals,
+    pub preserve_tokens: bool,
+    pub directive_prefix: Option<&'static str>,
+    pub case_insensitive_close_tags: bool,
+    pub component_rules: ComponentRules,
+    pub always_self_closed_elements: HashSet<&'static str>,
+    pub strict_self_closing: bool,
+    pub reject_nested_fragments: bool,
+    pub reject_empty_fragments: bool,
+    pub reject_empty_blocks: bool,
+    pub raw_text_elements: HashSet<&'static str>,
+    pub special_attributes: HashSet<&'static str>,
+    pub max_depth: Option<usize>,
+    /// Names of elements with custom children parsers, see
+    /// [`ParserConfig::children_parser`].
+    pub children_parsers: HashSet<&'static str>,
+    /// Whether [`ParserConfig::macro_call_pattern`] is set.
+    #[cfg(feature = "rawtext-stable-hack")]
+    pub macro_pattern: bool,
+    /// Name of [`CustomNode`] type, as in [`std::any::type_name`].
+    pub custom_node: &'static str,
+}
+
 impl<C> Clone for ParserConfig<C> {
     fn clone(&self) -> Self {
         Self {
-            flat_tree: self.flat_tree.clone(),
+            flat_tree: self.flat_tree,
             number_of_top_level_nodes: self.number_of_top_level_nodes,
             type_of_top_level_nodes: self.type_of_top_level_nodes.clone(),
             transform_block: self.transform_block.clone(),
-            recover_block: self.recover_block.clone(),
+            recover_block: self.recover_block,
+            raw_blocks: self.raw_blocks,
+            recover_incomplete: self.recover_incomplete,
+            block_delimiters: self.block_delimiters.clone(),
+            merge_adjacent_text: self.merge_adjacent_text,
+            interpolate_text: self.interpolate_text,
+            tag_sugar: self.tag_sugar,
+            reject_flag_attributes: self.reject_flag_attributes,
+            attribute_value_parsing: self.attribute_value_parsing,
+            unquoted_values: self.unquoted_values,
+            name_policy: self.name_policy,
+            text_literals: self.text_literals,
+            preserve_tokens: self.preserve_tokens,
+            directive_prefix: self.directive_prefix,
+            case_insensitive_close_tags: self.case_insensitive_close_tags,
+            component_rules: self.component_rules,
             always_self_closed_elements: self.always_self_closed_elements.clone(),
+            strict_self_closing: self.strict_self_closing,
+            reject_nested_fragments: self.reject_nested_fragments,
+            reject_empty_fragments: self.reject_empty_fragments,
+            reject_empty_blocks: self.reject_empty_blocks,
             raw_text_elements: self.raw_text_elements.clone(),
+            special_attributes: self.special_attributes.clone(),
             element_close_wildcard: self.element_close_wildcard.clone(),
+            error_message: self.error_message.clone(),
+            element_name_check: self.element_name_check.clone(),
+            attribute_name_check: self.attribute_name_check.clone(),
+            max_depth: self.max_depth,
+            on_element_closed: self.on_element_closed.clone(),
+            children_parsers: self.children_parsers.clone(),
             #[cfg(feature = "rawtext-stable-hack")]
             macro_pattern: self.macro_pattern.clone(),
-            custom_node: self.custom_node.clone(),
+            custom_node: self.custom_node,
         }
     }
 }
@@ -55,9 +342,35 @@ impl Default for ParserConfig {
             type_of_top_level_nodes: Default::default(),
             transform_block: Default::default(),
             recover_block: Default::default(),
+            raw_blocks: Default::default(),
+            recover_incomplete: Default::default(),
+            block_delimiters: Default::default(),
+            merge_adjacent_text: Default::default(),
+            interpolate_text: Default::default(),
+            tag_sugar: Default::default(),
+            reject_flag_attributes: Default::default(),
+            attribute_value_parsing: Default::default(),
+            unquoted_values: Default::default(),
+            name_policy: Default::default(),
+            text_literals: Default::default(),
+            preserve_tokens: Default::default(),
+            directive_prefix: Default::default(),
+            case_insensitive_close_tags: Default::default(),
+            component_rules: Default::default(),
             always_self_closed_elements: Default::default(),
+            strict_self_closing: false,
+            reject_nested_fragments: false,
+            reject_empty_fragments: false,
+            reject_empty_blocks: false,
             raw_text_elements: Default::default(),
+            special_attributes: Default::default(),
             element_close_wildcard: Default::default(),
+            error_message: Default::default(),
+            element_name_check: Default::default(),
+            attribute_name_check: Default::default(),
+            max_depth: Default::default(),
+            on_element_closed: Default::default(),
+            children_parsers: Default::default(),
             #[cfg(feature = "rawtext-stable-hack")]
             macro_pattern: Default::default(),
             custom_node: Default::default(),
@@ -73,14 +386,46 @@ impl<C> Debug for ParserConfig<C> {
             .field("number_of_top_level_nodes", &self.number_of_top_level_nodes)
             .field("type_of_top_level_nodes", &self.type_of_top_level_nodes)
             .field("recover_block", &self.recover_block)
+            .field("raw_blocks", &self.raw_blocks)
+            .field("recover_incomplete", &self.recover_incomplete)
+            .field("block_delimiters", &self.block_delimiters)
+            .field("merge_adjacent_text", &self.merge_adjacent_text)
+            .field("interpolate_text", &self.interpolate_text)
+            .field("tag_sugar", &self.tag_sugar)
+            .field("reject_flag_attributes", &self.reject_flag_attributes)
+            .field("attribute_value_parsing", &self.attribute_value_parsing)
+            .field("unquoted_values", &self.unquoted_values)
+            .field("name_policy", &self.name_policy)
+            .field("text_literals", &self.text_literals)
+            .field("preserve_tokens", &self.preserve_tokens)
+            .field("directive_prefix", &self.directive_prefix)
+            .field(
+                "case_insensitive_close_tags",
+                &self.case_insensitive_close_tags,
+            )
+            .field("component_rules", &self.component_rules)
             .field(
                 "always_self_closed_elements",
                 &self.always_self_closed_elements,
             )
+            .field("strict_self_closing", &self.strict_self_closing)
+            .field("reject_nested_fragments", &self.reject_nested_fragments)
+            .field("reject_empty_fragments", &self.reject_empty_fragments)
+            .field("reject_empty_blocks", &self.reject_empty_blocks)
             .field("raw_text_elements", &self.raw_text_elements)
+            .field("special_attributes", &self.special_attributes)
             .field(
                 "element_close_wildcard",
                 &self.element_close_wildcard.is_some(),
+            )
+            .field("error_message", &self.error_message.is_some())
+            .field("element_name_check", &self.element_name_check.is_some())
+            .field("attribute_name_check", &self.attribute_name_check.is_some())
+            .field("max_depth", &self.max_depth)
+            .field("on_element_closed", &self.on_element_closed.is_some())
+            .field(
+                "children_parsers",
+                &self.children_parsers.keys().collect::<HashSet<_>>(),
             );
         #[cfg(feature = "rawtext-stable-hack")]
         s.field("macro_pattern", &self.macro_pattern);
@@ -117,6 +462,13 @@ impl<C> ParserConfig<C> {
     /// Try to parse invalid `syn::Block`.
     /// If set tot true, `NodeBlock` can return `Invalid` variant.
     ///
+    /// Invalid blocks in children of element are kept as `Invalid` even
+    /// without this option, so the rest of the tree is not lost in
+    /// [`Parser::parse_recoverable`](crate::Parser::parse_recoverable). This
+    /// option also keeps invalid blocks in attribute values, and parses
+    /// them as `Invalid` when [`NodeBlock`](crate::node::NodeBlock) is parsed
+    /// directly.
+    ///
     /// If `NodeBlock` is failed to parse as `syn::Block`
     /// it still usefull to emit it as expression.
     /// It will enhance IDE compatibility, and provide completion in cases of
@@ -128,6 +480,291 @@ impl<C> ParserConfig<C> {
         self
     }
 
+    /// Keep content of every block as raw tokens, without parsing it as
+    /// `syn::Block`, so the consumer can interpret it, e.g. `{ x, y }`.
+    ///
+    /// Blocks are returned as [`NodeBlock::Invalid`], and in attribute values
+    /// as [`KVAttributeValue::InvalidBraced`]. [`transform_block`] and
+    /// [`interpolate_text`] are not applied to them.
+    ///
+    /// ```rust
+    /// use quote::quote;
+    /// use rstml::{
+    ///     node::{Node, NodeBlock},
+    ///     Parser, ParserConfig,
+    /// };
+    ///
+    /// let config = ParserConfig::new().raw_blocks(true);
+    /// let nodes = Parser::new(config)
+    ///     .parse_simple(quote! { <p>{ x, y }</p> })
+    ///     .unwrap();
+    /// let Node::Block(NodeBlock::Invalid(block)) = &nodes[0].children().unwrap()[0] else {
+    ///     panic!("expected raw block")
+    /// };
+    /// assert_eq!(block.body.to_string(), "x , y");
+    /// ```
+    ///
+    /// [`NodeBlock::Invalid`]: crate::node::NodeBlock::Invalid
+    /// [`KVAttributeValue::InvalidBraced`]: crate::node::KVAttributeValue::InvalidBraced
+    /// [`transform_block`]: Self::transform_block
+    /// [`interpolate_text`]: Self::interpolate_text
+    pub fn raw_blocks(mut self, raw_blocks: bool) -> Self {
+        self.raw_blocks = raw_blocks;
+        self
+    }
+
+    /// Keep unfinished parts of template in the tree, instead of dropping
+    /// them.
+    ///
+    /// Useful for IDE completion, when user is in the middle of typing, e.g.
+    /// `<div cla`. Errors are still reported, but open tag without `>` is
+    /// returned as [`Node::Incomplete`], and attribute with `=` but without
+    /// value as [`KVAttributeValue::Incomplete`]. Elements without close tag
+    /// are kept in both modes, with `close_tag` set to `None`.
+    ///
+    /// [`Node::Incomplete`]: crate::node::Node::Incomplete
+    /// [`KVAttributeValue::Incomplete`]: crate::node::KVAttributeValue::Incomplete
+    pub fn recover_incomplete(mut self, recover_incomplete: bool) -> Self {
+        self.recover_incomplete = recover_incomplete;
+        self
+    }
+
+    /// Set delimiters of groups that are parsed as blocks, both in child
+    /// position and in attribute position. By default only `{}` is used.
+    ///
+    /// Useful when braces have other meaning in template, e.g. with
+    /// `[Delimiter::Bracket]` interpolation is written as `<p>[value]</p>`.
+    /// Parsed blocks are still stored as [`syn::Block`], so they are
+    /// converted back to tokens with braces.
+    ///
+    /// Blocks in element name position (`<{name}/>`) always use braces.
+    pub fn block_delimiters(mut self, delimiters: impl IntoIterator<Item = Delimiter>) -> Self {
+        self.block_delimiters = delimiters.into_iter().collect();
+        self
+    }
+
+    /// Merge consecutive text nodes into one, e.g. `"Hello " "world"` is
+    /// parsed as single `"Hello world"` text node.
+    ///
+    /// Span of merged node is joined from spans of all literals, if joining
+    /// spans is supported, otherwise span of the first literal is used.
+    pub fn merge_adjacent_text(mut self, merge_adjacent_text: bool) -> Self {
+        self.merge_adjacent_text = merge_adjacent_text;
+        self
+    }
+
+    /// Split text children of elements and fragments by `{expr}` segments,
+    /// similar to `format_args!`, e.g. `<p>"Hello {name}!"</p>` is parsed as
+    /// `<p>"Hello "{name}"!"</p>`. Braces can be escaped as `{{` and `}}`.
+    ///
+    /// Content of segments is parsed as [`syn::Expr`], and stored as
+    /// [`NodeBlock`](crate::node::NodeBlock) spanned to the segment inside
+    /// literal when possible. Invalid segments are reported as diagnostics,
+    /// and text node is kept as is. Top-level text nodes are not split.
+    pub fn interpolate_text(mut self, interpolate_text: bool) -> Self {
+        self.interpolate_text = interpolate_text;
+        self
+    }
+
+    /// Allow `#id` and `.class` shorthands after element name, e.g.
+    /// `<div#app.container.dark>` is parsed as element `div` with
+    /// `id="app"` and `class="container dark"` attributes.
+    ///
+    /// Shorthands produce [`KeyedAttribute`]s, placed before other
+    /// attributes, with [`KeyedAttribute::sugar`] set. Explicit `id` and
+    /// `class` attributes are kept as is. Since `.` is allowed in names, if
+    /// this option is disabled, `<div.app>` is parsed as element `div.app`.
+    ///
+    /// [`KeyedAttribute`]: crate::node::KeyedAttribute
+    /// [`KeyedAttribute::sugar`]: crate::node::KeyedAttribute::sugar
+    pub fn tag_sugar(mut self, tag_sugar: bool) -> Self {
+        self.tag_sugar = tag_sugar;
+        self
+    }
+
+    /// Report attributes without value (`<input disabled>`) as errors, for
+    /// DSLs that require explicit values.
+    ///
+    /// Attributes are still parsed as [`KeyedAttributeValue::Flag`].
+    ///
+    /// [`KeyedAttributeValue::Flag`]: crate::node::KeyedAttributeValue::Flag
+    pub fn reject_flag_attributes(mut self, reject_flag_attributes: bool) -> Self {
+        self.reject_flag_attributes = reject_flag_attributes;
+        self
+    }
+
+    /// Choose how far expressions in attribute values are parsed, see
+    /// [`AttributeValueParsing`]. Conservative by default.
+    ///
+    /// In conservative mode, value that looks like a comparison swallowed by
+    /// the end of tag (`<div cond=a > b>`) is reported as a warning.
+    pub fn attribute_value_parsing(mut self, parsing: AttributeValueParsing) -> Self {
+        self.attribute_value_parsing = parsing;
+        self
+    }
+
+    /// Parse CSS-like attribute values, that are numeric literals joined with
+    /// `-` or `/` (`grid-area=1/2`, `margin=10px-auto`), numeric literals with
+    /// units (`10px`, `1.5em`, `50%`), as a single string literal with the
+    /// same text, instead of expression. Literal is spanned to the whole
+    /// value, if spans can be joined. Numbers with suffixes of rust types
+    /// (`10u8`) are kept as numbers.
+    ///
+    /// Values that continue with other operators (`x=1/2 + y`) are still
+    /// parsed as expressions, and so are values in braces (`x={1 / 2}`), and
+    /// values with whitespace between parts (`x=1 / 2`). Whitespace is known
+    /// only if tokens have locations, e.g. on nightly, or in `proc_macro2`
+    /// fallback mode with `span-locations`. Otherwise parts are considered
+    /// adjacent, and `x=1 / 2` is parsed as `"1/2"` too.
+    ///
+    /// ```rust
+    /// use quote::quote;
+    /// use rstml::{
+    ///     node::{Node, NodeAttribute},
+    ///     Parser, ParserConfig,
+    /// };
+    ///
+    /// let parser = Parser::new(ParserConfig::new().unquoted_values(true));
+    /// let nodes = parser
+    ///     .parse_simple(quote! { <div grid-area=1/2 margin=10px-auto width=50%/> })
+    ///     .unwrap();
+    /// let Node::Element(element) = &nodes[0] else {
+    ///     unreachable!()
+    /// };
+    /// let values: Vec<_> = element
+    ///     .attributes()
+    ///     .iter()
+    ///     .filter_map(|a| match a {
+    ///         NodeAttribute::Attribute(a) => a.value_literal_string(),
+    ///         _ => None,
+    ///     })
+    ///     .collect();
+    /// assert_eq!(values, ["1/2", "10px-auto", "50%"]);
+    /// ```
+    pub fn unquoted_values(mut self, unquoted_values: bool) -> Self {
+        self.unquoted_values = unquoted_values;
+        self
+    }
+
+    /// Set characters allowed in names of elements and attributes, see
+    /// [`NamePolicy`]. Permissive by default.
+    ///
+    /// The first character that is not allowed is reported as
+    /// [`ParserError::InvalidNameCharacter`], spanned to the name part that
+    /// contains it, and element is parsed as usual. Block names, like
+    /// `<{name}>`, are not checked.
+    ///
+    /// ```rust
+    /// use quote::quote;
+    /// use rstml::{NamePolicy, Parser, ParserConfig};
+    ///
+    /// let parser = Parser::new(ParserConfig::new().name_policy(NamePolicy::Html));
+    /// parser
+    ///     .parse_simple(quote! { <my-élément data-é="x"/> })
+    ///     .unwrap();
+    /// let error = parser.parse_simple(quote! { <élément/> }).unwrap_err();
+    /// assert_eq!(
+    ///     error.to_string().lines().next().unwrap(),
+    ///     "character `é` in name `élément` is not allowed by HTML rules"
+    /// );
+    /// ```
+    ///
+    /// [`ParserError::InvalidNameCharacter`]: crate::ParserError::InvalidNameCharacter
+    pub fn name_policy(mut self, policy: NamePolicy) -> Self {
+        self.name_policy = policy;
+        self
+    }
+
+    /// Choose which literals can be used as text nodes, see [`TextLiterals`].
+    /// Any literal is accepted by default.
+    ///
+    /// ```rust
+    /// use quote::quote;
+    /// use rstml::{Parser, ParserConfig, TextLiterals};
+    ///
+    /// let parser = Parser::new(ParserConfig::new().text_literals(TextLiterals::Strings));
+    /// parser.parse_simple(quote! { <p>"Total: "</p> }).unwrap();
+    /// let error = parser
+    ///     .parse_simple(quote! { <p>"Total: " 42</p> })
+    ///     .unwrap_err();
+    /// assert_eq!(
+    ///     error.to_string().lines().next().unwrap(),
+    ///     "literal `42` should be quoted to be used as text, e.g. `\"42\"`"
+    /// );
+    /// ```
+    pub fn text_literals(mut self, literals: TextLiterals) -> Self {
+        self.text_literals = literals;
+        self
+    }
+
+    /// Keep a copy of tokens every element and fragment was parsed from,
+    /// available with [`Node::tokens`]. Disabled by default, since tokens of
+    /// nested elements are copied several times.
+    ///
+    /// [`Node::tokens`]: crate::node::Node::tokens
+    pub fn preserve_tokens(mut self, preserve_tokens: bool) -> Self {
+        self.preserve_tokens = preserve_tokens;
+        self
+    }
+
+    /// Recognize comments, which value starts with `prefix`, as directives for
+    /// the following element, see [`Directive`]. Directives are collected
+    /// into [`NodeElement::directives`], comments are kept in the tree.
+    ///
+    /// ```rust
+    /// use quote::quote;
+    /// use rstml::{
+    ///     node::{Directive, Node},
+    ///     Parser, ParserConfig,
+    /// };
+    ///
+    /// let config = ParserConfig::new().directive_prefix("rstml-");
+    /// let nodes = Parser::new(config)
+    ///     .parse_simple(quote! { <!-- "rstml-raw" --> <pre>a < b</pre> })
+    ///     .unwrap();
+    /// let Node::Element(pre) = &nodes[1] else {
+    ///     unreachable!()
+    /// };
+    /// assert_eq!(pre.directives, [Directive::Raw]);
+    /// assert!(matches!(pre.children[0], Node::RawText(_)));
+    /// ```
+    ///
+    /// [`Directive`]: crate::node::Directive
+    pub fn directive_prefix(mut self, prefix: &'static str) -> Self {
+        self.directive_prefix = Some(prefix);
+        self
+    }
+
+    /// Match close tags of html elements ignoring ASCII case, e.g.
+    /// `<DIV></div>` is accepted. Components are still matched exactly, see
+    /// [`NodeName::eq_ignore_ascii_case`].
+    ///
+    /// Names are kept as written, [`NodeElement::name`] returns name of open
+    /// tag.
+    ///
+    /// [`NodeName::eq_ignore_ascii_case`]: crate::node::NodeName::eq_ignore_ascii_case
+    /// [`NodeElement::name`]: crate::node::NodeElement::name
+    pub fn case_insensitive_close_tags(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive_close_tags = case_insensitive;
+        self
+    }
+
+    /// Set rules used to determine [`ElementKind`] of elements, by default
+    /// names starting with uppercase letter and paths are components.
+    ///
+    /// ```rust
+    /// use rstml::{ComponentRules, ParserConfig};
+    ///
+    /// let config = ParserConfig::new().component_rules(ComponentRules {
+    ///     contains_dash: true,
+    ///     ..Default::default()
+    /// });
+    /// ```
+    pub fn component_rules(mut self, rules: ComponentRules) -> Self {
+        self.component_rules = rules;
+        self
+    }
+
     /// Set array of nodes that is known to be self closed,
     /// it also known as void element.
     /// Void elements has no child and must not have closing tag.
@@ -136,6 +773,10 @@ impl<C> ParserConfig<C> {
     ///
     /// Because we work in proc-macro context, we expect it as 'static refs.
     ///
+    /// Only html elements are affected, inside of `<svg>` and `<math>` (see
+    /// [`Namespace`](crate::node::Namespace)) elements should be closed
+    /// explicitly.
+    ///
     /// Examples:
     /// <br> <link> <img>
     pub fn always_self_closed_elements(mut self, elements: HashSet<&'static str>) -> Self {
@@ -143,6 +784,68 @@ impl<C> ParserConfig<C> {
         self
     }
 
+    /// Require void elements to be written self-closed, like in XHTML:
+    /// `<br>` from [`always_self_closed_elements`] is reported as error
+    /// (but still parsed as element without children), and `<br/>` should be
+    /// used instead.
+    ///
+    /// Any other element should be closed explicitly in both modes.
+    ///
+    /// [`always_self_closed_elements`]: Self::always_self_closed_elements
+    pub fn strict_self_closing(mut self, strict: bool) -> Self {
+        self.strict_self_closing = strict;
+        self
+    }
+
+    /// Report fragments that are not top-level nodes, e.g. `<div><></></div>`,
+    /// for frameworks that allow fragments only as root of template.
+    ///
+    /// Fragments are still parsed with their children.
+    ///
+    /// ```rust
+    /// use quote::quote;
+    /// use rstml::{Parser, ParserConfig, ParserError};
+    ///
+    /// let config = ParserConfig::new().reject_nested_fragments(true);
+    /// let result = Parser::new(config).parse_recoverable(quote! { <><div><>"a"</></div></> });
+    /// let (nodes, diagnostics) = result.split_vec();
+    /// assert_eq!(nodes.len(), 1);
+    /// assert_eq!(ParserError::code_of(&diagnostics[0]), Some("E026"));
+    /// ```
+    pub fn reject_nested_fragments(mut self, reject_nested_fragments: bool) -> Self {
+        self.reject_nested_fragments = reject_nested_fragments;
+        self
+    }
+
+    /// Report fragments without children, e.g. `<></>`.
+    pub fn reject_empty_fragments(mut self, reject_empty_fragments: bool) -> Self {
+        self.reject_empty_fragments = reject_empty_fragments;
+        self
+    }
+
+    /// Report blocks without content, e.g. `{}` or `<div attr={}/>`.
+    ///
+    /// Empty blocks are kept as [`NodeBlock::Invalid`], so the rest of the
+    /// tree is still parsed. Blocks with syntax errors are recovered the same
+    /// way with [`recover_block`](Self::recover_block).
+    ///
+    /// ```rust
+    /// use quote::quote;
+    /// use rstml::{node::NodeBlock, Parser, ParserConfig, ParserError};
+    ///
+    /// let config = ParserConfig::new().reject_empty_blocks(true);
+    /// let result = Parser::new(config).parse_recoverable(quote! { <div>{}"a"</div> });
+    /// let (nodes, diagnostics) = result.split_vec();
+    /// assert_eq!(nodes[0].children().unwrap().len(), 2);
+    /// assert_eq!(ParserError::code_of(&diagnostics[0]), Some("E032"));
+    /// ```
+    ///
+    /// [`NodeBlock::Invalid`]: crate::node::NodeBlock::Invalid
+    pub fn reject_empty_blocks(mut self, reject_empty_blocks: bool) -> Self {
+        self.reject_empty_blocks = reject_empty_blocks;
+        self
+    }
+
     /// Set array of nodes that is known to be parsed in two-phases,
     /// Parser will skip parsing of children nodes.
     /// and provide one child with RawText instead.
@@ -153,11 +856,43 @@ impl<C> ParserConfig<C> {
     /// be inserted.
     ///
     /// Raw texts has few limitations, check out `RawText` documentation.
+    /// Content can be written as a single string literal instead, e.g.
+    /// `<script>r#"let s = 'Open Sans';"#</script>`, in that case element has
+    /// [`NodeText`](crate::node::NodeText) child.
     pub fn raw_text_elements(mut self, elements: HashSet<&'static str>) -> Self {
         self.raw_text_elements = elements;
         self
     }
 
+    /// Set names of attributes, like `key` or `ref`, that are moved from
+    /// [`NodeElement::attributes`] to [`NodeElement::special_attributes`], so
+    /// the macro doesn't need to filter them out.
+    ///
+    /// Special attributes are still emitted by `ToTokens` at their original
+    /// positions.
+    ///
+    /// ```rust
+    /// use quote::quote;
+    /// use rstml::{node::Node, Parser, ParserConfig};
+    ///
+    /// let config = ParserConfig::new().special_attributes(["key"].into());
+    /// let nodes = Parser::new(config)
+    ///     .parse_simple(quote! { <li key={id} class="item" /> })
+    ///     .unwrap();
+    /// let Node::Element(element) = &nodes[0] else {
+    ///     unreachable!()
+    /// };
+    /// assert_eq!(element.attributes().len(), 1);
+    /// assert!(element.special_attribute("key").is_some());
+    /// ```
+    ///
+    /// [`NodeElement::attributes`]: crate::node::NodeElement::attributes
+    /// [`NodeElement::special_attributes`]: crate::node::NodeElement::special_attributes
+    pub fn special_attributes(mut self, names: HashSet<&'static str>) -> Self {
+        self.special_attributes = names;
+        self
+    }
+
     /// Transforms the `value` of all `NodeType::Block`s with the given closure
     /// callback. The provided `ParseStream` is the content of the block.
     ///
@@ -235,6 +970,197 @@ impl<C> ParserConfig<C> {
         })
     }
 
+    /// Set callback that provides custom message for parser errors, e.g. to
+    /// use wording of DSL. Errors for which callback returns `None` keep
+    /// default message.
+    ///
+    /// Errors of [`syn::parse::Parse`] implementations, like
+    /// [`ParserError::InvalidNameStart`], keep default message. Code of error
+    /// is reported in a note, so
+    /// [`ParserError::code_of`] works with custom messages as well.
+    ///
+    /// ```rust
+    /// use quote::quote;
+    /// use rstml::{Parser, ParserConfig, ParserError};
+    ///
+    /// let config = ParserConfig::new().error_message(|error| match error {
+    ///     ParserError::UnclosedTag { .. } => Some("component must be closed".into()),
+    ///     _ => None,
+    /// });
+    /// let error = Parser::new(config)
+    ///     .parse_simple(quote! { <div> })
+    ///     .unwrap_err();
+    /// assert_eq!(
+    ///     error.to_string().lines().next(),
+    ///     Some("component must be closed")
+    /// );
+    /// assert_eq!(ParserError::code_of_error(&error), Some("E001"));
+    /// ```
+    pub fn error_message<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&ParserError) -> Option<String> + 'static,
+    {
+        self.error_message = Some(Rc::new(callback));
+        self
+    }
+
+    /// Set callback that checks names of elements, e.g. to reject unknown
+    /// elements of DSL. Error returned by callback is reported as
+    /// [`ParserError::InvalidElementName`], spanned to the name in open tag,
+    /// and element is parsed as usual.
+    ///
+    /// ```rust
+    /// use quote::quote;
+    /// use rstml::{Parser, ParserConfig};
+    ///
+    /// let config = ParserConfig::new().element_name_check(|name| match name.to_string().as_str() {
+    ///     "row" | "column" => Ok(()),
+    ///     "col" => Err("did you mean `column`?".into()),
+    ///     _ => Err("expected `row` or `column`".into()),
+    /// });
+    /// let error = Parser::new(config)
+    ///     .parse_simple(quote! { <row><col/></row> })
+    ///     .unwrap_err();
+    /// assert_eq!(
+    ///     error.to_string().lines().next().unwrap(),
+    ///     "invalid element name `col`: did you mean `column`?"
+    /// );
+    /// ```
+    pub fn element_name_check<F>(mut self, check: F) -> Self
+    where
+        F: Fn(&NodeName) -> std::result::Result<(), String> + 'static,
+    {
+        self.element_name_check = Some(Rc::new(check));
+        self
+    }
+
+    /// Set callback that checks keys of attributes, it receives name of
+    /// element and key of attribute, e.g. to reject unknown props of
+    /// components. Error returned by callback is reported as
+    /// [`ParserError::InvalidAttributeName`], spanned to the key.
+    ///
+    /// Block attributes (`<div {..attrs}>`) are not checked.
+    ///
+    /// ```rust
+    /// use quote::quote;
+    /// use rstml::{Parser, ParserConfig};
+    ///
+    /// let config = ParserConfig::new().attribute_name_check(|element, key| {
+    ///     match (element.to_string().as_str(), key.to_string().as_str()) {
+    ///         ("Button", "label" | "on_click") | ("div", _) => Ok(()),
+    ///         _ => Err("unknown prop".into()),
+    ///     }
+    /// });
+    /// let error = Parser::new(config)
+    ///     .parse_simple(quote! { <div id="a"><Button label="ok" size=2 /></div> })
+    ///     .unwrap_err();
+    /// assert_eq!(
+    ///     error.to_string().lines().next().unwrap(),
+    ///     "invalid attribute `size` of element `Button`: unknown prop"
+    /// );
+    /// ```
+    pub fn attribute_name_check<F>(mut self, check: F) -> Self
+    where
+        F: Fn(&NodeName, &NodeName) -> std::result::Result<(), String> + 'static,
+    {
+        self.attribute_name_check = Some(Rc::new(check));
+        self
+    }
+
+    /// Limit nesting depth of elements and fragments.
+    ///
+    /// Parser doesn't use recursion for nested elements, but the resulting
+    /// tree is still processed recursively by most of the consumers (like
+    /// `ToTokens` implementation). Once `max_depth` is exceeded, "maximum
+    /// element nesting depth exceeded" error is reported, spanned to the open
+    /// tag of the first too deep element. That element is parsed, but dropped
+    /// from the tree, so its ancestors and siblings are kept.
+    ///
+    /// Top level element has depth 1.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Set callback that is invoked on every element as soon as it is
+    /// completed, before it is added to its parent. Children of element are
+    /// already parsed and passed to callback for their own elements.
+    ///
+    /// Callback can validate or rewrite the element. If callback returns an
+    /// error, it is reported as diagnostic, and the element is kept in the
+    /// tree as is.
+    ///
+    /// Callback is reset by [`custom_node`](Self::custom_node), because it
+    /// depends on the type of custom node, so it should be set after it.
+    ///
+    /// ```rust
+    /// use quote::quote;
+    /// use rstml::{Parser, ParserConfig};
+    ///
+    /// let config = ParserConfig::new().on_element_closed(|element| {
+    ///     if element.name().to_string() == "img" && element.attributes().is_empty() {
+    ///         return Err(syn::Error::new_spanned(element.name(), "img without src"));
+    ///     }
+    ///     Ok(())
+    /// });
+    /// let result = Parser::new(config).parse_recoverable(quote!(<div><img /></div>));
+    /// let (_nodes, diagnostics) = result.split_vec();
+    /// assert_eq!(diagnostics.len(), 1);
+    /// ```
+    pub fn on_element_closed<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&mut NodeElement<C>) -> Result<()> + 'static,
+    {
+        self.on_element_closed = Some(Rc::new(callback));
+        self
+    }
+
+    /// Parse children of elements named `name` with `callback`, instead of
+    /// parsing them as nodes. Useful to embed custom DSL into template.
+    ///
+    /// All tokens until the close tag with the same name are passed to
+    /// `callback` as `ParseStream`, along with the open tag. Callback should
+    /// consume all tokens. If callback returns an error, it is reported as
+    /// diagnostic, and tokens are kept as a single [`Node::RawText`] child.
+    ///
+    /// Takes precedence over [`raw_text_elements`](Self::raw_text_elements),
+    /// but not over
+    /// [`always_self_closed_elements`](Self::always_self_closed_elements).
+    /// Callbacks are reset by [`custom_node`](Self::custom_node), same as
+    /// [`on_element_closed`](Self::on_element_closed).
+    ///
+    /// ```rust
+    /// use quote::quote;
+    /// use rstml::{
+    ///     node::{Node, NodeText},
+    ///     Parser, ParserConfig,
+    /// };
+    /// use syn::{parse::Parse, Ident, LitStr, Token};
+    ///
+    /// // Children of `<list>` are comma separated identifiers.
+    /// let config = ParserConfig::new().children_parser("list", |_open_tag, input| {
+    ///     let items = input.parse_terminated(Ident::parse, Token![,])?;
+    ///     Ok(items
+    ///         .into_iter()
+    ///         .map(|item| {
+    ///             let value = LitStr::new(&item.to_string(), item.span());
+    ///             Node::Text(NodeText { value })
+    ///         })
+    ///         .collect())
+    /// });
+    /// let nodes = Parser::new(config)
+    ///     .parse_simple(quote!(<list> a, b, c </list>))
+    ///     .unwrap();
+    /// assert_eq!(nodes[0].children().unwrap().len(), 3);
+    /// ```
+    pub fn children_parser<F>(mut self, name: &'static str, callback: F) -> Self
+    where
+        F: Fn(&OpenTag, ParseStream) -> Result<Vec<Node<C>>> + 'static,
+    {
+        self.children_parsers.insert(name, Rc::new(callback));
+        self
+    }
+
     ///
     /// Provide pattern of macro call.
     ///
@@ -270,8 +1196,8 @@ impl<C> ParserConfig<C> {
     /// Panics if no `%%` token was found.
     ///
     /// If macro_call_patern is set rstml will parse input two times in order to
-    /// recover spaces in `RawText`. Rstml will panic if macro source text
-    /// is not possible to recover.
+    /// recover spaces in `RawText`. If macro source text is not possible to
+    /// recover, warning is reported, and raw text is left empty.
     #[cfg(feature = "rawtext-stable-hack")]
     pub fn macro_call_pattern(mut self, pattern: TokenStream) -> Self {
         self.macro_pattern =
@@ -279,6 +1205,59 @@ impl<C> ParserConfig<C> {
         self
     }
 
+    /// Options that affect the shape of parsed tree, it is attached to
+    /// [`ParsedTemplate`] by [`Parser::parse_template`].
+    ///
+    /// ```rust
+    /// use rstml::ParserConfig;
+    ///
+    /// let config = ParserConfig::new()
+    ///     .flat_tree()
+    ///     .raw_text_elements(["script"].into());
+    /// let summary = config.summary();
+    /// assert!(summary.flat_tree);
+    /// assert!(summary.raw_text_elements.contains("script"));
+    /// assert!(summary.custom_node.ends_with("Infallible"));
+    /// ```
+    ///
+    /// [`ParsedTemplate`]: crate::node::ParsedTemplate
+    /// [`Parser::parse_template`]: crate::Parser::parse_template
+    pub fn summary(&self) -> ConfigSummary {
+        ConfigSummary {
+            flat_tree: self.flat_tree,
+            number_of_top_level_nodes: self.number_of_top_level_nodes,
+            type_of_top_level_nodes: self.type_of_top_level_nodes.clone(),
+            recover_block: self.recover_block,
+            raw_blocks: self.raw_blocks,
+            recover_incomplete: self.recover_incomplete,
+            block_delimiters: self.block_delimiters.clone(),
+            merge_adjacent_text: self.merge_adjacent_text,
+            interpolate_text: self.interpolate_text,
+            tag_sugar: self.tag_sugar,
+            reject_flag_attributes: self.reject_flag_attributes,
+            attribute_value_parsing: self.attribute_value_parsing,
+            unquoted_values: self.unquoted_values,
+            name_policy: self.name_policy,
+            text_literals: self.text_literals,
+            preserve_tokens: self.preserve_tokens,
+            directive_prefix: self.directive_prefix,
+            case_insensitive_close_tags: self.case_insensitive_close_tags,
+            component_rules: self.component_rules,
+            always_self_closed_elements: self.always_self_closed_elements.clone(),
+            strict_self_closing: self.strict_self_closing,
+            reject_nested_fragments: self.reject_nested_fragments,
+            reject_empty_fragments: self.reject_empty_fragments,
+            reject_empty_blocks: self.reject_empty_blocks,
+            raw_text_elements: self.raw_text_elements.clone(),
+            special_attributes: self.special_attributes.clone(),
+            max_depth: self.max_depth,
+            children_parsers: self.children_parsers.keys().copied().collect(),
+            #[cfg(feature = "rawtext-stable-hack")]
+            macro_pattern: !self.macro_pattern.is_empty(),
+            custom_node: std::any::type_name::<C>(),
+        }
+    }
+
     /// Enables parsing for [`Node::Custom`] using a type implementing
     /// [`CustomNode`].
     pub fn custom_node<CN: CustomNode>(self) -> ParserConfig<CN> {
@@ -288,9 +1267,35 @@ impl<C> ParserConfig<C> {
             type_of_top_level_nodes: self.type_of_top_level_nodes,
             transform_block: self.transform_block,
             recover_block: self.recover_block,
+            raw_blocks: self.raw_blocks,
+            recover_incomplete: self.recover_incomplete,
+            block_delimiters: self.block_delimiters,
+            merge_adjacent_text: self.merge_adjacent_text,
+            interpolate_text: self.interpolate_text,
+            tag_sugar: self.tag_sugar,
+            reject_flag_attributes: self.reject_flag_attributes,
+            attribute_value_parsing: self.attribute_value_parsing,
+            unquoted_values: self.unquoted_values,
+            name_policy: self.name_policy,
+            text_literals: self.text_literals,
+            preserve_tokens: self.preserve_tokens,
+            directive_prefix: self.directive_prefix,
+            case_insensitive_close_tags: self.case_insensitive_close_tags,
+            component_rules: self.component_rules,
             always_self_closed_elements: self.always_self_closed_elements,
+            strict_self_closing: self.strict_self_closing,
+            reject_nested_fragments: self.reject_nested_fragments,
+            reject_empty_fragments: self.reject_empty_fragments,
+            reject_empty_blocks: self.reject_empty_blocks,
             raw_text_elements: self.raw_text_elements,
+            special_attributes: self.special_attributes,
             element_close_wildcard: self.element_close_wildcard,
+            error_message: self.error_message,
+            element_name_check: self.element_name_check,
+            attribute_name_check: self.attribute_name_check,
+            max_depth: self.max_depth,
+            on_element_closed: None,
+            children_parsers: Default::default(),
             #[cfg(feature = "rawtext-stable-hack")]
             macro_pattern: self.macro_pattern,
             custom_node: Default::default(),
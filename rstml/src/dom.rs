@@ -0,0 +1,214 @@
+//! Conversion between node trees and a minimal DOM, available with `dom`
+//! feature.
+//!
+//! [`DomNode`] contains only static content: names, literal attribute values
+//! and text, so it can be converted to and from other DOM implementations
+//! (e.g. `html5ever` RcDom) by tools like hydration checkers or "paste HTML,
+//! get rsx" converters.
+//!
+//! Blocks, custom nodes and attributes with non-literal values can't be
+//! converted to DOM, and fragments are replaced with their children.
+//!
+//! ```rust
+//! use quote::ToTokens;
+//! use rstml::{dom::DomNode, node::Node, tree};
+//!
+//! let nodes = tree! { <p class="note">"Hello" <br/></p> };
+//! let dom = DomNode::from_nodes(&nodes).unwrap();
+//! let DomNode::Element(p) = &dom[0] else {
+//!     unreachable!()
+//! };
+//! assert_eq!(p.name, "p");
+//! assert_eq!(p.attribute("class"), Some("note"));
+//!
+//! let nodes: Vec<Node> = DomNode::to_nodes(&dom).unwrap();
+//! assert_eq!(
+//!     nodes[0].to_token_stream().to_string(),
+//!     r#"< p class = "note" > "Hello" < br / > < / p >"#
+//! );
+//! ```
+
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::LitStr;
+
+use crate::{
+    node::{CustomNode, KeyedAttributeValue, Node, NodeAttribute, NodeElement, NodeName},
+    Error, Parser, ParserConfig,
+};
+
+/// Node of DOM tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DomNode {
+    Element(DomElement),
+    Text(String),
+    Comment(String),
+    /// Value of doctype, e.g. `html`.
+    Doctype(String),
+}
+
+/// Element of DOM tree.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DomElement {
+    pub name: String,
+    pub attributes: Vec<DomAttribute>,
+    pub children: Vec<DomNode>,
+}
+
+/// Attribute of element, `value` is `None` for attributes without value,
+/// e.g. `disabled`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DomAttribute {
+    pub name: String,
+    pub value: Option<String>,
+}
+
+impl DomElement {
+    /// Value of attribute with given `name`, `Some("")` for attributes without
+    /// value.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|a| a.name == name)
+            .map(|a| a.value.as_deref().unwrap_or_default())
+    }
+
+    fn to_tokens(&self) -> Result<TokenStream, Error> {
+        let name = parse_name(&self.name)?;
+        let mut attributes = TokenStream::new();
+        for attribute in &self.attributes {
+            parse_name(&attribute.name)?.to_tokens(&mut attributes);
+            if let Some(value) = &attribute.value {
+                let value = LitStr::new(value, Span::call_site());
+                attributes.extend(quote!(= #value));
+            }
+        }
+        if self.children.is_empty() {
+            return Ok(quote!(<#name #attributes />));
+        }
+        let mut children = TokenStream::new();
+        for child in &self.children {
+            children.extend(child.to_tokens()?);
+        }
+        Ok(quote!(<#name #attributes> #children </#name>))
+    }
+}
+
+impl DomNode {
+    /// Convert `nodes` to DOM, fragments are replaced with their children.
+    pub fn from_nodes<C: CustomNode>(nodes: &[Node<C>]) -> Result<Vec<Self>, Error> {
+        let mut dom = vec![];
+        for node in nodes {
+            match node {
+                Node::Fragment(fragment) => dom.extend(Self::from_nodes(&fragment.children)?),
+                node => dom.push(Self::try_from(node)?),
+            }
+        }
+        Ok(dom)
+    }
+
+    /// Convert `dom` to nodes, by parsing it as rsx with default
+    /// [`ParserConfig`], so element kinds and namespaces are set as usual.
+    /// Elements without children are self-closed.
+    pub fn to_nodes<C: CustomNode + fmt::Debug>(dom: &[Self]) -> Result<Vec<Node<C>>, Error> {
+        let mut tokens = TokenStream::new();
+        for node in dom {
+            tokens.extend(node.to_tokens()?);
+        }
+        Parser::new(ParserConfig::new().custom_node::<C>())
+            .parse_simple(tokens)
+            .map_err(|e| Error::TryFrom(e.to_string()))
+    }
+
+    /// Rsx tokens of node. Fails if name is not valid [`NodeName`].
+    fn to_tokens(&self) -> Result<TokenStream, Error> {
+        Ok(match self {
+            DomNode::Element(element) => element.to_tokens()?,
+            DomNode::Text(text) => LitStr::new(text, Span::call_site()).into_token_stream(),
+            DomNode::Comment(text) => {
+                let text = LitStr::new(text, Span::call_site());
+                quote!(<!-- #text -->)
+            }
+            DomNode::Doctype(value) => TokenStream::from_str(&format!("<!DOCTYPE {value}>"))
+                .map_err(|e| Error::TryFrom(e.to_string()))?,
+        })
+    }
+}
+
+fn parse_name(name: &str) -> Result<NodeName, Error> {
+    syn::parse_str(name).map_err(|e| Error::TryFrom(format!("invalid name `{name}`: {e}")))
+}
+
+impl<C: CustomNode> TryFrom<&Node<C>> for DomNode {
+    type Error = Error;
+
+    fn try_from(node: &Node<C>) -> Result<Self, Self::Error> {
+        Ok(match node {
+            Node::Element(element) => DomNode::Element(DomElement::try_from(element)?),
+            Node::Text(text) => DomNode::Text(text.value_string()),
+            Node::RawText(text) => DomNode::Text(text.to_string_best()),
+            Node::Comment(comment) => DomNode::Comment(comment.value.value()),
+            Node::Doctype(doctype) => DomNode::Doctype(doctype.value.to_string_best()),
+            Node::Fragment(_) => {
+                return Err(Error::TryFrom(
+                    "fragment can't be converted to a single DOM node, use `DomNode::from_nodes`"
+                        .into(),
+                ))
+            }
+            Node::Block(_) | Node::Custom(_) | Node::Incomplete(_) => {
+                return Err(Error::TryFrom(format!(
+                    "{} node can't be converted to DOM",
+                    node.r#type()
+                )))
+            }
+        })
+    }
+}
+
+impl<C: CustomNode> TryFrom<&NodeElement<C>> for DomElement {
+    type Error = Error;
+
+    fn try_from(element: &NodeElement<C>) -> Result<Self, Self::Error> {
+        if element.name().is_block() {
+            return Err(Error::TryFrom(
+                "element with block name can't be converted to DOM".into(),
+            ));
+        }
+        let attributes = element
+            .all_attributes()
+            .iter()
+            .map(|attribute| {
+                let NodeAttribute::Attribute(attribute) = &**attribute else {
+                    return Err(Error::TryFrom(
+                        "block attribute can't be converted to DOM".into(),
+                    ));
+                };
+                let name = attribute.key.to_string();
+                let value = match &attribute.possible_value {
+                    KeyedAttributeValue::Flag => None,
+                    _ => Some(attribute.value_literal_string().ok_or_else(|| {
+                        Error::TryFrom(format!("value of attribute `{name}` is not a literal"))
+                    })?),
+                };
+                Ok(DomAttribute { name, value })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(DomElement {
+            name: element.name().to_string(),
+            attributes,
+            children: DomNode::from_nodes(&element.children)?,
+        })
+    }
+}
+
+impl<C: CustomNode + fmt::Debug> TryFrom<&DomNode> for Node<C> {
+    type Error = Error;
+
+    fn try_from(node: &DomNode) -> Result<Self, Self::Error> {
+        let mut nodes = DomNode::to_nodes(std::slice::from_ref(node))?;
+        // Single rsx node is produced for every DOM node.
+        Ok(nodes.remove(0))
+    }
+}
@@ -1,5 +1,270 @@
+use proc_macro2::Span;
+use proc_macro2_diagnostics::{Diagnostic, Level};
+
+use crate::config::ErrorMessageFn;
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("TryFrom failed: {0}")]
     TryFrom(String),
 }
+
+/// Error reported by parser, with a stable code.
+///
+/// Messages of diagnostics are not stable, use [`ParserError::code_of`] to
+/// match on the kind of reported error. Code is reported in a note after the
+/// message, like `= note: error[E002]`, so it doesn't depend on message:
+///
+/// ```rust
+/// use quote::quote;
+/// use rstml::{Parser, ParserConfig, ParserError};
+///
+/// let result = Parser::new(ParserConfig::new()).parse_recoverable(quote! { <div></span> });
+/// let (_, diagnostics) = result.split_vec();
+/// assert_eq!(ParserError::code_of(&diagnostics[0]), Some("E002"));
+/// assert_eq!(ParserError::MismatchedCloseTag.code(), "E002");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ParserError {
+    /// Input ended before close tag of element, `suggested_fix` is the
+    /// missing close tag, e.g. `</div>`.
+    #[error("open tag has no corresponding close tag, expected `{suggested_fix}`")]
+    UnclosedTag { name: String, suggested_fix: String },
+    #[error("wrong close tag found")]
+    MismatchedCloseTag,
+    #[error("close tag generics missmatch")]
+    CloseTagGenericsMismatch,
+    #[error("expected end of tag '>'")]
+    MissingTagEnd,
+    #[error("close tag was parsed while waiting for open tag")]
+    UnexpectedCloseTag,
+    #[error("expected fragment closing, found element closing tag")]
+    UnexpectedElementClose,
+    #[error("expected DOCTYPE keyword")]
+    ExpectedDoctype,
+    #[error("maximum element nesting depth exceeded")]
+    MaxDepthExceeded,
+    #[error("expected attribute value after `=`")]
+    MissingAttributeValue,
+    /// Attribute without value, see
+    /// [`ParserConfig::reject_flag_attributes`](crate::ParserConfig::reject_flag_attributes).
+    #[error("expected value for attribute `{0}`")]
+    FlagAttribute(String),
+    #[error("failed to parse expression: {0}")]
+    InvalidExpression(String),
+    /// Warning about `>` in unbraced attribute value.
+    #[error(
+        "`>` in attribute value is treated as the end of tag, wrap the value in braces to \
+         compare, e.g. `attr={{a > b}}`"
+    )]
+    AmbiguousAttributeValue,
+    #[error("attributes are not supported on this pattern")]
+    UnsupportedPatternAttributes,
+    #[error("tokens was ignored during parsing")]
+    IgnoredTokens,
+    #[error("Tokens was skipped after incorrect parsing")]
+    SkippedTokens,
+    #[error("Node parse failed")]
+    NodeParseFailed,
+    #[error("top level nodes need to be of type {0}")]
+    TopLevelNodeType(String),
+    #[error("saw {found} top level nodes but exactly {expected} are required")]
+    TopLevelNodeCount { found: usize, expected: usize },
+    #[error("Name must start with latin character")]
+    InvalidNameStart,
+    #[error("element can have only one `#id` shorthand")]
+    DuplicateIdShorthand,
+    #[error("expected id after `#`")]
+    ExpectedIdShorthand,
+    #[error("expected class name after `.`")]
+    ExpectedClassName,
+    /// Void element without `/>`, see
+    /// [`ParserConfig::strict_self_closing`](crate::ParserConfig::strict_self_closing).
+    #[error("void element `{0}` should be self-closed: `<{0}/>`")]
+    VoidElementNotSelfClosed(String),
+    /// Element name rejected by
+    /// [`ParserConfig::element_name_check`](crate::ParserConfig::element_name_check).
+    #[error("invalid element name `{name}`: {reason}")]
+    InvalidElementName { name: String, reason: String },
+    /// Attribute key rejected by
+    /// [`ParserConfig::attribute_name_check`](crate::ParserConfig::attribute_name_check).
+    #[error("invalid attribute `{name}` of element `{element}`: {reason}")]
+    InvalidAttributeName {
+        element: String,
+        name: String,
+        reason: String,
+    },
+    /// Fragment inside of element or other fragment, see
+    /// [`ParserConfig::reject_nested_fragments`](crate::ParserConfig::reject_nested_fragments).
+    #[error("fragments are allowed only at the top level")]
+    NestedFragment,
+    /// Fragment without children, see
+    /// [`ParserConfig::reject_empty_fragments`](crate::ParserConfig::reject_empty_fragments).
+    #[error("fragment should have children")]
+    EmptyFragment,
+    #[error("close tags cannot have attributes")]
+    CloseTagAttributes,
+    /// Source of template can't be converted to tokens, see
+    /// [`Parser::parse_str`](crate::Parser::parse_str). `column` is 1-based.
+    #[error(
+        "failed to tokenize template at {line}:{column}, braces and quotes should be balanced, \
+         even in raw text elements like `<style>`; wrap such content into string literal, e.g. \
+         `r#\"...\"#`, or move it to external file"
+    )]
+    UnbalancedSource { line: usize, column: usize },
+    /// Name rejected by
+    /// [`ParserConfig::name_policy`](crate::ParserConfig::name_policy).
+    #[error("character `{character}` in name `{name}` is not allowed by {policy} rules")]
+    InvalidNameCharacter {
+        name: String,
+        character: char,
+        policy: crate::NamePolicy,
+    },
+    /// Literal other than string used as text, see
+    /// [`ParserConfig::text_literals`](crate::ParserConfig::text_literals).
+    #[error("literal `{literal}` should be quoted to be used as text, e.g. `{suggested_fix}`")]
+    UnquotedLiteral {
+        literal: String,
+        suggested_fix: String,
+    },
+    /// Block without content, like `{}`, see
+    /// [`ParserConfig::reject_empty_blocks`](crate::ParserConfig::reject_empty_blocks).
+    #[error("block should not be empty")]
+    EmptyBlock,
+    /// Source text of template has different nodes than parsed tokens, see
+    /// `Parser::parse_with_source_text`, available with `rawtext-stable-hack`
+    /// feature.
+    #[error("source text doesn't match template, raw text can't be recovered")]
+    SourceTextMismatch,
+}
+
+/// Codes of all errors, see [`ParserError::code`].
+const CODES: &[&str] = &[
+    "E001", "E002", "E003", "E004", "E005", "E006", "E007", "E008", "E009", "E010", "E011", "E012",
+    "E013", "E014", "E015", "E016", "E017", "E018", "E019", "E020", "E021", "E022", "E023", "E024",
+    "E025", "E026", "E027", "E028", "E029", "E030", "E031", "E032", "E033",
+];
+
+impl ParserError {
+    /// Stable code of error, like `E001`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::UnclosedTag { .. } => "E001",
+            Self::MismatchedCloseTag => "E002",
+            Self::CloseTagGenericsMismatch => "E003",
+            Self::MissingTagEnd => "E004",
+            Self::UnexpectedCloseTag => "E005",
+            Self::UnexpectedElementClose => "E006",
+            Self::ExpectedDoctype => "E007",
+            Self::MaxDepthExceeded => "E008",
+            Self::MissingAttributeValue => "E009",
+            Self::FlagAttribute(_) => "E010",
+            Self::InvalidExpression(_) => "E011",
+            Self::AmbiguousAttributeValue => "E012",
+            Self::UnsupportedPatternAttributes => "E013",
+            Self::IgnoredTokens => "E014",
+            Self::SkippedTokens => "E015",
+            Self::NodeParseFailed => "E016",
+            Self::TopLevelNodeType(_) => "E017",
+            Self::TopLevelNodeCount { .. } => "E018",
+            Self::InvalidNameStart => "E019",
+            Self::DuplicateIdShorthand => "E020",
+            Self::ExpectedIdShorthand => "E021",
+            Self::ExpectedClassName => "E022",
+            Self::VoidElementNotSelfClosed(_) => "E023",
+            Self::InvalidElementName { .. } => "E024",
+            Self::InvalidAttributeName { .. } => "E025",
+            Self::NestedFragment => "E026",
+            Self::EmptyFragment => "E027",
+            Self::CloseTagAttributes => "E028",
+            Self::UnbalancedSource { .. } => "E029",
+            Self::InvalidNameCharacter { .. } => "E030",
+            Self::UnquotedLiteral { .. } => "E031",
+            Self::EmptyBlock => "E032",
+            Self::SourceTextMismatch => "E033",
+        }
+    }
+
+    /// Code that fixes error, if it is known, e.g. missing close tag.
+    pub fn suggested_fix(&self) -> Option<&str> {
+        match self {
+            Self::UnclosedTag { suggested_fix, .. }
+            | Self::UnquotedLiteral { suggested_fix, .. } => Some(suggested_fix),
+            _ => None,
+        }
+    }
+
+    /// Level of diagnostic, that is reported for error.
+    pub fn level(&self) -> Level {
+        match self {
+            Self::AmbiguousAttributeValue => Level::Warning,
+            _ => Level::Error,
+        }
+    }
+
+    /// Diagnostic pointing to `span`.
+    ///
+    /// Code of error is attached as a note, like `error[E001]`, so it is
+    /// available from [`ParserError::code_of`] regardless of message.
+    pub fn spanned(self, span: Span) -> Diagnostic {
+        let message = self.to_string();
+        self.spanned_with_message(span, message)
+    }
+
+    /// Same as [`ParserError::spanned`], but with custom `message`.
+    pub(crate) fn spanned_with_message(&self, span: Span, message: String) -> Diagnostic {
+        let level = self.level();
+        Diagnostic::spanned(span, level, message).note(format!("{level}[{}]", self.code()))
+    }
+
+    /// Message of error, provided by `custom` callback, or the default one.
+    pub(crate) fn message(&self, custom: Option<&ErrorMessageFn>) -> String {
+        custom
+            .and_then(|message| message(self))
+            .unwrap_or_else(|| self.to_string())
+    }
+
+    /// Error pointing to `span`, with code note, see
+    /// [`ParserError::spanned`].
+    pub fn error(self, span: Span) -> syn::Error {
+        self.spanned(span).into()
+    }
+
+    /// Code of error, that was reported as `diagnostic`, or `None` if it was
+    /// not reported by parser (e.g. by [`syn`] or user callbacks).
+    pub fn code_of(diagnostic: &Diagnostic) -> Option<&'static str> {
+        Self::code_of_error(&diagnostic.clone().into())
+    }
+
+    /// Code of error, that was returned as [`syn::Error`], see
+    /// [`ParserError::code_of`].
+    ///
+    /// Only the code note is checked, so message of error is not relevant.
+    pub fn code_of_error(error: &syn::Error) -> Option<&'static str> {
+        // Note is joined to the message as the last line, like
+        // `= note: error[E001]`, possibly with colors.
+        let message = error.to_string();
+        let note = strip_colors(message.lines().skip(1).last()?);
+        let note = note.trim_start().strip_prefix("= note: ")?;
+        let (level, code) = note.strip_suffix(']')?.split_once('[')?;
+        if !matches!(level, "error" | "warning") {
+            return None;
+        }
+        CODES.iter().copied().find(|known| *known == code)
+    }
+}
+
+/// Remove ANSI escape sequences, that are added with `colors` feature.
+fn strip_colors(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // Sequences look like `ESC[1;31m`.
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
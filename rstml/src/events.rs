@@ -0,0 +1,131 @@
+//! Flat SAX-style events of parsed tree, see [`events`].
+//!
+//! Code generators that write output sequentially, like streaming html
+//! writers, can iterate over events instead of recursing into children.
+//!
+//! ```rust
+//! use rstml::{
+//!     events::{events, Event},
+//!     node::NodeAttribute,
+//!     tree,
+//! };
+//!
+//! let nodes = tree! { <p hidden>"Hi, " {name}<br/></p> };
+//! let mut html = String::new();
+//! for event in events(&nodes) {
+//!     match event {
+//!         Event::ElementStart(element) => html += &format!("<{}", element.name()),
+//!         Event::Attribute(NodeAttribute::Attribute(attribute)) => {
+//!             html += &format!(" {}", attribute.key)
+//!         }
+//!         Event::AttributesEnd(_) => html += ">",
+//!         Event::Text(text) => html += &text.value_string(),
+//!         Event::Block(_) => html += "{}",
+//!         Event::ElementEnd(element) if element.close_tag.is_some() => {
+//!             html += &format!("</{}>", element.name())
+//!         }
+//!         _ => {}
+//!     }
+//! }
+//! assert_eq!(html, "<p hidden>Hi, {}<br></p>");
+//! ```
+
+use derive_where::derive_where;
+use proc_macro2::Span;
+use syn::spanned::Spanned;
+
+use crate::{
+    atoms::PartialOpenTag,
+    node::{
+        CustomNode, KeyedAttribute, Node, NodeAttribute, NodeBlock, NodeComment, NodeDoctype,
+        NodeElement, NodeText, RawText,
+    },
+    Infallible,
+};
+
+/// Event of [`events`] output.
+///
+/// Every element produces `ElementStart`, `Attribute` (or `SpecialAttribute`)
+/// for each of its attributes, `AttributesEnd`, events of its children and
+/// `ElementEnd`, even if it is self-closed. Fragments have no events of their
+/// own, their children are emitted in place of them.
+#[derive(Debug)]
+#[derive_where(Clone, Copy)]
+pub enum Event<'a, C = Infallible> {
+    ElementStart(&'a NodeElement<C>),
+    Attribute(&'a NodeAttribute),
+    /// Attribute from [`NodeElement::special_attributes`], emitted at its
+    /// position among other attributes.
+    SpecialAttribute(&'a KeyedAttribute),
+    /// End of element's open tag.
+    AttributesEnd(&'a NodeElement<C>),
+    ElementEnd(&'a NodeElement<C>),
+    Text(&'a NodeText),
+    RawText(&'a RawText<C>),
+    Block(&'a NodeBlock),
+    Comment(&'a NodeComment),
+    Doctype(&'a NodeDoctype),
+    Custom(&'a C),
+    /// Open tag without `>`, see [`Node::Incomplete`].
+    Incomplete(&'a PartialOpenTag),
+}
+
+impl<C: CustomNode> Event<'_, C> {
+    /// Span of event source: open tag of `ElementStart`, `>` or `/>` of
+    /// `AttributesEnd`, and close tag of `ElementEnd` (or end of open tag if
+    /// element has none).
+    pub fn span(&self) -> Span {
+        match self {
+            Self::ElementStart(element) => element.open_tag.span(),
+            Self::Attribute(attribute) => attribute.span(),
+            Self::SpecialAttribute(attribute) => attribute.span(),
+            Self::AttributesEnd(element) => element.open_tag.end_tag.span(),
+            Self::ElementEnd(element) => match &element.close_tag {
+                Some(close_tag) => close_tag.span(),
+                None => element.open_tag.end_tag.span(),
+            },
+            Self::Text(text) => text.span(),
+            Self::RawText(text) => text.span(),
+            Self::Block(block) => block.span(),
+            Self::Comment(comment) => comment.span(),
+            Self::Doctype(doctype) => doctype.span(),
+            Self::Custom(custom) => custom.span(),
+            Self::Incomplete(tag) => tag.span(),
+        }
+    }
+}
+
+/// Events of `nodes` and their children, in document order.
+pub fn events<C: CustomNode>(nodes: &[Node<C>]) -> Vec<Event<'_, C>> {
+    let mut events = vec![];
+    // Iterators over children, with element that should be closed after them.
+    let mut stack = vec![(nodes.iter(), None)];
+    while let Some((iter, parent)) = stack.last_mut() {
+        let Some(node) = iter.next() else {
+            if let Some(element) = parent {
+                events.push(Event::ElementEnd(*element));
+            }
+            stack.pop();
+            continue;
+        };
+        match node {
+            Node::Element(element) => {
+                events.push(Event::ElementStart(element));
+                events.extend(
+                    element.merge_special_attributes(Event::Attribute, Event::SpecialAttribute),
+                );
+                events.push(Event::AttributesEnd(element));
+                stack.push((element.children.iter(), Some(element)));
+            }
+            Node::Fragment(fragment) => stack.push((fragment.children.iter(), None)),
+            Node::Text(text) => events.push(Event::Text(text)),
+            Node::RawText(text) => events.push(Event::RawText(text)),
+            Node::Block(block) => events.push(Event::Block(block)),
+            Node::Comment(comment) => events.push(Event::Comment(comment)),
+            Node::Doctype(doctype) => events.push(Event::Doctype(doctype)),
+            Node::Custom(custom) => events.push(Event::Custom(custom)),
+            Node::Incomplete(tag) => events.push(Event::Incomplete(tag)),
+        }
+    }
+    events
+}
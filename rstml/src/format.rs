@@ -0,0 +1,334 @@
+//! Pretty-printing of parsed trees, available with `format` feature.
+//!
+//! [`format()`] takes an already parsed tree along with the source text it was
+//! parsed from, and writes it back with consistent indentation. Rust code
+//! (blocks, attribute values, custom nodes) and string literals are copied
+//! from the source as is, so their formatting and escapes are kept, only
+//! continuation lines of multi-line nodes are re-indented. Source ranges are
+//! taken from token spans, see [`source_map`](crate::source_map)
+//! for when they are available; otherwise tokens are printed with `ToTokens`.
+//!
+//! Open tags, that don't fit into [`FormatOptions::max_width`], are split into
+//! one attribute per line. Children are written on the same line only if all
+//! of them are text or blocks and the result fits, otherwise every child is
+//! written on its own line.
+//!
+//! ```rust
+//! use rstml::{
+//!     format::{format, FormatOptions},
+//!     parse2,
+//! };
+//!
+//! let source = r#"<div class = "x"><p>"Hello " {name}</p><br/></div>"#;
+//! let nodes = parse2(source.parse().unwrap()).unwrap();
+//! assert_eq!(
+//!     format(&nodes, source, &FormatOptions::default()),
+//!     r#"<div class="x">
+//!     <p>"Hello " {name}</p>
+//!     <br />
+//! </div>"#
+//! );
+//! ```
+
+use proc_macro2::{TokenStream, TokenTree};
+use quote::ToTokens;
+
+use crate::{
+    node::{CustomNode, KeyedAttributeValue, Node, NodeAttribute, NodeElement, NodeName},
+    source_map::boundary_spans,
+};
+
+/// How elements without children are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SelfClosing {
+    /// Keep `<div/>` and `<div></div>` as they were written.
+    #[default]
+    Preserve,
+    /// Write all elements without children as `<div />`.
+    Always,
+    /// Write all elements without children as `<div></div>`.
+    ///
+    /// Elements written without close tag at all, like `<br>`, are kept.
+    Never,
+}
+
+/// Options of [`format()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Number of spaces per nesting level.
+    pub indent: usize,
+    /// Maximum width of line, before open tag or children are wrapped.
+    pub max_width: usize,
+    pub self_closing: SelfClosing,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent: 4,
+            max_width: 100,
+            self_closing: SelfClosing::default(),
+        }
+    }
+}
+
+/// Format `nodes` parsed from `source`, see [module documentation](self).
+///
+/// Top-level nodes are written on separate lines, without trailing newline.
+pub fn format<C: CustomNode>(nodes: &[Node<C>], source: &str, options: &FormatOptions) -> String {
+    let formatter = Formatter { source, options };
+    nodes
+        .iter()
+        .map(|node| formatter.node(node, 0))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+struct Formatter<'a> {
+    source: &'a str,
+    options: &'a FormatOptions,
+}
+
+impl Formatter<'_> {
+    /// Source text of `tokens`, or their string representation, if spans
+    /// don't point to the source.
+    fn source_of(&self, tokens: impl ToTokens) -> String {
+        let tokens = tokens.to_token_stream();
+        match self.source_range(&tokens) {
+            Some((_, text)) => text.to_string(),
+            None => tokens.to_string(),
+        }
+    }
+
+    /// Start offset and source text of `tokens`, if spans point to the source.
+    fn source_range(&self, tokens: &TokenStream) -> Option<(usize, &str)> {
+        let (first, last) = boundary_spans(tokens.clone());
+        let range = first.byte_range().start..last.byte_range().end;
+        match self.source.get(range.clone()) {
+            Some(text) if !range.is_empty() && text_matches(text, tokens) => {
+                Some((range.start, text))
+            }
+            _ => None,
+        }
+    }
+
+    /// Same as [`source_of`](Self::source_of), but continuation lines are
+    /// moved from indentation of the first line in source to `level`, keeping
+    /// their relative indentation.
+    fn source_at(&self, tokens: impl ToTokens, level: usize) -> String {
+        let tokens = tokens.to_token_stream();
+        let Some((start, text)) = self.source_range(&tokens) else {
+            return tokens.to_string();
+        };
+        let line_start = self.source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let base = self.source[line_start..start].len()
+            - self.source[line_start..start].trim_start().len();
+        let indent = self.indent(level);
+        let mut lines = text.split('\n');
+        let mut result = lines.next().unwrap_or_default().to_string();
+        for line in lines {
+            result.push('\n');
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() {
+                continue;
+            }
+            let removed = (line.len() - trimmed.len()).min(base);
+            result.push_str(&indent);
+            result.push_str(&line[removed..]);
+        }
+        result
+    }
+
+    fn indent(&self, level: usize) -> String {
+        " ".repeat(level * self.options.indent)
+    }
+
+    fn fits(&self, level: usize, line: &str) -> bool {
+        !line.contains('\n')
+            && level * self.options.indent + line.chars().count() <= self.options.max_width
+    }
+
+    /// Format node, that starts at `level` indentation. First line is not
+    /// indented.
+    fn node<C: CustomNode>(&self, node: &Node<C>, level: usize) -> String {
+        match node {
+            Node::Element(element) => self.element(element, level),
+            Node::Fragment(fragment) => {
+                self.children("<>".to_string(), &fragment.children, "</>", level)
+            }
+            Node::Comment(comment) => format!("<!-- {} -->", self.source_of(&comment.value)),
+            Node::Doctype(doctype) => format!(
+                "<!{} {}>",
+                doctype.token_doctype,
+                self.source_of(&doctype.value)
+            ),
+            node => self.source_at(node, level),
+        }
+    }
+
+    fn element<C: CustomNode>(&self, element: &NodeElement<C>, level: usize) -> String {
+        let close = self.close_tag(element);
+        if element.children.is_empty() {
+            let self_closed = match self.options.self_closing {
+                // Element without close tag, like `<br>`.
+                _ if !element.is_self_closed() && !element.has_close_tag() => {
+                    return self.open_tag(element, false, level)
+                }
+                SelfClosing::Preserve => element.is_self_closed(),
+                SelfClosing::Always => true,
+                SelfClosing::Never => false,
+            };
+            let open = self.open_tag(element, self_closed, level);
+            if self_closed {
+                return open;
+            }
+            return format!("{open}{close}");
+        }
+        let open = self.open_tag(element, false, level);
+        self.children(open, &element.children, &close, level)
+    }
+
+    /// Close tag of element, with its generics, or with generics of open tag,
+    /// if element is written without close tag.
+    fn close_tag<C: CustomNode>(&self, element: &NodeElement<C>) -> String {
+        let (name, generics) = match &element.close_tag {
+            Some(tag) => (&tag.name, &tag.generics),
+            None => (&element.open_tag.name, &element.open_tag.generics),
+        };
+        let mut close = format!("</{}", self.name(name));
+        if !generics.params.is_empty() {
+            close.push_str(&self.source_of(generics));
+        }
+        close.push('>');
+        close
+    }
+
+    fn name(&self, name: &NodeName) -> String {
+        match name {
+            NodeName::Block(_) => self.source_of(name),
+            name => name.to_string(),
+        }
+    }
+
+    /// Open tag of element, including its special attributes.
+    fn open_tag<C: CustomNode>(
+        &self,
+        element: &NodeElement<C>,
+        self_closed: bool,
+        level: usize,
+    ) -> String {
+        let tag = &element.open_tag;
+        let mut head = format!("<{}", self.name(&tag.name));
+        if !tag.generics.params.is_empty() {
+            head.push_str(&self.source_of(&tag.generics));
+        }
+        let end = if self_closed { "/>" } else { ">" };
+        let attributes: Vec<_> = element
+            .all_attributes()
+            .iter()
+            .map(|a| self.attribute(a))
+            .collect();
+
+        let mut line = head.clone();
+        for attribute in &attributes {
+            line.push(' ');
+            line.push_str(attribute);
+        }
+        if self_closed {
+            line.push(' ');
+        }
+        line.push_str(end);
+        if attributes.is_empty() || self.fits(level, &line) {
+            return line;
+        }
+
+        let mut tag = head;
+        for attribute in &attributes {
+            tag.push('\n');
+            tag.push_str(&self.indent(level + 1));
+            tag.push_str(attribute);
+        }
+        tag.push('\n');
+        tag.push_str(&self.indent(level));
+        tag.push_str(end);
+        tag
+    }
+
+    fn attribute(&self, attribute: &NodeAttribute) -> String {
+        match attribute {
+            NodeAttribute::Attribute(a) if a.sugar.is_none() => match &a.possible_value {
+                KeyedAttributeValue::Value(v) => {
+                    format!("{}={}", self.name(&a.key), self.source_of(&v.value))
+                }
+                KeyedAttributeValue::Flag => self.name(&a.key),
+                KeyedAttributeValue::Binding(b) => {
+                    format!("{}{}", self.name(&a.key), self.source_of(b))
+                }
+            },
+            attribute => self.source_of(attribute),
+        }
+    }
+
+    /// Format `children` between `open` and `close` tags.
+    fn children<C: CustomNode>(
+        &self,
+        open: String,
+        children: &[Node<C>],
+        close: &str,
+        level: usize,
+    ) -> String {
+        let parts: Vec<_> = children
+            .iter()
+            .map(|child| self.node(child, level + 1))
+            .collect();
+
+        let inline_children = children
+            .iter()
+            .all(|child| matches!(child, Node::Text(_) | Node::Block(_) | Node::RawText(_)));
+        if inline_children && !open.contains('\n') {
+            let line = format!("{open}{}{close}", parts.join(" "));
+            if self.fits(level, &line) {
+                return line;
+            }
+        }
+
+        let mut result = open;
+        for part in parts {
+            result.push('\n');
+            result.push_str(&self.indent(level + 1));
+            result.push_str(&part);
+        }
+        result.push('\n');
+        result.push_str(&self.indent(level));
+        result.push_str(close);
+        result
+    }
+}
+
+/// Check that `text` from source consists of the same tokens, to not use
+/// source of a different input.
+fn text_matches(text: &str, tokens: &TokenStream) -> bool {
+    match text.parse::<TokenStream>() {
+        Ok(parsed) => tokens_eq(parsed, tokens.clone()),
+        Err(_) => false,
+    }
+}
+
+/// Compare token streams ignoring spacing of puncts, which differs between
+/// source (`<!--`) and tokens of nodes (`< ! - -`).
+fn tokens_eq(a: TokenStream, b: TokenStream) -> bool {
+    let (mut a, mut b) = (a.into_iter(), b.into_iter());
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return true,
+            (Some(TokenTree::Group(a)), Some(TokenTree::Group(b)))
+                if a.delimiter() == b.delimiter() && tokens_eq(a.stream(), b.stream()) => {}
+            (Some(TokenTree::Punct(a)), Some(TokenTree::Punct(b)))
+                if a.as_char() == b.as_char() => {}
+            (Some(TokenTree::Ident(a)), Some(TokenTree::Ident(b))) if a == b => {}
+            (Some(TokenTree::Literal(a)), Some(TokenTree::Literal(b)))
+                if a.to_string() == b.to_string() => {}
+            _ => return false,
+        }
+    }
+}
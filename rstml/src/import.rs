@@ -0,0 +1,309 @@
+//! Import of HTML markup as rsx, available with `html-import` feature.
+//!
+//! [`parse_html`] reads HTML leniently, like browsers do: unknown or
+//! mismatched close tags are ignored, unclosed elements are closed at the end
+//! of their parent, void elements (`<br>`, `<img>`, ...) never have children,
+//! and content of `<script>` and `<style>` is kept as text. Character
+//! references are decoded, and whitespace in text is collapsed.
+//!
+//! Result can be converted to nodes or tokens with [`html_to_nodes`] and
+//! [`html_to_tokens`], to paste existing markup into templates.
+//!
+//! ```rust
+//! use rstml::import::html_to_tokens;
+//!
+//! let tokens = html_to_tokens("<ul><li>Tom &amp; Jerry<li class=x>Bob</ul>").unwrap();
+//! assert_eq!(
+//!     tokens.to_string(),
+//!     r#"< ul > < li > "Tom & Jerry" < / li > < li class = "x" > "Bob" < / li > < / ul >"#
+//! );
+//! ```
+
+use std::fmt;
+
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+
+use crate::{
+    dom::{DomAttribute, DomElement, DomNode},
+    node::{CustomNode, Entity, Node, RAW_TEXT_ELEMENTS, VOID_ELEMENTS},
+    Error,
+};
+
+/// Elements that are implicitly closed by the open tag of the same element,
+/// e.g. `<li>a<li>b`.
+const SELF_NESTING_FORBIDDEN: &[&str] = &["li", "p", "option", "tr", "td", "th", "dt", "dd"];
+
+/// Parse `html` into DOM, never fails, see [module documentation](self).
+pub fn parse_html(html: &str) -> Vec<DomNode> {
+    HtmlParser { html, position: 0 }.parse()
+}
+
+/// Parse `html` and convert it to nodes, see [`DomNode::to_nodes`].
+///
+/// Fails if some element or attribute name is not valid rsx name, e.g.
+/// `@click`.
+pub fn html_to_nodes<C: CustomNode + fmt::Debug>(html: &str) -> Result<Vec<Node<C>>, Error> {
+    DomNode::to_nodes(&parse_html(html))
+}
+
+/// Parse `html` and convert it to rsx tokens.
+pub fn html_to_tokens(html: &str) -> Result<TokenStream, Error> {
+    let nodes: Vec<Node> = html_to_nodes(html)?;
+    let mut tokens = TokenStream::new();
+    for node in nodes {
+        node.to_tokens(&mut tokens);
+    }
+    Ok(tokens)
+}
+
+struct HtmlParser<'a> {
+    html: &'a str,
+    position: usize,
+}
+
+impl<'a> HtmlParser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.html[self.position..]
+    }
+
+    fn parse(mut self) -> Vec<DomNode> {
+        // Open elements, the first one is a root that holds top-level nodes.
+        let mut stack = vec![DomElement::default()];
+        let mut text = String::new();
+        while !self.rest().is_empty() {
+            let rest = self.rest();
+            if !rest.starts_with('<') {
+                let end = rest.find('<').unwrap_or(rest.len());
+                text.push_str(&rest[..end]);
+                self.position += end;
+                continue;
+            }
+            if let Some(comment) = rest.strip_prefix("<!--") {
+                push_text(&mut stack, &mut text);
+                // `<!-->` and `<!--->` are empty comments closed abruptly.
+                let abrupt = [">", "->"].into_iter().find(|end| comment.starts_with(end));
+                if let Some(end) = abrupt {
+                    push_child(&mut stack, DomNode::Comment(String::new()));
+                    self.position += 4 + end.len();
+                    continue;
+                }
+                let end = comment.find("-->");
+                let value = &comment[..end.unwrap_or(comment.len())];
+                push_child(&mut stack, DomNode::Comment(value.trim().to_string()));
+                self.position += 4 + end.map(|e| e + 3).unwrap_or(comment.len());
+            } else if rest.starts_with("<!") {
+                push_text(&mut stack, &mut text);
+                let end = rest.find('>').unwrap_or(rest.len());
+                let declaration = &rest[2..end];
+                if let Some(value) = strip_prefix_ignore_case(declaration, "doctype") {
+                    push_child(&mut stack, DomNode::Doctype(value.trim().to_string()));
+                }
+                self.position += (end + 1).min(rest.len());
+            } else if rest.starts_with("</") {
+                push_text(&mut stack, &mut text);
+                self.position += 2;
+                let name = self.take_name();
+                self.skip_past('>');
+                // Close the nearest element with this name, and all elements
+                // inside of it. Unknown close tags are ignored.
+                if let Some(i) = stack
+                    .iter()
+                    .rposition(|e| e.name.eq_ignore_ascii_case(&name))
+                    .filter(|i| *i > 0)
+                {
+                    while stack.len() > i {
+                        close(&mut stack);
+                    }
+                }
+            } else if rest[1..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+                push_text(&mut stack, &mut text);
+                self.position += 1;
+                self.open_tag(&mut stack);
+            } else {
+                text.push('<');
+                self.position += 1;
+            }
+        }
+        push_text(&mut stack, &mut text);
+        while stack.len() > 1 {
+            close(&mut stack);
+        }
+        stack.pop().map(|root| root.children).unwrap_or_default()
+    }
+
+    /// Parse open tag after `<`, and push element to `stack`.
+    fn open_tag(&mut self, stack: &mut Vec<DomElement>) {
+        let in_svg = stack.iter().any(|e| e.name == "svg");
+        let mut name = self.take_name();
+        if !in_svg {
+            name = name.to_ascii_lowercase();
+        }
+        // Attributes of svg elements are case-sensitive, e.g. `viewBox`.
+        let in_svg = in_svg || name == "svg";
+        let mut attributes = vec![];
+        let mut self_closed = false;
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.is_empty() {
+                break;
+            }
+            if let Some(after) = rest.strip_prefix('>') {
+                self.position = self.html.len() - after.len();
+                break;
+            }
+            if rest.starts_with("/>") {
+                self.position += 2;
+                self_closed = true;
+                break;
+            }
+            if rest.starts_with('/') {
+                self.position += 1;
+                continue;
+            }
+            let mut attribute = self.take_name();
+            if attribute.is_empty() {
+                // Skip unexpected character, like `=` without name.
+                self.position += rest.chars().next().map_or(1, char::len_utf8);
+                continue;
+            }
+            if !in_svg {
+                attribute = attribute.to_ascii_lowercase();
+            }
+            self.skip_whitespace();
+            let value = if self.rest().starts_with('=') {
+                self.position += 1;
+                self.skip_whitespace();
+                Some(Entity::decode_str(&self.take_value()))
+            } else {
+                None
+            };
+            attributes.push(DomAttribute {
+                name: attribute,
+                value,
+            });
+        }
+
+        if SELF_NESTING_FORBIDDEN.contains(&name.as_str()) {
+            if let Some(i) = stack
+                .iter()
+                .rposition(|e| e.name == name)
+                .filter(|i| *i > 0)
+            {
+                while stack.len() > i {
+                    close(stack);
+                }
+            }
+        }
+        let mut element = DomElement {
+            name,
+            attributes,
+            children: vec![],
+        };
+        let name = element.name.as_str();
+        if self_closed || VOID_ELEMENTS.contains(&name) {
+            push_child(stack, DomNode::Element(element));
+        } else if RAW_TEXT_ELEMENTS.contains(&name) {
+            let close_tag = format!("</{name}");
+            let rest = self.rest();
+            let end = rest
+                .to_ascii_lowercase()
+                .find(&close_tag)
+                .unwrap_or(rest.len());
+            let content = rest[..end].trim();
+            if !content.is_empty() {
+                element.children.push(DomNode::Text(content.to_string()));
+            }
+            self.position += end;
+            self.skip_past('>');
+            push_child(stack, DomNode::Element(element));
+        } else {
+            stack.push(element);
+        }
+    }
+
+    fn take_name(&mut self) -> String {
+        let rest = self.rest();
+        let end = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '>' | '/' | '=' | '<'))
+            .unwrap_or(rest.len());
+        self.position += end;
+        rest[..end].to_string()
+    }
+
+    fn take_value(&mut self) -> String {
+        let rest = self.rest();
+        match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let end = rest[1..].find(quote).map_or(rest.len(), |e| e + 1);
+                self.position += (end + 1).min(rest.len());
+                rest[1..end].to_string()
+            }
+            _ => {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || c == '>')
+                    .unwrap_or(rest.len());
+                self.position += end;
+                rest[..end].to_string()
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn skip_past(&mut self, c: char) {
+        let rest = self.rest();
+        self.position += rest.find(c).map_or(rest.len(), |e| e + c.len_utf8());
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = text.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &text[prefix.len()..])
+}
+
+fn push_child(stack: &mut [DomElement], child: DomNode) {
+    if let Some(parent) = stack.last_mut() {
+        parent.children.push(child);
+    }
+}
+
+/// Close the innermost open element.
+fn close(stack: &mut Vec<DomElement>) {
+    if let Some(element) = stack.pop() {
+        push_child(stack, DomNode::Element(element));
+    }
+}
+
+/// Push collected `text` as text node, with collapsed whitespace.
+///
+/// Whitespace at the start or the end of text is removed, if it contains line
+/// break, since it's most likely indentation.
+fn push_text(stack: &mut [DomElement], text: &mut String) {
+    let raw = std::mem::take(text);
+    let decoded = Entity::decode_str(&raw);
+    let mut collapsed = String::new();
+    for (i, word) in decoded.split_whitespace().enumerate() {
+        if i > 0 {
+            collapsed.push(' ');
+        }
+        collapsed.push_str(word);
+    }
+    if collapsed.is_empty() {
+        return;
+    }
+    let leading = &decoded[..decoded.len() - decoded.trim_start().len()];
+    let trailing = &decoded[decoded.trim_end().len()..];
+    if !leading.is_empty() && !leading.contains('\n') {
+        collapsed.insert(0, ' ');
+    }
+    if !trailing.is_empty() && !trailing.contains('\n') {
+        collapsed.push(' ');
+    }
+    push_child(stack, DomNode::Text(collapsed));
+}
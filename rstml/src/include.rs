@@ -0,0 +1,181 @@
+//! Resolution of template includes, like `<include src="header.html"/>`.
+//!
+//! [`IncludeResolver`] is a post-parse pass: it finds include elements in
+//! already parsed tree, loads referenced template with user callback (e.g.
+//! from string that macro obtained with `include_str!`), parses it with the
+//! same [`Parser`], and splices resulting nodes in place of include element.
+//! Includes inside of included templates are resolved too.
+//!
+//! Spans of included nodes are relative to the included source, when it is
+//! tokenized in `proc_macro2` fallback mode (e.g. in tests or build scripts).
+//! Inside of proc-macro they point to the macro call site, so every diagnostic
+//! of included template has a note pointing to the include element.
+//!
+//! ```rust
+//! use proc_macro2_diagnostics::Level;
+//! use quote::quote;
+//! use rstml::{include::IncludeResolver, Parser, ParserConfig};
+//!
+//! let parser = Parser::new(ParserConfig::new());
+//! let mut nodes = parser
+//!     .parse_simple(quote! { <div><include src="header"/></div> })
+//!     .unwrap();
+//! let diagnostics = IncludeResolver::new(|src| match src {
+//!     "header" => Ok(r#"<h1>"Title"</h1>"#.to_string()),
+//!     _ => Err(format!("unknown template `{src}`")),
+//! })
+//! .resolve(&parser, &mut nodes);
+//! assert!(diagnostics.iter().all(|d| d.level() != Level::Error));
+//! assert_eq!(
+//!     nodes[0].children().unwrap()[0].to_debug_tree(),
+//!     "h1\n  \"Title\"\n"
+//! );
+//! ```
+
+use proc_macro2::Span;
+use proc_macro2_diagnostics::{Diagnostic, Level};
+use syn::spanned::Spanned;
+
+use crate::{
+    node::{CustomNode, Node, NodeAttribute, NodeElement},
+    Parser,
+};
+
+/// Callback that returns source of template by value of `src` attribute.
+pub type LoadIncludeFn<'a> = dyn FnMut(&str) -> Result<String, String> + 'a;
+
+/// Replaces include elements with nodes of included templates, see
+/// [module documentation](self).
+pub struct IncludeResolver<'a> {
+    element: &'static str,
+    attribute: &'static str,
+    max_depth: usize,
+    load: Box<LoadIncludeFn<'a>>,
+}
+
+impl<'a> IncludeResolver<'a> {
+    /// Resolver of `<include src="..."/>` elements, `load` is called with
+    /// value of `src` and returns source of template, or error message.
+    pub fn new(load: impl FnMut(&str) -> Result<String, String> + 'a) -> Self {
+        Self {
+            element: "include",
+            attribute: "src",
+            max_depth: 16,
+            load: Box::new(load),
+        }
+    }
+
+    /// Set name of include element, `include` by default.
+    pub fn element(mut self, name: &'static str) -> Self {
+        self.element = name;
+        self
+    }
+
+    /// Set name of attribute with path of template, `src` by default.
+    pub fn attribute(mut self, name: &'static str) -> Self {
+        self.attribute = name;
+        self
+    }
+
+    /// Set how deep includes in included templates are resolved, 16 by
+    /// default. Deeper includes are reported, e.g. when template includes
+    /// itself.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Resolve includes in `nodes`, templates are parsed with `parser`.
+    ///
+    /// Include element is removed if template can't be loaded or parsed, and
+    /// the error is returned in diagnostics.
+    pub fn resolve<C: CustomNode + std::fmt::Debug>(
+        &mut self,
+        parser: &Parser<C>,
+        nodes: &mut Vec<Node<C>>,
+    ) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        self.resolve_nodes(parser, nodes, 0, &mut diagnostics);
+        diagnostics
+    }
+
+    fn resolve_nodes<C: CustomNode + std::fmt::Debug>(
+        &mut self,
+        parser: &Parser<C>,
+        nodes: &mut Vec<Node<C>>,
+        depth: usize,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let mut i = 0;
+        while i < nodes.len() {
+            let include = match &nodes[i] {
+                Node::Element(element) if element.name().to_string() == self.element => {
+                    Some(element.open_tag.name.span())
+                }
+                _ => None,
+            };
+            let Some(span) = include else {
+                if let Some(children) = nodes[i].children_mut() {
+                    self.resolve_nodes(parser, children, depth, diagnostics);
+                }
+                i += 1;
+                continue;
+            };
+            let Node::Element(element) = nodes.remove(i) else {
+                unreachable!("include is an element")
+            };
+            let mut included = self.load_template(parser, &element, span, depth, diagnostics);
+            self.resolve_nodes(parser, &mut included, depth + 1, diagnostics);
+            let len = included.len();
+            nodes.splice(i..i, included);
+            i += len;
+        }
+    }
+
+    /// Load and parse template of include `element`.
+    fn load_template<C: CustomNode + std::fmt::Debug>(
+        &mut self,
+        parser: &Parser<C>,
+        element: &NodeElement<C>,
+        span: Span,
+        depth: usize,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Vec<Node<C>> {
+        let mut error = |message: String| {
+            diagnostics.push(Diagnostic::spanned(span, Level::Error, message));
+            vec![]
+        };
+        if depth >= self.max_depth {
+            return error(format!(
+                "`{}` is nested deeper than {} levels",
+                self.element, self.max_depth
+            ));
+        }
+        let src = element
+            .attributes()
+            .iter()
+            .find_map(|attribute| match attribute {
+                NodeAttribute::Attribute(a) if a.key.to_string() == self.attribute => {
+                    a.value_literal_string()
+                }
+                _ => None,
+            });
+        let Some(src) = src else {
+            return error(format!(
+                "`{}` should have `{}` attribute with string literal",
+                self.element, self.attribute
+            ));
+        };
+        let source = match (self.load)(&src) {
+            Ok(source) => source,
+            Err(message) => return error(format!("failed to include `{src}`: {message}")),
+        };
+        let (nodes, errors) = parser.parse_str(&source).split_vec();
+        diagnostics.extend(
+            errors
+                .into_iter()
+                .map(|d| d.span_note(span, format!("in template `{src}` included here"))),
+        );
+        nodes
+    }
+}
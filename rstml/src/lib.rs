@@ -130,6 +130,7 @@
 //!   <{ let block = "in node name position"; } />
 //!   <div>{ let block = "in node position"; }</div>
 //!   <div { let block = "in attribute position"; } />
+//!   <div { let block = "in attribute key position"; }="value" />
 //!   <div key={ let block = "in attribute value position"; } />
 //!   # }).unwrap();
 //!   ```
@@ -198,17 +199,43 @@
 //! - **Customization**
 //!
 //!   A [`ParserConfig`] to customize parsing behavior is available, so if you
-//! have   slightly different requirements for parsing and it's not yet
-//! customizable   feel free to open an issue or pull request to extend the
-//! configuration.
+//!   have slightly different requirements for parsing and it's not yet
+//!   customizable feel free to open an issue or pull request to extend the
+//!   configuration.
 //!
 //!   One highlight with regards to customization is the [`transform_block`]
-//! configuration, which takes a closure that receives raw block content as
-//! `ParseStream` and lets you optionally convert it to a `TokenStream`. That
-//! makes it possible to have custom syntax in blocks. More details in [#9].
+//!   configuration, which takes a closure that receives raw block content as
+//!   `ParseStream` and lets you optionally convert it to a `TokenStream`. That
+//!   makes it possible to have custom syntax in blocks. More details in [#9].
 //!
 //!   Additionally, [`CustomNode`] can be used to implement fully custom
-//! parsing.
+//!   parsing.
+//!
+//! ## Cargo features
+//!
+//! By default parser, node types and lightweight helpers over them are built
+//! (and `colors` for diagnostics), `syn` is used without `visit`/`visit-mut`.
+//! Helpers that are always available:
+//!
+//! - `rstml::arena`: flat storage of parsed nodes, see [`Parser::parse_arena`].
+//! - `rstml::events`: flat SAX-style events of parsed trees.
+//! - `rstml::include`: resolution of template includes, like `<include
+//!   src="header.html"/>`.
+//! - `rstml::source_map`: mapping from parsed nodes back to the original input.
+//!
+//! Other parts are enabled by features:
+//!
+//! - `visitor`: traversal of node trees and rust code in them, see
+//!   `rstml::visitor`.
+//! - `html-validate`, `style-attribute`, `lint`: checks of parsed trees.
+//! - `format`, `codegen`, `dom`, `html-import`, `analysis`: conversion and
+//!   analysis of parsed trees.
+//! - `rawtext-stable-hack`: recovery of spaces in unquoted text on stable, see
+//!   `rstml::parse_source!`.
+//! - `tracing`: events of parsing steps.
+//!
+//! Custom nodes with control flow (`if`, `for`, `match`) are in
+//! `rstml-control-flow` crate.
 //!
 //! [`syn`]: /syn
 //! [`TokenStream`]: https://doc.rust-lang.org/proc_macro/struct.TokenStream.html
@@ -227,19 +254,60 @@ extern crate proc_macro;
 
 use syn::Result;
 
+#[cfg(feature = "analysis")]
+pub mod analysis;
+pub mod arena;
+#[cfg(feature = "codegen")]
+pub mod codegen;
 mod config;
+#[cfg(feature = "dom")]
+pub mod dom;
 mod error;
+pub mod events;
+#[cfg(feature = "format")]
+pub mod format;
+#[cfg(feature = "html-import")]
+pub mod import;
+pub mod include;
+#[cfg(feature = "lint")]
+pub mod lint;
+mod macros;
 pub mod node;
 mod parser;
 #[doc(hidden)] // Currently its api is not planned to be stable.
 #[cfg(feature = "rawtext-stable-hack-module")]
 pub mod rawtext_stable_hack;
+pub mod source_map;
+#[cfg(feature = "style-attribute")]
+pub mod style;
+mod trace;
+#[cfg(feature = "html-validate")]
+pub mod validate;
+#[cfg(feature = "visitor")]
 pub mod visitor;
-pub use config::ParserConfig;
-pub use error::Error;
+pub use config::{
+    AttributeValueParsing, ComponentRules, ConfigSummary, NamePolicy, ParserConfig, TextLiterals,
+};
+pub use error::{Error, ParserError};
 pub use node::{atoms, Infallible};
-use node::{CustomNode, Node};
-pub use parser::{recoverable, recoverable::ParsingResult, Parser};
+use node::{CustomNode, Node, NodeAttribute};
+pub use parser::{recoverable, recoverable::ParsingResult, ParseIter, Parser};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use quote;
+
+    #[cfg(feature = "rawtext-stable-hack")]
+    pub fn parse_source<C: crate::node::CustomNode + std::fmt::Debug>(
+        parser: &crate::Parser<C>,
+        source: &str,
+    ) -> crate::ParsingResult<Vec<crate::node::Node<C>>> {
+        match source.parse::<proc_macro2::TokenStream>() {
+            Ok(tokens) => parser.parse_with_source_text(tokens, source),
+            Err(error) => Err(syn::Error::from(error)).into(),
+        }
+    }
+}
 
 /// Parse the given [`proc-macro::TokenStream`] into a [`Node`] tree.
 ///
@@ -270,6 +338,28 @@ pub fn parse2(tokens: proc_macro2::TokenStream) -> Result<Vec<Node>> {
     Parser::new(ParserConfig::default()).parse_simple(tokens)
 }
 
+/// Parse list of attributes from [`ParseStream`], e.g. to reuse attribute
+/// syntax in macros like `props!(class="x" id="y")`.
+///
+/// See [`Parser::parse_attributes`] for details.
+///
+/// ```rust
+/// use rstml::node::NodeAttribute;
+/// use syn::parse::Parser;
+///
+/// let attributes = rstml::parse_attributes
+///     .parse2(quote::quote!(class="x" id={y} checked))
+///     .unwrap();
+/// assert_eq!(attributes.len(), 3);
+/// ```
+///
+/// [`ParseStream`]: syn::parse::ParseStream
+pub fn parse_attributes(input: syn::parse::ParseStream) -> Result<Vec<NodeAttribute>> {
+    Parser::new(ParserConfig::default())
+        .parse_attributes(input)
+        .into_result()
+}
+
 /// Parse the given [`proc-macro2::TokenStream`] into a [`Node`] tree with
 /// custom [`ParserConfig`].
 ///
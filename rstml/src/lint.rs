@@ -0,0 +1,238 @@
+//! Lint pass over parsed trees, available with `lint` feature.
+//!
+//! [`Linter`] runs a set of [`Rule`]s on every node of a tree and collects
+//! their diagnostics, which can be emitted by a macro the same way as parser
+//! diagnostics. [`lint`] runs all built-in rules:
+//! - [`DuplicateAttribute`]: the same attribute key used twice on element,
+//! - [`ImgWithoutAlt`]: `<img>` without `alt` attribute,
+//! - [`ElementNameCase`]: html element name with uppercase letters, like
+//!   `<dIv>`,
+//! - [`UnreachableAfterBlock`]: nodes after a block that never completes, like
+//!   `{return}` or `{panic!()}`.
+//!
+//! ```rust
+//! use rstml::{lint::lint, tree};
+//!
+//! let nodes = tree! { <img src="a.png" src="b.png" /> };
+//! let diagnostics = lint(&nodes);
+//! assert_eq!(diagnostics.len(), 2);
+//! ```
+
+use proc_macro2_diagnostics::{Diagnostic, Level};
+use syn::{spanned::Spanned, Expr, Stmt};
+
+use crate::{
+    node::{
+        CustomNode, Directive, ElementKind, Namespace, Node, NodeAttribute, NodeBlock, NodeName,
+    },
+    Infallible,
+};
+
+/// Single lint check.
+///
+/// Both methods do nothing by default, so rules implement only the one they
+/// need.
+pub trait Rule<C = Infallible> {
+    /// Check single node, called for every node of tree, parents before
+    /// children.
+    fn check_node(&self, _node: &Node<C>, _diagnostics: &mut Vec<Diagnostic>) {}
+
+    /// Check list of sibling nodes, called for top-level nodes and for
+    /// children of every element and fragment.
+    fn check_siblings(&self, _siblings: &[Node<C>], _diagnostics: &mut Vec<Diagnostic>) {}
+}
+
+/// Set of rules that are run together.
+pub struct Linter<C = Infallible> {
+    rules: Vec<Box<dyn Rule<C>>>,
+}
+
+impl<C: CustomNode> Default for Linter<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: CustomNode> Linter<C> {
+    /// Linter without any rules.
+    pub fn new() -> Self {
+        Self { rules: vec![] }
+    }
+
+    /// Linter with all built-in rules, see [module documentation](self).
+    pub fn with_default_rules() -> Self {
+        Self::new()
+            .rule(DuplicateAttribute)
+            .rule(ImgWithoutAlt)
+            .rule(ElementNameCase)
+            .rule(UnreachableAfterBlock)
+    }
+
+    /// Add `rule` to the set.
+    pub fn rule(mut self, rule: impl Rule<C> + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Run all rules on `nodes`, diagnostics are returned in order of rules
+    /// for every node.
+    ///
+    /// Elements marked with [`Directive::Ignore`] are skipped along with their
+    /// children.
+    pub fn lint(&self, nodes: &[Node<C>]) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        self.lint_siblings(nodes, &mut diagnostics);
+        diagnostics
+    }
+
+    fn lint_siblings(&self, nodes: &[Node<C>], diagnostics: &mut Vec<Diagnostic>) {
+        for rule in &self.rules {
+            rule.check_siblings(nodes, diagnostics);
+        }
+        for node in nodes {
+            if let Node::Element(element) = node {
+                if element.directives.contains(&Directive::Ignore) {
+                    continue;
+                }
+            }
+            for rule in &self.rules {
+                rule.check_node(node, diagnostics);
+            }
+            if let Some(children) = node.children() {
+                self.lint_siblings(children, diagnostics);
+            }
+        }
+    }
+}
+
+/// Run all built-in rules on `nodes`.
+pub fn lint<C: CustomNode>(nodes: &[Node<C>]) -> Vec<Diagnostic> {
+    Linter::with_default_rules().lint(nodes)
+}
+
+/// Reports attribute keys, that are used more than once on the same element.
+/// Keys computed from blocks are skipped.
+pub struct DuplicateAttribute;
+
+impl<C: CustomNode> Rule<C> for DuplicateAttribute {
+    fn check_node(&self, node: &Node<C>, diagnostics: &mut Vec<Diagnostic>) {
+        let Node::Element(element) = node else {
+            return;
+        };
+        let mut seen = vec![];
+        for attribute in element.all_attributes() {
+            let NodeAttribute::Attribute(attribute) = &*attribute else {
+                continue;
+            };
+            if attribute.key.is_block() {
+                continue;
+            }
+            let key = attribute.key.to_string();
+            if seen.contains(&key) {
+                diagnostics.push(Diagnostic::spanned(
+                    attribute.key.span(),
+                    Level::Warning,
+                    format!("duplicate attribute `{key}`"),
+                ));
+            } else {
+                seen.push(key);
+            }
+        }
+    }
+}
+
+/// Reports `<img>` elements without `alt` attribute. Elements with block
+/// attributes (`<img {..attrs}>`) are skipped, since they can provide it.
+pub struct ImgWithoutAlt;
+
+impl<C: CustomNode> Rule<C> for ImgWithoutAlt {
+    fn check_node(&self, node: &Node<C>, diagnostics: &mut Vec<Diagnostic>) {
+        let Node::Element(element) = node else {
+            return;
+        };
+        if element.namespace != Namespace::Html
+            || !element.name().to_string().eq_ignore_ascii_case("img")
+        {
+            return;
+        }
+        let has_alt = element.all_attributes().iter().any(|a| match &**a {
+            NodeAttribute::Attribute(a) => a.key.to_string() == "alt",
+            NodeAttribute::Block(_) => true,
+        });
+        if !has_alt {
+            diagnostics.push(Diagnostic::spanned(
+                element.name().span(),
+                Level::Warning,
+                "`img` element should have `alt` attribute",
+            ));
+        }
+    }
+}
+
+/// Reports names of html elements with uppercase letters, e.g. `<dIv>`.
+///
+/// Components, block names and elements inside of `<svg>` and `<math>`,
+/// which names are case-sensitive, are skipped.
+pub struct ElementNameCase;
+
+impl<C: CustomNode> Rule<C> for ElementNameCase {
+    fn check_node(&self, node: &Node<C>, diagnostics: &mut Vec<Diagnostic>) {
+        let Node::Element(element) = node else {
+            return;
+        };
+        if element.kind != ElementKind::HtmlElement
+            || element.namespace != Namespace::Html
+            || matches!(element.name(), NodeName::Block(_))
+        {
+            return;
+        }
+        let name = element.name().to_string();
+        if name.chars().any(|c| c.is_uppercase()) {
+            diagnostics.push(Diagnostic::spanned(
+                element.name().span(),
+                Level::Warning,
+                format!(
+                    "html element name `{name}` should be lowercase: `{}`",
+                    name.to_lowercase()
+                ),
+            ));
+        }
+    }
+}
+
+/// Reports the first node after a block, that never completes, e.g.
+/// `{return}`, `{break}`, `{continue}`, or a block ending with `panic!`,
+/// `unreachable!`, `todo!` or `unimplemented!`.
+pub struct UnreachableAfterBlock;
+
+impl<C: CustomNode> Rule<C> for UnreachableAfterBlock {
+    fn check_siblings(&self, siblings: &[Node<C>], diagnostics: &mut Vec<Diagnostic>) {
+        let diverging = siblings.iter().position(|node| match node {
+            Node::Block(NodeBlock::ValidBlock(block)) => {
+                block.stmts.last().is_some_and(is_diverging)
+            }
+            _ => false,
+        });
+        if let Some(next) = diverging.and_then(|i| siblings.get(i + 1)) {
+            diagnostics.push(Diagnostic::spanned(
+                next.span(),
+                Level::Warning,
+                "unreachable node after block that never completes",
+            ));
+        }
+    }
+}
+
+fn is_diverging(stmt: &Stmt) -> bool {
+    let mac = match stmt {
+        Stmt::Expr(Expr::Return(_) | Expr::Break(_) | Expr::Continue(_), _) => return true,
+        Stmt::Expr(Expr::Macro(mac), _) => &mac.mac,
+        Stmt::Macro(mac) => &mac.mac,
+        _ => return false,
+    };
+    mac.path.segments.last().is_some_and(|segment| {
+        ["panic", "unreachable", "todo", "unimplemented"]
+            .iter()
+            .any(|name| segment.ident == name)
+    })
+}
@@ -0,0 +1,105 @@
+//! Macros for constructing node trees, mostly useful in tests.
+
+/// Construct `Vec<`[`Node`]`>` from template.
+///
+/// Template is converted to tokens with [`quote!`], so `#var` can be used to
+/// interpolate values, and then parsed with [`Parser::parse_simple`]. By
+/// default [`ParserConfig::new`] is used, custom config can be provided with
+/// `config = ...;` prefix.
+///
+/// # Panics
+///
+/// Panics if template can't be parsed.
+///
+/// ```rust
+/// use rstml::{tree, ParserConfig};
+///
+/// let name = "world";
+/// let nodes = tree! { <div>"Hello"</div> <span>#name</span> };
+/// assert_eq!(nodes.len(), 2);
+///
+/// let config = ParserConfig::new().always_self_closed_elements(["br"].into());
+/// let nodes = tree! { config = config; <br> <br> };
+/// assert_eq!(nodes.len(), 2);
+/// ```
+///
+/// [`Node`]: crate::node::Node
+/// [`quote!`]: https://docs.rs/quote/latest/quote/macro.quote.html
+/// [`Parser::parse_simple`]: crate::Parser::parse_simple
+/// [`ParserConfig::new`]: crate::ParserConfig::new
+#[macro_export]
+macro_rules! tree {
+    (config = $config:expr; $($template:tt)*) => {
+        $crate::Parser::new($config)
+            .parse_simple($crate::__private::quote::quote!($($template)*))
+            .expect("failed to parse template")
+    };
+    ($($template:tt)*) => {
+        $crate::tree!(config = $crate::ParserConfig::new(); $($template)*)
+    };
+}
+
+/// Construct single [`Node`] from template, see [`tree!`] for details.
+///
+/// # Panics
+///
+/// Panics if template can't be parsed, or contains more than one node.
+///
+/// ```rust
+/// use rstml::{node, node::Node};
+///
+/// let Node::Element(element) = node!(<div class="item">"text"</div>) else {
+///     panic!("expected element")
+/// };
+/// assert_eq!(element.name().to_string(), "div");
+/// assert_eq!(element.children.len(), 1);
+/// ```
+///
+/// [`Node`]: crate::node::Node
+#[macro_export]
+macro_rules! node {
+    ($($template:tt)*) => {{
+        let mut nodes = $crate::tree!($($template)*);
+        assert_eq!(nodes.len(), 1, "expected exactly one node in template");
+        nodes.remove(0)
+    }};
+}
+
+/// Parse template written as string, like `include_str!("page.html")`, with
+/// [`Parser::parse_with_source_text`], so spaces in [`RawText`] are kept on
+/// stable. Returns [`ParsingResult`], custom config can be provided with
+/// `config = ...;` prefix, like in [`tree!`].
+///
+/// Unlike [`tree!`], template can't interpolate values with `#var`.
+///
+/// ```rust
+/// use rstml::{node::Node, parse_source, ParserConfig};
+///
+/// let nodes = parse_source!("<p> a  b </p>").into_result()?;
+/// let Node::Element(p) = &nodes[0] else {
+///     panic!("expected element")
+/// };
+/// let Node::RawText(text) = &p.children[0] else {
+///     panic!("expected raw text")
+/// };
+/// assert_eq!(text.to_string_best(), " a  b ");
+///
+/// let config = ParserConfig::new().always_self_closed_elements(["br"].into());
+/// let nodes = parse_source!(config = config; "<br><br>").into_result()?;
+/// assert_eq!(nodes.len(), 2);
+/// # Ok::<(), syn::Error>(())
+/// ```
+///
+/// [`Parser::parse_with_source_text`]: crate::Parser::parse_with_source_text
+/// [`RawText`]: crate::node::RawText
+/// [`ParsingResult`]: crate::ParsingResult
+#[cfg(feature = "rawtext-stable-hack")]
+#[macro_export]
+macro_rules! parse_source {
+    (config = $config:expr; $source:expr) => {
+        $crate::__private::parse_source(&$crate::Parser::new($config), $source)
+    };
+    ($source:expr) => {
+        $crate::parse_source!(config = $crate::ParserConfig::new(); $source)
+    };
+}
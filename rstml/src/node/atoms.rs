@@ -8,12 +8,12 @@
 //! `</close_tag>`
 
 use proc_macro2::Ident;
-use proc_macro2_diagnostics::{Diagnostic, Level};
-use syn::{ext::IdentExt, Token};
+use syn::{ext::IdentExt, spanned::Spanned, Token};
 
 use crate::{
     node::{parse, NodeAttribute, NodeName},
     parser::recoverable::RecoverableContext,
+    ParserError,
 };
 
 pub(crate) mod tokens {
@@ -115,11 +115,10 @@ impl FragmentClose {
         let start_tag = start_tag?;
         if input.peek(Ident::peek_any) {
             let ident_from_invalid_closing = Ident::parse_any(input).expect("parse after peek");
-            parser.push_diagnostic(Diagnostic::spanned(
+            parser.push_error(
+                ParserError::UnexpectedElementClose,
                 ident_from_invalid_closing.span(),
-                Level::Error,
-                "expected fragment closing, found element closing tag",
-            ));
+            );
         };
         Some(Self {
             start_tag,
@@ -130,7 +129,7 @@ impl FragmentClose {
 
 /// Open tag for element, possibly self-closed.
 /// `<name attr=x, attr_flag>`
-#[derive(Clone, Debug, syn_derive::ToTokens)]
+#[derive(Clone, Debug, PartialEq, syn_derive::ToTokens)]
 pub struct OpenTag {
     pub token_lt: Token![<],
     pub name: NodeName,
@@ -146,10 +145,49 @@ impl OpenTag {
     }
 }
 
+/// Open tag, that may be unfinished, e.g. `<name attr=x` without `>`.
+///
+/// Useful for tools that work with incomplete input, like editors. Can be
+/// converted to [`OpenTag`] with `TryFrom`, if tag has end.
+#[derive(Clone, Debug, PartialEq, syn_derive::ToTokens)]
+pub struct PartialOpenTag {
+    pub token_lt: Token![<],
+    pub name: NodeName,
+    pub generics: syn::Generics,
+    #[to_tokens(parse::to_tokens_array)]
+    pub attributes: Vec<NodeAttribute>,
+    pub end_tag: Option<tokens::OpenTagEnd>,
+}
+
+impl PartialOpenTag {
+    /// Returns true if tag ends with `>` or `/>`.
+    pub fn is_complete(&self) -> bool {
+        self.end_tag.is_some()
+    }
+}
+
+impl TryFrom<PartialOpenTag> for OpenTag {
+    type Error = PartialOpenTag;
+
+    fn try_from(tag: PartialOpenTag) -> Result<Self, Self::Error> {
+        match tag.end_tag {
+            Some(end_tag) => Ok(OpenTag {
+                token_lt: tag.token_lt,
+                name: tag.name,
+                generics: tag.generics,
+                attributes: tag.attributes,
+                end_tag,
+            }),
+            None => Err(tag),
+        }
+    }
+}
+
 /// Open tag for element, `<name attr=x, attr_flag>`
-#[derive(Clone, Debug, syn_derive::Parse, syn_derive::ToTokens)]
+#[derive(Clone, Debug, PartialEq, syn_derive::Parse, syn_derive::ToTokens)]
 pub struct CloseTag {
     pub start_tag: tokens::CloseTagStart,
+    #[parse(NodeName::parse_element_name)]
     pub name: NodeName,
     pub generics: syn::Generics,
     pub token_gt: Token![>],
@@ -161,10 +199,21 @@ impl CloseTag {
         input: syn::parse::ParseStream,
         start_tag: Option<tokens::CloseTagStart>,
     ) -> Option<Self> {
+        let start_tag = start_tag?;
+        let name = parser.save_diagnostics(NodeName::parse_element_name(input))?;
+        let generics = parser.parse_simple(input)?;
+        // Attributes are parsed only to report them, e.g. in pasted html.
+        if !input.peek(Token![>]) {
+            let attributes = NodeAttribute::parse_list(parser, input);
+            if let (Some(first), Some(last)) = (attributes.first(), attributes.last()) {
+                let span = first.span().join(last.span()).unwrap_or(first.span());
+                parser.push_error(ParserError::CloseTagAttributes, span);
+            }
+        }
         Some(Self {
-            start_tag: start_tag?,
-            name: parser.parse_simple(input)?,
-            generics: parser.parse_simple(input)?,
+            start_tag,
+            name,
+            generics,
             token_gt: parser.parse_simple(input)?,
         })
     }
@@ -1,30 +1,43 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream, TokenTree};
 use quote::ToTokens;
 use syn::{
-    parse::{discouraged::Speculative, Parse, ParseStream},
-    parse_quote,
+    buffer::Cursor,
+    parse::{discouraged::Speculative, Parse, ParseStream, Parser},
     punctuated::Punctuated,
     spanned::Spanned,
     token::{Brace, Comma, Paren},
-    Attribute, Expr, Lit, Pat, PatType, Token,
+    Attribute, Expr, ExprBlock, ExprLit, ExprUnary, Lit, LitStr, Pat, PatType, Token, UnOp,
 };
 
-use super::{parse::parse_valid_block_expr, InvalidBlock};
+use super::{
+    atoms::OpenTagEnd,
+    node_name::is_adjacent,
+    parse::{peek_block, take_invalid_block},
+    AttributeSugar, InvalidBlock,
+};
 use crate::{
-    node::{NodeBlock, NodeName},
+    config::{AttributeValueParsing, ErrorMessageFn},
+    node::{Name, NodeBlock, NodeName, NodeNameFragment},
     parser::recoverable::{ParseRecoverable, RecoverableContext},
+    trace, ParserError,
 };
 
-#[derive(Clone, Debug, syn_derive::ToTokens)]
+#[derive(Clone, Debug, PartialEq, syn_derive::ToTokens)]
 pub struct AttributeValueExpr {
     pub token_eq: Token![=],
     pub value: KVAttributeValue,
 }
 
-#[derive(Clone, Debug, syn_derive::ToTokens)]
+#[derive(Clone, Debug, PartialEq, syn_derive::ToTokens)]
 pub enum KVAttributeValue {
     Expr(Expr),
     InvalidBraced(InvalidBlock),
+    /// Value is missing, e.g. `attr=` at the end of tag.
+    ///
+    /// Produced only with [`ParserConfig::recover_incomplete`].
+    ///
+    /// [`ParserConfig::recover_incomplete`]: crate::ParserConfig::recover_incomplete
+    Incomplete,
 }
 
 impl AttributeValueExpr {
@@ -37,6 +50,7 @@ impl AttributeValueExpr {
     /// `"string"`      // string
     /// `'c'`           // char
     /// `0x12`, `1231`  // integer - converted to decimal form
+    /// `-1`, `-0.5`    // negative numbers
     /// `0.12`          // float point value - converted to decimal form
     /// `true`, `false` // booleans
     ///
@@ -54,35 +68,124 @@ impl AttributeValueExpr {
     ///
     /// Adapted from leptos
     pub fn value_literal_string(&self) -> Option<String> {
-        match &self.value {
-            KVAttributeValue::Expr(Expr::Lit(l)) => match &l.lit {
-                Lit::Str(s) => Some(s.value()),
-                Lit::Char(c) => Some(c.value().to_string()),
-                Lit::Int(i) => Some(i.base10_digits().to_string()),
-                Lit::Float(f) => Some(f.base10_digits().to_string()),
-                Lit::Bool(b) => Some(b.value.to_string()),
+        match self.literal()? {
+            Literal::Positive(Lit::Str(s)) => Some(s.value()),
+            Literal::Positive(Lit::Char(c)) => Some(c.value().to_string()),
+            Literal::Positive(Lit::Bool(b)) => Some(b.value.to_string()),
+            literal => literal.number_string(),
+        }
+    }
+
+    /// Returns value as integer, if it is integer literal, e.g. `42`, `0x12`
+    /// or `-1`.
+    pub fn value_int(&self) -> Option<i128> {
+        self.literal()?.int()
+    }
+
+    /// Returns value as float, if it is integer or float literal, e.g. `0.5`,
+    /// `1` or `-1.5`.
+    pub fn value_float(&self) -> Option<f64> {
+        self.literal()?.float()
+    }
+
+    /// Returns value of boolean literal.
+    pub fn value_bool(&self) -> Option<bool> {
+        match self.literal()? {
+            Literal::Positive(Lit::Bool(b)) => Some(b.value),
+            _ => None,
+        }
+    }
+
+    /// Returns literal of the value, numeric literals can be negated.
+    fn literal(&self) -> Option<Literal<'_>> {
+        let KVAttributeValue::Expr(expr) = &self.value else {
+            return None;
+        };
+        Literal::of(expr)
+    }
+}
+
+/// Literal in attribute value, with sign.
+pub(super) enum Literal<'a> {
+    Positive(&'a Lit),
+    Negative(&'a Lit),
+}
+
+impl<'a> Literal<'a> {
+    /// Literal of expression, numeric literals can be negated.
+    pub(super) fn of(expr: &'a Expr) -> Option<Self> {
+        match expr {
+            Expr::Lit(l) => Some(Literal::Positive(&l.lit)),
+            Expr::Unary(ExprUnary {
+                op: UnOp::Neg(_),
+                expr,
+                ..
+            }) => match &**expr {
+                Expr::Lit(l) if matches!(l.lit, Lit::Int(_) | Lit::Float(_)) => {
+                    Some(Literal::Negative(&l.lit))
+                }
                 _ => None,
             },
             _ => None,
         }
     }
+
+    /// Value of integer literal.
+    pub(super) fn int(&self) -> Option<i128> {
+        match self {
+            Literal::Positive(Lit::Int(_)) | Literal::Negative(Lit::Int(_)) => {
+                self.number_string()?.parse().ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Value of integer or float literal.
+    pub(super) fn float(&self) -> Option<f64> {
+        self.number_string()?.parse().ok()
+    }
+
+    /// Decimal representation of numeric literal.
+    fn number_string(&self) -> Option<String> {
+        let (sign, lit) = match self {
+            Literal::Positive(lit) => ("", lit),
+            Literal::Negative(lit) => ("-", lit),
+        };
+        let digits = match lit {
+            Lit::Int(i) => i.base10_digits(),
+            Lit::Float(f) => f.base10_digits(),
+            _ => return None,
+        };
+        Some(format!("{sign}{digits}"))
+    }
 }
 
-#[derive(Clone, Debug, syn_derive::ToTokens)]
+#[derive(Clone, Debug, PartialEq, syn_derive::ToTokens)]
+#[allow(clippy::large_enum_variant)]
 pub enum KeyedAttributeValue {
     Binding(FnBinding),
     Value(AttributeValueExpr),
-    None,
+    /// Attribute without value, e.g. `<input disabled>`.
+    ///
+    /// Can be rejected with [`ParserConfig::reject_flag_attributes`].
+    ///
+    /// [`ParserConfig::reject_flag_attributes`]: crate::ParserConfig::reject_flag_attributes
+    Flag,
 }
 
 impl KeyedAttributeValue {
     pub fn to_value(&self) -> Option<&AttributeValueExpr> {
         match self {
             KeyedAttributeValue::Value(v) => Some(v),
-            KeyedAttributeValue::None => None,
+            KeyedAttributeValue::Flag => None,
             KeyedAttributeValue::Binding(_) => None,
         }
     }
+
+    /// Returns true if attribute has no value.
+    pub fn is_flag(&self) -> bool {
+        matches!(self, KeyedAttributeValue::Flag)
+    }
 }
 ///
 /// Element attribute with fixed key.
@@ -90,13 +193,60 @@ impl KeyedAttributeValue {
 /// Example:
 /// key=value // attribute with ident as value
 /// key // attribute without value
-#[derive(Clone, Debug, syn_derive::ToTokens)]
+/// {key}=value // attribute with key computed from block, see
+/// [`NodeName::Block`]
+#[derive(Clone, Debug)]
 pub struct KeyedAttribute {
     /// Key of the element attribute.
     pub key: NodeName,
     /// Value of the element attribute.
     pub possible_value: KeyedAttributeValue,
+    /// Shorthand (`#id` or `.class`) that produced this attribute, see
+    /// [`ParserConfig::tag_sugar`].
+    ///
+    /// If set, `key` and `possible_value` are synthetic, and attribute is
+    /// converted to tokens as it was written in source.
+    ///
+    /// [`ParserConfig::tag_sugar`]: crate::ParserConfig::tag_sugar
+    pub sugar: Option<AttributeSugar>,
+    /// Index of attribute in open tag it was parsed from, `None` for
+    /// attributes that was created after parsing.
+    ///
+    /// Unlike index in [`NodeElement::attributes`], it is kept when
+    /// attributes are reordered, so it can be reported in diagnostics.
+    ///
+    /// [`NodeElement::attributes`]: super::NodeElement::attributes
+    pub position: Option<usize>,
+    /// Key of attribute interned by parser, see
+    /// [`NodeElement::interned_name`]. `None` for block keys and attributes
+    /// that was created after parsing.
+    ///
+    /// [`NodeElement::interned_name`]: super::NodeElement::interned_name
+    pub interned_key: Option<Name>,
+}
+
+// Position and interned key are ignored, like spans, so attributes are equal
+// regardless of where they were parsed, or if they were created after parsing.
+impl PartialEq for KeyedAttribute {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+            && self.possible_value == other.possible_value
+            && self.sugar == other.sugar
+    }
+}
+
+impl ToTokens for KeyedAttribute {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match &self.sugar {
+            Some(sugar) => sugar.to_tokens(tokens),
+            None => {
+                self.key.to_tokens(tokens);
+                self.possible_value.to_tokens(tokens);
+            }
+        }
+    }
 }
+
 impl KeyedAttribute {
     ///
     /// Returns string representation of inner value,
@@ -129,29 +279,320 @@ impl KeyedAttribute {
             .and_then(|v| v.value_literal_string())
     }
 
+    /// See [`AttributeValueExpr::value_int`].
+    pub fn value_int(&self) -> Option<i128> {
+        self.possible_value.to_value().and_then(|v| v.value_int())
+    }
+
+    /// See [`AttributeValueExpr::value_float`].
+    pub fn value_float(&self) -> Option<f64> {
+        self.possible_value.to_value().and_then(|v| v.value_float())
+    }
+
+    /// See [`AttributeValueExpr::value_bool`].
+    pub fn value_bool(&self) -> Option<bool> {
+        self.possible_value.to_value().and_then(|v| v.value_bool())
+    }
+
+    /// Returns true if attribute has no value, e.g. `disabled`.
+    pub fn is_flag(&self) -> bool {
+        self.possible_value.is_flag()
+    }
+
+    /// Treat attribute as boolean, like html does for `disabled` or
+    /// `checked`: attribute without value is `true`, otherwise value is
+    /// expected to be boolean literal, e.g. `hidden=false`.
+    ///
+    /// Returns `None` for any other value.
+    pub fn as_bool(&self) -> Option<bool> {
+        if self.is_flag() {
+            return Some(true);
+        }
+        self.value_bool()
+    }
+
+    /// Returns true if attribute has no value, or its value is a literal,
+    /// e.g. `class="x"`, see [`Node::is_static`](super::Node::is_static).
+    pub fn is_static(&self) -> bool {
+        match &self.possible_value {
+            KeyedAttributeValue::Flag => true,
+            KeyedAttributeValue::Value(value) => {
+                matches!(&value.value, KVAttributeValue::Expr(Expr::Lit(_)))
+            }
+            KeyedAttributeValue::Binding(_) => false,
+        }
+    }
+
+    /// Returns true if attribute is produced by `#id` or `.class` shorthand.
+    pub fn is_sugar(&self) -> bool {
+        self.sugar.is_some()
+    }
+
     pub fn value(&self) -> Option<&Expr> {
-        self.possible_value
-            .to_value()
-            .map(|v| match &v.value {
-                KVAttributeValue::Expr(expr) => Some(expr),
-                KVAttributeValue::InvalidBraced(_) => None,
+        self.possible_value.to_value().and_then(|v| match &v.value {
+            KVAttributeValue::Expr(expr) => Some(expr),
+            KVAttributeValue::InvalidBraced(_) | KVAttributeValue::Incomplete => None,
+        })
+    }
+
+    /// Parse attribute key, that is bounded by the end of open tag.
+    ///
+    /// Name is parsed directly from `input`, so key with trailing punct, that
+    /// is followed by the end of tag (`<div data->`), would get an extra
+    /// `Empty` fragment. It is removed, to keep such key ending with punct.
+    pub(crate) fn parse_key(input: ParseStream) -> syn::Result<NodeName> {
+        let fork = input.fork();
+        let mut key = NodeName::parse(&fork)?;
+        if let NodeName::Punctuated(name) = &mut key {
+            let at_tag_end = fork.is_empty() || fork.fork().parse::<OpenTagEnd>().is_ok();
+            if at_tag_end && matches!(name.last(), Some(NodeNameFragment::Empty)) {
+                name.pop();
+                if name.len() < 2 {
+                    return Err(fork.error("expected punctuated node name"));
+                }
+            }
+        }
+        input.advance_to(&fork);
+        Ok(key)
+    }
+
+    /// Parse attribute value, that is bounded by the end of open tag (`>` or
+    /// `/>`), see [`AttributeValueParsing`].
+    ///
+    /// Expression is parsed directly from a fork of `input`, and it is used as
+    /// is, unless it crosses a possible end of tag (e.g. `x > y`, or `x`
+    /// followed by end of tag). `>` that closes generics, like in
+    /// `Vec::<u8>::new()`, is never treated as the end of tag. Syn can't
+    /// bound a `ParseStream`, so only expressions that cross the end of tag
+    /// are parsed again from the tokens before an end.
+    fn parse_value_expr(parser: &mut RecoverableContext, input: ParseStream) -> syn::Result<Expr> {
+        let fork = input.fork();
+        let (ends, crossing) = match fork.parse::<Expr>() {
+            Ok(expr) => {
+                let ends = Self::value_ends(input, Some(fork.cursor()));
+                if ends.is_empty() {
+                    input.advance_to(&fork);
+                    return Ok(expr);
+                }
+                if fork.is_empty() || fork.fork().parse::<OpenTagEnd>().is_ok() {
+                    // Expression that crosses the first end is the longest one.
+                    (ends, Some((expr, fork)))
+                } else {
+                    (Self::value_ends(input, None), None)
+                }
+            }
+            Err(_) => (Self::value_ends(input, None), None),
+        };
+        let first_end = ends.first().copied().unwrap_or_else(|| {
+            let fork = input.fork();
+            let _ = fork.parse::<TokenStream>();
+            fork.cursor()
+        });
+
+        // Longest expression, that crosses the first end of tag.
+        let crossing = crossing.or_else(|| {
+            ends.iter().skip(1).rev().find_map(|&end| {
+                let fork = input.fork();
+                let expr = Self::parse_value_until(parser, &fork, end).ok()?;
+                (fork.cursor() > first_end).then_some((expr, fork))
             })
-            .flatten()
+        });
+        match crossing {
+            Some((expr, fork))
+                if parser.config().attribute_value_parsing == AttributeValueParsing::Greedy =>
+            {
+                input.advance_to(&fork);
+                Ok(expr)
+            }
+            Some(_) => {
+                trace::debug!("attribute value crosses end of tag, using the first end");
+                parser.push_error(ParserError::AmbiguousAttributeValue, first_end.span());
+                Self::parse_value_until(parser, input, first_end)
+            }
+            None => Self::parse_value_until(parser, input, first_end),
+        }
+    }
+
+    /// Cursors of possible ends of tag in attribute value, which are not `>`
+    /// of generics (`Vec::<u8>::new()`, `|e: Event<Click>| ..`).
+    ///
+    /// Scan stops at `bound`, or at the first `<` after an end, which can't be
+    /// a part of open tag.
+    fn value_ends<'a>(input: ParseStream<'a>, bound: Option<Cursor<'a>>) -> Vec<Cursor<'a>> {
+        let scan = input.fork();
+        let mut ends = vec![];
+        let mut count = 0;
+        let mut generics = 0usize;
+        let mut closure_params = false;
+        let mut after_path_sep = false;
+        while !scan.is_empty()
+            && Some(scan.cursor()) != bound
+            && (ends.is_empty() || !scan.peek(Token![<]))
+        {
+            let path_sep = scan.peek(Token![::]);
+            if scan.peek(Token![->]) || scan.peek(Token![=>]) || scan.peek(Token![||]) || path_sep {
+                // Two tokens, which are not the end of tag, nor generics.
+                count += 1;
+                let _ = scan.parse::<TokenTree>();
+            } else if scan.peek(Token![|]) && (count == 0 || closure_params) {
+                closure_params = !closure_params;
+            } else if scan.peek(Token![<])
+                && !scan.peek(Token![<=])
+                && !scan.peek(Token![<<])
+                && (generics > 0 || count == 0 || after_path_sep || closure_params)
+            {
+                generics += 1;
+            } else if generics > 0 && scan.peek(Token![>]) {
+                generics -= 1;
+            } else if generics == 0 && scan.fork().parse::<OpenTagEnd>().is_ok() {
+                ends.push(scan.cursor());
+            }
+            after_path_sep = path_sep;
+            count += 1;
+            if scan.parse::<TokenTree>().is_err() {
+                break;
+            }
+        }
+        ends
+    }
+
+    /// Parse expression from tokens of `input` before `end`, and skip the
+    /// rest of them.
+    fn parse_value_until(
+        parser: &RecoverableContext,
+        input: ParseStream,
+        end: Cursor,
+    ) -> syn::Result<Expr> {
+        let mut tokens = TokenStream::new();
+        let mut total = 0;
+        let mut cursor = input.cursor();
+        while let Some((token, next)) = cursor.token_tree().filter(|_| cursor != end) {
+            tokens.extend([token]);
+            total += 1;
+            cursor = next;
+        }
+        let error_message = parser.config().error_message.clone();
+        let parse_bounded = |input: ParseStream| {
+            let fork = input.fork();
+            let expr = match fork.parse::<Expr>() {
+                Ok(expr) => {
+                    input.advance_to(&fork);
+                    Ok(expr)
+                }
+                // if we stuck on end of input, span that is created will be call_site,
+                // so we need to correct it, in order to
+                // make it more IDE friendly.
+                // This also skips the rest of tokens before the end of tag.
+                Err(e) if fork.is_empty() => Err(KeyedAttribute::correct_expr_error_span(
+                    error_message.as_deref(),
+                    e,
+                    input,
+                )),
+                Err(e) => Err(e),
+            };
+            let rest = input.parse::<TokenStream>()?.into_iter().count();
+            Ok((expr, rest))
+        };
+        let (expr, rest) = parse_bounded.parse2(tokens)?;
+        for _ in 0..total - rest {
+            input.parse::<TokenTree>()?;
+        }
+        expr
+    }
+
+    /// Parse numeric literal joined with other literals or idents by `-` or
+    /// `/`, like `1/2` or `10px-auto`, numeric literal with unit suffix, like
+    /// `10px` or `1.5em`, or followed by `%`, as string literal, see
+    /// [`ParserConfig::unquoted_values`](crate::ParserConfig::unquoted_values).
+    ///
+    /// Parts of value should be written without whitespace between them.
+    /// Tokens without location are considered adjacent.
+    fn parse_unquoted_value(input: ParseStream) -> Option<Expr> {
+        // Suffixes of rust numeric types, that are kept as numbers.
+        const NUMERIC_SUFFIXES: &[&str] = &[
+            "", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128",
+            "usize", "f32", "f64",
+        ];
+        let number_suffix = |token: &TokenTree| match token {
+            TokenTree::Literal(literal) => match Lit::new(literal.clone()) {
+                Lit::Int(int) => Some(!NUMERIC_SUFFIXES.contains(&int.suffix())),
+                Lit::Float(float) => Some(!NUMERIC_SUFFIXES.contains(&float.suffix())),
+                _ => None,
+            },
+            _ => None,
+        };
+        let fork = input.fork();
+        let first = fork.parse::<TokenTree>().ok()?;
+        let mut raw = number_suffix(&first)?;
+        let mut text = first.to_string();
+        let mut last = first.span();
+        let percent = |fork: ParseStream, text: &mut String, last: &mut Span| {
+            if fork.peek(Token![%]) && !fork.peek(Token![%=]) && is_adjacent(*last, fork.span()) {
+                let punct = fork.parse::<TokenTree>().ok()?;
+                text.push_str(&punct.to_string());
+                *last = punct.span();
+                return Some(true);
+            }
+            Some(false)
+        };
+        raw |= percent(&fork, &mut text, &mut last)?;
+        while (fork.peek(Token![-]) || fork.peek(Token![/]))
+            && !fork.peek(Token![-=])
+            && !fork.peek(Token![->])
+            && !fork.peek(Token![/=])
+            && fork.fork().parse::<OpenTagEnd>().is_err()
+            && is_adjacent(last, fork.span())
+        {
+            let punct_fork = fork.fork();
+            let punct = punct_fork.parse::<TokenTree>().ok()?;
+            let part = punct_fork.parse::<TokenTree>().ok()?;
+            if !is_adjacent(punct.span(), part.span()) {
+                break;
+            }
+            let is_number = number_suffix(&part).is_some();
+            if !is_number && !matches!(part, TokenTree::Ident(_)) {
+                return None;
+            }
+            fork.advance_to(&punct_fork);
+            text.push_str(&punct.to_string());
+            text.push_str(&part.to_string());
+            last = part.span();
+            if is_number {
+                percent(&fork, &mut text, &mut last)?;
+            }
+            raw = true;
+        }
+        let span = first.span().join(last).unwrap_or(first.span());
+        // Single number, or value that continues as expression.
+        let continues =
+            fork.cursor().punct().is_some() && fork.fork().parse::<OpenTagEnd>().is_err();
+        if !raw || continues {
+            return None;
+        }
+        input.advance_to(&fork);
+        Some(Expr::Lit(ExprLit {
+            attrs: vec![],
+            lit: Lit::Str(LitStr::new(&text, span)),
+        }))
     }
 
     // Checks if error is about eof.
     // This error is known to report Span::call_site.
     // Correct them to point to ParseStream
-    pub(crate) fn correct_expr_error_span(error: syn::Error, input: ParseStream) -> syn::Error {
+    pub(crate) fn correct_expr_error_span(
+        error_message: Option<&ErrorMessageFn>,
+        error: syn::Error,
+        input: ParseStream,
+    ) -> syn::Error {
         let error_str = error.to_string();
         if error_str.starts_with("unexpected end of input") {
             let stream = input
                 .parse::<TokenStream>()
                 .expect("BUG: Token stream should always be parsable");
-            return syn::Error::new(
-                stream.span(),
-                format!("failed to parse expression: {}", error),
-            );
+            let error = ParserError::InvalidExpression(error.to_string());
+            return error
+                .spanned_with_message(stream.span(), error.message(error_message))
+                .into();
         }
         error
     }
@@ -159,7 +600,7 @@ impl KeyedAttribute {
 
 /// Represent arguments of closure.
 /// One can use it to represent variable binding from one scope to another.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct FnBinding {
     pub paren: Paren,
     pub inputs: Punctuated<Pat, Comma>,
@@ -183,6 +624,7 @@ fn closure_arg(input: ParseStream) -> syn::Result<Pat> {
             Pat::Lit(pat) => pat.attrs = attrs,
             Pat::Macro(pat) => pat.attrs = attrs,
             Pat::Or(pat) => pat.attrs = attrs,
+            Pat::Paren(pat) => pat.attrs = attrs,
             Pat::Path(pat) => pat.attrs = attrs,
             Pat::Range(pat) => pat.attrs = attrs,
             Pat::Reference(pat) => pat.attrs = attrs,
@@ -194,7 +636,9 @@ fn closure_arg(input: ParseStream) -> syn::Result<Pat> {
             Pat::Type(_) => unreachable!("BUG: Type handled in if"),
             Pat::Verbatim(_) => {}
             Pat::Wild(pat) => pat.attrs = attrs,
-            _ => unreachable!(),
+            Pat::Const(pat) => pat.attrs = attrs,
+            _ if attrs.is_empty() => {}
+            _ => return Err(ParserError::UnsupportedPatternAttributes.error(pat.span())),
         }
         Ok(pat)
     }
@@ -203,7 +647,8 @@ fn closure_arg(input: ParseStream) -> syn::Result<Pat> {
 /// Sum type for Dyn and Keyed attributes.
 ///
 /// Attributes is stored in opening tags.
-#[derive(Clone, Debug, syn_derive::ToTokens)]
+#[derive(Clone, Debug, PartialEq, syn_derive::ToTokens)]
+#[allow(clippy::large_enum_variant)]
 pub enum NodeAttribute {
     ///
     /// Element attribute that is computed from rust code block.
@@ -229,58 +674,59 @@ pub enum NodeAttribute {
 
 impl ParseRecoverable for KeyedAttribute {
     fn parse_recoverable(parser: &mut RecoverableContext, input: ParseStream) -> Option<Self> {
-        let key = NodeName::parse(input)
+        let key = KeyedAttribute::parse_key(input)
             .map_err(|e| parser.push_diagnostic(e))
             .ok()?;
+        trace::trace!(key = %key, "attribute");
 
         let possible_value = if input.peek(Paren) {
-            KeyedAttributeValue::Binding(
-                FnBinding::parse(input)
-                    .map_err(|e| parser.push_diagnostic(e))
-                    .ok()?,
-            )
+            // Parse on fork, and skip the whole group even if binding is invalid,
+            // so tokens inside parens will not be reported twice.
+            let binding = FnBinding::parse(&input.fork());
+            parser.parse_simple::<TokenTree>(input)?;
+            KeyedAttributeValue::Binding(binding.map_err(|e| parser.push_diagnostic(e)).ok()?)
         } else if input.peek(Token![=]) {
             let eq = input
                 .parse::<Token![=]>()
                 .map_err(|e| parser.push_diagnostic(e))
                 .ok()?;
-            if input.is_empty() {
-                parser.push_diagnostic(syn::Error::new(eq.span(), "missing attribute value"));
-                return None;
-            }
-
-            let fork = input.fork();
-
-            let rs = match parse_valid_block_expr(parser, &fork) {
-                Ok(vbl) => {
-                    input.advance_to(&fork);
-                    KVAttributeValue::Expr(parse_quote!(#vbl))
-                }
-
-                Err(err) if input.fork().peek(Brace) && parser.config().recover_block => {
-                    parser.push_diagnostic(err);
-                    let ivb = parser.parse_simple(input)?;
-                    KVAttributeValue::InvalidBraced(ivb)
+            // Value is missing, if `=` is followed by the end of tag.
+            let recover_incomplete = parser.config().recover_incomplete;
+            let rs = if input.is_empty() || input.fork().parse::<OpenTagEnd>().is_ok() {
+                parser.push_error(ParserError::MissingAttributeValue, eq.span());
+                if !recover_incomplete {
+                    return None;
                 }
-                Err(_) => {
-                    let res = fork
-                        .parse::<Expr>()
-                        .map_err(|e| {
-                            // if we stuck on end of input, span that is created will be call_site,
-                            // so we need to correct it, in order to
-                            // make it more IDE friendly.
-                            if fork.is_empty() {
-                                KeyedAttribute::correct_expr_error_span(e, input)
-                            } else {
-                                e
-                            }
-                        })
-                        .map_err(|e| parser.push_diagnostic(e))
-                        .ok()?;
-
-                    input.advance_to(&fork);
-                    KVAttributeValue::Expr(res)
+                KVAttributeValue::Incomplete
+            } else if peek_block(input, parser.config().block_delimiters()) {
+                match parser.parse_recoverable(input) {
+                    Some(NodeBlock::ValidBlock(block)) => {
+                        KVAttributeValue::Expr(Expr::Block(ExprBlock {
+                            attrs: vec![],
+                            label: None,
+                            block,
+                        }))
+                    }
+                    Some(NodeBlock::Invalid(ivb)) => KVAttributeValue::InvalidBraced(ivb),
+                    None => {
+                        // Skip the block, so it will not be reported again as
+                        // a separate attribute.
+                        take_invalid_block(parser, input);
+                        return None;
+                    }
                 }
+            } else if let Some(value) = parser
+                .config()
+                .unquoted_values
+                .then(|| KeyedAttribute::parse_unquoted_value(input))
+                .flatten()
+            {
+                KVAttributeValue::Expr(value)
+            } else {
+                let res = KeyedAttribute::parse_value_expr(parser, input)
+                    .map_err(|e| parser.push_diagnostic(e))
+                    .ok()?;
+                KVAttributeValue::Expr(res)
             };
 
             KeyedAttributeValue::Value(AttributeValueExpr {
@@ -288,19 +734,79 @@ impl ParseRecoverable for KeyedAttribute {
                 value: rs,
             })
         } else {
-            KeyedAttributeValue::None
+            if parser.config().reject_flag_attributes {
+                parser.push_error(ParserError::FlagAttribute(key.to_string()), key.span());
+            }
+            KeyedAttributeValue::Flag
         };
 
         Some(KeyedAttribute {
             key,
             possible_value,
+            sugar: None,
+            position: None,
+            interned_key: None,
         })
     }
 }
 
+impl NodeAttribute {
+    /// Index of attribute in open tag it was parsed from, see
+    /// [`KeyedAttribute::position`]. Always `None` for block attributes.
+    pub fn position(&self) -> Option<usize> {
+        match self {
+            NodeAttribute::Attribute(attribute) => attribute.position,
+            NodeAttribute::Block(_) => None,
+        }
+    }
+
+    /// Set [`KeyedAttribute::position`] and [`KeyedAttribute::interned_key`]
+    /// of parsed attributes.
+    pub(crate) fn set_positions(parser: &mut RecoverableContext, attributes: &mut [Self]) {
+        for (i, attribute) in attributes.iter_mut().enumerate() {
+            if let NodeAttribute::Attribute(attribute) = attribute {
+                attribute.position = Some(i);
+                if !attribute.key.is_block() {
+                    attribute.interned_key = Some(parser.intern(&attribute.key));
+                }
+            }
+        }
+    }
+
+    /// Parse attributes until the end of open tag (`>` or `/>`), or the end of
+    /// input. End of tag is not consumed.
+    pub(crate) fn parse_list(parser: &mut RecoverableContext, input: ParseStream) -> Vec<Self> {
+        let mut attributes = vec![];
+        while !input.is_empty() && input.fork().parse::<OpenTagEnd>().is_err() {
+            let old_cursor = input.cursor();
+            if let Some(attribute) = parser.parse_recoverable(input) {
+                attributes.push(attribute)
+            }
+            if old_cursor == input.cursor() {
+                Self::skip_until_end(parser, input);
+            }
+        }
+        attributes
+    }
+
+    /// Skip tokens that can't be parsed as attribute, up to the end of tag.
+    fn skip_until_end(parser: &mut RecoverableContext, input: ParseStream) {
+        let mut ignored = TokenStream::new();
+        while !input.is_empty() && input.fork().parse::<OpenTagEnd>().is_err() {
+            if let Some(token) = parser.parse_simple::<TokenTree>(input) {
+                ignored.extend([token]);
+            }
+        }
+        trace::debug!(tokens = %ignored, "skipping tokens until the end of tag");
+        parser.push_error(ParserError::IgnoredTokens, ignored.span());
+    }
+}
+
 impl ParseRecoverable for NodeAttribute {
     fn parse_recoverable(parser: &mut RecoverableContext, input: ParseStream) -> Option<Self> {
-        let node = if input.peek(Brace) {
+        // Block followed by `=` is a key of attribute, e.g. `{key}=value`.
+        let is_block_key = input.peek(Brace) && input.peek2(Token![=]);
+        let node = if !is_block_key && peek_block(input, parser.config().block_delimiters()) {
             NodeAttribute::Block(parser.parse_recoverable(input)?)
         } else {
             NodeAttribute::Attribute(parser.parse_recoverable(input)?)
@@ -0,0 +1,224 @@
+//! Construction of synthesized elements, see [`NodeElementBuilder`].
+
+use std::str::FromStr;
+
+use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
+use syn::{
+    parse::{Parse, Parser},
+    Token,
+};
+
+use super::{
+    atoms::{CloseTag, CloseTagStart, OpenTag, OpenTagEnd},
+    interpolation::respan,
+    AttributeValueExpr, CustomNode, KVAttributeValue, KeyedAttribute, KeyedAttributeValue,
+    Namespace, Node, NodeAttribute, NodeElement, NodeName,
+};
+use crate::ComponentRules;
+
+/// Builder of element that is not parsed from source, e.g. wrapper injected
+/// by macro, created with [`NodeElement::builder`].
+///
+/// All tokens of element, including its name and attribute keys, get the span
+/// set by [`NodeElementBuilder::span`] (call site by default), so errors
+/// reported for the element point to the chosen place. Attribute values and
+/// children are kept as is.
+///
+/// ```rust
+/// use proc_macro2::Span;
+/// use quote::{quote, ToTokens};
+/// use rstml::node::NodeElement;
+///
+/// let element = NodeElement::<rstml::Infallible>::builder("div")
+///     .attribute("class", "wrapper")
+///     .attribute("data-id", quote!(id + 1))
+///     .flag("hidden")
+///     .children(rstml::tree! { <p>"a"</p> })
+///     .span(Span::call_site())
+///     .build();
+/// assert_eq!(
+///     element.to_token_stream().to_string(),
+///     quote! { <div class="wrapper" data-id=id + 1 hidden><p>"a"</p></div> }.to_string()
+/// );
+/// ```
+#[derive(Debug)]
+pub struct NodeElementBuilder<C> {
+    name: BuilderName,
+    attributes: Vec<(String, Option<TokenStream>)>,
+    children: Vec<Node<C>>,
+    self_closed: bool,
+    parent_namespace: Namespace,
+    span: Span,
+}
+
+/// Name of element, that is parsed in [`NodeElementBuilder::build`], or that
+/// was already parsed.
+#[derive(Debug)]
+enum BuilderName {
+    Source(String),
+    Parsed(NodeName),
+}
+
+impl<C: CustomNode> NodeElement<C> {
+    /// Builder of element named `name`, like `div`, `my-element` or
+    /// `ui::Button`.
+    pub fn builder(name: impl Into<String>) -> NodeElementBuilder<C> {
+        NodeElementBuilder::new(BuilderName::Source(name.into()))
+    }
+
+    /// Builder of element with already parsed `name`, which keeps its spans.
+    pub fn builder_with_name(name: NodeName) -> NodeElementBuilder<C> {
+        NodeElementBuilder::new(BuilderName::Parsed(name))
+    }
+}
+
+impl<C: CustomNode> NodeElementBuilder<C> {
+    fn new(name: BuilderName) -> Self {
+        Self {
+            name,
+            attributes: vec![],
+            children: vec![],
+            self_closed: false,
+            parent_namespace: Namespace::default(),
+            span: Span::call_site(),
+        }
+    }
+
+    /// Add attribute `key=value`, `value` is parsed as expression, so string
+    /// literals can be passed as `&str`.
+    ///
+    /// Value is checked only when element is built, see
+    /// [`try_build`](Self::try_build).
+    pub fn attribute(mut self, key: impl Into<String>, value: impl ToTokens) -> Self {
+        self.attributes
+            .push((key.into(), Some(value.to_token_stream())));
+        self
+    }
+
+    /// Add attribute without value, e.g. `disabled`.
+    pub fn flag(mut self, key: impl Into<String>) -> Self {
+        self.attributes.push((key.into(), None));
+        self
+    }
+
+    /// Add child node, e.g. element built by another builder, or
+    /// [`NodeText`](super::NodeText).
+    pub fn child(mut self, node: impl Into<Node<C>>) -> Self {
+        self.children.push(node.into());
+        self
+    }
+
+    /// Add child nodes, after already added ones.
+    pub fn children(mut self, nodes: impl IntoIterator<Item = Node<C>>) -> Self {
+        self.children.extend(nodes);
+        self
+    }
+
+    /// Write element as `<name/>`, without close tag. Children are dropped.
+    pub fn self_closed(mut self, self_closed: bool) -> Self {
+        self.self_closed = self_closed;
+        self
+    }
+
+    /// Namespace of parent element, which is used to compute namespace of
+    /// element, see [`Namespace::of_element`]. [`Namespace::Html`] by default.
+    pub fn parent_namespace(mut self, namespace: Namespace) -> Self {
+        self.parent_namespace = namespace;
+        self
+    }
+
+    /// Span of synthesized tokens.
+    pub fn span(mut self, span: Span) -> Self {
+        self.span = span;
+        self
+    }
+
+    /// Same as [`try_build`](Self::try_build), for input that is known to be
+    /// valid, like names and values written in macro source.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`try_build`](Self::try_build) returns error.
+    pub fn build(self) -> NodeElement<C> {
+        self.try_build().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Build element.
+    ///
+    /// Returns error, with the span set by [`span`](Self::span), if name of
+    /// element, or key of any attribute, can't be parsed as [`NodeName`], or if
+    /// value of any attribute is not a valid expression.
+    pub fn try_build(self) -> syn::Result<NodeElement<C>> {
+        let span = self.span;
+        let name = match self.name {
+            BuilderName::Source(name) => parse_name(&name, span)?,
+            BuilderName::Parsed(name) => name,
+        };
+        let attributes = self
+            .attributes
+            .into_iter()
+            .map(|(key, value)| {
+                let possible_value = match value {
+                    Some(value) => KeyedAttributeValue::Value(AttributeValueExpr {
+                        token_eq: Token![=](span),
+                        value: KVAttributeValue::Expr(syn::parse2(value).map_err(|e| {
+                            syn::Error::new(
+                                span,
+                                format!("invalid value of attribute `{key}`: {e}"),
+                            )
+                        })?),
+                    }),
+                    None => KeyedAttributeValue::Flag,
+                };
+                Ok(NodeAttribute::Attribute(KeyedAttribute {
+                    key: parse_name(&key, span)?,
+                    possible_value,
+                    sugar: None,
+                    position: None,
+                    interned_key: None,
+                }))
+            })
+            .collect::<syn::Result<_>>()?;
+        let close_tag = (!self.self_closed).then(|| CloseTag {
+            start_tag: CloseTagStart {
+                token_lt: Token![<](span),
+                token_solidus: Token![/](span),
+            },
+            name: name.clone(),
+            generics: Default::default(),
+            token_gt: Token![>](span),
+        });
+        Ok(NodeElement {
+            kind: ComponentRules::default().kind(&name),
+            namespace: Namespace::of_element(&name.to_string(), self.parent_namespace),
+            open_tag: OpenTag {
+                token_lt: Token![<](span),
+                name,
+                generics: Default::default(),
+                attributes,
+                end_tag: OpenTagEnd {
+                    token_solidus: self.self_closed.then(|| Token![/](span)),
+                    token_gt: Token![>](span),
+                },
+            },
+            children: if self.self_closed {
+                vec![]
+            } else {
+                self.children
+            },
+            close_tag,
+            source_tokens: Default::default(),
+            directives: vec![],
+            special_attributes: vec![],
+            interned_name: None,
+        })
+    }
+}
+
+fn parse_name(name: &str, span: Span) -> syn::Result<NodeName> {
+    TokenStream::from_str(name)
+        .map_err(|e| syn::Error::new(span, e))
+        .and_then(|tokens| NodeName::parse.parse2(respan(tokens, span)))
+        .map_err(|e| syn::Error::new(span, format!("invalid name `{name}`: {e}")))
+}
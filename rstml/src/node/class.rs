@@ -0,0 +1,117 @@
+//! Splitting of `class` attribute values, see [`KeyedAttribute::class_names`].
+
+use std::ops::Range;
+
+use proc_macro2::Span;
+use syn::{Expr, ExprLit, Lit, LitStr};
+
+use super::KeyedAttribute;
+
+/// Value of `class` attribute.
+#[derive(Clone, Debug)]
+pub enum ClassValue<'a> {
+    /// String literal, split by whitespace.
+    Names(Vec<ClassName>),
+    /// Any other expression (e.g. `class={("a", flag.then_some("b"))}`),
+    /// which value is known only at runtime.
+    Opaque(&'a Expr),
+}
+
+/// Single class name from string literal.
+#[derive(Clone, Debug)]
+pub struct ClassName {
+    name: String,
+    range: Range<usize>,
+    span: Span,
+}
+
+impl ClassName {
+    /// Split value of `lit` by whitespace into class names, in source order.
+    ///
+    /// Duplicates are kept, so consumers can report or merge them.
+    pub fn split(lit: &LitStr) -> Vec<Self> {
+        let value = lit.value();
+        let mut names = vec![];
+        let mut start = None;
+        for (i, c) in value.char_indices().chain([(value.len(), ' ')]) {
+            match (start, c.is_whitespace()) {
+                (None, false) => start = Some(i),
+                (Some(s), true) => {
+                    names.push(ClassName {
+                        name: value[s..i].to_string(),
+                        range: s..i,
+                        span: literal_subspan(lit, &value, s..i),
+                    });
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        names
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Byte offsets of class name in the value of string literal.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+    /// Span of class name inside of literal.
+    ///
+    /// Spans inside of literal are only available on nightly, or in
+    /// `proc_macro2` fallback mode with `span-locations`, and when literal has
+    /// no escapes. Otherwise span of the whole literal is returned.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl KeyedAttribute {
+    /// Split value of `class` attribute into class names.
+    ///
+    /// Returns `None` if attribute key is not `class`, or it has no value.
+    ///
+    /// ```rust
+    /// use rstml::node::{ClassValue, Node, NodeAttribute};
+    ///
+    /// let Node::Element(element) = rstml::node!(<div class="btn  btn-primary" />) else {
+    ///     unreachable!()
+    /// };
+    /// let NodeAttribute::Attribute(class) = &element.attributes()[0] else {
+    ///     unreachable!()
+    /// };
+    /// let Some(ClassValue::Names(names)) = class.class_names() else {
+    ///     unreachable!()
+    /// };
+    /// let names: Vec<_> = names.iter().map(|n| n.name()).collect();
+    /// assert_eq!(names, ["btn", "btn-primary"]);
+    /// ```
+    pub fn class_names(&self) -> Option<ClassValue<'_>> {
+        if self.key.to_string() != "class" {
+            return None;
+        }
+        Some(match self.value()? {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(lit), ..
+            }) => ClassValue::Names(ClassName::split(lit)),
+            expr => ClassValue::Opaque(expr),
+        })
+    }
+}
+
+/// Span of `range` of literal `value`, or span of the whole literal, if
+/// literal source differs from its value (has escapes).
+pub(crate) fn literal_subspan(lit: &LitStr, value: &str, range: Range<usize>) -> Span {
+    let source = lit.token().to_string();
+    let (Some(start), Some(end)) = (source.find('"'), source.rfind('"')) else {
+        return lit.span();
+    };
+    let content_start = start + 1;
+    if content_start > end || &source[content_start..end] != value {
+        return lit.span();
+    }
+    lit.token()
+        .subspan(content_start + range.start..content_start + range.end)
+        .unwrap_or_else(|| lit.span())
+}
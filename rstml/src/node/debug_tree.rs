@@ -0,0 +1,91 @@
+//! Compact tree printer for snapshot tests, see [`Node::to_debug_tree`].
+
+use std::fmt::Write;
+
+use quote::ToTokens;
+
+use super::{CustomNode, KeyedAttributeValue, Node, NodeAttribute, NodeElement, ParsedTemplate};
+
+impl<C: CustomNode> Node<C> {
+    /// Render node and its children as indented tree, one node per line.
+    ///
+    /// Unlike [`Debug`](std::fmt::Debug), output contains only names, values
+    /// and tokens, without spans, so it is stable enough for snapshot tests.
+    ///
+    /// ```rust
+    /// use rstml::tree;
+    ///
+    /// let nodes = tree! { <div class="x" hidden><!-- "c" -->"text" {block}</div> };
+    /// assert_eq!(
+    ///     nodes[0].to_debug_tree(),
+    ///     "div [class=\"x\" hidden]\n  <!-- \"c\" -->\n  \"text\"\n  { block }\n"
+    /// );
+    /// ```
+    pub fn to_debug_tree(&self) -> String {
+        let mut output = String::new();
+        write_node(&mut output, self, 0);
+        output
+    }
+}
+
+impl<C: CustomNode> ParsedTemplate<C> {
+    /// Render all top-level nodes with [`Node::to_debug_tree`].
+    pub fn to_debug_tree(&self) -> String {
+        let mut output = String::new();
+        for node in &self.nodes {
+            write_node(&mut output, node, 0);
+        }
+        output
+    }
+}
+
+fn write_node<C: CustomNode>(output: &mut String, node: &Node<C>, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let _ = match node {
+        Node::Element(element) => writeln!(output, "{indent}{}", element_header(element)),
+        Node::Fragment(_) => writeln!(output, "{indent}<>"),
+        Node::Text(text) => writeln!(output, "{indent}{:?}", text.value_string()),
+        Node::RawText(text) => writeln!(output, "{indent}raw {:?}", text.to_string_best()),
+        Node::Block(block) => writeln!(output, "{indent}{}", block.to_token_stream()),
+        Node::Comment(comment) => writeln!(output, "{indent}<!-- {:?} -->", comment.value.value()),
+        Node::Doctype(doctype) => writeln!(
+            output,
+            "{indent}<!DOCTYPE {}>",
+            doctype.value.to_string_best()
+        ),
+        Node::Custom(custom) => writeln!(output, "{indent}custom {}", custom.to_token_stream()),
+        Node::Incomplete(tag) => writeln!(output, "{indent}incomplete {}", tag.to_token_stream()),
+    };
+    for child in node.children().into_iter().flatten() {
+        write_node(output, child, depth + 1);
+    }
+}
+
+/// Name of element with its attributes, e.g. `div [class="x" {..rest}]`.
+fn element_header<C: CustomNode>(element: &NodeElement<C>) -> String {
+    let mut header = element.name().to_string();
+    let attributes = element.all_attributes();
+    if attributes.is_empty() {
+        return header;
+    }
+    let attributes: Vec<_> = attributes
+        .iter()
+        .map(|attribute| match &**attribute {
+            NodeAttribute::Block(block) => block.to_token_stream().to_string(),
+            NodeAttribute::Attribute(attribute) => {
+                let key = attribute.key.to_string();
+                match &attribute.possible_value {
+                    KeyedAttributeValue::Flag => key,
+                    KeyedAttributeValue::Value(value) => {
+                        format!("{key}={}", value.value.to_token_stream())
+                    }
+                    KeyedAttributeValue::Binding(binding) => {
+                        format!("{key}({})", binding.inputs.to_token_stream())
+                    }
+                }
+            }
+        })
+        .collect();
+    let _ = write!(header, " [{}]", attributes.join(" "));
+    header
+}
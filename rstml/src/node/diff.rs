@@ -0,0 +1,158 @@
+//! Structural comparison of node trees, see [`Node::diff`].
+
+use std::{borrow::Cow, fmt};
+
+use quote::ToTokens;
+
+use super::{CustomNode, Node, NodeAttribute};
+
+/// First difference between two node trees, found by [`Node::diff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeDiff {
+    /// Path to the difference.
+    ///
+    /// Each node is represented by its index among siblings, with element
+    /// name appended, and attributes are prefixed with `@` (e.g.
+    /// `/0/div/1/span/@class`). Block attributes are represented by their
+    /// index (`@0`).
+    pub path: String,
+    /// Description of the difference.
+    pub message: String,
+}
+
+impl fmt::Display for NodeDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl<C: CustomNode + PartialEq> Node<C> {
+    /// Find the first difference between `self` and `other`.
+    ///
+    /// Returns `None` if nodes are equal, spans are ignored in comparison,
+    /// same as in `PartialEq` implementation.
+    ///
+    /// ```rust
+    /// use rstml::node;
+    ///
+    /// let left = node!(<div><span class="a"/></div>);
+    /// let right = node!(<div><span class="b"/></div>);
+    /// let diff = left.diff(&right).unwrap();
+    /// assert_eq!(diff.path, "/0/div/0/span/@class");
+    /// assert_eq!(diff.to_string(), r#"/0/div/0/span/@class: `class = "a"` != `class = "b"`"#);
+    /// ```
+    pub fn diff(&self, other: &Self) -> Option<NodeDiff> {
+        diff_node(String::new(), 0, self, other)
+    }
+
+    /// Same as [`diff`](Self::diff), but for lists of nodes, e.g. two parsed
+    /// templates.
+    pub fn diff_all(left: &[Self], right: &[Self]) -> Option<NodeDiff> {
+        diff_nodes(String::new(), left, right)
+    }
+}
+
+fn diff_nodes<C: CustomNode + PartialEq>(
+    path: String,
+    left: &[Node<C>],
+    right: &[Node<C>],
+) -> Option<NodeDiff> {
+    let diff = left
+        .iter()
+        .zip(right)
+        .enumerate()
+        .find_map(|(index, (l, r))| diff_node(path.clone(), index, l, r));
+    if diff.is_some() {
+        return diff;
+    }
+    let index = left.len().min(right.len());
+    let path = format!("{path}/{index}");
+    match (left.get(index), right.get(index)) {
+        (Some(l), None) => Some(different(path, format!("`{}` != nothing", tokens(l)))),
+        (None, Some(r)) => Some(different(path, format!("nothing != `{}`", tokens(r)))),
+        _ => None,
+    }
+}
+
+fn diff_node<C: CustomNode + PartialEq>(
+    path: String,
+    index: usize,
+    left: &Node<C>,
+    right: &Node<C>,
+) -> Option<NodeDiff> {
+    if left == right {
+        return None;
+    }
+    let mut path = format!("{path}/{index}");
+    if left.r#type() != right.r#type() {
+        return Some(different(
+            path,
+            format!("{} node != {} node", left.r#type(), right.r#type()),
+        ));
+    }
+    match (left, right) {
+        (Node::Element(l), Node::Element(r)) => {
+            path = format!("{path}/{}", l.name());
+            if l.name() != r.name() {
+                return Some(different(path, format!("`{}` != `{}`", l.name(), r.name())));
+            }
+            let attribute_diff = diff_attributes(&path, &l.all_attributes(), &r.all_attributes());
+            if attribute_diff.is_some() {
+                return attribute_diff;
+            }
+            let children_diff = diff_nodes(path.clone(), &l.children, &r.children);
+            if children_diff.is_some() {
+                return children_diff;
+            }
+        }
+        (Node::Fragment(l), Node::Fragment(r)) => {
+            let children_diff = diff_nodes(path.clone(), &l.children, &r.children);
+            if children_diff.is_some() {
+                return children_diff;
+            }
+        }
+        _ => {}
+    }
+    // Nodes still differ, e.g. one of elements is self-closed.
+    Some(different(
+        path,
+        format!("`{}` != `{}`", tokens(left), tokens(right)),
+    ))
+}
+
+fn diff_attributes(
+    path: &str,
+    left: &[Cow<'_, NodeAttribute>],
+    right: &[Cow<'_, NodeAttribute>],
+) -> Option<NodeDiff> {
+    let attribute_path = |index, attribute: &NodeAttribute| match attribute {
+        NodeAttribute::Attribute(a) => format!("{path}/@{}", a.key),
+        NodeAttribute::Block(_) => format!("{path}/@{index}"),
+    };
+    let count = left.len().max(right.len());
+    (0..count).find_map(|index| match (left.get(index), right.get(index)) {
+        (Some(l), Some(r)) if l == r => None,
+        (Some(l), r) => Some(different(
+            attribute_path(index, l),
+            format!(
+                "`{}` != {}",
+                tokens(l),
+                r.map(|r| format!("`{}`", tokens(r)))
+                    .unwrap_or_else(|| "nothing".into())
+            ),
+        )),
+        (None, Some(r)) => Some(different(
+            attribute_path(index, r),
+            format!("nothing != `{}`", tokens(r)),
+        )),
+        (None, None) => None,
+    })
+}
+
+fn different(path: String, message: String) -> NodeDiff {
+    NodeDiff { path, message }
+}
+
+fn tokens(value: &impl ToTokens) -> String {
+    value.to_token_stream().to_string()
+}
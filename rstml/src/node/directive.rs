@@ -0,0 +1,37 @@
+//! Directive comments, like `<!-- "rstml-raw" -->`, see
+//! [`ParserConfig::directive_prefix`].
+//!
+//! [`ParserConfig::directive_prefix`]: crate::ParserConfig::directive_prefix
+
+use super::NodeComment;
+
+/// Directive for the element that follows directive comment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Directive {
+    /// `ignore`: element and its children are skipped by validation and lint
+    /// passes.
+    Ignore,
+    /// `raw`: children of element are parsed as [`RawText`], like children
+    /// of [`ParserConfig::raw_text_elements`].
+    ///
+    /// [`RawText`]: super::RawText
+    /// [`ParserConfig::raw_text_elements`]: crate::ParserConfig::raw_text_elements
+    Raw,
+    /// Any other directive, without prefix, e.g. `hydrate` for
+    /// `<!-- "rstml-hydrate" -->`. Kept for the user macro, and ignored by the
+    /// parser.
+    Custom(String),
+}
+
+impl NodeComment {
+    /// Directive of comment, if its value starts with `prefix`.
+    pub fn directive(&self, prefix: &str) -> Option<Directive> {
+        let value = self.value.value();
+        let name = value.trim().strip_prefix(prefix)?;
+        Some(match name {
+            "ignore" => Directive::Ignore,
+            "raw" => Directive::Raw,
+            name => Directive::Custom(name.to_string()),
+        })
+    }
+}
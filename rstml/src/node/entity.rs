@@ -0,0 +1,232 @@
+//! Character references in text, e.g. `&amp;` or `&#x1F600;`.
+
+/// Named references that can be decoded, other names are kept as is.
+const NAMED_ENTITIES: &[(&str, char)] = &[
+    ("amp", '&'),
+    ("lt", '<'),
+    ("gt", '>'),
+    ("quot", '"'),
+    ("apos", '\''),
+    ("nbsp", '\u{a0}'),
+    ("ensp", '\u{2002}'),
+    ("emsp", '\u{2003}'),
+    ("thinsp", '\u{2009}'),
+    ("zwnj", '\u{200c}'),
+    ("zwj", '\u{200d}'),
+    ("lrm", '\u{200e}'),
+    ("rlm", '\u{200f}'),
+    ("shy", '\u{ad}'),
+    ("iexcl", '¡'),
+    ("iquest", '¿'),
+    ("cent", '¢'),
+    ("pound", '£'),
+    ("euro", '€'),
+    ("yen", '¥'),
+    ("copy", '©'),
+    ("reg", '®'),
+    ("trade", '™'),
+    ("sect", '§'),
+    ("para", '¶'),
+    ("deg", '°'),
+    ("micro", 'µ'),
+    ("middot", '·'),
+    ("bull", '•'),
+    ("hellip", '…'),
+    ("ndash", '–'),
+    ("mdash", '—'),
+    ("lsquo", '‘'),
+    ("rsquo", '’'),
+    ("sbquo", '‚'),
+    ("ldquo", '“'),
+    ("rdquo", '”'),
+    ("bdquo", '„'),
+    ("laquo", '«'),
+    ("raquo", '»'),
+    ("lsaquo", '‹'),
+    ("rsaquo", '›'),
+    ("times", '×'),
+    ("divide", '÷'),
+    ("plusmn", '±'),
+    ("minus", '−'),
+    ("le", '≤'),
+    ("ge", '≥'),
+    ("ne", '≠'),
+    ("asymp", '≈'),
+    ("infin", '∞'),
+    ("sum", '∑'),
+    ("prod", '∏'),
+    ("radic", '√'),
+    ("frac12", '½'),
+    ("frac14", '¼'),
+    ("frac34", '¾'),
+    ("sup1", '¹'),
+    ("sup2", '²'),
+    ("sup3", '³'),
+    ("larr", '←'),
+    ("uarr", '↑'),
+    ("rarr", '→'),
+    ("darr", '↓'),
+    ("harr", '↔'),
+    ("hearts", '♥'),
+];
+
+/// Kind of character reference.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EntityKind {
+    /// `&name;`
+    Named(String),
+    /// `&#38;`
+    Decimal(u32),
+    /// `&#x26;`
+    Hex(u32),
+}
+
+/// Character reference found in text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entity {
+    source: String,
+    kind: EntityKind,
+}
+
+impl Entity {
+    /// Text of reference as it was written, including `&` and `;`.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+    pub fn kind(&self) -> &EntityKind {
+        &self.kind
+    }
+    /// Referenced character, `None` for unknown names and invalid code points.
+    pub fn decoded(&self) -> Option<char> {
+        match &self.kind {
+            EntityKind::Named(name) => NAMED_ENTITIES
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, c)| *c),
+            EntityKind::Decimal(code) | EntityKind::Hex(code) => char::from_u32(*code),
+        }
+    }
+
+    /// Replace all known character references in `text` with characters.
+    ///
+    /// ```rust
+    /// use rstml::node::Entity;
+    ///
+    /// assert_eq!(
+    ///     Entity::decode_str("a &lt; b &#x1F600; &unknown;"),
+    ///     "a < b 😀 &unknown;"
+    /// );
+    /// ```
+    pub fn decode_str(text: &str) -> String {
+        TextSegment::decode(&TextSegment::split(text))
+    }
+}
+
+/// Part of text, either plain text or character reference.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TextSegment {
+    Text(String),
+    Entity(Entity),
+}
+
+impl TextSegment {
+    /// Split `text` into plain text and character references.
+    pub fn split(text: &str) -> Vec<Self> {
+        split(text, false)
+    }
+
+    /// Concatenate segments, replacing known references with characters.
+    pub fn decode(segments: &[Self]) -> String {
+        segments.iter().fold(String::new(), |mut decoded, segment| {
+            match segment {
+                Self::Text(text) => decoded.push_str(text),
+                Self::Entity(entity) => match entity.decoded() {
+                    Some(c) => decoded.push(c),
+                    None => decoded.push_str(entity.source()),
+                },
+            }
+            decoded
+        })
+    }
+}
+
+/// Split `text` into segments.
+///
+/// If `spaced` is set, text is expected to be a token stream string, where
+/// parts of reference are separated by spaces (`& amp ;`), and only known
+/// names are recognized, to avoid treating `& b ;` as a reference.
+pub(crate) fn split(text: &str, spaced: bool) -> Vec<TextSegment> {
+    let mut segments = vec![];
+    let mut text_start = 0;
+    let mut position = 0;
+    while let Some(offset) = text[position..].find('&') {
+        let start = position + offset;
+        match parse_entity(&text[start..], spaced) {
+            Some((len, kind)) => {
+                if text_start < start {
+                    segments.push(TextSegment::Text(text[text_start..start].to_string()));
+                }
+                segments.push(TextSegment::Entity(Entity {
+                    source: text[start..start + len].to_string(),
+                    kind,
+                }));
+                position = start + len;
+                text_start = position;
+            }
+            None => position = start + 1,
+        }
+    }
+    if text_start < text.len() {
+        segments.push(TextSegment::Text(text[text_start..].to_string()));
+    }
+    segments
+}
+
+/// Parse reference at the start of `text`, returns its length and kind.
+fn parse_entity(text: &str, spaced: bool) -> Option<(usize, EntityKind)> {
+    let skip_spaces = |position: usize| {
+        if spaced {
+            position + (text[position..].len() - text[position..].trim_start().len())
+        } else {
+            position
+        }
+    };
+    let take_while = |position: usize, f: fn(char) -> bool| {
+        position
+            + text[position..]
+                .find(|c: char| !f(c))
+                .unwrap_or(text.len() - position)
+    };
+
+    let mut position = skip_spaces(1);
+    let kind = if text[position..].starts_with('#') {
+        position = skip_spaces(position + 1);
+        let rest = &text[position..];
+        if rest.starts_with(['x', 'X']) {
+            let end = take_while(position + 1, |c| c.is_ascii_hexdigit());
+            let code = u32::from_str_radix(&text[position + 1..end], 16).ok()?;
+            position = end;
+            EntityKind::Hex(code)
+        } else {
+            let end = take_while(position, |c| c.is_ascii_digit());
+            let code = text[position..end].parse().ok()?;
+            position = end;
+            EntityKind::Decimal(code)
+        }
+    } else {
+        if !text[position..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        let end = take_while(position, |c| c.is_ascii_alphanumeric());
+        let name = &text[position..end];
+        if spaced && !NAMED_ENTITIES.iter().any(|(n, _)| *n == name) {
+            return None;
+        }
+        position = end;
+        EntityKind::Named(name.to_string())
+    };
+    position = skip_spaces(position);
+    text[position..]
+        .starts_with(';')
+        .then_some((position + 1, kind))
+}
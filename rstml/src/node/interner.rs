@@ -0,0 +1,136 @@
+//! Interning of element and attribute names, see [`NameInterner`].
+
+use std::{
+    collections::HashSet,
+    fmt::{self, Write},
+    hash::{Hash, Hasher},
+    ops::Deref,
+    sync::Arc,
+};
+
+use super::NodeName;
+
+/// Interned name of element or attribute.
+///
+/// Names interned by the same [`NameInterner`] share one allocation, so they
+/// are cheap to clone and are compared by pointer. Names from different
+/// interners are compared by value.
+#[derive(Clone, Eq)]
+pub struct Name(Arc<str>);
+
+impl Name {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns true if both names were interned by the same interner, and
+    /// are equal.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.0, &other.0)
+    }
+}
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0) || self.0 == other.0
+    }
+}
+
+impl PartialEq<str> for Name {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Name {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl Hash for Name {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl Deref for Name {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Debug for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Table of interned names.
+///
+/// Parser keeps one table per parse, and stores interned names in
+/// [`NodeElement::interned_name`] and [`KeyedAttribute::interned_key`].
+///
+/// ```rust
+/// use rstml::node::{NameInterner, NodeName};
+///
+/// let mut interner = NameInterner::new();
+/// let div: NodeName = syn::parse_quote!(div);
+/// assert_eq!(interner.intern_name(&div), interner.intern("div"));
+/// assert_eq!(interner.len(), 1);
+/// ```
+///
+/// [`NodeElement::interned_name`]: super::NodeElement::interned_name
+/// [`KeyedAttribute::interned_key`]: super::KeyedAttribute::interned_key
+#[derive(Clone, Debug, Default)]
+pub struct NameInterner {
+    names: HashSet<Arc<str>>,
+    /// Reused to format names, that are looked up in `names`.
+    buffer: String,
+}
+
+impl NameInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Interned `name`, allocated only the first time it is seen.
+    pub fn intern(&mut self, name: &str) -> Name {
+        if let Some(name) = self.names.get(name) {
+            return Name(name.clone());
+        }
+        let name: Arc<str> = name.into();
+        self.names.insert(name.clone());
+        Name(name)
+    }
+
+    /// Interned string representation of `name`, see [`NodeName::interned`].
+    ///
+    /// Name is formatted into a buffer, that is reused between calls, so only
+    /// names that are seen the first time are allocated.
+    pub fn intern_name(&mut self, name: &NodeName) -> Name {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+        write!(buffer, "{name}").expect("BUG: formatting into String can't fail");
+        let interned = self.intern(&buffer);
+        self.buffer = buffer;
+        interned
+    }
+
+    /// Number of distinct names.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
@@ -0,0 +1,179 @@
+//! Splitting of text nodes by `{expr}` segments, see
+//! [`ParserConfig::interpolate_text`], and joining them back into `format!`
+//! call, see [`Node::format_tokens`].
+//!
+//! [`ParserConfig::interpolate_text`]: crate::ParserConfig::interpolate_text
+
+use std::{ops::Range, str::FromStr};
+
+use proc_macro2::{Delimiter, Group, Span, TokenStream, TokenTree};
+use quote::quote;
+use syn::{spanned::Spanned, Block, Expr, LitStr};
+
+use super::{class::literal_subspan, CustomNode, Node, NodeBlock, NodeText};
+use crate::recoverable::RecoverableContext;
+
+impl<C: CustomNode> Node<C> {
+    /// Join sibling text and block nodes into `format!` call, e.g. nodes of
+    /// `"Hello " {name} "!"` are converted to
+    /// `::std::format!("Hello {}!", { name })`.
+    ///
+    /// Braces in text are escaped, raw text is used as is. Returns `None` if
+    /// some node is not a text, raw text or valid block.
+    ///
+    /// ```rust
+    /// use quote::quote;
+    /// use rstml::{node::Node, parse2};
+    ///
+    /// let nodes = parse2(quote! { "Hello {" {name} "}" }).unwrap();
+    /// let tokens = Node::format_tokens(&nodes).unwrap();
+    /// assert_eq!(
+    ///     tokens.to_string(),
+    ///     r#":: std :: format ! ("Hello {{{}}}" , { name })"#
+    /// );
+    /// ```
+    pub fn format_tokens(nodes: &[Self]) -> Option<TokenStream> {
+        let mut template = String::new();
+        let mut args = vec![];
+        for node in nodes {
+            match node {
+                Node::Text(text) => template.push_str(&escape(&text.value.value())),
+                Node::RawText(text) => template.push_str(&escape(&text.to_string_best())),
+                Node::Block(NodeBlock::ValidBlock(block)) => {
+                    template.push_str("{}");
+                    args.push(block);
+                }
+                _ => return None,
+            }
+        }
+        let span = nodes
+            .first()
+            .map_or_else(Span::call_site, |node| node.span());
+        let template = LitStr::new(&template, span);
+        Some(quote!(::std::format!(#template #(, #args)*)))
+    }
+}
+
+/// Escape braces in text for `format!` template.
+fn escape(text: &str) -> String {
+    text.replace('{', "{{").replace('}', "}}")
+}
+
+/// Part of text literal.
+enum Segment {
+    Text(String, Range<usize>),
+    Expr(Range<usize>),
+}
+
+/// Split text nodes in `children`, if interpolation is enabled.
+pub(crate) fn interpolate_children<C>(
+    parser: &mut RecoverableContext,
+    children: Vec<Node<C>>,
+) -> Vec<Node<C>> {
+    if !parser.config().interpolate_text {
+        return children;
+    }
+    let mut result = Vec::with_capacity(children.len());
+    for child in children {
+        match child {
+            Node::Text(text) if text.value.value().contains(['{', '}']) => {
+                match interpolate(&text.value) {
+                    Ok(nodes) => result.extend(nodes),
+                    Err(e) => {
+                        parser.push_diagnostic(e);
+                        result.push(Node::Text(text));
+                    }
+                }
+            }
+            child => result.push(child),
+        }
+    }
+    result
+}
+
+fn interpolate<C>(lit: &LitStr) -> syn::Result<Vec<Node<C>>> {
+    let value = lit.value();
+    let span_of = |range: Range<usize>| literal_subspan(lit, &value, range);
+    split(&value)
+        .map_err(|(message, range)| syn::Error::new(span_of(range), message))?
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text, range) => Ok(Node::Text(NodeText {
+                value: LitStr::new(&text, span_of(range)),
+            })),
+            Segment::Expr(range) => {
+                let span = span_of(range.clone());
+                let source = &value[range];
+                let tokens = TokenStream::from_str(source)
+                    .map_err(|e| syn::Error::new(span, e.to_string()))?;
+                // Check that segment is a single expression.
+                syn::parse2::<Expr>(tokens.clone())
+                    .map_err(|e| syn::Error::new(span, e.to_string()))?;
+                let mut group = Group::new(Delimiter::Brace, respan(tokens, span));
+                group.set_span(span);
+                let block: Block = syn::parse2(TokenTree::Group(group).into())?;
+                Ok(Node::Block(NodeBlock::ValidBlock(block)))
+            }
+        })
+        .collect()
+}
+
+/// Split `value` into text and expression segments, returns error message
+/// with its range on unmatched braces or empty expressions.
+fn split(value: &str) -> Result<Vec<Segment>, (&'static str, Range<usize>)> {
+    let mut segments = vec![];
+    let mut text = String::new();
+    let mut text_start = 0;
+    let mut chars = value.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '{' if chars.next_if(|(_, c)| *c == '{').is_some() => text.push('{'),
+            '}' if chars.next_if(|(_, c)| *c == '}').is_some() => text.push('}'),
+            '}' => return Err(("unmatched `}` in text, use `}}` to escape it", i..i + 1)),
+            '{' => {
+                let mut depth = 1;
+                let end = loop {
+                    match chars.next() {
+                        Some((j, '}')) if depth == 1 => break j,
+                        Some((_, '}')) => depth -= 1,
+                        Some((_, '{')) => depth += 1,
+                        Some(_) => {}
+                        None => {
+                            return Err(("unmatched `{` in text, use `{{` to escape it", i..i + 1))
+                        }
+                    }
+                };
+                if value[i + 1..end].trim().is_empty() {
+                    return Err(("expected expression in `{}`", i..end + 1));
+                }
+                if !text.is_empty() {
+                    segments.push(Segment::Text(std::mem::take(&mut text), text_start..i));
+                }
+                segments.push(Segment::Expr(i + 1..end));
+                text_start = end + 1;
+            }
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        segments.push(Segment::Text(text, text_start..value.len()));
+    }
+    Ok(segments)
+}
+
+/// Set `span` for all tokens in `tokens`.
+pub(crate) fn respan(tokens: TokenStream, span: Span) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|mut token| {
+            if let TokenTree::Group(group) = &token {
+                let mut respanned = Group::new(group.delimiter(), respan(group.stream(), span));
+                respanned.set_span(span);
+                token = TokenTree::Group(respanned);
+            } else {
+                token.set_span(span);
+            }
+            token
+        })
+        .collect()
+}
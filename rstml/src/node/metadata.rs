@@ -0,0 +1,153 @@
+//! Typed side table of data attached to nodes, see [`NodeMetadata`].
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt,
+};
+
+use super::NodeId;
+
+/// Node or attribute that metadata is attached to.
+///
+/// Node ids are converted into keys, so methods of [`NodeMetadata`] accept
+/// both.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MetadataKey {
+    Node(NodeId),
+    /// Attribute of element, by its index in
+    /// [`NodeElement::attributes`](super::NodeElement::attributes).
+    Attribute(NodeId, usize),
+}
+
+impl MetadataKey {
+    pub fn attribute(node: NodeId, index: usize) -> Self {
+        Self::Attribute(node, index)
+    }
+
+    /// Id of node, or of element that has the attribute.
+    pub fn node(self) -> NodeId {
+        match self {
+            Self::Node(node) | Self::Attribute(node, _) => node,
+        }
+    }
+}
+
+impl From<NodeId> for MetadataKey {
+    fn from(node: NodeId) -> Self {
+        Self::Node(node)
+    }
+}
+
+/// Data computed by passes over parsed tree, like types, scopes or
+/// sanitization status, stored outside of the tree.
+///
+/// Every node or attribute can have one value of each type. Values are keyed
+/// by [`NodeId`], so table stays valid while the tree is not restructured.
+///
+/// ```rust
+/// use rstml::{
+///     node::{MetadataKey, Node, NodeMetadata},
+///     tree,
+/// };
+///
+/// struct Escaped(bool);
+///
+/// let nodes = tree! { <p class="x">{name}</p> };
+/// let mut metadata = NodeMetadata::new();
+/// for (id, node) in Node::preorder(&nodes) {
+///     metadata.insert(id, Escaped(!matches!(node, Node::Block(_))));
+/// }
+/// let (p, _) = Node::preorder(&nodes).next().unwrap();
+/// metadata.insert(MetadataKey::attribute(p, 0), "class");
+///
+/// assert!(metadata.get::<Escaped>(p).unwrap().0);
+/// assert_eq!(metadata.iter::<Escaped>().filter(|(_, e)| !e.0).count(), 1);
+/// assert_eq!(
+///     metadata.get::<&str>(MetadataKey::attribute(p, 0)),
+///     Some(&"class")
+/// );
+/// ```
+#[derive(Default)]
+pub struct NodeMetadata {
+    values: HashMap<(MetadataKey, TypeId), Box<dyn Any>>,
+}
+
+impl NodeMetadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attach `value` to `key`, returning previous value of the same type.
+    pub fn insert<T: 'static>(&mut self, key: impl Into<MetadataKey>, value: T) -> Option<T> {
+        self.values
+            .insert((key.into(), TypeId::of::<T>()), Box::new(value))
+            .map(|old| *old.downcast().expect("value is stored by its type"))
+    }
+
+    pub fn get<T: 'static>(&self, key: impl Into<MetadataKey>) -> Option<&T> {
+        self.values
+            .get(&(key.into(), TypeId::of::<T>()))
+            .and_then(|value| value.downcast_ref())
+    }
+
+    pub fn get_mut<T: 'static>(&mut self, key: impl Into<MetadataKey>) -> Option<&mut T> {
+        self.values
+            .get_mut(&(key.into(), TypeId::of::<T>()))
+            .and_then(|value| value.downcast_mut())
+    }
+
+    /// Value of type `T` attached to `key`, inserting `T::default()` if it is
+    /// missing.
+    pub fn get_or_default<T: Default + 'static>(&mut self, key: impl Into<MetadataKey>) -> &mut T {
+        self.values
+            .entry((key.into(), TypeId::of::<T>()))
+            .or_insert_with(|| Box::<T>::default())
+            .downcast_mut()
+            .expect("value is stored by its type")
+    }
+
+    pub fn contains<T: 'static>(&self, key: impl Into<MetadataKey>) -> bool {
+        self.values.contains_key(&(key.into(), TypeId::of::<T>()))
+    }
+
+    pub fn remove<T: 'static>(&mut self, key: impl Into<MetadataKey>) -> Option<T> {
+        self.values
+            .remove(&(key.into(), TypeId::of::<T>()))
+            .map(|value| *value.downcast().expect("value is stored by its type"))
+    }
+
+    /// Remove values of all types attached to node, including its
+    /// attributes.
+    pub fn remove_node(&mut self, node: NodeId) {
+        self.values.retain(|(key, _), _| key.node() != node)
+    }
+
+    /// All values of type `T`, sorted by key.
+    pub fn iter<T: 'static>(&self) -> impl Iterator<Item = (MetadataKey, &T)> {
+        let mut values: Vec<_> = self
+            .values
+            .iter()
+            .filter_map(|((key, _), value)| Some((*key, value.downcast_ref::<T>()?)))
+            .collect();
+        values.sort_by_key(|(key, _)| *key);
+        values.into_iter()
+    }
+
+    /// Number of stored values, of all types.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl fmt::Debug for NodeMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeMetadata")
+            .field("len", &self.values.len())
+            .finish()
+    }
+}
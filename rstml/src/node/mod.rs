@@ -1,26 +1,48 @@
 //! Tree of nodes.
 
-use std::{convert, fmt};
+use std::{borrow::Cow, convert, fmt};
 
 use atoms::{tokens, FragmentClose, FragmentOpen};
-use proc_macro2::{Ident, TokenStream};
+use proc_macro2::{Ident, TokenStream, TokenTree};
 use quote::ToTokens;
 use syn::{parse::ParseStream, ExprPath, LitStr, Token};
 
 pub mod atoms;
 mod attribute;
+mod builder;
+pub(crate) mod class;
+mod debug_tree;
+mod diff;
+mod directive;
+mod entity;
+mod interner;
+mod interpolation;
+mod metadata;
 mod node_name;
 mod node_value;
 pub mod parse;
 mod parser_ext;
 mod raw_text;
+mod sugar;
+mod template;
+mod tree;
 
 pub use attribute::{
     AttributeValueExpr, FnBinding, KVAttributeValue, KeyedAttribute, KeyedAttributeValue,
     NodeAttribute,
 };
-pub use node_name::{NodeName, NodeNameFragment};
+pub use builder::NodeElementBuilder;
+pub use class::{ClassName, ClassValue};
+pub use diff::NodeDiff;
+pub use directive::Directive;
+pub use entity::{Entity, EntityKind, TextSegment};
+pub use interner::{Name, NameInterner};
+pub use metadata::{MetadataKey, NodeMetadata};
+pub use node_name::{NameError, NameKind, NodeName, NodeNameFragment};
 pub use node_value::{InvalidBlock, NodeBlock};
+pub use sugar::AttributeSugar;
+pub use template::ParsedTemplate;
+pub use tree::NodeTree;
 
 pub use self::raw_text::RawText;
 use crate::recoverable::{ParseRecoverable, RecoverableContext};
@@ -58,7 +80,31 @@ impl fmt::Display for NodeType {
 }
 
 /// Node in the tree.
-#[derive(Clone, Debug)]
+///
+/// Nodes can be compared with `==`, spans of tokens are ignored.
+///
+/// # Thread safety
+///
+/// Nodes hold `proc_macro2` tokens, which are neither `Send` nor `Sync`,
+/// because inside of proc-macro they are handles to compiler state. So node
+/// trees can't be passed between threads:
+///
+/// ```compile_fail
+/// fn assert_send<T: Send>(_: &T) {}
+/// assert_send(&rstml::node!(<div/>));
+/// ```
+///
+/// Tools that process many templates in parallel (e.g. formatter) should
+/// tokenize and parse every template on its worker thread, with its own
+/// [`Parser`], and send back plain data: strings, [`ParserError`],
+/// [`SourceMap`], interned [`Name`]s or `DomNode` trees. These types are
+/// `Send + Sync`.
+///
+/// [`Parser`]: crate::Parser
+/// [`ParserError`]: crate::ParserError
+/// [`SourceMap`]: crate::source_map::SourceMap
+#[derive(Clone, Debug, PartialEq)]
+#[allow(clippy::large_enum_variant)]
 pub enum Node<C = Infallible> {
     Comment(NodeComment),
     Doctype(NodeDoctype),
@@ -68,6 +114,12 @@ pub enum Node<C = Infallible> {
     Text(NodeText),
     RawText(RawText<C>),
     Custom(C),
+    /// Open tag without `>`, e.g. `<div cla` at the end of input.
+    ///
+    /// Produced only with [`ParserConfig::recover_incomplete`].
+    ///
+    /// [`ParserConfig::recover_incomplete`]: crate::ParserConfig::recover_incomplete
+    Incomplete(atoms::PartialOpenTag),
 }
 // Manual implementation, because derive macro doesn't support generics.
 impl<C: CustomNode> ToTokens for Node<C> {
@@ -81,6 +133,7 @@ impl<C: CustomNode> ToTokens for Node<C> {
             Self::Text(text) => text.to_tokens(tokens),
             Self::RawText(raw_text) => raw_text.to_tokens(tokens),
             Self::Custom(custom) => custom.to_tokens(tokens),
+            Self::Incomplete(tag) => tag.to_tokens(tokens),
         }
     }
 }
@@ -99,6 +152,8 @@ impl<C: CustomNode> Node<C> {
             .collect()
     }
     /// Get the type of the node.
+    ///
+    /// [`Node::Incomplete`] is reported as [`NodeType::Element`].
     pub fn r#type(&self) -> NodeType {
         match &self {
             Self::Element(_) => NodeType::Element,
@@ -109,6 +164,7 @@ impl<C: CustomNode> Node<C> {
             Self::Fragment(_) => NodeType::Fragment,
             Self::RawText(_) => NodeType::RawText,
             Self::Custom(_) => NodeType::Custom,
+            Self::Incomplete(_) => NodeType::Element,
         }
     }
 
@@ -129,8 +185,265 @@ impl<C: CustomNode> Node<C> {
             _ => None,
         }
     }
+
+    /// Returns true if node and its children contain no rust code, so they
+    /// can be rendered to string at compile time.
+    ///
+    /// Subtree is dynamic if it has blocks, attribute blocks (`{..rest}`),
+    /// attribute values other than literals, function bindings, components or
+    /// custom nodes. Incomplete nodes are never static.
+    ///
+    /// ```rust
+    /// let node = rstml::node!(<p class="x" hidden>"Hi" <br/></p>);
+    /// assert!(node.is_static());
+    /// assert!(!rstml::node!(<p class="x">{name}</p>).is_static());
+    /// assert!(!rstml::node!(<p class=name></p>).is_static());
+    /// ```
+    pub fn is_static(&self) -> bool {
+        Self::preorder(std::slice::from_ref(self)).all(|(_, node)| match node {
+            Self::Element(element) => {
+                !element.is_component()
+                    && element
+                        .attributes()
+                        .iter()
+                        .all(|attribute| match attribute {
+                            NodeAttribute::Attribute(attribute) => attribute.is_static(),
+                            NodeAttribute::Block(_) => false,
+                        })
+                    && element
+                        .special_attributes
+                        .iter()
+                        .all(KeyedAttribute::is_static)
+            }
+            Self::Fragment(_)
+            | Self::Text(_)
+            | Self::RawText(_)
+            | Self::Comment(_)
+            | Self::Doctype(_) => true,
+            Self::Block(_) | Self::Custom(_) | Self::Incomplete(_) => false,
+        })
+    }
+
+    /// Tokens node was parsed from.
+    ///
+    /// With [`ParserConfig::preserve_tokens`], elements and fragments keep a
+    /// copy of tokens they were parsed from, which stays the same even if
+    /// node is changed later. Otherwise, and for other nodes, tokens are
+    /// produced by `ToTokens`, which matches source unless node was changed,
+    /// e.g. with [`ParserConfig::transform_block`].
+    ///
+    /// [`ParserConfig::preserve_tokens`]: crate::ParserConfig::preserve_tokens
+    /// [`ParserConfig::transform_block`]: crate::ParserConfig::transform_block
+    pub fn tokens(&self) -> TokenStream {
+        let source = match self {
+            Self::Element(element) => &element.source_tokens,
+            Self::Fragment(fragment) => &fragment.source_tokens,
+            _ => return self.to_token_stream(),
+        };
+        match source.get() {
+            Some(tokens) => tokens.clone(),
+            None => self.to_token_stream(),
+        }
+    }
+
+    /// Store copy of source tokens, if node is element or fragment.
+    pub(crate) fn set_source_tokens(&mut self, tokens: TokenStream) {
+        match self {
+            Self::Element(element) => element.source_tokens = SourceTokens(Some(tokens)),
+            Self::Fragment(fragment) => fragment.source_tokens = SourceTokens(Some(tokens)),
+            _ => {}
+        }
+    }
+}
+
+impl<C> From<NodeElement<C>> for Node<C> {
+    fn from(element: NodeElement<C>) -> Self {
+        Self::Element(element)
+    }
+}
+
+impl<C> From<NodeFragment<C>> for Node<C> {
+    fn from(fragment: NodeFragment<C>) -> Self {
+        Self::Fragment(fragment)
+    }
+}
+
+impl<C> From<NodeText> for Node<C> {
+    fn from(text: NodeText) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl<C> From<NodeBlock> for Node<C> {
+    fn from(block: NodeBlock) -> Self {
+        Self::Block(block)
+    }
+}
+
+/// Copy of tokens node was parsed from, see [`Node::tokens`].
+///
+/// Is not taken into account when nodes are compared.
+#[derive(Clone, Debug, Default)]
+pub struct SourceTokens(Option<TokenStream>);
+
+impl SourceTokens {
+    pub fn get(&self) -> Option<&TokenStream> {
+        self.0.as_ref()
+    }
+}
+
+impl PartialEq for SourceTokens {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+/// Stable identifier of a node in a parsed tree.
+///
+/// Ids are assigned in preorder (parent before its children, siblings from
+/// left to right), starting from zero at the first top-level node. The same
+/// input always produces the same ids, so they can be used as keys into side
+/// tables like [`SourceMap`](crate::source_map::SourceMap) or
+/// [`NodeMetadata`]. Nodes can be looked up by id with [`Node::get_by_id`].
+///
+/// Ids are not stored in nodes, so that nodes can be moved and built by hand.
+/// They are yielded by [`Node::preorder`], and id of a borrowed node can be
+/// searched with [`NodeTree::find_id`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(pub(crate) usize);
+
+impl NodeId {
+    /// Preorder index of the node.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+impl<C: CustomNode> Node<C> {
+    /// Iterate over all nodes of parsed tree `nodes` in preorder, along with
+    /// their [`NodeId`]s.
+    ///
+    /// ```rust
+    /// use rstml::{
+    ///     node::{Node, NodeTree},
+    ///     tree,
+    /// };
+    ///
+    /// let nodes = tree! { <div><span/></div> "text" };
+    /// let span = Node::preorder(&nodes).nth(1).map(|(id, _)| id).unwrap();
+    ///
+    /// // Ids still point to the same nodes after an immutable pass.
+    /// let node = Node::get_by_id(&nodes, span).unwrap();
+    /// assert_eq!(NodeTree::find_id(&nodes, node), Some(span));
+    /// ```
+    pub fn preorder(nodes: &[Self]) -> impl Iterator<Item = (NodeId, &Self)> {
+        let mut stack = vec![nodes.iter()];
+        let mut index = 0;
+        std::iter::from_fn(move || loop {
+            let iter = stack.last_mut()?;
+            let Some(node) = iter.next() else {
+                stack.pop();
+                continue;
+            };
+            if let Some(children) = node.children() {
+                stack.push(children.iter());
+            }
+            index += 1;
+            return Some((NodeId(index - 1), node));
+        })
+    }
+
+    /// Get node with given `id` in parsed tree `nodes`.
+    pub fn get_by_id(nodes: &[Self], id: NodeId) -> Option<&Self> {
+        Self::preorder(nodes)
+            .find(|(node_id, _)| *node_id == id)
+            .map(|(_, node)| node)
+    }
+
+    /// Same as [`get_by_id`](Self::get_by_id), but returns mutable reference.
+    ///
+    /// Ids are assigned by position, so inserting or removing nodes changes
+    /// ids of every node after them.
+    pub fn get_by_id_mut(nodes: &mut [Self], id: NodeId) -> Option<&mut Self> {
+        let mut stack = vec![nodes.iter_mut()];
+        let mut index = 0;
+        while let Some(iter) = stack.last_mut() {
+            let Some(node) = iter.next() else {
+                stack.pop();
+                continue;
+            };
+            if index == id.0 {
+                return Some(node);
+            }
+            index += 1;
+            if let Some(children) = node.children_mut() {
+                stack.push(children.iter_mut());
+            }
+        }
+        None
+    }
+}
+
+/// Kind of element, determined from its name by [`ComponentRules`].
+///
+/// [`ComponentRules`]: crate::ComponentRules
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ElementKind {
+    /// Regular html element, e.g. `<div>`.
+    #[default]
+    HtmlElement,
+    /// Component, e.g. `<MyButton>` or `<ui::Button>`.
+    Component,
 }
 
+/// Namespace of element, determined by the closest `<svg>` or `<math>`
+/// ancestor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Namespace {
+    #[default]
+    Html,
+    Svg,
+    MathMl,
+}
+
+impl Namespace {
+    /// Namespace of element named `name`, placed in `parent` namespace.
+    pub fn of_element(name: &str, parent: Namespace) -> Self {
+        match name {
+            "svg" => Self::Svg,
+            "math" => Self::MathMl,
+            _ => parent,
+        }
+    }
+    /// Namespace of children of element named `name`, placed in `self`
+    /// namespace.
+    ///
+    /// Differs from `self` only for `<foreignObject>`, which content is html.
+    pub fn of_children(self, name: &str) -> Self {
+        match (self, name) {
+            (Self::Svg, "foreignObject") => Self::Html,
+            (namespace, _) => namespace,
+        }
+    }
+}
+
+/// Html elements that can't have children.
+#[cfg(any(feature = "analysis", feature = "html-import"))]
+pub(crate) const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Html elements which content is not parsed as markup.
+#[cfg(any(feature = "analysis", feature = "html-import"))]
+pub(crate) const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
 /// Element node.
 ///
 /// A HTMLElement tag, with optional children and attributes.
@@ -140,11 +453,62 @@ pub struct NodeElement<C> {
     pub open_tag: atoms::OpenTag,
     pub children: Vec<Node<C>>,
     pub close_tag: Option<atoms::CloseTag>,
+    /// Kind of element, see [`ParserConfig::component_rules`].
+    ///
+    /// [`ParserConfig::component_rules`]: crate::ParserConfig::component_rules
+    pub kind: ElementKind,
+    /// Namespace of element, self-closing rules of
+    /// [`ParserConfig::always_self_closed_elements`] are applied only to html
+    /// elements.
+    ///
+    /// [`ParserConfig::always_self_closed_elements`]: crate::ParserConfig::always_self_closed_elements
+    pub namespace: Namespace,
+    /// Tokens element was parsed from, see [`Node::tokens`].
+    pub source_tokens: SourceTokens,
+    /// Directives of comments before element, see
+    /// [`ParserConfig::directive_prefix`].
+    ///
+    /// [`ParserConfig::directive_prefix`]: crate::ParserConfig::directive_prefix
+    pub directives: Vec<Directive>,
+    /// Attributes listed in [`ParserConfig::special_attributes`], which are
+    /// not included in [`NodeElement::attributes`].
+    ///
+    /// [`ParserConfig::special_attributes`]: crate::ParserConfig::special_attributes
+    pub special_attributes: Vec<KeyedAttribute>,
+    /// Name of element, interned by parser, so names of elements parsed
+    /// together are compared by pointer. `None` for elements that was created
+    /// after parsing.
+    pub interned_name: Option<Name>,
+}
+// Interned name is ignored, so elements created after parsing are equal to
+// parsed ones.
+impl<C: PartialEq> PartialEq for NodeElement<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.open_tag == other.open_tag
+            && self.children == other.children
+            && self.close_tag == other.close_tag
+            && self.kind == other.kind
+            && self.namespace == other.namespace
+            && self.source_tokens == other.source_tokens
+            && self.directives == other.directives
+            && self.special_attributes == other.special_attributes
+    }
 }
+
 // Manual implementation, because derive macro doesn't support generics.
 impl<C: CustomNode> ToTokens for NodeElement<C> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        self.open_tag.to_tokens(tokens);
+        if self.special_attributes.is_empty() {
+            self.open_tag.to_tokens(tokens);
+        } else {
+            let mut open_tag = self.open_tag.clone();
+            open_tag.attributes = self
+                .all_attributes()
+                .into_iter()
+                .map(Cow::into_owned)
+                .collect();
+            open_tag.to_tokens(tokens);
+        }
         for child in &self.children {
             child.to_tokens(tokens);
         }
@@ -158,12 +522,132 @@ impl<C: CustomNode> NodeElement<C> {
     pub fn name(&self) -> &NodeName {
         &self.open_tag.name
     }
+    /// Returns true if element is [`ElementKind::Component`].
+    pub fn is_component(&self) -> bool {
+        self.kind == ElementKind::Component
+    }
+    /// Returns true if element was written as self-closed, e.g. `<div/>`.
+    ///
+    /// Elements from [`ParserConfig::always_self_closed_elements`] written
+    /// without `/` (`<br>`) are not self-closed, but have no close tag too.
+    ///
+    /// [`ParserConfig::always_self_closed_elements`]: crate::ParserConfig::always_self_closed_elements
+    pub fn is_self_closed(&self) -> bool {
+        self.open_tag.is_self_closed()
+    }
+    /// Returns true if element has close tag, e.g. `<div></div>`.
+    pub fn has_close_tag(&self) -> bool {
+        self.close_tag.is_some()
+    }
+    /// Attributes of element, in the same order as they was written in
+    /// source, including ones produced by [`ParserConfig::tag_sugar`].
+    /// Special attributes are not included, see
+    /// [`all_attributes`](Self::all_attributes).
+    ///
+    /// [`ParserConfig::tag_sugar`]: crate::ParserConfig::tag_sugar
     pub fn attributes(&self) -> &[NodeAttribute] {
         &self.open_tag.attributes
     }
+    /// Mutable attributes of element, see [`NodeAttribute::position`] to get
+    /// source index of reordered attribute.
     pub fn attributes_mut(&mut self) -> &mut Vec<NodeAttribute> {
         &mut self.open_tag.attributes
     }
+    /// Keep only attributes for which `f` returns true, order and positions
+    /// of remaining attributes are not changed.
+    pub fn retain_attributes(&mut self, f: impl FnMut(&NodeAttribute) -> bool) {
+        self.open_tag.attributes.retain(f)
+    }
+    /// Insert `attribute` at `index`, shifting attributes after it.
+    ///
+    /// Position of attribute is kept as is, so attribute moved from another
+    /// place keeps its source index, and new attribute has none.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > attributes().len()`.
+    pub fn insert_attribute(&mut self, index: usize, attribute: NodeAttribute) {
+        self.open_tag.attributes.insert(index, attribute)
+    }
+    /// Attributes of element along with
+    /// [`special_attributes`](Self::special_attributes), which are put back
+    /// in order of their positions, as they are written by `ToTokens`.
+    pub fn all_attributes(&self) -> Vec<Cow<'_, NodeAttribute>> {
+        self.merge_special_attributes(Cow::Borrowed, |attribute| {
+            Cow::Owned(NodeAttribute::Attribute(attribute.clone()))
+        })
+    }
+    /// Map attributes and special attributes, and put the latter at their
+    /// positions, see [`all_attributes`](Self::all_attributes).
+    pub(crate) fn merge_special_attributes<'a, T>(
+        &'a self,
+        attribute: impl Fn(&'a NodeAttribute) -> T,
+        special: impl Fn(&'a KeyedAttribute) -> T,
+    ) -> Vec<T> {
+        let mut attributes: Vec<_> = self.open_tag.attributes.iter().map(attribute).collect();
+        let mut specials: Vec<_> = self.special_attributes.iter().collect();
+        specials.sort_by_key(|attribute| attribute.position);
+        for attribute in specials {
+            let len = attributes.len();
+            let index = attribute.position.map_or(len, |position| position.min(len));
+            attributes.insert(index, special(attribute));
+        }
+        attributes
+    }
+    /// Special attribute with key `name`, see
+    /// [`ParserConfig::special_attributes`].
+    ///
+    /// [`ParserConfig::special_attributes`]: crate::ParserConfig::special_attributes
+    pub fn special_attribute(&self, name: &str) -> Option<&KeyedAttribute> {
+        self.special_attributes
+            .iter()
+            .find(|attribute| attribute.key.to_string() == name)
+    }
+    /// Remove the first attribute with key `name`, returns removed attribute.
+    pub fn remove_attribute(&mut self, name: &str) -> Option<KeyedAttribute> {
+        let index = self.open_tag.attributes.iter().position(
+            |attribute| matches!(attribute, NodeAttribute::Attribute(a) if a.key.to_string() == name),
+        )?;
+        match self.open_tag.attributes.remove(index) {
+            NodeAttribute::Attribute(attribute) => Some(attribute),
+            NodeAttribute::Block(_) => unreachable!("position of keyed attribute"),
+        }
+    }
+    /// Replace child at `index` with `nodes`, returns replaced child, or
+    /// `None` if `index` is out of range, in which case children are left
+    /// unchanged.
+    ///
+    /// Child is addressed by position in [`children`](Self::children) rather
+    /// than by [`NodeId`]: ids are assigned in preorder of the whole tree, so
+    /// element can't resolve them, and splicing shifts ids of every following
+    /// node anyway. Position of node with known id is available from
+    /// [`NodeTree::sibling_index`].
+    pub fn replace_child(
+        &mut self,
+        index: usize,
+        nodes: impl IntoIterator<Item = Node<C>>,
+    ) -> Option<Node<C>> {
+        if index >= self.children.len() {
+            return None;
+        }
+        self.children.splice(index..=index, nodes).next()
+    }
+    /// Move all children into a new element `<name>..</name>`, that becomes
+    /// the only child of this element.
+    ///
+    /// Tokens of the new element have call site spans, kind of element is
+    /// computed by default [`ComponentRules`](crate::ComponentRules).
+    pub fn wrap_children(&mut self, name: NodeName) -> &mut NodeElement<C> {
+        let wrapper = NodeElement::builder_with_name(name)
+            .children(std::mem::take(&mut self.children))
+            .parent_namespace(self.namespace)
+            .build();
+        self.children.push(Node::Element(wrapper));
+        match self.children.last_mut() {
+            Some(Node::Element(wrapper)) => wrapper,
+            _ => unreachable!("wrapper was pushed"),
+        }
+    }
     pub fn chidlren(&self) -> &[Node<C>] {
         &self.children
     }
@@ -175,7 +659,7 @@ impl<C: CustomNode> NodeElement<C> {
 /// Text node.
 ///
 /// Quoted text. Unquoted can be found in `RawText`.
-#[derive(Clone, Debug, syn_derive::Parse, syn_derive::ToTokens)]
+#[derive(Clone, Debug, PartialEq, syn_derive::Parse, syn_derive::ToTokens)]
 pub struct NodeText {
     /// The text value.
     pub value: LitStr,
@@ -186,13 +670,39 @@ impl NodeText {
     pub fn value_string(&self) -> String {
         self.value.value()
     }
+
+    /// Append value of `other` to this text, span is joined if possible.
+    pub(crate) fn merge(&mut self, other: NodeText) {
+        let span = self.value.span();
+        let span = span.join(other.value.span()).unwrap_or(span);
+        self.value = LitStr::new(&(self.value.value() + &other.value.value()), span);
+    }
+
+    /// Returns value parsed as integer, e.g. `"-1"`, surrounding whitespace
+    /// is ignored.
+    ///
+    /// Unquoted numbers, like `<p>42</p>`, are parsed as [`RawText`], see
+    /// [`RawText::value_int`].
+    pub fn value_int(&self) -> Option<i128> {
+        self.value.value().trim().parse().ok()
+    }
+
+    /// Returns value parsed as float, e.g. `"0.5"`, surrounding whitespace is
+    /// ignored.
+    pub fn value_float(&self) -> Option<f64> {
+        self.value.value().trim().parse().ok()
+    }
 }
 
 /// Comment node.
 ///
 /// Comment: `<!-- "comment" -->`, currently has the same restrictions as
 /// `Text` (comment needs to be quoted).
-#[derive(Clone, Debug, syn_derive::Parse, syn_derive::ToTokens)]
+///
+/// Doc comments between nodes, like `/// comment`, are parsed as comments too,
+/// with value `" comment"`. Other rust comments (`// comment`, `/* comment */`)
+/// are removed by tokenizer, and never reach parser.
+#[derive(Clone, Debug, PartialEq, syn_derive::Parse, syn_derive::ToTokens)]
 pub struct NodeComment {
     pub token_start: tokens::ComStart,
     /// The comment value.
@@ -206,7 +716,7 @@ pub struct NodeComment {
 /// Usually doctype only contaim html, but also can contain arbitrary DOCTYPE
 /// legacy string, or "obsolete permitted DOCTYPE string", therewhy value is
 /// RawText.
-#[derive(Clone, Debug, syn_derive::ToTokens)]
+#[derive(Clone, Debug, PartialEq, syn_derive::ToTokens)]
 pub struct NodeDoctype {
     pub token_start: tokens::DocStart,
     /// "doctype"
@@ -219,7 +729,7 @@ pub struct NodeDoctype {
 /// Fragement node.
 ///
 /// Fragment: `<></>`
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct NodeFragment<C> {
     /// Open fragment token
     pub tag_open: FragmentOpen,
@@ -227,6 +737,8 @@ pub struct NodeFragment<C> {
     pub children: Vec<Node<C>>,
     /// Close fragment token
     pub tag_close: Option<FragmentClose>,
+    /// Tokens fragment was parsed from, see [`Node::tokens`].
+    pub source_tokens: SourceTokens,
 }
 // Manual implementation, because derive macro doesn't support generics.
 impl<C: CustomNode> ToTokens for NodeFragment<C> {
@@ -250,6 +762,33 @@ impl<C> NodeFragment<C> {
     }
 }
 
+/// Compare token streams ignoring spans.
+pub(crate) fn token_stream_eq(left: &TokenStream, right: &TokenStream) -> bool {
+    let mut left = left.clone().into_iter();
+    let mut right = right.clone().into_iter();
+    loop {
+        match (left.next(), right.next()) {
+            (None, None) => return true,
+            (Some(l), Some(r)) if token_tree_eq(&l, &r) => {}
+            _ => return false,
+        }
+    }
+}
+
+fn token_tree_eq(left: &TokenTree, right: &TokenTree) -> bool {
+    match (left, right) {
+        (TokenTree::Group(l), TokenTree::Group(r)) => {
+            l.delimiter() == r.delimiter() && token_stream_eq(&l.stream(), &r.stream())
+        }
+        (TokenTree::Ident(l), TokenTree::Ident(r)) => l == r,
+        (TokenTree::Punct(l), TokenTree::Punct(r)) => {
+            l.as_char() == r.as_char() && l.spacing() == r.spacing()
+        }
+        (TokenTree::Literal(l), TokenTree::Literal(r)) => l.to_string() == r.to_string(),
+        _ => false,
+    }
+}
+
 fn path_to_string(expr: &ExprPath) -> String {
     expr.path
         .segments
@@ -259,7 +798,14 @@ fn path_to_string(expr: &ExprPath) -> String {
         .join("::")
 }
 
-pub trait CustomNode: ParseRecoverable + ToTokens {
+/// Custom node type, see [`ParserConfig::custom_node`].
+///
+/// Should be `'static`, to allow type erased callbacks in
+/// [`ParserConfig::on_element_closed`].
+///
+/// [`ParserConfig::custom_node`]: crate::ParserConfig::custom_node
+/// [`ParserConfig::on_element_closed`]: crate::ParserConfig::on_element_closed
+pub trait CustomNode: ParseRecoverable + ToTokens + 'static {
     /// Peeks the token stream to decide whether this node should be parsed.
     ///
     /// Recieves a [`ParseStream::fork`].
@@ -1,19 +1,22 @@
 use std::{
     convert::TryFrom,
     fmt::{self, Display},
+    hash::{Hash, Hasher},
+    str::FromStr,
 };
 
-use proc_macro2::Punct;
+use proc_macro2::{Punct, Span, TokenStream, TokenTree};
+use quote::ToTokens;
 use syn::{
     ext::IdentExt,
-    parse::{discouraged::Speculative, Parse, ParseStream, Peek},
+    parse::{discouraged::Speculative, Parse, ParseStream, Parser, Peek},
     punctuated::{Pair, Punctuated},
     token::{Brace, Colon, Dot, PathSep},
     Block, ExprPath, Ident, LitInt, Path, PathSegment,
 };
 
-use super::{atoms::tokens::Dash, path_to_string};
-use crate::{node::parse::block_expr, Error};
+use super::{atoms::tokens::Dash, path_to_string, KeyedAttribute, Name, NameInterner};
+use crate::{node::parse::block_expr, Error, NamePolicy, ParserError};
 
 #[derive(Clone, Debug, syn_derive::Parse, syn_derive::ToTokens)]
 pub enum NodeNameFragment {
@@ -51,6 +54,47 @@ impl Display for NodeNameFragment {
     }
 }
 
+/// Place where name is used, see [`NodeName::validity`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NameKind {
+    /// Name of element, which can also start with digit or `-`, e.g. `<1a>`.
+    Element,
+    /// Key of attribute.
+    Attribute,
+}
+
+impl Display for NameKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Element => "element",
+            Self::Attribute => "attribute",
+        })
+    }
+}
+
+/// Reason why string can't be used as name, see [`NodeName::validity`].
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum NameError {
+    /// Name can't be converted to tokens, e.g. it has unbalanced quotes.
+    #[error("name `{0}` can't be tokenized")]
+    Tokenize(String),
+    /// Tokens of name are not parsed as [`NodeName`], or there are tokens
+    /// left after it, e.g. `a b`.
+    #[error("`{name}` is not a valid {kind} name: {message}")]
+    Syntax {
+        name: String,
+        kind: NameKind,
+        message: String,
+    },
+    /// Character of name is not allowed by [`NamePolicy`].
+    #[error("character `{character}` in name `{name}` is not allowed by {policy} rules")]
+    Character {
+        name: String,
+        character: char,
+        policy: NamePolicy,
+    },
+}
+
 /// Name of the node.
 #[derive(Clone, Debug, syn_derive::ToTokens)]
 pub enum NodeName {
@@ -67,6 +111,8 @@ pub enum NodeName {
     /// ID and NAME tokens must begin with a letter ([A-Za-z]) and may be
     /// followed by any number of letters, digits ([0-9]), hyphens ("-"),
     /// underscores ("_"), colons (":"), and periods (".").
+    /// Names of elements can also start with digits or hyphen, e.g. `<1a>` or
+    /// `<-custom>`.
     ///
     /// Support more than one punctuation in series, in this case
     /// `NodeNameFragment::Empty` would be used.
@@ -83,6 +129,101 @@ pub enum NodeName {
 }
 
 impl NodeName {
+    /// Check that `name` would be parsed as a single name of `kind`, e.g.
+    /// before using it in generated template.
+    ///
+    /// Only syntax of name is checked, use
+    /// [`validity_with_policy`](Self::validity_with_policy) to also check its
+    /// characters.
+    ///
+    /// ```rust
+    /// use rstml::node::{NameError, NameKind, NodeName};
+    ///
+    /// assert!(NodeName::validity("my-element", NameKind::Element).is_ok());
+    /// assert!(NodeName::validity("1a", NameKind::Element).is_ok());
+    /// assert!(NodeName::validity("on:click", NameKind::Attribute).is_ok());
+    /// assert!(matches!(
+    ///     NodeName::validity("1a", NameKind::Attribute),
+    ///     Err(NameError::Syntax { .. })
+    /// ));
+    /// assert!(!NodeName::is_valid("data id", NameKind::Attribute));
+    /// ```
+    pub fn validity(name: &str, kind: NameKind) -> Result<(), NameError> {
+        Self::validity_with_policy(name, kind, NamePolicy::Permissive)
+    }
+
+    /// Check that `name` would be parsed as a single name of `kind`, and its
+    /// characters are allowed by `policy`, like with
+    /// [`ParserConfig::name_policy`](crate::ParserConfig::name_policy).
+    pub fn validity_with_policy(
+        name: &str,
+        kind: NameKind,
+        policy: NamePolicy,
+    ) -> Result<(), NameError> {
+        let tokens =
+            TokenStream::from_str(name).map_err(|_| NameError::Tokenize(name.to_string()))?;
+        let parse = |input: ParseStream| {
+            let name = match kind {
+                NameKind::Element => Self::parse_element_name(input)?,
+                NameKind::Attribute => KeyedAttribute::parse_key(input)?,
+            };
+            if !input.is_empty() {
+                return Err(input.error("unexpected tokens after name"));
+            }
+            Ok(name)
+        };
+        let parsed = parse.parse2(tokens).map_err(|e| NameError::Syntax {
+            name: name.to_string(),
+            kind,
+            message: e.to_string(),
+        })?;
+        match parsed.invalid_character(policy, kind == NameKind::Attribute) {
+            Some((character, _)) => Err(NameError::Character {
+                name: parsed.to_string(),
+                character,
+                policy,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns true if [`NodeName::validity`] of `name` is ok.
+    pub fn is_valid(name: &str, kind: NameKind) -> bool {
+        Self::validity(name, kind).is_ok()
+    }
+
+    /// First character of name that is not allowed by `policy`, with span of
+    /// the token that contains it. Blocks are not checked.
+    pub(crate) fn invalid_character(
+        &self,
+        policy: NamePolicy,
+        attribute: bool,
+    ) -> Option<(char, Span)> {
+        if policy == NamePolicy::Permissive || self.is_block() {
+            return None;
+        }
+        let custom = self.to_string().contains('-');
+        let mut first = true;
+        for token in self.to_token_stream() {
+            let text = match &token {
+                TokenTree::Ident(ident) => ident.unraw().to_string(),
+                token => token.to_string(),
+            };
+            for character in text.chars() {
+                if !policy.allows(character, first, attribute, custom) {
+                    return Some((character, token.span()));
+                }
+                first = false;
+            }
+        }
+        None
+    }
+
+    /// Interned string representation of name, see [`NameInterner`].
+    pub fn interned(&self, interner: &mut NameInterner) -> Name {
+        interner.intern_name(self)
+    }
+
     /// Returns true if `NodeName` parsed as block of code.
     ///
     /// Example:
@@ -97,10 +238,11 @@ impl NodeName {
     /// foo-bar
     pub fn is_dashed(&self) -> bool {
         match self {
-            Self::Punctuated(p) => {
-                let p = p.pairs().next().unwrap();
-                p.punct().unwrap().as_char() == '-'
-            }
+            Self::Punctuated(p) => p
+                .pairs()
+                .next()
+                .and_then(|p| p.punct().map(|p| p.as_char() == '-'))
+                .unwrap_or(false),
             _ => false,
         }
     }
@@ -124,6 +266,34 @@ impl NodeName {
         }
     }
 
+    /// Compare names ignoring ASCII case, e.g. `DIV` and `div` are equal.
+    ///
+    /// Only names of html elements are compared this way, component names,
+    /// like `Foo` or `foo::bar` (path of several segments), and blocks are
+    /// equal only if they are exactly the same. Punctuated names (`my-tag`)
+    /// are always compared ignoring case.
+    pub fn eq_ignore_ascii_case(&self, other: &NodeName) -> bool {
+        if self.is_component_like() || other.is_component_like() {
+            return self == other;
+        }
+        self.to_string().eq_ignore_ascii_case(&other.to_string())
+    }
+
+    /// Returns true if name is block, path of several segments, or ident that
+    /// starts with uppercase letter followed by lowercase letters (`Foo`).
+    fn is_component_like(&self) -> bool {
+        match self {
+            Self::Block(_) => true,
+            Self::Path(e) if e.path.segments.len() != 1 => true,
+            Self::Path(_) => {
+                let name = self.to_string();
+                name.starts_with(|c: char| c.is_ascii_uppercase())
+                    && name.contains(|c: char| c.is_ascii_lowercase())
+            }
+            Self::Punctuated(_) => false,
+        }
+    }
+
     /// Parse the stream as punctuated idents.
     ///
     /// We can't replace this with [`Punctuated::parse_separated_nonempty`]
@@ -192,6 +362,64 @@ impl NodeName {
             Err(fork.error("expected punctuated node name"))
         }
     }
+
+    /// Parse name of element, which unlike attribute key can start with digits
+    /// or `-`, e.g. `<1a>` or `<-custom>`.
+    ///
+    /// Parts of such name are joined only if there is no whitespace between
+    /// them, which is checked by adjacency of their spans. Other names are
+    /// parsed with [`NodeName::parse`].
+    pub(crate) fn parse_element_name(input: ParseStream) -> syn::Result<Self> {
+        if !input.peek(LitInt) && !input.peek(Dash) {
+            return Self::parse(input);
+        }
+        let fork = &input.fork();
+        let mut segments = Punctuated::<NodeNameFragment, Punct>::new();
+        let mut prev_span: Option<Span> = None;
+        loop {
+            let adjacent = prev_span.is_none_or(|prev| is_adjacent(prev, fork.span()));
+            let fragment = if adjacent {
+                NodeNameFragment::parse(fork)?
+            } else {
+                NodeNameFragment::Empty
+            };
+            match &fragment {
+                NodeNameFragment::Ident(ident) => prev_span = Some(ident.span()),
+                NodeNameFragment::Literal(lit) => prev_span = Some(lit.span()),
+                NodeNameFragment::Empty => {}
+            }
+            segments.push_value(fragment);
+
+            let adjacent = prev_span.is_none_or(|prev| is_adjacent(prev, fork.span()));
+            if adjacent && (fork.peek(Colon) || fork.peek(Dash) || fork.peek(Dot)) {
+                let punct: Punct = fork.parse()?;
+                prev_span = Some(punct.span());
+                segments.push_punct(punct);
+            } else {
+                break;
+            }
+        }
+
+        if segments.iter().all(|f| *f == NodeNameFragment::Empty) {
+            return Err(input.error("invalid tag name or attribute key"));
+        }
+        input.advance_to(fork);
+        Ok(NodeName::Punctuated(segments))
+    }
+}
+
+/// Returns true if there is no whitespace between `prev` and `next` tokens.
+///
+/// Tokens without location, or generated tokens that share the same span (e.g.
+/// from `quote!`), are considered adjacent, even if only one of them is
+/// generated.
+pub(crate) fn is_adjacent(prev: Span, next: Span) -> bool {
+    let call_site = Span::call_site();
+    let generated = |span: Span| span.start() == call_site.start() && span.end() == call_site.end();
+    prev.end() == next.start()
+        || (prev.start() == next.start() && prev.end() == next.end())
+        || generated(prev)
+        || generated(next)
 }
 
 impl TryFrom<&NodeName> for Block {
@@ -217,18 +445,20 @@ impl PartialEq for NodeName {
             // can't be derived automatically because `Punct` doesn't impl `PartialEq`
             Self::Punctuated(this) => match other {
                 Self::Punctuated(other) => {
-                    this.pairs()
-                        .zip(other.pairs())
-                        .all(|(this, other)| match (this, other) {
-                            (
-                                Pair::Punctuated(this_ident, this_punct),
-                                Pair::Punctuated(other_ident, other_punct),
-                            ) => {
-                                this_ident == other_ident
-                                    && this_punct.as_char() == other_punct.as_char()
+                    this.len() == other.len()
+                        && this.trailing_punct() == other.trailing_punct()
+                        && this.pairs().zip(other.pairs()).all(|(this, other)| {
+                            match (this, other) {
+                                (
+                                    Pair::Punctuated(this_ident, this_punct),
+                                    Pair::Punctuated(other_ident, other_punct),
+                                ) => {
+                                    this_ident == other_ident
+                                        && this_punct.as_char() == other_punct.as_char()
+                                }
+                                (Pair::End(this), Pair::End(other)) => this == other,
+                                _ => false,
                             }
-                            (Pair::End(this), Pair::End(other)) => this == other,
-                            _ => false,
                         })
                 }
                 _ => false,
@@ -241,6 +471,15 @@ impl PartialEq for NodeName {
     }
 }
 
+impl Eq for NodeName {}
+
+// Hash of string representation, names that are equal have the same string.
+impl Hash for NodeName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_string().hash(state)
+    }
+}
+
 impl fmt::Display for NodeName {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -267,10 +506,7 @@ impl fmt::Display for NodeName {
 impl Parse for NodeName {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         if input.peek(LitInt) {
-            Err(syn::Error::new(
-                input.span(),
-                "Name must start with latin character",
-            ))
+            Err(ParserError::InvalidNameStart.error(input.span()))
         } else if input.peek2(PathSep) {
             NodeName::node_name_punctuated_ident::<PathSep, fn(_) -> PathSep, PathSegment>(
                 input, PathSep,
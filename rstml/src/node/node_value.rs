@@ -5,6 +5,8 @@ use std::convert::TryFrom;
 use proc_macro2::TokenStream;
 use syn::{token::Brace, Block};
 
+use super::token_stream_eq;
+
 #[derive(Clone, Debug, syn_derive::ToTokens, syn_derive::Parse)]
 pub struct InvalidBlock {
     #[syn(braced)]
@@ -13,14 +15,24 @@ pub struct InvalidBlock {
     pub body: TokenStream,
 }
 
+impl PartialEq for InvalidBlock {
+    fn eq(&self, other: &Self) -> bool {
+        token_stream_eq(&self.body, &other.body)
+    }
+}
+
 /// Block node.
 ///
 /// Arbitrary rust code in braced `{}` blocks.
-#[derive(Clone, Debug, syn_derive::ToTokens)]
+#[derive(Clone, Debug, PartialEq, syn_derive::ToTokens)]
 pub enum NodeBlock {
     /// The block value..
     ValidBlock(Block),
 
+    /// Block that is not a valid `syn::Block`, or any block, if
+    /// [`ParserConfig::raw_blocks`] is set.
+    ///
+    /// [`ParserConfig::raw_blocks`]: crate::ParserConfig::raw_blocks
     Invalid(InvalidBlock),
 }
 
@@ -1,34 +1,51 @@
 //!
 //! Implementation of ToTokens and Spanned for node related structs
 
-use proc_macro2::{extra::DelimSpan, Delimiter, TokenStream};
-use proc_macro2_diagnostics::{Diagnostic, Level};
+use std::borrow::Cow;
+
+use proc_macro2::{extra::DelimSpan, Delimiter, Span, TokenStream};
+use proc_macro2_diagnostics::Level;
 use quote::ToTokens;
 use syn::{
-    braced,
+    braced, bracketed,
+    buffer::Cursor,
+    parenthesized,
     parse::{discouraged::Speculative, Parse, ParseStream, Parser},
     spanned::Spanned,
-    token::Brace,
+    token::{Brace, Bracket, Paren},
     Block, Ident, LitStr, Token,
 };
 
 use super::{
     atoms::{
         tokens::{self, DocStart},
-        CloseTag, FragmentClose, FragmentOpen, OpenTag,
+        CloseTag, FragmentClose, FragmentOpen, OpenTag, PartialOpenTag,
     },
+    interpolation::interpolate_children,
     raw_text::RawText,
-    CustomNode, Node, NodeBlock, NodeDoctype, NodeFragment,
+    sugar::parse_tag_sugar,
+    CustomNode, Directive, InvalidBlock, Node, NodeBlock, NodeComment, NodeDoctype, NodeFragment,
+    NodeText,
 };
 use crate::{
     atoms::CloseTagStart,
-    config::TransformBlockFn,
-    node::{NodeAttribute, NodeElement},
+    config::{ChildrenParserFn, TransformBlockFn},
+    node::{Name, Namespace, NodeAttribute, NodeElement, NodeName},
     parser::recoverable::{ParseRecoverable, RecoverableContext},
+    trace, ParserConfig, ParserError,
 };
 
 impl ParseRecoverable for NodeBlock {
     fn parse_recoverable(parser: &mut RecoverableContext, input: ParseStream) -> Option<Self> {
+        if parser.config().reject_empty_blocks && peek_empty_block(parser, input) {
+            let block = take_invalid_block(parser, input)?;
+            parser.push_error(ParserError::EmptyBlock, block.brace.span.join());
+            return Some(NodeBlock::Invalid(block));
+        }
+        if parser.config().raw_blocks {
+            return take_invalid_block(parser, input).map(NodeBlock::Invalid);
+        }
+        // Content is parsed on fork, to keep `input` untouched on error.
         let fork = input.fork();
 
         let block = match parse_valid_block_expr(parser, &fork) {
@@ -37,8 +54,9 @@ impl ParseRecoverable for NodeBlock {
                 NodeBlock::ValidBlock(value)
             }
             Err(e) if parser.config().recover_block => {
+                trace::debug!("recovering invalid block");
                 parser.push_diagnostic(e);
-                NodeBlock::Invalid(parser.parse_simple(input)?)
+                NodeBlock::Invalid(take_invalid_block(parser, input)?)
             }
             Err(e) => {
                 parser.push_diagnostic(e);
@@ -49,35 +67,108 @@ impl ParseRecoverable for NodeBlock {
     }
 }
 
+/// Returns true if the next token is a block without content.
+fn peek_empty_block(parser: &RecoverableContext, input: ParseStream) -> bool {
+    let cursor = input.cursor();
+    parser
+        .config()
+        .block_delimiters()
+        .iter()
+        .find_map(|delimiter| cursor.group(*delimiter))
+        .is_some_and(|(content, ..)| content.eof())
+}
+
+/// Take next braced group as [`InvalidBlock`] directly from cursor, without
+/// parsing its content again.
+pub(crate) fn take_invalid_block(
+    parser: &mut RecoverableContext,
+    input: ParseStream,
+) -> Option<InvalidBlock> {
+    let delimiters = parser.config().block_delimiters();
+    let block = input.step(|cursor| {
+        let (content, span, next) = delimiters
+            .iter()
+            .find_map(|delimiter| cursor.group(*delimiter))
+            .ok_or_else(|| cursor.error("expected curly braces"))?;
+        let block = InvalidBlock {
+            brace: Brace { span },
+            body: content.token_stream(),
+        };
+        Ok((block, next))
+    });
+    parser.save_diagnostics(block)
+}
+
 impl<C: CustomNode> ParseRecoverable for NodeFragment<C> {
     fn parse_recoverable(parser: &mut RecoverableContext, input: ParseStream) -> Option<Self> {
+        let cursor = input.cursor();
+        let start = Self::parse_start(parser, input)?;
+        match parse_node_tree(parser, input, Some(cursor), start)? {
+            Node::Fragment(fragment) => Some(fragment),
+            _ => None,
+        }
+    }
+}
+
+impl<C: CustomNode> NodeFragment<C> {
+    fn parse_start(parser: &mut RecoverableContext, input: ParseStream) -> Option<NodeStart<C>> {
         let tag_open: FragmentOpen = parser.parse_simple(input)?;
+        trace::debug!("fragment open");
+        if parser.config().reject_nested_fragments && parser.depth > 0 {
+            parser.push_error(ParserError::NestedFragment, tag_open.span());
+        }
 
         let is_raw = |name| parser.config().raw_text_elements.contains(name);
 
-        let (children, tag_close) = if is_raw("") {
-            let (child, closed_tag) =
+        if is_raw("") {
+            let (child, tag_close) =
                 parser.parse_with_ending(input, |_, t| RawText::from(t), FragmentClose::parse);
+            let child = match child.as_single_literal() {
+                Some(text) => Node::Text(NodeText { value: text }),
+                None => Node::RawText(child),
+            };
+            let fragment = Self::finish(parser, tag_open, vec![child], tag_close);
+            return Some(NodeStart::Complete(Node::Fragment(fragment)));
+        }
+        Some(NodeStart::Open(OpenNode {
+            tag: OpenNodeTag::Fragment(tag_open),
+            children: vec![],
+            namespace: parser.namespace,
+            directives: vec![],
+        }))
+    }
 
-            (vec![Node::<C>::RawText(child)], closed_tag)
-        } else {
-            let (child, close_tag_start) =
-                parser.parse_tokens_until_call::<Node<C>, _, _>(input, CloseTagStart::parse);
-            (
-                child,
-                FragmentClose::parse_with_start_tag(parser, input, close_tag_start),
-            )
-        };
+    fn finish(
+        parser: &mut RecoverableContext,
+        tag_open: FragmentOpen,
+        children: Vec<Node<C>>,
+        tag_close: Option<FragmentClose>,
+    ) -> Self {
+        let is_empty = children.iter().all(|child| match child {
+            Node::RawText(text) => text.is_empty(),
+            _ => false,
+        });
+        if parser.config().reject_empty_fragments && is_empty {
+            parser.push_error(ParserError::EmptyFragment, tag_open.span());
+        }
+        if tag_close.is_none() {
+            let error = ParserError::UnclosedTag {
+                name: String::new(),
+                suggested_fix: "</>".to_string(),
+            };
+            parser.push_error(error, tag_open.span());
+        }
         let open_tag_end = tag_open.token_gt.span();
         let close_tag_start = tag_close.as_ref().map(|v| v.start_tag.token_lt.span());
 
         let children = RawText::vec_set_context(open_tag_end, close_tag_start, children);
 
-        Some(NodeFragment {
+        NodeFragment {
             tag_open,
             children,
             tag_close,
-        })
+            source_tokens: Default::default(),
+        }
     }
 }
 
@@ -86,11 +177,7 @@ impl ParseRecoverable for NodeDoctype {
         let token_start = parser.parse_simple::<DocStart>(input)?;
         let doctype_keyword = parser.parse_simple::<Ident>(input)?;
         if doctype_keyword.to_string().to_lowercase() != "doctype" {
-            parser.push_diagnostic(Diagnostic::spanned(
-                doctype_keyword.span(),
-                Level::Error,
-                "expected DOCTYPE keyword",
-            ));
+            parser.push_error(ParserError::ExpectedDoctype, doctype_keyword.span());
             return None;
         }
         let (value, token_end) =
@@ -106,6 +193,43 @@ impl ParseRecoverable for NodeDoctype {
     }
 }
 
+impl NodeComment {
+    /// Check if input starts with doc comment, like `/// text` or `//! text`.
+    pub(crate) fn peek_doc(input: ParseStream) -> bool {
+        input.peek(Token![#]) && Self::parse_doc(&input.fork()).is_ok()
+    }
+
+    /// Parse doc comment, which tokenizer turns into `#[doc = " text"]`
+    /// attribute, as comment with the same value and span of `#`.
+    pub(crate) fn parse_doc(input: ParseStream) -> syn::Result<Self> {
+        let span = input.parse::<Token![#]>()?.span;
+        input.parse::<Option<Token![!]>>()?;
+        let content;
+        bracketed!(content in input);
+        let path = content.parse::<Ident>()?;
+        if path != "doc" {
+            return Err(syn::Error::new(path.span(), "expected doc comment"));
+        }
+        content.parse::<Token![=]>()?;
+        let value = content.parse::<LitStr>()?;
+        if !content.is_empty() {
+            return Err(content.error("unexpected tokens after doc comment"));
+        }
+        Ok(Self {
+            token_start: tokens::ComStart {
+                token_lt: Token![<](span),
+                token_not: Token![!](span),
+                token_minus: [Token![-](span); 2],
+            },
+            value,
+            token_end: tokens::ComEnd {
+                token_minus: [Token![-](span); 2],
+                token_gt: Token![>](span),
+            },
+        })
+    }
+}
+
 impl OpenTag {
     /// Parses the opening `<` of an open tag, providing handling of the
     /// unexpected `</` of a close tag.
@@ -125,32 +249,24 @@ impl OpenTag {
             } else {
                 token_lt.span()
             };
-            parser.push_diagnostic(Diagnostic::spanned(
-                span,
-                Level::Error,
-                "close tag was parsed while waiting for open tag",
-            ));
+            parser.push_error(ParserError::UnexpectedCloseTag, span);
         }
         Some(token_lt)
     }
 }
 
-impl ParseRecoverable for OpenTag {
+impl ParseRecoverable for PartialOpenTag {
     fn parse_recoverable(parser: &mut RecoverableContext, input: ParseStream) -> Option<Self> {
-        let token_lt = Self::parse_start_tag(parser, input)?;
-        let name = parser.parse_simple(input)?;
+        let token_lt = OpenTag::parse_start_tag(parser, input)?;
+        let mut name = parser.save_diagnostics(NodeName::parse_element_name(input))?;
+        let mut attributes = parse_tag_sugar(parser, input, &mut name);
         let generics = parser.parse_simple(input)?;
 
-        let (attributes, end_tag) = parser
-            .parse_tokens_with_conflicted_ending::<NodeAttribute, _, _>(
-                input,
-                tokens::OpenTagEnd::parse,
-            );
-
-        if end_tag.is_none() {
-            parser.push_diagnostic(Diagnostic::new(Level::Error, "expected end of tag '>'"));
-        }
-        end_tag.map(|end_tag| OpenTag {
+        attributes.extend(NodeAttribute::parse_list(parser, input));
+        NodeAttribute::set_positions(parser, &mut attributes);
+        // Attributes are parsed until the end of tag or end of input.
+        let end_tag = input.parse::<tokens::OpenTagEnd>().ok();
+        Some(PartialOpenTag {
             token_lt,
             name,
             generics,
@@ -160,6 +276,19 @@ impl ParseRecoverable for OpenTag {
     }
 }
 
+impl ParseRecoverable for OpenTag {
+    fn parse_recoverable(parser: &mut RecoverableContext, input: ParseStream) -> Option<Self> {
+        let tag: PartialOpenTag = parser.parse_recoverable(input)?;
+        match OpenTag::try_from(tag) {
+            Ok(tag) => Some(tag),
+            Err(tag) => {
+                parser.push_error(ParserError::MissingTagEnd, tag.span());
+                None
+            }
+        }
+    }
+}
+
 impl<C: CustomNode> NodeElement<C> {
     /// Parses the children of a node, stopping at the first matching closing
     /// tag, following the behavior specified in the [`ParserConfig`].
@@ -174,115 +303,389 @@ impl<C: CustomNode> NodeElement<C> {
         raw: bool,
         open_tag: &OpenTag,
     ) -> Option<(Vec<Node<C>>, Option<CloseTag>)> {
-        let (children, close_tag) = if raw {
-            let (child, closed_tag) =
-                parser.parse_with_ending(input, |_, t| RawText::from(t), CloseTag::parse);
-            // don't keep empty RawText
-            let children = if !child.is_empty() {
-                vec![Node::RawText(child)]
-            } else {
-                vec![]
+        let name = parser.intern(&open_tag.name);
+        let namespace = Namespace::of_element(&name, parser.namespace);
+        let element = if raw {
+            Self::parse_raw(parser, input, open_tag.clone(), name, namespace, vec![])
+        } else {
+            let start = NodeStart::Open(OpenNode {
+                tag: OpenNodeTag::Element(open_tag.clone(), name),
+                children: vec![],
+                namespace,
+                directives: vec![],
+            });
+            match parse_node_tree(parser, input, None, start)? {
+                Node::Element(element) => element,
+                _ => return None,
+            }
+        };
+        Some((element.children, element.close_tag))
+    }
+
+    fn parse_start(parser: &mut RecoverableContext, input: ParseStream) -> Option<NodeStart<C>> {
+        let directives = std::mem::take(&mut parser.directives);
+        let open_tag = if parser.config().recover_incomplete {
+            let tag: PartialOpenTag = parser.parse_recoverable(input)?;
+            match OpenTag::try_from(tag) {
+                Ok(tag) => tag,
+                Err(tag) => {
+                    parser.push_error(ParserError::MissingTagEnd, tag.span());
+                    return Some(NodeStart::Complete(Node::Incomplete(tag)));
+                }
+            }
+        } else {
+            parser.parse_recoverable(input)?
+        };
+        let tag_name = parser.intern(&open_tag.name);
+        let tag_name_str = tag_name.as_str();
+        check_name_chars(parser, &open_tag.name, false);
+        for attribute in &open_tag.attributes {
+            if let NodeAttribute::Attribute(attribute) = attribute {
+                check_name_chars(parser, &attribute.key, true);
+            }
+        }
+        if let Some(check) = parser.config().element_name_check.clone() {
+            if let Err(reason) = check(&open_tag.name) {
+                let error = ParserError::InvalidElementName {
+                    name: tag_name_str.to_string(),
+                    reason,
+                };
+                parser.push_error(error, open_tag.name.span());
+            }
+        }
+        if let Some(check) = parser.config().attribute_name_check.clone() {
+            for attribute in &open_tag.attributes {
+                let NodeAttribute::Attribute(attribute) = attribute else {
+                    continue;
+                };
+                if let Err(reason) = check(&open_tag.name, &attribute.key) {
+                    let error = ParserError::InvalidAttributeName {
+                        element: tag_name_str.to_string(),
+                        name: attribute.key.to_string(),
+                        reason,
+                    };
+                    parser.push_error(error, attribute.key.span());
+                }
+            }
+        }
+        let namespace = Namespace::of_element(tag_name_str, parser.namespace);
+        trace::debug!(
+            name = tag_name_str,
+            attributes = open_tag.attributes.len(),
+            self_closed = open_tag.is_self_closed(),
+            ?namespace,
+            "open tag"
+        );
+        let is_void = namespace == Namespace::Html
+            && parser
+                .config()
+                .always_self_closed_elements
+                .contains(tag_name_str);
+        if is_void && parser.config().strict_self_closing && !open_tag.is_self_closed() {
+            parser.push_error(
+                ParserError::VoidElementNotSelfClosed(tag_name_str.to_string()),
+                open_tag.span(),
+            );
+        }
+        let is_raw = |name| parser.config().raw_text_elements.contains(name);
+        let element = if open_tag.is_self_closed() || is_void {
+            let element = NodeElement {
+                kind: parser.config().component_rules.kind(&open_tag.name),
+                open_tag,
+                children: vec![],
+                close_tag: None,
+                namespace,
+                source_tokens: Default::default(),
+                directives,
+                special_attributes: vec![],
+                interned_name: Some(tag_name.clone()),
             };
-            (children, closed_tag)
+            Self::closed(parser, element)
+        } else if let Some(children_parser) = parser.config().children_parser::<C>(tag_name_str) {
+            Self::parse_with_children_parser(
+                parser,
+                input,
+                open_tag,
+                tag_name.clone(),
+                namespace,
+                directives,
+                &*children_parser,
+            )
+        } else if is_raw(tag_name_str) || directives.contains(&Directive::Raw) {
+            Self::parse_raw(
+                parser,
+                input,
+                open_tag,
+                tag_name.clone(),
+                namespace,
+                directives,
+            )
         } else {
-            // If node is not raw use any closing tag as separator, to early report about
-            // invalid closing tags.
-            // Also parse only </ part to recover parser as soon as user types </
-            let (children, close_tag) =
-                parser.parse_tokens_until_call::<Node<C>, _, _>(input, CloseTagStart::parse);
+            return Some(NodeStart::Open(OpenNode {
+                tag: OpenNodeTag::Element(open_tag, tag_name.clone()),
+                children: vec![],
+                namespace,
+                directives,
+            }));
+        };
+        Some(NodeStart::Complete(Node::Element(element)))
+    }
 
-            let close_tag = CloseTag::parse_with_start_tag(parser, input, close_tag);
+    fn parse_raw(
+        parser: &mut RecoverableContext,
+        input: ParseStream,
+        open_tag: OpenTag,
+        name: Name,
+        namespace: Namespace,
+        directives: Vec<Directive>,
+    ) -> Self {
+        let (child, close_tag) =
+            parser.parse_with_ending(input, |_, t| RawText::from(t), CloseTag::parse);
+        // don't keep empty RawText
+        let children = if let Some(text) = child.as_single_literal() {
+            vec![Node::Text(NodeText { value: text })]
+        } else if !child.is_empty() {
+            vec![Node::RawText(child)]
+        } else {
+            vec![]
+        };
+        Self::finish(
+            parser, open_tag, name, children, close_tag, namespace, directives,
+        )
+    }
 
-            (children, close_tag)
+    /// Parse children with [`ParserConfig::children_parser`] callback, until
+    /// close tag with the same name.
+    ///
+    /// [`ParserConfig::children_parser`]: crate::ParserConfig::children_parser
+    fn parse_with_children_parser(
+        parser: &mut RecoverableContext,
+        input: ParseStream,
+        open_tag: OpenTag,
+        name: Name,
+        namespace: Namespace,
+        directives: Vec<Directive>,
+        children_parser: &ChildrenParserFn<C>,
+    ) -> Self {
+        let (tokens, close_tag) = parser.parse_with_ending(
+            input,
+            |_, t| t,
+            |input| {
+                let close_tag = CloseTag::parse(input)?;
+                if close_tag.name != open_tag.name {
+                    return Err(input.error("close tag name doesn't match"));
+                }
+                Ok(close_tag)
+            },
+        );
+        let parse = |input: ParseStream| children_parser(&open_tag, input);
+        let children = match parse.parse2(tokens.clone()) {
+            Ok(children) => children,
+            Err(e) => {
+                parser.push_diagnostic(e);
+                if tokens.is_empty() {
+                    vec![]
+                } else {
+                    vec![Node::RawText(RawText::from(tokens))]
+                }
+            }
         };
+        Self::finish(
+            parser, open_tag, name, children, close_tag, namespace, directives,
+        )
+    }
 
+    /// Set context of raw text children, and check that `close_tag` matches
+    /// `open_tag`.
+    fn finish(
+        parser: &mut RecoverableContext,
+        open_tag: OpenTag,
+        name: Name,
+        children: Vec<Node<C>>,
+        close_tag: Option<CloseTag>,
+        namespace: Namespace,
+        directives: Vec<Directive>,
+    ) -> Self {
         let open_tag_end = open_tag.end_tag.token_gt.span();
         let close_tag_start = close_tag.as_ref().map(|c| c.start_tag.token_lt.span());
         let children = RawText::vec_set_context(open_tag_end, close_tag_start, children);
 
-        let Some(close_tag) = close_tag else {
-            let mut diagnostic = Diagnostic::spanned(
-                open_tag.span(),
-                Level::Error,
-                "open tag has no corresponding close tag",
-            );
-            if !children.is_empty() {
-                let mut note_span = TokenStream::new();
-                children.iter().for_each(|v| v.to_tokens(&mut note_span));
-                diagnostic = diagnostic.span_note(
-                    note_span.span(),
-                    "treating all inputs after open tag as it content",
-                );
-            }
+        match &close_tag {
+            None => {
+                trace::debug!(name = %open_tag.name, "unclosed tag, rest of input is its children");
+                let name = open_tag.name.to_string();
+                let generics = &open_tag.generics;
+                let generics = if generics.params.is_empty() {
+                    String::new()
+                } else {
+                    generics.to_token_stream().to_string().replace(' ', "")
+                };
+                let error = ParserError::UnclosedTag {
+                    suggested_fix: format!("</{name}{generics}>"),
+                    name,
+                };
+                let mut diagnostic = parser.error_diagnostic(error, open_tag.span());
+                if !children.is_empty() {
+                    let mut note_span = TokenStream::new();
+                    children.iter().for_each(|v| v.to_tokens(&mut note_span));
+                    diagnostic = diagnostic.span_note(
+                        note_span.span(),
+                        "treating all inputs after open tag as it content",
+                    );
+                }
 
-            parser.push_diagnostic(diagnostic);
-            return Some((children, None));
+                parser.push_diagnostic(diagnostic);
+            }
+            Some(close_tag) => {
+                trace::trace!(name = %close_tag.name, "close tag");
+                Self::check_close_tag(parser, &open_tag, close_tag)
+            }
+        }
+        let element = NodeElement {
+            kind: parser.config().component_rules.kind(&open_tag.name),
+            interned_name: Some(name),
+            open_tag,
+            children,
+            close_tag,
+            namespace,
+            source_tokens: Default::default(),
+            directives,
+            special_attributes: vec![],
         };
+        Self::closed(parser, element)
+    }
 
-        if close_tag.name != open_tag.name {
+    /// Move [`ParserConfig::special_attributes`] out of attributes, and invoke
+    /// [`ParserConfig::on_element_closed`] callback on completed element.
+    ///
+    /// [`ParserConfig::special_attributes`]: crate::ParserConfig::special_attributes
+    /// [`ParserConfig::on_element_closed`]: crate::ParserConfig::on_element_closed
+    fn closed(parser: &mut RecoverableContext, mut element: Self) -> Self {
+        let special = &parser.config().special_attributes;
+        if !special.is_empty() {
+            let attributes = std::mem::take(&mut element.open_tag.attributes);
+            for attribute in attributes {
+                match attribute {
+                    NodeAttribute::Attribute(attribute)
+                        if attribute
+                            .interned_key
+                            .as_ref()
+                            .is_some_and(|key| special.contains(key.as_str())) =>
+                    {
+                        element.special_attributes.push(attribute)
+                    }
+                    attribute => element.open_tag.attributes.push(attribute),
+                }
+            }
+        }
+        if let Some(callback) = parser.config().on_element_closed::<C>() {
+            if let Err(e) = callback(&mut element) {
+                parser.push_diagnostic(e);
+            }
+        }
+        element
+    }
+
+    fn check_close_tag(parser: &mut RecoverableContext, open_tag: &OpenTag, close_tag: &CloseTag) {
+        let names_eq = if parser.config().case_insensitive_close_tags {
+            close_tag.name.eq_ignore_ascii_case(&open_tag.name)
+        } else {
+            close_tag.name == open_tag.name
+        };
+        if !names_eq {
             match parser.config().element_close_wildcard.as_deref() {
-                Some(is_wildcard) if is_wildcard(open_tag, &close_tag) => {}
+                Some(is_wildcard) if is_wildcard(open_tag, close_tag) => {}
                 _ => {
-                    let diagnostic = Diagnostic::spanned(
-                        close_tag.span(),
-                        Level::Error,
-                        "wrong close tag found",
-                    )
-                    .spanned_child(
-                        open_tag.span(),
-                        Level::Help,
-                        "open tag that should be closed; it's started here",
-                    );
+                    let diagnostic = parser
+                        .error_diagnostic(ParserError::MismatchedCloseTag, close_tag.span())
+                        .spanned_child(
+                            open_tag.span(),
+                            Level::Help,
+                            "open tag that should be closed; it's started here",
+                        );
 
                     parser.push_diagnostic(diagnostic)
                 }
             }
         }
         if close_tag.generics != open_tag.generics {
-            let diagnostic = Diagnostic::spanned(
-                close_tag.span(),
-                Level::Error,
-                "close tag generics missmatch",
-            )
-            .spanned_child(
-                open_tag.span(),
-                Level::Help,
-                "open tag generics should match close tag generics",
-            );
+            let diagnostic = parser
+                .error_diagnostic(ParserError::CloseTagGenericsMismatch, close_tag.span())
+                .spanned_child(
+                    open_tag.span(),
+                    Level::Help,
+                    "open tag generics should match close tag generics",
+                );
             parser.push_diagnostic(diagnostic)
         }
-        Some((children, Some(close_tag)))
     }
 }
 
 impl<C: CustomNode> ParseRecoverable for NodeElement<C> {
     fn parse_recoverable(parser: &mut RecoverableContext, input: ParseStream) -> Option<Self> {
-        let open_tag: OpenTag = parser.parse_recoverable(input)?;
-        let is_known_self_closed =
-            |name| parser.config().always_self_closed_elements.contains(name);
-        let is_raw = |name| parser.config().raw_text_elements.contains(name);
-
-        let tag_name_str = &*open_tag.name.to_string();
-        if open_tag.is_self_closed() || is_known_self_closed(tag_name_str) {
-            return Some(NodeElement {
-                open_tag,
-                children: vec![],
-                close_tag: None,
-            });
+        let cursor = input.cursor();
+        let start = Self::parse_start(parser, input)?;
+        match parse_node_tree(parser, input, Some(cursor), start)? {
+            Node::Element(element) => Some(element),
+            _ => None,
         }
-        let (children, close_tag) =
-            Self::parse_children(parser, input, is_raw(tag_name_str), &open_tag)?;
-        let element = NodeElement {
-            open_tag,
-            children,
-            close_tag,
-        };
-        Some(element)
     }
 }
 
 impl<C: CustomNode> ParseRecoverable for Node<C> {
     fn parse_recoverable(parser: &mut RecoverableContext, input: ParseStream) -> Option<Self> {
+        let cursor = input.cursor();
+        let start = Self::parse_start(parser, input)?;
+        parse_node_tree(parser, input, Some(cursor), start)
+    }
+}
+
+impl<C: CustomNode + std::fmt::Debug> Node<C> {
+    /// Parser of a single node with `config`, for embedding rsx into a larger
+    /// grammar. Tokens after the node are left in input.
+    ///
+    /// Returned closure is called with [`ParseStream`] directly, or used as
+    /// [`syn::parse::Parser`]. It returns error if any error was reported
+    /// during parsing.
+    ///
+    /// ```rust
+    /// use quote::quote;
+    /// use rstml::{node::Node, ParserConfig};
+    /// use syn::{
+    ///     parse::{ParseStream, Parser},
+    ///     Ident, Token,
+    /// };
+    ///
+    /// let config = ParserConfig::new();
+    /// let arms = |input: ParseStream| {
+    ///     let mut arms = vec![];
+    ///     while !input.is_empty() {
+    ///         let pattern: Ident = input.parse()?;
+    ///         input.parse::<Token![=>]>()?;
+    ///         arms.push((pattern, Node::parse_with(&config)(input)?));
+    ///         if !input.is_empty() {
+    ///             input.parse::<Token![,]>()?;
+    ///         }
+    ///     }
+    ///     Ok(arms)
+    /// };
+    /// let arms = arms.parse2(quote! { A => <div/>, B => <p>"b"</p> }).unwrap();
+    /// assert_eq!(arms[1].1.to_debug_tree(), "p\n  \"b\"\n");
+    /// ```
+    pub fn parse_with(config: &ParserConfig<C>) -> impl Fn(ParseStream) -> syn::Result<Self> + '_ {
+        move |input| {
+            let mut parser = RecoverableContext::new(config.clone().into());
+            let node = Self::parse_recoverable(&mut parser, input);
+            parser.parse_result(node).into_result()
+        }
+    }
+}
+
+impl<C: CustomNode> Node<C> {
+    /// Parse node, or only the open tag, if node has children.
+    fn parse_start(parser: &mut RecoverableContext, input: ParseStream) -> Option<NodeStart<C>> {
+        // Directives are applied only to the element right after them.
+        let directives = std::mem::take(&mut parser.directives);
         let node = if C::peek_element(&input.fork()) {
             Node::Custom(C::parse_recoverable(parser, input)?)
         } else if input.peek(Token![<]) {
@@ -290,27 +693,353 @@ impl<C: CustomNode> ParseRecoverable for Node<C> {
                 if input.peek3(Ident) {
                     Node::Doctype(parser.parse_recoverable(input)?)
                 } else {
-                    Node::Comment(parser.parse_simple(input)?)
+                    let comment = parser.parse_simple(input)?;
+                    Self::comment(parser, directives, comment)
                 }
             } else if input.peek2(Token![>]) {
-                Node::Fragment(parser.parse_recoverable(input)?)
+                return NodeFragment::parse_start(parser, input);
             } else {
-                Node::Element(parser.parse_recoverable(input)?)
+                parser.directives = directives;
+                return NodeElement::parse_start(parser, input);
+            }
+        } else if peek_block(input, parser.config().block_delimiters()) {
+            // Broken block is already reported, keep it as invalid, so it will
+            // not abort parsing of the parent and its siblings.
+            match parser.parse_recoverable(input) {
+                Some(block) => Node::Block(block),
+                None => Node::Block(NodeBlock::Invalid(take_invalid_block(parser, input)?)),
             }
-        } else if input.peek(Brace) {
-            Node::Block(parser.parse_recoverable(input)?)
         } else if input.peek(LitStr) {
             Node::Text(parser.parse_simple(input)?)
+        } else if NodeComment::peek_doc(input) {
+            let comment = parser.save_diagnostics(NodeComment::parse_doc(input))?;
+            Self::comment(parser, directives, comment)
         } else if !input.is_empty() {
             // Parse any input except of any other Node starting
             Node::RawText(parser.parse_recoverable(input)?)
         } else {
             return None;
         };
-        Some(node)
+        trace::trace!(r#type = %node.r#type(), "node");
+        Some(NodeStart::Complete(node))
+    }
+
+    /// Comment node, which directive, if any, is applied to the next element.
+    fn comment(
+        parser: &mut RecoverableContext,
+        directives: Vec<Directive>,
+        comment: NodeComment,
+    ) -> Self {
+        let prefix = parser.config().directive_prefix;
+        if let Some(directive) = prefix.and_then(|prefix| comment.directive(prefix)) {
+            parser.directives = directives;
+            parser.directives.push(directive);
+        }
+        Node::Comment(comment)
+    }
+}
+
+/// Element or fragment, which children are not parsed yet.
+struct OpenNode<C> {
+    tag: OpenNodeTag,
+    children: Vec<Node<C>>,
+    /// Namespace of element, or of fragment parent.
+    namespace: Namespace,
+    directives: Vec<Directive>,
+}
+
+#[allow(clippy::large_enum_variant)]
+enum OpenNodeTag {
+    /// Open tag and its interned name.
+    Element(OpenTag, Name),
+    Fragment(FragmentOpen),
+}
+
+impl<C: CustomNode> OpenNode<C> {
+    fn children_namespace(&self) -> Namespace {
+        match &self.tag {
+            OpenNodeTag::Element(_, name) => self.namespace.of_children(name),
+            OpenNodeTag::Fragment(_) => self.namespace,
+        }
+    }
+
+    fn close(
+        self,
+        parser: &mut RecoverableContext,
+        input: ParseStream,
+        close_tag_start: Option<CloseTagStart>,
+    ) -> Node<C> {
+        let children = interpolate_children(parser, self.children);
+        match self.tag {
+            OpenNodeTag::Element(open_tag, name) => {
+                let close_tag = CloseTag::parse_with_start_tag(parser, input, close_tag_start);
+                Node::Element(NodeElement::finish(
+                    parser,
+                    open_tag,
+                    name,
+                    children,
+                    close_tag,
+                    self.namespace,
+                    self.directives,
+                ))
+            }
+            OpenNodeTag::Fragment(tag_open) => {
+                let tag_close = FragmentClose::parse_with_start_tag(parser, input, close_tag_start);
+                Node::Fragment(NodeFragment::finish(parser, tag_open, children, tag_close))
+            }
+        }
+    }
+}
+
+/// Whether children of `element` can be parsed again after its first
+/// `children`, when tokens after them are changed, see
+/// [`resume_elements`].
+pub(crate) fn is_resumable<C: CustomNode>(
+    parser: &RecoverableContext,
+    element: &NodeElement<C>,
+) -> bool {
+    let name = element.name().to_string();
+    element.close_tag.is_some()
+        && !parser.config().preserve_tokens
+        && !parser.config().raw_text_elements.contains(name.as_str())
+        && parser.config().children_parser::<C>(&name).is_none()
+        && !element.directives.contains(&Directive::Raw)
+}
+
+/// Continue parsing of `elements` after the children that they keep, each of
+/// element is the parent of the next one, and return the first of them once
+/// it is closed, see [`Parser::reparse`](crate::Parser::reparse).
+///
+/// Elements should be [resumable](is_resumable).
+pub(crate) fn resume_elements<C: CustomNode>(
+    parser: &mut RecoverableContext,
+    input: ParseStream,
+    elements: Vec<NodeElement<C>>,
+) -> Option<Node<C>> {
+    let mut ancestors: Vec<_> = elements
+        .into_iter()
+        .map(|mut element| {
+            // Special attributes are moved out again once element is closed.
+            element.open_tag.attributes = element
+                .all_attributes()
+                .into_iter()
+                .map(Cow::into_owned)
+                .collect();
+            let name = parser.intern(&element.open_tag.name);
+            OpenNode {
+                tag: OpenNodeTag::Element(element.open_tag, name),
+                children: element.children,
+                namespace: element.namespace,
+                directives: element.directives,
+            }
+        })
+        .collect();
+    let start = NodeStart::Open(ancestors.pop()?);
+    parse_node_tree_within(parser, input, None, ancestors, start)
+}
+
+#[allow(clippy::large_enum_variant)]
+enum NodeStart<C> {
+    /// Node is parsed with all of its children.
+    Complete(Node<C>),
+    /// Only the open tag is parsed.
+    Open(OpenNode<C>),
+}
+
+impl<C: CustomNode> NodeStart<C> {
+    /// Span of open tag, if node is element or fragment.
+    fn tag_span(&self) -> Option<Span> {
+        match self {
+            Self::Complete(Node::Element(element)) => Some(element.open_tag.span()),
+            Self::Complete(Node::Fragment(fragment)) => Some(fragment.tag_open.span()),
+            Self::Open(OpenNode {
+                tag: OpenNodeTag::Element(open_tag, _),
+                ..
+            }) => Some(open_tag.span()),
+            Self::Open(OpenNode {
+                tag: OpenNodeTag::Fragment(tag_open),
+                ..
+            }) => Some(tag_open.span()),
+            Self::Complete(_) => None,
+        }
+    }
+}
+
+/// Skip close tags without open tag, like `</div>` between top-level nodes,
+/// reporting each of them, so the following nodes are parsed as siblings.
+pub(crate) fn skip_stray_close_tags(parser: &mut RecoverableContext, input: ParseStream) {
+    while input.peek(Token![<]) && input.peek2(Token![/]) {
+        let (element_fork, fragment_fork) = (input.fork(), input.fork());
+        let (fork, span) = if let Ok(close_tag) = element_fork.parse::<CloseTag>() {
+            (element_fork, close_tag.span())
+        } else if let Ok(fragment_close) = FragmentClose::parse(&fragment_fork) {
+            (fragment_fork, fragment_close.span())
+        } else {
+            // Only `</` can be skipped safely.
+            let Some(start) = parser.parse_simple::<CloseTagStart>(input) else {
+                return;
+            };
+            parser.push_error(ParserError::UnexpectedCloseTag, start.span());
+            continue;
+        };
+        input.advance_to(&fork);
+        trace::debug!("skipping stray close tag");
+        parser.push_error(ParserError::UnexpectedCloseTag, span);
+    }
+}
+
+/// Push `node` to the end of `nodes`, if `merge_text` is set, text node is
+/// merged into previous text node instead. Returns false if node was merged.
+pub(crate) fn push_node<C>(nodes: &mut Vec<Node<C>>, node: Node<C>, merge_text: bool) -> bool {
+    match (nodes.last_mut(), node) {
+        (Some(Node::Text(last)), Node::Text(text)) if merge_text => {
+            last.merge(text);
+            false
+        }
+        (_, node) => {
+            nodes.push(node);
+            true
+        }
     }
 }
 
+/// Parse children of `start` node, until its close tag.
+///
+/// Instead of recursion, uses explicit stack of open elements and fragments,
+/// so nesting depth is limited only by memory.
+///
+/// `start_cursor` points to the beginning of `start` node, and is used to
+/// preserve its tokens, see [`ParserConfig::preserve_tokens`].
+///
+/// [`ParserConfig::preserve_tokens`]: crate::ParserConfig::preserve_tokens
+fn parse_node_tree<'a, C: CustomNode>(
+    parser: &mut RecoverableContext,
+    input: ParseStream<'a>,
+    start_cursor: Option<Cursor<'a>>,
+    start: NodeStart<C>,
+) -> Option<Node<C>> {
+    parse_node_tree_within(parser, input, start_cursor, vec![], start)
+}
+
+/// Version of [`parse_node_tree`], that continues parsing of `ancestors`,
+/// where `start` is a child of the last of them.
+fn parse_node_tree_within<'a, C: CustomNode>(
+    parser: &mut RecoverableContext,
+    input: ParseStream<'a>,
+    start_cursor: Option<Cursor<'a>>,
+    mut ancestors: Vec<OpenNode<C>>,
+    start: NodeStart<C>,
+) -> Option<Node<C>> {
+    let base_depth = parser.depth;
+    let base_namespace = parser.namespace;
+    let preserve_tokens = parser.config().preserve_tokens;
+    // Start cursors of ancestors, and of the `next` node.
+    let mut ancestor_starts = vec![None; ancestors.len()];
+    let mut next_start = start_cursor;
+    let mut next = start;
+    // Number of ancestors of too deep node, that is parsed only to skip its
+    // tokens, and dropped once complete.
+    let mut too_deep = None;
+    loop {
+        let depth = base_depth + ancestors.len() + 1;
+        if let (None, Some(max_depth)) = (too_deep, parser.config().max_depth) {
+            if depth > max_depth {
+                if let Some(span) = next.tag_span() {
+                    parser.push_error(ParserError::MaxDepthExceeded, span);
+                    too_deep = Some(ancestors.len());
+                }
+            }
+        }
+
+        let current = match next {
+            NodeStart::Complete(mut node) => {
+                if let (true, Some(start)) = (preserve_tokens, next_start) {
+                    node.set_source_tokens(tokens_between(start, input.cursor()));
+                }
+                let dropped = too_deep == Some(ancestors.len());
+                if dropped {
+                    too_deep = None;
+                }
+                let Some(mut parent) = ancestors.pop() else {
+                    parser.depth = base_depth;
+                    parser.namespace = base_namespace;
+                    parser.dropped = dropped;
+                    return (!dropped).then_some(node);
+                };
+                next_start = ancestor_starts.pop().flatten();
+                if !dropped {
+                    push_node(
+                        &mut parent.children,
+                        node,
+                        parser.config().merge_adjacent_text,
+                    );
+                }
+                parent
+            }
+            NodeStart::Open(open) => open,
+        };
+
+        // Use any closing tag as separator, to early report about invalid
+        // closing tags. Also parse only </ part to recover parser as soon as
+        // user types </
+        let old_cursor = input.cursor();
+        let fork = input.fork();
+        if let Ok(close_tag_start) = CloseTagStart::parse(&fork) {
+            input.advance_to(&fork);
+            // Directive comment that is the last child has no node to apply to.
+            parser.directives.clear();
+            next = NodeStart::Complete(current.close(parser, input, Some(close_tag_start)));
+            continue;
+        }
+
+        // Custom nodes can parse their children using nested call.
+        parser.depth = base_depth + ancestors.len() + 1;
+        parser.namespace = current.children_namespace();
+        let child_start = input.cursor();
+        next = match Node::parse_start(parser, input) {
+            Some(child) => {
+                ancestors.push(current);
+                ancestor_starts.push(next_start);
+                next_start = Some(child_start);
+                child
+            }
+            None if old_cursor != input.cursor() => NodeStart::Open(current),
+            None => {
+                parser.directives.clear();
+                NodeStart::Complete(current.close(parser, input, None))
+            }
+        };
+    }
+}
+
+/// Report the first character of `name` that is not allowed by
+/// [`ParserConfig::name_policy`], see [`NodeName::invalid_character`].
+fn check_name_chars(parser: &mut RecoverableContext, name: &NodeName, attribute: bool) {
+    let policy = parser.config().name_policy;
+    if let Some((character, span)) = name.invalid_character(policy, attribute) {
+        let error = ParserError::InvalidNameCharacter {
+            name: name.to_string(),
+            character,
+            policy,
+        };
+        parser.push_error(error, span);
+    }
+}
+
+/// Collect tokens from `start` up to `end`, which should be at the same level.
+fn tokens_between(start: Cursor, end: Cursor) -> TokenStream {
+    let mut tokens = TokenStream::new();
+    let mut cursor = start;
+    while cursor != end {
+        let Some((token, next)) = cursor.token_tree() else {
+            break;
+        };
+        tokens.extend([token]);
+        cursor = next;
+    }
+    tokens
+}
+
 // This method couldn't be const generic until https://github.com/rust-lang/rust/issues/63569
 /// Parse array of tokens with
 pub(super) fn parse_array_of2_tokens<T: Parse>(input: ParseStream) -> syn::Result<[T; 2]> {
@@ -329,10 +1058,15 @@ where
 /// Replace the next [`TokenTree::Group`] in the given parse stream with a
 /// token stream returned by a user callback, or parse as original block if
 /// no token stream is returned.
-fn block_transform(input: ParseStream, transform_fn: &TransformBlockFn) -> syn::Result<Block> {
+fn block_transform(
+    input: ParseStream,
+    delimiters: &[Delimiter],
+    transform_fn: &TransformBlockFn,
+) -> syn::Result<Block> {
     input.step(|cursor| {
-        let (block_group, block_span, next) = cursor
-            .group(Delimiter::Brace)
+        let (block_group, block_span, next) = delimiters
+            .iter()
+            .find_map(|delimiter| cursor.group(*delimiter))
             .ok_or_else(|| cursor.error("unexpected: no Group found"))?;
         let parser = move |block_content: ParseStream| {
             let forked_block_content = block_content.fork();
@@ -362,14 +1096,40 @@ pub(crate) fn parse_valid_block_expr(
     parser: &mut RecoverableContext,
     input: syn::parse::ParseStream,
 ) -> syn::Result<Block> {
-    let transform_block = parser.config().transform_block.clone();
-    let value = if let Some(transform_fn) = transform_block {
-        block_transform(input, &*transform_fn)?
-    } else {
+    let delimiters = parser.config().block_delimiters();
+    let value = if let Some(transform_fn) = &parser.config().transform_block {
+        block_transform(input, delimiters, &**transform_fn)?
+    } else if delimiters == [Delimiter::Brace] {
         block_expr(input)?
+    } else {
+        block_expr_in(input, delimiters)?
     };
     Ok(value)
 }
+
+/// Returns true if the next token is a group with one of `delimiters`.
+pub(crate) fn peek_block(input: ParseStream, delimiters: &[Delimiter]) -> bool {
+    let cursor = input.cursor();
+    delimiters
+        .iter()
+        .any(|delimiter| cursor.group(*delimiter).is_some())
+}
+
+/// Parse the given stream as [`Expr::Block`], written in a group with one of
+/// `delimiters`.
+fn block_expr_in(input: ParseStream, delimiters: &[Delimiter]) -> syn::Result<Block> {
+    let content;
+    let span = if input.peek(Brace) && delimiters.contains(&Delimiter::Brace) {
+        braced!(content in input).span
+    } else if input.peek(Bracket) && delimiters.contains(&Delimiter::Bracket) {
+        bracketed!(content in input).span
+    } else if input.peek(Paren) && delimiters.contains(&Delimiter::Parenthesis) {
+        parenthesized!(content in input).span
+    } else {
+        return Err(input.error("expected block"));
+    };
+    block_expr_with_extern_span(&content, span)
+}
 /// Parse the given stream and span as [`Expr::Block`].
 fn block_expr_with_extern_span(input: ParseStream, span: DelimSpan) -> syn::Result<Block> {
     Ok(Block {
@@ -1,11 +1,13 @@
 use proc_macro2::{TokenStream, TokenTree};
-use proc_macro2_diagnostics::{Diagnostic, Level};
 use syn::{
     parse::{discouraged::Speculative, Parse, ParseStream, Parser},
     spanned::Spanned,
 };
 
-use crate::recoverable::{ParseRecoverable, RecoverableContext};
+use crate::{
+    recoverable::{ParseRecoverable, RecoverableContext},
+    ParserError,
+};
 
 impl RecoverableContext {
     /// Like [`parse_simple`], but splits the tokenstream at `E` first only
@@ -136,9 +138,8 @@ impl RecoverableContext {
         F: Fn(ParseStream) -> syn::Result<U>,
     {
         let parser = |parser: &mut Self, tokens: TokenStream| {
+            let mut collection = vec![];
             let parse = |input: ParseStream| {
-                let mut collection = vec![];
-
                 while !input.is_empty() {
                     let old_cursor = input.cursor();
                     if let Some(o) = parser.parse_recoverable(input) {
@@ -148,23 +149,25 @@ impl RecoverableContext {
                         break;
                     }
                 }
-                let eated_tokens = input.parse::<TokenStream>()?;
-                Ok((collection, eated_tokens))
+                input.parse::<TokenStream>()
             };
-            let (collection, eaten_tokens) = parse.parse2(tokens).expect("No errors allowed");
-            if !eaten_tokens.is_empty() {
-                parser.push_diagnostic(Diagnostic::spanned(
-                    eaten_tokens.span(),
-                    Level::Error,
-                    "tokens was ignored during parsing",
-                ))
+            // Error can still be returned, if some group was not fully parsed.
+            match parse.parse2(tokens) {
+                Ok(eaten_tokens) if !eaten_tokens.is_empty() => {
+                    parser.push_error(ParserError::IgnoredTokens, eaten_tokens.span())
+                }
+                Ok(_) => {}
+                Err(e) => parser.push_diagnostic(e),
             }
             collection
         };
         self.parse_with_ending(input, parser, separator)
     }
 
-    pub(crate) fn parse_with_ending<F, CNV, V, U>(
+    /// Collect tokens until `ending` can be parsed, or until the end of
+    /// input. Collected tokens are converted with `parser`, and returned
+    /// along with the parsed ending, if it was found.
+    pub fn parse_with_ending<F, CNV, V, U>(
         &mut self,
         input: ParseStream,
         parser: CNV,
@@ -1,12 +1,20 @@
 use std::marker::PhantomData;
 
 use derive_where::derive_where;
-use proc_macro2::{Span, TokenStream, TokenTree};
+use proc_macro2::{Literal, Span, TokenStream, TokenTree};
 use quote::ToTokens;
-use syn::{parse::ParseStream, spanned::Spanned, token::Brace, LitStr, Token};
+use syn::{parse::ParseStream, spanned::Spanned, Lit, LitStr, Token};
 
-use super::{CustomNode, Infallible, Node};
-use crate::recoverable::ParseRecoverable;
+use super::{
+    attribute::Literal as ValueLiteral,
+    entity::{self, TextSegment},
+    parse::peek_block,
+    token_stream_eq, CustomNode, Infallible, Node, NodeComment,
+};
+use crate::{
+    recoverable::{ParseRecoverable, RecoverableContext},
+    ParserError, TextLiterals,
+};
 
 /// Raw unquoted text
 ///
@@ -16,6 +24,11 @@ use crate::recoverable::ParseRecoverable;
 /// 2. Some tokens like ' ` can be treated as invalid, because in rust it only
 /// allowed in certain contexts.
 ///
+/// Content that breaks these rules can be written as a single string literal,
+/// e.g. `<style>r#"p { font-family: 'Open Sans'; }"#</style>`. Raw text
+/// elements with such content get [`NodeText`](super::NodeText) child instead
+/// of `RawText`.
+///
 /// Can be formatted to a string using `to_source_text`,
 /// `to_token_stream_string` or `to_string_best` methods.
 ///
@@ -47,6 +60,13 @@ impl<C> Default for RawText<C> {
     }
 }
 
+// Manual implementation, to ignore spans and custom node type.
+impl<C> PartialEq for RawText<C> {
+    fn eq(&self, other: &Self) -> bool {
+        token_stream_eq(&self.token_stream, &other.token_stream)
+    }
+}
+
 impl<C> RawText<C> {
     /// Custom node type parameter is used only for parsing, so it can be
     /// changed during usage.
@@ -111,6 +131,31 @@ impl<C> RawText<C> {
         self.token_stream.is_empty()
     }
 
+    /// Returns text parsed as integer literal, e.g. `<p>42</p>` or `<p>-1</p>`.
+    ///
+    /// Quoted text, like `<p>"42"</p>`, is [`NodeText`](super::NodeText), see
+    /// [`NodeText::value_int`](super::NodeText::value_int).
+    pub fn value_int(&self) -> Option<i128> {
+        let expr = syn::parse2(self.token_stream.clone()).ok()?;
+        ValueLiteral::of(&expr)?.int()
+    }
+
+    /// Returns text parsed as integer or float literal, e.g. `<p>0.5</p>` or
+    /// `<p>-1</p>`.
+    pub fn value_float(&self) -> Option<f64> {
+        let expr = syn::parse2(self.token_stream.clone()).ok()?;
+        ValueLiteral::of(&expr)?.float()
+    }
+
+    /// String literal, if it is the only token of text.
+    pub(crate) fn as_single_literal(&self) -> Option<LitStr> {
+        let mut tokens = self.token_stream.clone().into_iter();
+        match (tokens.next(), tokens.next()) {
+            (Some(token @ TokenTree::Literal(_)), None) => syn::parse2(token.into()).ok(),
+            _ => None,
+        }
+    }
+
     pub(crate) fn vec_set_context(
         open_tag_end: Span,
         close_tag_start: Option<Span>,
@@ -119,15 +164,21 @@ impl<C> RawText<C> {
     where
         C: CustomNode,
     {
-        let spans: Vec<Span> = Some(open_tag_end)
-            .into_iter()
-            .chain(children.iter().map(|n| n.span()))
-            .chain(close_tag_start)
-            .collect();
-
-        for (spans, children) in spans.windows(3).zip(&mut children) {
-            if let Node::RawText(t) = children {
-                t.set_tag_spans(spans[0], spans[2])
+        // Only neighbours of raw text are needed, computing span of every child
+        // would make parsing of nested elements quadratic.
+        for i in 0..children.len() {
+            if !matches!(children[i], Node::RawText(_)) {
+                continue;
+            }
+            let before = match i.checked_sub(1) {
+                Some(prev) => children[prev].span(),
+                None => open_tag_end,
+            };
+            let Some(after) = children.get(i + 1).map(|n| n.span()).or(close_tag_start) else {
+                continue;
+            };
+            if let Node::RawText(t) = &mut children[i] {
+                t.set_tag_spans(before, after)
             }
         }
         children
@@ -139,13 +190,37 @@ impl<C> RawText<C> {
     /// 3. calls `to_source_text(false)`
     /// 4. as fallback calls `to_token_stream_string()`
     pub fn to_string_best(&self) -> String {
+        self.best_source_text()
+            .unwrap_or_else(|| self.to_token_stream_string())
+    }
+
+    /// Same as [`to_string_best`](Self::to_string_best), but without the
+    /// token stream fallback.
+    fn best_source_text(&self) -> Option<String> {
         #[cfg(feature = "rawtext-stable-hack-module")]
         if let Some(recovered) = &self.recovered_text {
-            return recovered.clone();
+            return Some(recovered.clone());
         }
         self.to_source_text(true)
             .or_else(|| self.to_source_text(false))
-            .unwrap_or_else(|| self.to_token_stream_string())
+    }
+
+    /// Split text from [`to_string_best`](Self::to_string_best) into plain text
+    /// and character references, like `&amp;`.
+    ///
+    /// If source text is not available, references are recognized in token
+    /// stream string (`& amp ;`), where only known names are accepted.
+    pub fn entity_segments(&self) -> Vec<TextSegment> {
+        match self.best_source_text() {
+            Some(text) => entity::split(&text, false),
+            None => entity::split(&self.to_token_stream_string(), true),
+        }
+    }
+
+    /// Text with known character references replaced by characters, see
+    /// [`entity_segments`](Self::entity_segments).
+    pub fn to_string_decoded(&self) -> String {
+        TextSegment::decode(&self.entity_segments())
     }
 
     // Returns text recovered using recover_space_hack.
@@ -155,19 +230,20 @@ impl<C> RawText<C> {
     pub fn to_source_text_hack(&self) -> Option<String> {
         #[cfg(feature = "rawtext-stable-hack-module")]
         {
-            return self.recovered_text.clone();
+            self.recovered_text.clone()
         }
         #[cfg(not(feature = "rawtext-stable-hack-module"))]
         None
     }
 
     #[cfg(feature = "rawtext-stable-hack-module")]
-    pub(crate) fn recover_space(&mut self, other: &Self) {
-        self.recovered_text = Some(
-            other
-                .to_source_text(self.context_span.is_some())
-                .expect("Cannot recover space in this context"),
-        )
+    // Returns false, if text of `other` is not available, and default text was
+    // used instead.
+    pub(crate) fn recover_space(&mut self, other: &Self) -> bool {
+        let text = other.to_source_text(self.context_span.is_some());
+        let recovered = text.is_some();
+        self.recovered_text = Some(text.unwrap_or_default());
+        recovered
     }
     #[cfg(feature = "rawtext-stable-hack-module")]
     pub(crate) fn init_recover_space(&mut self, init: String) {
@@ -184,21 +260,26 @@ impl RawText {
 }
 
 impl<C: CustomNode> ParseRecoverable for RawText<C> {
-    fn parse_recoverable(
-        parser: &mut crate::recoverable::RecoverableContext,
-        input: ParseStream,
-    ) -> Option<Self> {
+    fn parse_recoverable(parser: &mut RecoverableContext, input: ParseStream) -> Option<Self> {
         let mut token_stream = TokenStream::new();
+        let block_delimiters = parser.config().block_delimiters().to_vec();
         let any_node = |input: ParseStream| {
             input.peek(Token![<])
-                || input.peek(Brace)
+                || peek_block(input, &block_delimiters)
                 || input.peek(LitStr)
+                || NodeComment::peek_doc(input)
                 || C::peek_element(&input.fork())
         };
         // Parse any input until catching any node.
         // Fail only on eof.
         while !any_node(input) && !input.is_empty() {
-            token_stream.extend([parser.save_diagnostics(input.parse::<TokenTree>())?])
+            let token = parser.save_diagnostics(input.parse::<TokenTree>())?;
+            if let TokenTree::Literal(literal) = &token {
+                if parser.config().text_literals == TextLiterals::Strings {
+                    report_unquoted_literal(parser, literal);
+                }
+            }
+            token_stream.extend([token])
         }
         Some(Self {
             token_stream,
@@ -210,6 +291,20 @@ impl<C: CustomNode> ParseRecoverable for RawText<C> {
     }
 }
 
+/// Report literal that is not allowed by [`TextLiterals::Strings`], with
+/// suggestion to quote it.
+fn report_unquoted_literal(parser: &mut RecoverableContext, literal: &Literal) {
+    let text = match Lit::new(literal.clone()) {
+        Lit::Char(c) => c.value().to_string(),
+        _ => literal.to_string(),
+    };
+    let error = ParserError::UnquotedLiteral {
+        literal: literal.to_string(),
+        suggested_fix: format!("{text:?}"),
+    };
+    parser.push_error(error, literal.span());
+}
+
 impl<C: CustomNode> ToTokens for RawText<C> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         self.token_stream.to_tokens(tokens)
@@ -0,0 +1,163 @@
+//! `#id` and `.class` shorthands on open tags, see
+//! [`ParserConfig::tag_sugar`].
+//!
+//! [`ParserConfig::tag_sugar`]: crate::ParserConfig::tag_sugar
+
+use proc_macro2::{Punct, TokenStream};
+use quote::ToTokens;
+use syn::{
+    parse::ParseStream, punctuated::Punctuated, spanned::Spanned, Expr, ExprLit, ExprPath, Ident,
+    Lit, LitStr, Token,
+};
+
+use super::{
+    AttributeValueExpr, KVAttributeValue, KeyedAttribute, KeyedAttributeValue, NodeAttribute,
+    NodeName, NodeNameFragment,
+};
+use crate::{recoverable::RecoverableContext, ParserError};
+
+/// Shorthand that produced synthetic `id` or `class` attribute, e.g. `#app`
+/// or `.container.dark`.
+#[derive(Clone, Debug)]
+pub struct AttributeSugar {
+    tokens: TokenStream,
+}
+
+impl AttributeSugar {
+    /// Tokens of shorthand, as written in source.
+    pub fn tokens(&self) -> &TokenStream {
+        &self.tokens
+    }
+}
+
+impl PartialEq for AttributeSugar {
+    fn eq(&self, other: &Self) -> bool {
+        self.tokens.to_string() == other.tokens.to_string()
+    }
+}
+
+impl ToTokens for AttributeSugar {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.tokens.to_tokens(tokens)
+    }
+}
+
+type NameParts = Punctuated<NodeNameFragment, Punct>;
+
+/// Parse shorthands following element `name`, and split `.class` parts off
+/// the name itself. Returns synthetic attributes, `id` first.
+pub(crate) fn parse_tag_sugar(
+    parser: &mut RecoverableContext,
+    input: ParseStream,
+    name: &mut NodeName,
+) -> Vec<NodeAttribute> {
+    if !parser.config().tag_sugar {
+        return vec![];
+    }
+    let mut id = None;
+    let mut classes = vec![];
+    let mut class_tokens = TokenStream::new();
+
+    let mut push_classes = |parser: &mut RecoverableContext, parts: Vec<(Punct, NameParts)>| {
+        for (dot, part) in parts {
+            let class = part_to_name(part);
+            if class.to_string().is_empty() {
+                parser.push_error(ParserError::ExpectedClassName, dot.span());
+                continue;
+            }
+            dot.to_tokens(&mut class_tokens);
+            class.to_tokens(&mut class_tokens);
+            classes.push(class.to_string());
+        }
+    };
+
+    if let NodeName::Punctuated(parts) = name {
+        let (first, rest) = split_dots(parts.clone());
+        if !rest.is_empty() {
+            *name = part_to_name(first);
+            push_classes(parser, rest);
+        }
+    }
+    while input.peek(Token![#]) {
+        let Some(hash) = parser.parse_simple::<Token![#]>(input) else {
+            break;
+        };
+        let (value, rest) = match parser.parse_simple(input) {
+            Some(NodeName::Punctuated(parts)) => {
+                let (first, rest) = split_dots(parts);
+                (part_to_name(first), rest)
+            }
+            Some(value @ NodeName::Path(_)) => (value, vec![]),
+            Some(NodeName::Block(block)) => {
+                parser.push_error(ParserError::ExpectedIdShorthand, block.span());
+                continue;
+            }
+            None => break,
+        };
+        if id.is_some() {
+            parser.push_error(ParserError::DuplicateIdShorthand, hash.span());
+        } else {
+            let mut tokens = hash.to_token_stream();
+            value.to_tokens(&mut tokens);
+            id = Some((value.to_string(), tokens));
+        }
+        push_classes(parser, rest);
+    }
+
+    let id = id.map(|(id, tokens)| synthetic_attribute("id", id, tokens));
+    let class = (!classes.is_empty())
+        .then(|| synthetic_attribute("class", classes.join(" "), class_tokens));
+    id.into_iter().chain(class).collect()
+}
+
+/// Split name by `.` puncts, returns the first part, and the rest of parts
+/// along with preceding dots.
+fn split_dots(parts: NameParts) -> (NameParts, Vec<(Punct, NameParts)>) {
+    let mut first = NameParts::new();
+    let mut rest: Vec<(Punct, NameParts)> = vec![];
+    for pair in parts.into_pairs() {
+        let (fragment, punct) = pair.into_tuple();
+        let current = rest.last_mut().map(|(_, part)| part).unwrap_or(&mut first);
+        current.push_value(fragment);
+        match punct {
+            Some(punct) if punct.as_char() == '.' => rest.push((punct, NameParts::new())),
+            Some(punct) => current.push_punct(punct),
+            None => {}
+        }
+    }
+    (first, rest)
+}
+
+fn part_to_name(part: NameParts) -> NodeName {
+    match part.first() {
+        Some(NodeNameFragment::Ident(ident)) if part.len() == 1 && !part.trailing_punct() => {
+            NodeName::Path(path(ident.clone()))
+        }
+        _ => NodeName::Punctuated(part),
+    }
+}
+
+fn path(ident: Ident) -> ExprPath {
+    ExprPath {
+        attrs: vec![],
+        qself: None,
+        path: ident.into(),
+    }
+}
+
+fn synthetic_attribute(key: &str, value: String, tokens: TokenStream) -> NodeAttribute {
+    let span = tokens.span();
+    NodeAttribute::Attribute(KeyedAttribute {
+        key: NodeName::Path(path(Ident::new(key, span))),
+        possible_value: KeyedAttributeValue::Value(AttributeValueExpr {
+            token_eq: Token![=](span),
+            value: KVAttributeValue::Expr(Expr::Lit(ExprLit {
+                attrs: vec![],
+                lit: Lit::Str(LitStr::new(&value, span)),
+            })),
+        }),
+        sugar: Some(AttributeSugar { tokens }),
+        position: None,
+        interned_key: None,
+    })
+}
@@ -0,0 +1,129 @@
+//! Top-level nodes of template, see [`ParsedTemplate`].
+
+use std::{ops::Deref, sync::Arc};
+
+use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
+use syn::spanned::Spanned;
+
+use super::{CustomNode, Node, NodeType};
+use crate::{ConfigSummary, Infallible, ParserError};
+
+/// Top-level nodes of parsed template, returned by
+/// [`Parser::parse_template`].
+///
+/// Dereferences to slice of nodes, and can be converted back to `Vec` with
+/// [`ParsedTemplate::into_nodes`].
+///
+/// ```rust
+/// use quote::quote;
+/// use rstml::{node::NodeType, Parser, ParserConfig};
+///
+/// let template = Parser::new(ParserConfig::new())
+///     .parse_template(quote! { <!-- "header" --> <div/> })
+///     .into_result()
+///     .unwrap();
+/// assert_eq!(template.len(), 2);
+/// assert_eq!(template.roots_of_type(NodeType::Element).count(), 1);
+/// assert!(template.single_root().is_err());
+/// assert!(!template.config.unwrap().flat_tree);
+/// ```
+///
+/// [`Parser::parse_template`]: crate::Parser::parse_template
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParsedTemplate<C = Infallible> {
+    pub nodes: Vec<Node<C>>,
+    /// Options of config that nodes were parsed with, `None` if template was
+    /// created from nodes directly.
+    pub config: Option<Arc<ConfigSummary>>,
+}
+
+impl<C: CustomNode> ParsedTemplate<C> {
+    pub fn new(nodes: Vec<Node<C>>) -> Self {
+        Self {
+            nodes,
+            config: None,
+        }
+    }
+
+    /// Attach summary of config that nodes were parsed with.
+    pub fn with_config(mut self, config: impl Into<Arc<ConfigSummary>>) -> Self {
+        self.config = Some(config.into());
+        self
+    }
+
+    pub fn into_nodes(self) -> Vec<Node<C>> {
+        self.nodes
+    }
+
+    /// The only top-level node, fails if template has no nodes or more than
+    /// one.
+    pub fn single_root(&self) -> syn::Result<&Node<C>> {
+        match self.nodes.as_slice() {
+            [node] => Ok(node),
+            nodes => Err(ParserError::TopLevelNodeCount {
+                found: nodes.len(),
+                expected: 1,
+            }
+            .error(self.span())),
+        }
+    }
+
+    /// Top-level nodes of the given type.
+    pub fn roots_of_type(&self, r#type: NodeType) -> impl Iterator<Item = &Node<C>> {
+        self.nodes
+            .iter()
+            .filter(move |node| node.r#type() == r#type)
+    }
+
+    /// Span from the first to the last node, only span of the first node is
+    /// returned if spans can't be joined (on stable rust), or call site if
+    /// template is empty.
+    pub fn span(&self) -> Span {
+        let (Some(first), Some(last)) = (self.nodes.first(), self.nodes.last()) else {
+            return Span::call_site();
+        };
+        let first = first.span();
+        first.join(last.span()).unwrap_or(first)
+    }
+}
+
+impl<C> Deref for ParsedTemplate<C> {
+    type Target = [Node<C>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.nodes
+    }
+}
+
+impl<C: CustomNode> From<Vec<Node<C>>> for ParsedTemplate<C> {
+    fn from(nodes: Vec<Node<C>>) -> Self {
+        Self::new(nodes)
+    }
+}
+
+impl<C> IntoIterator for ParsedTemplate<C> {
+    type Item = Node<C>;
+    type IntoIter = std::vec::IntoIter<Node<C>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.nodes.into_iter()
+    }
+}
+
+impl<'a, C> IntoIterator for &'a ParsedTemplate<C> {
+    type Item = &'a Node<C>;
+    type IntoIter = std::slice::Iter<'a, Node<C>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.nodes.iter()
+    }
+}
+
+impl<C: CustomNode> ToTokens for ParsedTemplate<C> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        for node in &self.nodes {
+            node.to_tokens(tokens);
+        }
+    }
+}
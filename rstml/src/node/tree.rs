@@ -0,0 +1,154 @@
+//! Index of parent/child relationships, see [`NodeTree`].
+
+use super::{CustomNode, Node, NodeId};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct TreeEntry {
+    parent: Option<NodeId>,
+    depth: usize,
+    /// Position among siblings.
+    position: usize,
+    children: Vec<NodeId>,
+}
+
+/// Parent ids, sibling order and depth of every node in parsed tree.
+///
+/// Built in a separate pass with [`NodeTree::index`], and uses the same
+/// [`NodeId`]s as [`Node::preorder`], so it is only valid until the tree is
+/// modified.
+///
+/// ```rust
+/// use rstml::{
+///     node::{Node, NodeTree},
+///     tree,
+/// };
+///
+/// let nodes = tree! { <table><tr><td/><td/></tr></table> };
+/// let tree = NodeTree::index(&nodes);
+/// let (td, _) = Node::preorder(&nodes).last().unwrap();
+///
+/// let names: Vec<_> = tree
+///     .ancestors(td)
+///     .map(|id| match Node::get_by_id(&nodes, id) {
+///         Some(Node::Element(e)) => e.name().to_string(),
+///         _ => unreachable!(),
+///     })
+///     .collect();
+/// assert_eq!(names, ["tr", "table"]);
+/// assert_eq!(tree.depth(td), Some(2));
+/// assert!(tree.previous_sibling(td).is_some());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NodeTree {
+    entries: Vec<TreeEntry>,
+    roots: Vec<NodeId>,
+}
+
+impl NodeTree {
+    /// Build index of `nodes`.
+    pub fn index<C: CustomNode>(nodes: &[Node<C>]) -> Self {
+        let mut tree = Self::default();
+        // Iterators over children, with id of their parent.
+        let mut stack = vec![(nodes.iter(), None)];
+        while let Some((iter, parent)) = stack.last_mut() {
+            let parent = *parent;
+            let Some(node) = iter.next() else {
+                stack.pop();
+                continue;
+            };
+            let id = NodeId(tree.entries.len());
+            let siblings = match parent {
+                Some(NodeId(parent)) => &mut tree.entries[parent].children,
+                None => &mut tree.roots,
+            };
+            let position = siblings.len();
+            siblings.push(id);
+            tree.entries.push(TreeEntry {
+                parent,
+                depth: stack.len() - 1,
+                position,
+                children: vec![],
+            });
+            if let Some(children) = node.children() {
+                stack.push((children.iter(), Some(id)));
+            }
+        }
+        tree
+    }
+
+    /// Search for id of `node` in parsed tree `nodes`, returns `None` if node
+    /// is not part of it.
+    ///
+    /// Nodes are compared by address, so `node` should be borrowed from
+    /// `nodes`, and clones of nodes are never found. Ids are not stored in
+    /// nodes, so search walks `nodes` in preorder and takes O(n) time; passes
+    /// that need ids of many nodes should take them from [`Node::preorder`],
+    /// and ids of children from [`children_of`](Self::children_of).
+    pub fn find_id<C: CustomNode>(nodes: &[Node<C>], node: &Node<C>) -> Option<NodeId> {
+        Node::preorder(nodes)
+            .find(|(_, n)| std::ptr::eq(*n, node))
+            .map(|(id, _)| id)
+    }
+
+    fn entry(&self, id: NodeId) -> Option<&TreeEntry> {
+        self.entries.get(id.0)
+    }
+
+    /// Ids of top-level nodes.
+    pub fn roots(&self) -> &[NodeId] {
+        &self.roots
+    }
+
+    /// Id of the parent element or fragment, `None` for top-level nodes.
+    pub fn parent_of(&self, id: NodeId) -> Option<NodeId> {
+        self.entry(id)?.parent
+    }
+
+    /// Ids of all ancestors, starting from the parent.
+    pub fn ancestors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(self.parent_of(id), |id| self.parent_of(*id))
+    }
+
+    /// Ids of children, empty for nodes without children.
+    pub fn children_of(&self, id: NodeId) -> &[NodeId] {
+        self.entry(id).map(|e| &*e.children).unwrap_or_default()
+    }
+
+    /// Ids of node and all its siblings, in source order.
+    pub fn siblings(&self, id: NodeId) -> &[NodeId] {
+        match self.parent_of(id) {
+            Some(parent) => self.children_of(parent),
+            None if self.entry(id).is_some() => &self.roots,
+            None => &[],
+        }
+    }
+
+    /// Position of node among its siblings.
+    pub fn sibling_index(&self, id: NodeId) -> Option<usize> {
+        Some(self.entry(id)?.position)
+    }
+
+    pub fn previous_sibling(&self, id: NodeId) -> Option<NodeId> {
+        let position = self.sibling_index(id)?;
+        self.siblings(id).get(position.checked_sub(1)?).copied()
+    }
+
+    pub fn next_sibling(&self, id: NodeId) -> Option<NodeId> {
+        let position = self.sibling_index(id)?;
+        self.siblings(id).get(position + 1).copied()
+    }
+
+    /// Number of ancestors, zero for top-level nodes.
+    pub fn depth(&self, id: NodeId) -> Option<usize> {
+        Some(self.entry(id)?.depth)
+    }
+
+    /// Number of indexed nodes.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,277 @@
+//! Lazy parsing of top-level nodes, see [`Parser::parse_iter`].
+
+use std::collections::VecDeque;
+
+use proc_macro2::{token_stream, Span, TokenStream, TokenTree};
+use proc_macro2_diagnostics::{Diagnostic, Level};
+use syn::{
+    parse::{ParseStream, Parser as _},
+    spanned::Spanned,
+};
+
+use super::{
+    recoverable::{ParseRecoverable, RecoverableContext},
+    Parser,
+};
+use crate::{
+    node::{parse::skip_stray_close_tags, CustomNode, Node},
+    ParserError,
+};
+
+/// Size of the first chunk of tokens, chunk is doubled every time the node
+/// doesn't fit in it.
+const INITIAL_CHUNK_SIZE: usize = 64;
+/// Number of tokens that should be left in chunk after the node, to be sure
+/// that the end of chunk didn't affect parsing of the node.
+const LOOKAHEAD: usize = 4;
+
+/// Iterator over top-level nodes, created by [`Parser::parse_iter`].
+pub struct ParseIter<'a, C> {
+    parser: &'a Parser<C>,
+    context: RecoverableContext,
+    tokens: token_stream::IntoIter,
+    chunk: Vec<TokenTree>,
+    ready: VecDeque<syn::Result<Node<C>>>,
+    /// Last text node, that is not yielded yet, because the next one can be
+    /// merged into it, see [`ParserConfig::merge_adjacent_text`].
+    ///
+    /// [`ParserConfig::merge_adjacent_text`]: crate::ParserConfig::merge_adjacent_text
+    text: Option<Node<C>>,
+    top_level_nodes: usize,
+    finished: bool,
+}
+
+impl<'a, C: CustomNode + std::fmt::Debug> ParseIter<'a, C> {
+    pub(super) fn new(parser: &'a Parser<C>, tokens: TokenStream) -> Self {
+        Self {
+            parser,
+            context: RecoverableContext::new(parser.config.clone().into()),
+            tokens: tokens.into_iter(),
+            chunk: vec![],
+            ready: VecDeque::new(),
+            text: None,
+            top_level_nodes: 0,
+            finished: false,
+        }
+    }
+
+    /// Parse next node from the start of chunk, growing parsed part until
+    /// some tokens are left after the node, so the end of chunk didn't affect
+    /// it. Node is accepted even if it has errors.
+    ///
+    /// Parsing of chunk that is too small is discarded, so if hooks that
+    /// observe parsed nodes are set, chunks are parsed speculatively, and only
+    /// the one that fits the node is parsed again with hooks, so they are
+    /// called once for every element. Otherwise every chunk is parsed once.
+    fn parse_next(&mut self) {
+        let hooks = self.context.has_hooks();
+        let mut size = INITIAL_CHUNK_SIZE;
+        loop {
+            let exhausted = self.fill_chunk(size);
+            let end = size.min(self.chunk.len());
+            if exhausted && end == self.chunk.len() {
+                // All tokens are in chunk, so there is nothing that could
+                // change the result.
+                self.parse_rest();
+                return;
+            }
+            let tokens: TokenStream = self.chunk[..end].iter().cloned().collect();
+            let mut attempt = if hooks {
+                self.context.speculative()
+            } else {
+                self.context.clone()
+            };
+            let parsed = Self::parse_chunk_node(&mut attempt, tokens.clone());
+            if parsed.1 < LOOKAHEAD {
+                size *= 2;
+                continue;
+            }
+            let (node, rest, diagnostics) = if hooks {
+                Self::parse_chunk_node(&mut self.context, tokens)
+            } else {
+                self.context = attempt;
+                parsed
+            };
+            self.chunk.drain(..end - rest);
+            match node {
+                Some(node) => {
+                    self.push_node(node, diagnostics);
+                }
+                // Too deep node is skipped, along with its tokens.
+                None if std::mem::take(&mut self.context.dropped) && end > rest => {
+                    self.push_error(diagnostics, None);
+                }
+                None => {
+                    let error = self.error(ParserError::NodeParseFailed, Span::call_site());
+                    self.push_error(diagnostics, error);
+                    self.finished = true;
+                }
+            }
+            return;
+        }
+    }
+
+    /// Move tokens from input to chunk, until it has at least `size` tokens.
+    /// Returns true if input is exhausted.
+    fn fill_chunk(&mut self, size: usize) -> bool {
+        while self.chunk.len() < size {
+            let Some(token) = self.tokens.next() else {
+                return true;
+            };
+            self.chunk.push(token);
+        }
+        false
+    }
+
+    /// Skip stray close tags at the start of `tokens`, and parse single node
+    /// after them. Returns the node with the number of tokens left, along with
+    /// diagnostics of parsing.
+    fn parse_chunk_node(
+        context: &mut RecoverableContext,
+        tokens: TokenStream,
+    ) -> (Option<Node<C>>, usize, Vec<Diagnostic>) {
+        let mut node = None;
+        let mut rest = 0;
+        let parser = |input: ParseStream| {
+            skip_stray_close_tags(context, input);
+            if !input.is_empty() {
+                node = Node::parse_recoverable(context, input);
+            }
+            rest = input.parse::<TokenStream>()?.into_iter().count();
+            Ok(())
+        };
+        let result = parser.parse2(tokens);
+        let diagnostics = std::mem::take(&mut context.diagnostics);
+        (result.ok().and(node), rest, diagnostics)
+    }
+
+    /// Parse all remaining tokens at once.
+    fn parse_rest(&mut self) {
+        self.finished = true;
+        let tokens: TokenStream = self.chunk.drain(..).collect();
+        let parser = |input: ParseStream| {
+            loop {
+                skip_stray_close_tags(&mut self.context, input);
+                if input.is_empty() {
+                    let diagnostics = std::mem::take(&mut self.context.diagnostics);
+                    self.push_error(diagnostics, None);
+                    break;
+                }
+                let start = input.cursor();
+                let node = Node::parse_recoverable(&mut self.context, input);
+                let diagnostics = std::mem::take(&mut self.context.diagnostics);
+                let Some(node) = node else {
+                    if std::mem::take(&mut self.context.dropped) && input.cursor() != start {
+                        self.push_error(diagnostics, None);
+                        continue;
+                    }
+                    self.push_error(
+                        diagnostics,
+                        self.error(ParserError::NodeParseFailed, input.span()),
+                    );
+                    break;
+                };
+                if !self.push_node(node, diagnostics) {
+                    break;
+                }
+            }
+            // its important to skip tokens, to avoid Unexpected tokens errors.
+            if !input.is_empty() {
+                let tts = input.parse::<TokenStream>()?;
+                let error = self.error(ParserError::SkippedTokens, tts.span());
+                self.push_error(vec![], error);
+            }
+            Ok(())
+        };
+        let result = parser.parse2(tokens);
+        self.flush_text();
+        if let Err(e) = result {
+            self.ready.push_back(Err(e));
+            return;
+        }
+
+        if let Some(number_of_top_level_nodes) = &self.parser.config.number_of_top_level_nodes {
+            if &self.top_level_nodes != number_of_top_level_nodes {
+                let error = ParserError::TopLevelNodeCount {
+                    found: self.top_level_nodes,
+                    expected: *number_of_top_level_nodes,
+                };
+                let error = self.error(error, Span::call_site());
+                self.ready.push_back(Err(error))
+            }
+        }
+    }
+
+    /// Error of parser, with message from config.
+    fn error(&self, error: ParserError, span: Span) -> syn::Error {
+        self.context.error_diagnostic(error, span).into()
+    }
+
+    /// Add parsed node to the output, returns false if parsing should be
+    /// stopped.
+    fn push_node(&mut self, node: Node<C>, diagnostics: Vec<Diagnostic>) -> bool {
+        let config = &self.parser.config;
+        if let Some(type_of_top_level_nodes) = &config.type_of_top_level_nodes {
+            if &node.r#type() != type_of_top_level_nodes {
+                let error = ParserError::TopLevelNodeType(type_of_top_level_nodes.to_string());
+                let error = self.error(error, node.span());
+                self.push_error(diagnostics, error);
+                self.finished = true;
+                return false;
+            }
+        }
+        let has_errors = diagnostics.iter().any(|d| d.level() == Level::Error);
+        if config.merge_adjacent_text && !has_errors && matches!(node, Node::Text(_)) {
+            match (&mut self.text, node) {
+                (Some(Node::Text(last)), Node::Text(text)) => last.merge(text),
+                (pending, node) => {
+                    self.ready.extend(pending.replace(node).map(Ok));
+                    self.top_level_nodes += 1;
+                }
+            }
+            return true;
+        }
+        self.flush_text();
+        self.top_level_nodes += 1;
+
+        if has_errors {
+            self.push_error(diagnostics, None);
+        } else if config.flat_tree {
+            self.ready.extend(node.flatten().into_iter().map(Ok));
+        } else {
+            self.ready.push_back(Ok(node));
+        }
+        true
+    }
+
+    /// Yield text node, that was kept for merging.
+    fn flush_text(&mut self) {
+        self.ready.extend(self.text.take().map(Ok));
+    }
+
+    /// Combine errors of a single node into one item.
+    fn push_error(&mut self, diagnostics: Vec<Diagnostic>, last: impl Into<Option<syn::Error>>) {
+        self.flush_text();
+        let error = diagnostics
+            .into_iter()
+            .filter(|d| d.level() == Level::Error)
+            .map(syn::Error::from)
+            .chain(last.into())
+            .reduce(|mut error, next| {
+                error.combine(next);
+                error
+            });
+        self.ready.extend(error.map(Err));
+    }
+}
+
+impl<'a, C: CustomNode + std::fmt::Debug> Iterator for ParseIter<'a, C> {
+    type Item = syn::Result<Node<C>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.ready.is_empty() && !self.finished {
+            self.parse_next();
+        }
+        self.ready.pop_front()
+    }
+}
@@ -1,26 +1,34 @@
 //! RSX Parser
 
-use std::vec;
+use std::{ops::Range, str::FromStr, sync::Arc, vec};
 
-use proc_macro2::TokenStream;
-use proc_macro2_diagnostics::Diagnostic;
+use proc_macro2::{TokenStream, TokenTree};
+use proc_macro2_diagnostics::{Diagnostic, Level};
 use syn::{parse::ParseStream, spanned::Spanned, Result};
 
+mod iter;
 pub mod recoverable;
 
 #[cfg(feature = "rawtext-stable-hack")]
-use {proc_macro2::Span, std::str::FromStr};
+use proc_macro2::Span;
 
+pub use self::iter::ParseIter;
 use self::recoverable::{ParseRecoverable, ParsingResult, RecoverableContext};
 #[cfg(feature = "rawtext-stable-hack")]
 use crate::rawtext_stable_hack;
-use crate::{node::*, ParserConfig};
+use crate::{
+    arena::NodeArena,
+    node::{
+        parse::{is_resumable, push_node, resume_elements, skip_stray_close_tags},
+        *,
+    },
+    trace, ParserConfig, ParserError,
+};
 ///
 /// Primary library interface to RSX Parser
 ///
 /// Allows customization through `ParserConfig`.
 /// Support recovery after parsing invalid token.
-
 pub struct Parser<C> {
     config: ParserConfig<C>,
 }
@@ -51,27 +59,267 @@ impl<C: CustomNode + std::fmt::Debug> Parser<C> {
 
     /// Advance version of `parse_simple` that returns array of errors in case
     /// of partial parsing.
+    ///
+    /// Close tags without open tag between top-level nodes, like `</div>` in
+    /// `<p/></div><span/>`, are reported as
+    /// [`UnexpectedCloseTag`](ParserError::UnexpectedCloseTag) and skipped,
+    /// so the following nodes are still parsed. Skipping is done by every
+    /// method of parser, `parse_simple` still returns the error.
     pub fn parse_recoverable(&self, v: impl Into<TokenStream>) -> ParsingResult<Vec<Node<C>>> {
-        use syn::parse::Parser as _;
-
-        let parser = move |input: ParseStream| Ok(self.parse_syn_stream(input));
-        let source = parser.parse2(v.into()).expect("No errors from parser");
+        let source = self.parse_token_stream(v.into());
 
         #[cfg(feature = "rawtext-stable-hack")]
         // re-parse using proc_macro2::fallback, only if output without error
-        let source = Self::reparse_raw_text(&self, parser, source);
+        let source = Self::reparse_raw_text(self, source);
         source
     }
+
+    /// Version of [`parse_recoverable`] that wraps top-level nodes in
+    /// [`ParsedTemplate`], along with [`ParserConfig::summary`].
+    ///
+    /// [`parse_recoverable`]: Self::parse_recoverable
+    pub fn parse_template(&self, v: impl Into<TokenStream>) -> ParsingResult<ParsedTemplate<C>> {
+        let config = Arc::new(self.config.summary());
+        self.parse_recoverable(v)
+            .map(|nodes| ParsedTemplate::new(nodes).with_config(config))
+    }
+
+    /// Version of [`parse_recoverable`] that moves parsed nodes into
+    /// [`NodeArena`], see [`arena`](crate::arena).
+    ///
+    /// [`parse_recoverable`]: Self::parse_recoverable
+    /// [`NodeArena`]: crate::arena::NodeArena
+    pub fn parse_arena(&self, v: impl Into<TokenStream>) -> ParsingResult<NodeArena<C>> {
+        self.parse_recoverable(v).map(NodeArena::from_nodes)
+    }
+
+    /// Lazy version of [`parse_recoverable`], that yields top-level nodes one
+    /// by one.
+    ///
+    /// Nodes are parsed from a chunk of tokens that is grown until the next
+    /// node fits in it, so the whole tree is never kept in memory at once.
+    /// Stray close tags are skipped before every node. Errors of a node are
+    /// combined into a single [`syn::Error`], and iteration continues with the
+    /// next node, as in [`parse_recoverable`].
+    ///
+    /// Hooks that observe parsed nodes, like
+    /// [`on_element_closed`](ParserConfig::on_element_closed), are called once
+    /// for every element, for that chunk is parsed again after the node fits
+    /// in it. Parsers of children and blocks, like
+    /// [`children_parser`](ParserConfig::children_parser), can be called again
+    /// for nodes that didn't fit in the chunk.
+    ///
+    /// **Note:** `rawtext-stable-hack` is not applied to yielded nodes.
+    ///
+    /// [`parse_recoverable`]: Self::parse_recoverable
+    pub fn parse_iter(&self, v: impl Into<TokenStream>) -> ParseIter<'_, C> {
+        ParseIter::new(self, v.into())
+    }
+
+    /// Parse `source` after an edit of template, that was parsed into `old`
+    /// nodes, e.g. on every keystroke in editor. Unchanged nodes are moved
+    /// from `old` to the result.
+    ///
+    /// `changed` is byte range of edited text in the old source. Nodes that
+    /// end before it are reused as is, the rest of `source` is parsed again,
+    /// since spans of nodes after the edit are shifted. If the edit is inside
+    /// of element, that is closed after it, children of element before the
+    /// edit are reused too, and the rest of its children are parsed as if
+    /// element keeps being open, the same for its descendants. Elements with
+    /// raw text or [`ParserConfig::children_parser`], and all elements with
+    /// [`ParserConfig::preserve_tokens`], are parsed again as a whole.
+    /// Diagnostics are reported only for re-parsed nodes.
+    ///
+    /// Positions of nodes are taken from spans, so `old` should be parsed
+    /// from string (`proc_macro2` fallback mode with `span-locations`). If
+    /// positions are not available, or [`ParserConfig::flat_tree`] or
+    /// [`ParserConfig::number_of_top_level_nodes`] is used, the whole `source`
+    /// is parsed.
+    ///
+    /// ```rust
+    /// use rstml::Parser;
+    ///
+    /// let parser = Parser::default();
+    /// let old = parser
+    ///     .parse_simple(r#"<a/><b>"x"</b>"#.parse::<proc_macro2::TokenStream>().unwrap())
+    ///     .unwrap();
+    /// let first = old[0].clone();
+    /// let new = parser
+    ///     .reparse(old, r#"<a/><b>"yy"</b>"#, 8..9)
+    ///     .into_result()
+    ///     .unwrap();
+    /// assert_eq!(new[0], first);
+    /// assert_eq!(new[1].to_debug_tree(), "b\n  \"yy\"\n");
+    /// ```
+    pub fn reparse(
+        &self,
+        mut old: Vec<Node<C>>,
+        source: &str,
+        changed: Range<usize>,
+    ) -> ParsingResult<Vec<Node<C>>> {
+        let tokens = match TokenStream::from_str(source) {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                return ParsingResult::Failed(vec![Diagnostic::new(Level::Error, e.to_string())])
+            }
+        };
+        let has_positions = old.iter().any(|node| node.span().byte_range().end > 0);
+        if !has_positions
+            || self.config.flat_tree
+            || self.config.number_of_top_level_nodes.is_some()
+        {
+            return self.parse_recoverable(tokens);
+        }
+
+        let mut parser = RecoverableContext::new(self.config.clone().into());
+        let reused = Self::reused_prefix(&old, &changed);
+        let mut start = reused
+            .checked_sub(1)
+            .map_or(0, |last| old[last].span().byte_range().end);
+        // Elements, that enclose the edit, each is the parent of the next one.
+        let mut enclosing = vec![];
+        let mut next = old.drain(reused..).next();
+        loop {
+            match next {
+                Some(Node::Element(mut element))
+                    if element.open_tag.span().byte_range().end <= changed.start
+                        && element
+                            .close_tag
+                            .as_ref()
+                            .is_some_and(|tag| tag.span().byte_range().start >= changed.end)
+                        && is_resumable(&parser, &element) =>
+                {
+                    let kept = Self::reused_prefix(&element.children, &changed);
+                    start = kept.checked_sub(1).map_or_else(
+                        || element.open_tag.span().byte_range().end,
+                        |last| element.children[last].span().byte_range().end,
+                    );
+                    next = element.children.drain(kept..).next();
+                    enclosing.push(element);
+                }
+                _ => break,
+            }
+        }
+        let rest: TokenStream = tokens
+            .into_iter()
+            .filter(|token: &TokenTree| token.span().byte_range().start >= start)
+            .collect();
+
+        let (nodes, errors) = if enclosing.is_empty() {
+            self.parse_recoverable(rest).split_vec()
+        } else {
+            use syn::parse::Parser as _;
+
+            let mut result = None;
+            let parse = |input: ParseStream| {
+                let nodes = Vec::from_iter(resume_elements(&mut parser, input, enclosing));
+                result = Some(self.parse_nodes(parser, input, nodes));
+                Ok(())
+            };
+            let parsed = parse.parse2(rest);
+            let mut result = result.unwrap_or_else(|| ParsingResult::Failed(vec![]));
+            if let Err(e) = parsed {
+                result.push_diagnostic(e.into());
+            }
+            result.split_vec()
+        };
+        trace::debug!(reused, parsed = nodes.len(), "reparse finished");
+        old.extend(nodes);
+        ParsingResult::from_parts_vec(old, errors)
+    }
+
+    /// Number of `nodes`, that end before `changed`, and can't be extended
+    /// by the changed node.
+    fn reused_prefix(nodes: &[Node<C>], changed: &Range<usize>) -> usize {
+        let mut reused = nodes
+            .iter()
+            .position(|node| node.span().byte_range().end > changed.start)
+            .unwrap_or(nodes.len());
+        // Nodes that can be extended by the next node: merged text, unquoted
+        // text, and directive comments that apply to the next element.
+        while reused > 0
+            && matches!(
+                nodes[reused - 1],
+                Node::Text(_) | Node::RawText(_) | Node::Comment(_)
+            )
+        {
+            reused -= 1;
+        }
+        reused
+    }
+
+    /// Tokenize `source` and parse it with [`parse_recoverable`], e.g. for
+    /// templates read from files.
+    ///
+    /// Source with unbalanced braces or quotes can't be tokenized, in that
+    /// case [`ParserError::UnbalancedSource`] with location in `source` is
+    /// reported.
+    ///
+    /// ```rust
+    /// use rstml::{Parser, ParserConfig, ParserError};
+    ///
+    /// let parser = Parser::new(ParserConfig::new());
+    /// let (_, errors) = parser
+    ///     .parse_str("<style>\n  p { font-family: 'Open Sans'; }\n</style>")
+    ///     .split_vec();
+    /// assert_eq!(ParserError::code_of(&errors[0]), Some("E029"));
+    /// ```
+    ///
+    /// [`parse_recoverable`]: Self::parse_recoverable
+    pub fn parse_str(&self, source: &str) -> ParsingResult<Vec<Node<C>>> {
+        match TokenStream::from_str(source) {
+            Ok(tokens) => self.parse_recoverable(tokens),
+            Err(e) => {
+                let start = e.span().start();
+                let error = ParserError::UnbalancedSource {
+                    line: start.line,
+                    column: start.column + 1,
+                };
+                ParsingResult::Failed(vec![error.spanned(e.span())])
+            }
+        }
+    }
+
+    /// Parse list of attributes, written the same way as in open tag of
+    /// element, e.g. `class="x" id={y} checked`.
+    ///
+    /// Parsing stops at the end of input, or before `>` / `/>`, remaining
+    /// tokens are left in `input`.
+    pub fn parse_attributes(&self, input: ParseStream) -> ParsingResult<Vec<NodeAttribute>> {
+        let mut parser = RecoverableContext::new(self.config.clone().into());
+        let mut attributes = NodeAttribute::parse_list(&mut parser, input);
+        NodeAttribute::set_positions(&mut parser, &mut attributes);
+        parser.parse_result(Some(attributes))
+    }
+
+    /// Run [`parse_syn_stream`] over `stream`.
+    ///
+    /// `syn` reports tokens that was left unparsed inside a group only after
+    /// parsing is finished, so that error is added to the result instead of
+    /// dropping already parsed nodes.
+    ///
+    /// [`parse_syn_stream`]: Self::parse_syn_stream
+    fn parse_token_stream(&self, stream: TokenStream) -> ParsingResult<Vec<Node<C>>> {
+        use syn::parse::Parser as _;
+
+        let mut source = None;
+        let parser = |input: ParseStream| {
+            source = Some(self.parse_syn_stream(input));
+            Ok(())
+        };
+        let result = parser.parse2(stream);
+        let mut source = source.unwrap_or_else(|| ParsingResult::Failed(vec![]));
+        if let Err(e) = result {
+            source.push_diagnostic(e.into());
+        }
+        source
+    }
+
     #[cfg(feature = "rawtext-stable-hack")]
-    fn reparse_raw_text<Parser>(
+    fn reparse_raw_text(
         &self,
-        parser: Parser,
         mut source: ParsingResult<Vec<Node<C>>>,
-    ) -> ParsingResult<Vec<Node<C>>>
-    where
-        Parser: FnOnce(ParseStream) -> syn::Result<ParsingResult<Vec<Node<C>>>>,
-    {
-        use syn::parse::Parser as _;
+    ) -> ParsingResult<Vec<Node<C>>> {
         // in case we already have valid raw_text, we can skip re-parsing
         if rawtext_stable_hack::is_join_span_available() {
             return source;
@@ -95,51 +343,141 @@ impl<C: CustomNode + std::fmt::Debug> Parser<C> {
         if self.config.macro_pattern.is_empty() {
             return source;
         }
-        let text = Span::call_site()
-            .source_text()
-            .expect("Source text should be available");
+        let Some(text) = Span::call_site().source_text() else {
+            let (mut source, mut errors) = source.split_vec();
+            rawtext_stable_hack::inject_raw_text_default(&mut source);
+            errors.push(Diagnostic::new(
+                proc_macro2_diagnostics::Level::Warning,
+                "Failed to retrive source text of macro call, maybe macro was called from other macro?",
+            ));
+            return ParsingResult::Partial(source, errors);
+        };
+        self.inject_source_text(source, &text, true)
+    }
 
+    /// Same as [`parse_recoverable`], but spaces in [`RawText`] are
+    /// recovered from `source_text`, that should contain the same template
+    /// as `v`, but with original formatting.
+    ///
+    /// Useful on stable when input is not written in the macro call, so
+    /// [`macro_call_pattern`] can't be used, e.g. when template is read from
+    /// file and converted to `TokenStream` with `FromStr`.
+    ///
+    /// If `source_text` contains different nodes than `v`, nodes of `v` are
+    /// returned with [`ParserError::SourceTextMismatch`].
+    ///
+    /// [`parse_recoverable`]: Self::parse_recoverable
+    /// [`macro_call_pattern`]: ParserConfig::macro_call_pattern
+    #[cfg(feature = "rawtext-stable-hack")]
+    pub fn parse_with_source_text(
+        &self,
+        v: impl Into<TokenStream>,
+        source_text: &str,
+    ) -> ParsingResult<Vec<Node<C>>> {
+        let source = self.parse_token_stream(v.into());
+        if rawtext_stable_hack::is_join_span_available() {
+            return source;
+        }
+        if !source.is_ok() {
+            let (mut source, errors) = source.split_vec();
+            rawtext_stable_hack::inject_raw_text_default(&mut source);
+            return ParsingResult::from_parts_vec(source, errors);
+        }
+        self.inject_source_text(source, source_text, false)
+    }
+
+    /// Parse `text` using `proc_macro2::fallback`, and copy recovered raw
+    /// text into `source`. If `is_macro_call` is true, template is extracted
+    /// from `text` using [`macro_call_pattern`].
+    ///
+    /// [`macro_call_pattern`]: ParserConfig::macro_call_pattern
+    #[cfg(feature = "rawtext-stable-hack")]
+    fn inject_source_text(
+        &self,
+        source: ParsingResult<Vec<Node<C>>>,
+        text: &str,
+        is_macro_call: bool,
+    ) -> ParsingResult<Vec<Node<C>>> {
         proc_macro2::fallback::force();
-        let stream = TokenStream::from_str(&text).unwrap();
-        let stream = self
-            .config
-            .macro_pattern
-            .match_content(stream)
-            .expect("Cannot find macro pattern inside Span::call_site");
-        let hacked = parser.parse2(stream).expect("No errors from parser");
-
-        let mut source = source.into_result().expect("was checked");
-        let hacked = hacked.into_result().expect("was checked");
+        let hacked = TokenStream::from_str(text)
+            .ok()
+            .and_then(|stream| {
+                if is_macro_call {
+                    self.config.macro_pattern.match_content(stream)
+                } else {
+                    Some(stream)
+                }
+            })
+            .map(|stream| self.parse_token_stream(stream));
         proc_macro2::fallback::unforce();
-        rawtext_stable_hack::inject_raw_text(&mut source, &hacked);
 
-        return ParsingResult::Ok(source);
+        let (mut source, mut errors) = source.split_vec();
+        match hacked.map(ParsingResult::into_result) {
+            Some(Ok(hacked)) => {
+                if !rawtext_stable_hack::inject_raw_text(&mut source, &hacked) {
+                    let span = source.first().map_or_else(Span::call_site, Node::span);
+                    errors.push(ParserError::SourceTextMismatch.spanned(span));
+                }
+            }
+            _ => {
+                rawtext_stable_hack::inject_raw_text_default(&mut source);
+                errors.push(Diagnostic::new(
+                    proc_macro2_diagnostics::Level::Warning,
+                    "Failed to reparse macro call to recover raw text",
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            ParsingResult::Ok(source)
+        } else {
+            ParsingResult::Partial(source, errors)
+        }
     }
 
     /// Parse a given [`ParseStream`].
     pub fn parse_syn_stream(&self, input: ParseStream) -> ParsingResult<Vec<Node<C>>> {
-        let mut nodes = vec![];
-        let mut top_level_nodes = 0;
+        let parser = RecoverableContext::new(self.config.clone().into());
+        trace::debug!("parse started");
+        self.parse_nodes(parser, input, vec![])
+    }
 
-        let mut parser = RecoverableContext::new(self.config.clone().into());
-        while !input.is_empty() {
+    /// Parse top-level nodes from `input`, after already parsed `nodes`.
+    fn parse_nodes(
+        &self,
+        mut parser: RecoverableContext,
+        input: ParseStream,
+        mut nodes: Vec<Node<C>>,
+    ) -> ParsingResult<Vec<Node<C>>> {
+        let mut top_level_nodes = nodes.len();
+        loop {
+            skip_stray_close_tags(&mut parser, input);
+            if input.is_empty() {
+                break;
+            }
+            let start = input.cursor();
             let Some(parsed_node) = Node::parse_recoverable(&mut parser, input) else {
-                parser.push_diagnostic(input.error("Node parse failed".to_string()));
+                // Too deep node is skipped, along with its tokens.
+                if std::mem::take(&mut parser.dropped) && input.cursor() != start {
+                    continue;
+                }
+                parser.push_error(ParserError::NodeParseFailed, input.span());
                 break;
             };
 
             if let Some(type_of_top_level_nodes) = &self.config.type_of_top_level_nodes {
                 if &parsed_node.r#type() != type_of_top_level_nodes {
-                    parser.push_diagnostic(input.error(format!(
-                        "top level nodes need to be of type {}",
-                        type_of_top_level_nodes
-                    )));
+                    parser.push_error(
+                        ParserError::TopLevelNodeType(type_of_top_level_nodes.to_string()),
+                        input.span(),
+                    );
                     break;
                 }
             }
 
-            top_level_nodes += 1;
-            nodes.push(parsed_node)
+            if push_node(&mut nodes, parsed_node, self.config.merge_adjacent_text) {
+                top_level_nodes += 1;
+            }
         }
 
         // its important to skip tokens, to avoid Unexpected tokens errors.
@@ -147,19 +485,17 @@ impl<C: CustomNode + std::fmt::Debug> Parser<C> {
             let tts = input
                 .parse::<TokenStream>()
                 .expect("No error in parsing token stream");
-            parser.push_diagnostic(Diagnostic::spanned(
-                tts.span(),
-                proc_macro2_diagnostics::Level::Error,
-                "Tokens was skipped after incorrect parsing",
-            ));
+            trace::debug!(tokens = %tts, "skipping tokens after the last node");
+            parser.push_error(ParserError::SkippedTokens, tts.span());
         }
 
         if let Some(number_of_top_level_nodes) = &self.config.number_of_top_level_nodes {
             if &top_level_nodes != number_of_top_level_nodes {
-                parser.push_diagnostic(input.error(format!(
-                    "saw {} top level nodes but exactly {} are required",
-                    top_level_nodes, number_of_top_level_nodes
-                )))
+                let error = ParserError::TopLevelNodeCount {
+                    found: top_level_nodes,
+                    expected: *number_of_top_level_nodes,
+                };
+                parser.push_error(error, input.span())
             }
         }
 
@@ -170,6 +506,11 @@ impl<C: CustomNode + std::fmt::Debug> Parser<C> {
         };
 
         let errors = parser.diagnostics;
+        trace::debug!(
+            nodes = nodes.len(),
+            diagnostics = errors.len(),
+            "parse finished"
+        );
 
         let nodes = if nodes.is_empty() {
             Some(vec![])
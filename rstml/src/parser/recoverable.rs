@@ -34,19 +34,90 @@
 //! unexpected.
 //!
 //!
+//! ## Custom nodes
+//!
+//! [`CustomNode`] implementations receive the same [`RecoverableContext`] as
+//! the built-in nodes. Use [`RecoverableContext::parse_simple`] or
+//! [`RecoverableContext::save_diagnostics`] to turn [`syn::Result`] into
+//! `Option`, saving the error, and [`RecoverableContext::push_diagnostic`] to
+//! report a problem and continue parsing:
+//!
+//! ```rust
+//! use proc_macro2::TokenStream;
+//! use quote::{quote, ToTokens};
+//! use rstml::{
+//!     node::{CustomNode, Node},
+//!     recoverable::{ParseRecoverable, RecoverableContext},
+//!     Parser, ParserConfig,
+//! };
+//! use syn::{parse::ParseStream, Ident, LitInt, Token};
+//!
+//! /// `<repeat 3>`
+//! #[derive(Debug)]
+//! struct Repeat {
+//!     tokens: TokenStream,
+//! }
+//!
+//! impl ParseRecoverable for Repeat {
+//!     fn parse_recoverable(parser: &mut RecoverableContext, input: ParseStream) -> Option<Self> {
+//!         let start = parser.parse_simple::<Token![<]>(input)?;
+//!         let keyword = parser.parse_simple::<Ident>(input)?;
+//!         let count = parser.parse_simple::<LitInt>(input)?;
+//!         if parser.save_diagnostics(count.base10_parse::<u8>()) == Some(0) {
+//!             parser.push_diagnostic(syn::Error::new(count.span(), "count can't be zero"));
+//!         }
+//!         let end = parser.parse_simple::<Token![>]>(input)?;
+//!         Some(Repeat {
+//!             tokens: quote!(#start #keyword #count #end),
+//!         })
+//!     }
+//! }
+//!
+//! impl ToTokens for Repeat {
+//!     fn to_tokens(&self, tokens: &mut TokenStream) {
+//!         self.tokens.to_tokens(tokens)
+//!     }
+//! }
+//!
+//! impl CustomNode for Repeat {
+//!     fn peek_element(input: ParseStream) -> bool {
+//!         input.peek(Token![<]) && input.peek2(Ident) && input.peek3(LitInt)
+//!     }
+//! }
+//!
+//! let parser = Parser::new(ParserConfig::new().custom_node::<Repeat>());
+//! let (nodes, errors) = parser
+//!     .parse_recoverable(quote! { <repeat 0> <div></span> })
+//!     .split_vec();
+//! assert!(matches!(nodes[0], Node::Custom(_)));
+//! // Errors of custom node are reported along with errors of other nodes.
+//! assert_eq!(errors.len(), 2);
+//! ```
+//!
 //! [`TokenStream`]: https://doc.rust-lang.org/proc_macro/struct.TokenStream.html
+//! [`CustomNode`]: crate::node::CustomNode
 //! [`Parser::parse_recoverable`]: struct.Parser.html#method.parse_recoverable
 //! [`Node`]: struct.Node.html
 
-use std::{collections::HashSet, fmt::Debug, rc::Rc};
+use std::{
+    any::Any,
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    rc::Rc,
+};
 
+use proc_macro2::{Delimiter, Span};
 use proc_macro2_diagnostics::{Diagnostic, Level};
 use syn::parse::{Parse, ParseStream};
 
 use crate::{
-    config::{ElementWildcardFn, TransformBlockFn},
-    node::CustomNode,
-    ParserConfig,
+    config::{
+        AttributeNameCheckFn, AttributeValueParsing, ChildrenParserFn, ComponentRules,
+        ElementClosedFn, ElementNameCheckFn, ElementWildcardFn, ErrorMessageFn, NamePolicy,
+        TextLiterals, TransformBlockFn,
+    },
+    node::{CustomNode, Directive, Name, NameInterner, Namespace, NodeName},
+    trace, ParserConfig, ParserError,
 };
 
 /// Config of parser.
@@ -59,39 +130,192 @@ pub struct RecoveryConfig {
     /// Try to parse invalid syn::Block as something.
     /// Usefull to make expressions more IDE-friendly.
     pub(crate) recover_block: bool,
+    /// Keep content of blocks as raw tokens.
+    pub(crate) raw_blocks: bool,
+    /// Keep unfinished open tags and attributes in the tree.
+    pub(crate) recover_incomplete: bool,
+    /// Delimiters of groups that are parsed as blocks, braces if empty.
+    pub(crate) block_delimiters: Vec<Delimiter>,
+    /// Merge consecutive text nodes.
+    pub(crate) merge_adjacent_text: bool,
+    /// Split text nodes by `{expr}` segments.
+    pub(crate) interpolate_text: bool,
+    /// Parse `#id` and `.class` shorthands on open tags.
+    pub(crate) tag_sugar: bool,
+    /// Report attributes without value.
+    pub(crate) reject_flag_attributes: bool,
+    /// How far expressions in attribute values are parsed.
+    pub(crate) attribute_value_parsing: AttributeValueParsing,
+    /// Parse literals joined with `-` or `/` in attribute values as strings.
+    pub(crate) unquoted_values: bool,
+    /// Characters allowed in names of elements and attributes.
+    pub(crate) name_policy: NamePolicy,
+    /// Literals that can be used as text nodes.
+    pub(crate) text_literals: TextLiterals,
+    /// Keep tokens of elements and fragments.
+    pub(crate) preserve_tokens: bool,
+    /// Prefix of directive comments.
+    pub(crate) directive_prefix: Option<&'static str>,
+    /// Compare names of open and close tags ignoring case.
+    pub(crate) case_insensitive_close_tags: bool,
+    /// Rules to tell components from html elements.
+    pub(crate) component_rules: ComponentRules,
     /// elements that has no child and is always self closed like <img> and <br>
     pub(crate) always_self_closed_elements: HashSet<&'static str>,
+    /// Report void elements written without `/>`.
+    pub(crate) strict_self_closing: bool,
+    /// Report fragments that are not top-level nodes.
+    pub(crate) reject_nested_fragments: bool,
+    /// Report fragments without children.
+    pub(crate) reject_empty_fragments: bool,
+    /// Report blocks without content.
+    pub(crate) reject_empty_blocks: bool,
     /// Elements like `<script>` `<style>`, context of which is not a valid
     /// html, and should be provided as is.
     pub(crate) raw_text_elements: HashSet<&'static str>,
+    /// Attributes that are moved to [`NodeElement::special_attributes`].
+    ///
+    /// [`NodeElement::special_attributes`]: crate::node::NodeElement::special_attributes
+    pub(crate) special_attributes: HashSet<&'static str>,
     pub(crate) transform_block: Option<Rc<TransformBlockFn>>,
     /// Allows wildcard closing tag matching for blocks
     pub(crate) element_close_wildcard: Option<Rc<ElementWildcardFn>>,
+    /// Custom messages of parser errors.
+    pub(crate) error_message: Option<Rc<ErrorMessageFn>>,
+    /// Check of element names, returns reason if name is invalid.
+    pub(crate) element_name_check: Option<Rc<ElementNameCheckFn>>,
+    /// Check of attribute keys, returns reason if key is invalid.
+    pub(crate) attribute_name_check: Option<Rc<AttributeNameCheckFn>>,
+    /// Maximum nesting depth of elements and fragments.
+    pub(crate) max_depth: Option<usize>,
+    /// Type erased `Rc<ElementClosedFn<C>>`.
+    pub(crate) on_element_closed: Option<Rc<dyn Any>>,
+    /// Type erased `Rc<ChildrenParserFn<C>>` for each element name.
+    pub(crate) children_parsers: HashMap<&'static str, Rc<dyn Any>>,
 }
 impl PartialEq for RecoveryConfig {
     fn eq(&self, other: &Self) -> bool {
         self.recover_block == other.recover_block
+            && self.raw_blocks == other.raw_blocks
+            && self.recover_incomplete == other.recover_incomplete
+            && self.block_delimiters == other.block_delimiters
+            && self.merge_adjacent_text == other.merge_adjacent_text
+            && self.interpolate_text == other.interpolate_text
+            && self.tag_sugar == other.tag_sugar
+            && self.reject_flag_attributes == other.reject_flag_attributes
+            && self.attribute_value_parsing == other.attribute_value_parsing
+            && self.unquoted_values == other.unquoted_values
+            && self.name_policy == other.name_policy
+            && self.text_literals == other.text_literals
+            && self.preserve_tokens == other.preserve_tokens
+            && self.directive_prefix == other.directive_prefix
+            && self.case_insensitive_close_tags == other.case_insensitive_close_tags
+            && self.component_rules == other.component_rules
             && self.always_self_closed_elements == other.always_self_closed_elements
+            && self.strict_self_closing == other.strict_self_closing
+            && self.reject_nested_fragments == other.reject_nested_fragments
+            && self.reject_empty_fragments == other.reject_empty_fragments
+            && self.reject_empty_blocks == other.reject_empty_blocks
             && self.raw_text_elements == other.raw_text_elements
+            && self.special_attributes == other.special_attributes
             && self.transform_block.is_some() == other.transform_block.is_some()
             && self.element_close_wildcard.is_some() == other.element_close_wildcard.is_some()
+            && self.error_message.is_some() == other.error_message.is_some()
+            && self.element_name_check.is_some() == other.element_name_check.is_some()
+            && self.attribute_name_check.is_some() == other.attribute_name_check.is_some()
+            && self.max_depth == other.max_depth
+            && self.on_element_closed.is_some() == other.on_element_closed.is_some()
+            && self.children_parsers.len() == other.children_parsers.len()
+            && self
+                .children_parsers
+                .keys()
+                .all(|name| other.children_parsers.contains_key(name))
     }
 }
 impl Eq for RecoveryConfig {}
 
+impl RecoveryConfig {
+    /// Callback for elements with custom node `C`, if it was set.
+    pub(crate) fn on_element_closed<C: CustomNode>(&self) -> Option<Rc<ElementClosedFn<C>>> {
+        self.on_element_closed
+            .as_ref()?
+            .downcast_ref::<Rc<ElementClosedFn<C>>>()
+            .cloned()
+    }
+
+    /// Parser of children of element `name` with custom node `C`, if it was
+    /// set.
+    pub(crate) fn children_parser<C: CustomNode>(
+        &self,
+        name: &str,
+    ) -> Option<Rc<ChildrenParserFn<C>>> {
+        self.children_parsers
+            .get(name)?
+            .downcast_ref::<Rc<ChildrenParserFn<C>>>()
+            .cloned()
+    }
+
+    /// Message of parser `error`, custom one if it was provided by
+    /// [`ParserConfig::error_message`].
+    pub(crate) fn error_message(&self, error: &ParserError) -> String {
+        error.message(self.error_message.as_deref())
+    }
+
+    /// Delimiters of groups that are parsed as blocks.
+    pub(crate) fn block_delimiters(&self) -> &[Delimiter] {
+        if self.block_delimiters.is_empty() {
+            &[Delimiter::Brace]
+        } else {
+            &self.block_delimiters
+        }
+    }
+}
+
 impl Debug for RecoveryConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RecoveryConfig")
             .field("recover_block", &self.recover_block)
+            .field("raw_blocks", &self.raw_blocks)
+            .field("recover_incomplete", &self.recover_incomplete)
+            .field("block_delimiters", &self.block_delimiters)
+            .field("merge_adjacent_text", &self.merge_adjacent_text)
+            .field("interpolate_text", &self.interpolate_text)
+            .field("tag_sugar", &self.tag_sugar)
+            .field("reject_flag_attributes", &self.reject_flag_attributes)
+            .field("attribute_value_parsing", &self.attribute_value_parsing)
+            .field("unquoted_values", &self.unquoted_values)
+            .field("name_policy", &self.name_policy)
+            .field("text_literals", &self.text_literals)
+            .field("preserve_tokens", &self.preserve_tokens)
+            .field("directive_prefix", &self.directive_prefix)
+            .field(
+                "case_insensitive_close_tags",
+                &self.case_insensitive_close_tags,
+            )
+            .field("component_rules", &self.component_rules)
             .field(
                 "always_self_closed_elements",
                 &self.always_self_closed_elements,
             )
+            .field("strict_self_closing", &self.strict_self_closing)
+            .field("reject_nested_fragments", &self.reject_nested_fragments)
+            .field("reject_empty_fragments", &self.reject_empty_fragments)
+            .field("reject_empty_blocks", &self.reject_empty_blocks)
             .field("raw_text_elements", &self.raw_text_elements)
+            .field("special_attributes", &self.special_attributes)
             .field(
                 "element_close_wildcard",
                 &self.element_close_wildcard.is_some(),
             )
+            .field("error_message", &self.error_message.is_some())
+            .field("element_name_check", &self.element_name_check.is_some())
+            .field("attribute_name_check", &self.attribute_name_check.is_some())
+            .field("max_depth", &self.max_depth)
+            .field("on_element_closed", &self.on_element_closed.is_some())
+            .field(
+                "children_parsers",
+                &self.children_parsers.keys().collect::<HashSet<_>>(),
+            )
             .finish()
     }
 }
@@ -104,6 +328,17 @@ impl Debug for RecoveryConfig {
 pub struct RecoverableContext {
     pub(super) diagnostics: Vec<Diagnostic>,
     config: RecoveryConfig,
+    /// Number of elements and fragments around current position.
+    pub(crate) depth: usize,
+    /// Namespace of elements at current position.
+    pub(crate) namespace: Namespace,
+    /// Directives of comments before current position, for the next element.
+    pub(crate) directives: Vec<Directive>,
+    /// Names of elements and attributes, interned during this parse.
+    pub(crate) names: NameInterner,
+    /// Set when node was too deep, and was parsed only to be dropped, see
+    /// [`ParserConfig::max_depth`](crate::ParserConfig::max_depth).
+    pub(crate) dropped: bool,
 }
 
 impl PartialEq for RecoverableContext {
@@ -125,11 +360,47 @@ impl RecoverableContext {
         Self {
             diagnostics: vec![],
             config,
+            depth: 0,
+            namespace: Namespace::Html,
+            directives: vec![],
+            names: Default::default(),
+            dropped: false,
         }
     }
     pub fn config(&self) -> &RecoveryConfig {
         &self.config
     }
+    /// Diagnostics collected so far.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+    /// Namespace of elements at current position, e.g. custom node inside of
+    /// `<svg>` is parsed in [`Namespace::Svg`].
+    pub fn namespace(&self) -> Namespace {
+        self.namespace
+    }
+    /// Copy of context for speculative parsing, which doesn't call hooks that
+    /// observe parsed nodes, like
+    /// [`ParserConfig::on_element_closed`](crate::ParserConfig::on_element_closed).
+    pub(crate) fn speculative(&self) -> Self {
+        let mut context = self.clone();
+        context.diagnostics.clear();
+        context.config.on_element_closed = None;
+        context.config.element_name_check = None;
+        context.config.attribute_name_check = None;
+        context
+    }
+    /// Whether hooks, that are disabled in [`speculative`](Self::speculative)
+    /// context, are set.
+    pub(crate) fn has_hooks(&self) -> bool {
+        self.config.on_element_closed.is_some()
+            || self.config.element_name_check.is_some()
+            || self.config.attribute_name_check.is_some()
+    }
+    /// Intern `name` in the table of this parse, see [`NameInterner`].
+    pub fn intern(&mut self, name: &NodeName) -> Name {
+        self.names.intern_name(name)
+    }
     pub fn parse_result<T>(self, val: Option<T>) -> ParsingResult<T> {
         ParsingResult::from_parts(val, self.diagnostics)
     }
@@ -176,15 +447,24 @@ impl RecoverableContext {
         }
     }
 
+    /// Diagnostic of parser `error` pointing to `span`, with message from
+    /// [`ParserConfig::error_message`] callback, if it was set.
+    pub fn error_diagnostic(&self, error: ParserError, span: Span) -> Diagnostic {
+        error.spanned_with_message(span, self.config.error_message(&error))
+    }
+
+    /// Push diagnostic of parser `error`, see
+    /// [`RecoverableContext::error_diagnostic`].
+    pub fn push_error(&mut self, error: ParserError, span: Span) {
+        let diagnostic = self.error_diagnostic(error, span);
+        self.push_diagnostic(diagnostic);
+    }
+
     /// Push custom message of [`syn::Error`] or
     /// [`proc_macro2_diagnostics::Diagnostic`]
     pub fn push_diagnostic(&mut self, diagnostic: impl Into<Diagnostic>) {
         let diag = diagnostic.into();
-        // println!(
-        //     "Push diagnostic: {:?}, backtrace={}",
-        //     diag,
-        //     std::backtrace::Backtrace::capture()
-        // );
+        trace::debug!(diagnostic = ?diag, "diagnostic reported");
         self.diagnostics.push(diag);
     }
 }
@@ -232,11 +512,7 @@ impl<T> ParsingResult<T> {
                 })
                 .into()),
             ParsingResult::Partial(ok, errors) => {
-                if let Some(err) = errors
-                    .into_iter()
-                    .filter(|p| p.level() == Level::Error)
-                    .next()
-                {
+                if let Some(err) = errors.into_iter().find(|p| p.level() == Level::Error) {
                     Err(err.into())
                 } else {
                     Ok(ok)
@@ -268,6 +544,15 @@ impl<T> ParsingResult<T> {
     pub fn is_ok(&self) -> bool {
         matches!(self, Self::Ok(_))
     }
+
+    /// Map parsed value, diagnostics are kept as is.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> ParsingResult<U> {
+        match self {
+            Self::Ok(r) => ParsingResult::Ok(f(r)),
+            Self::Failed(errors) => ParsingResult::Failed(errors),
+            Self::Partial(r, errors) => ParsingResult::Partial(f(r), errors),
+        }
+    }
 }
 
 impl<T> ParsingResult<Vec<T>> {
@@ -300,10 +585,42 @@ impl<C: CustomNode> From<ParserConfig<C>> for RecoveryConfig {
     fn from(config: ParserConfig<C>) -> Self {
         RecoveryConfig {
             recover_block: config.recover_block,
+            raw_blocks: config.raw_blocks,
+            recover_incomplete: config.recover_incomplete,
+            block_delimiters: config.block_delimiters.clone(),
+            merge_adjacent_text: config.merge_adjacent_text,
+            interpolate_text: config.interpolate_text,
+            tag_sugar: config.tag_sugar,
+            reject_flag_attributes: config.reject_flag_attributes,
+            attribute_value_parsing: config.attribute_value_parsing,
+            unquoted_values: config.unquoted_values,
+            name_policy: config.name_policy,
+            text_literals: config.text_literals,
+            preserve_tokens: config.preserve_tokens,
+            directive_prefix: config.directive_prefix,
+            case_insensitive_close_tags: config.case_insensitive_close_tags,
+            component_rules: config.component_rules,
             raw_text_elements: config.raw_text_elements.clone(),
+            special_attributes: config.special_attributes.clone(),
             always_self_closed_elements: config.always_self_closed_elements.clone(),
+            strict_self_closing: config.strict_self_closing,
+            reject_nested_fragments: config.reject_nested_fragments,
+            reject_empty_fragments: config.reject_empty_fragments,
+            reject_empty_blocks: config.reject_empty_blocks,
             transform_block: config.transform_block.clone(),
             element_close_wildcard: config.element_close_wildcard.clone(),
+            error_message: config.error_message.clone(),
+            element_name_check: config.element_name_check.clone(),
+            attribute_name_check: config.attribute_name_check.clone(),
+            max_depth: config.max_depth,
+            on_element_closed: config
+                .on_element_closed
+                .map(|callback| Rc::new(callback) as Rc<dyn Any>),
+            children_parsers: config
+                .children_parsers
+                .into_iter()
+                .map(|(name, callback)| (name, Rc::new(callback) as Rc<dyn Any>))
+                .collect(),
         }
     }
 }
@@ -340,14 +657,13 @@ impl<T: ParseRecoverable> Parse for Recoverable<T> {
 /// tokens, and more diagnostic messages.
 ///
 /// - If input stream can be parsed to valid, or partially valid object
-/// [`Option::Some`] should be returned.
+///   [`Option::Some`] should be returned.
 ///
-/// - If object is parsed partially one can save
-/// diagnostic message in [`RecoverableContext`].
+/// - If object is parsed partially one can save diagnostic message in
+///   [`RecoverableContext`].
 ///
-/// - If object is failed to parse
-/// [`Option::None`] should be returned, and any message should be left in
-/// [`RecoverableContext`].
+/// - If object is failed to parse [`Option::None`] should be returned, and any
+///   message should be left in [`RecoverableContext`].
 ///
 /// Instead of using [`RecoverableContext`] the interface can be changed to the
 /// following:
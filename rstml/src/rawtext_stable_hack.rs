@@ -25,25 +25,27 @@ pub fn is_macro_args_recoverable() -> bool {
 
 // Inject default text to every raw node, just to avoid panics.
 pub fn inject_raw_text_default<C: CustomNode>(source: &mut [Node<C>]) {
-    for source in source.into_iter() {
+    for source in source.iter_mut() {
         replace_node_default(source)
     }
 }
 
 // Inject raw text to every raw node, recovered using proc-macro2 second
 // parsing;
+// Returns false if `hacked` contains different nodes than `source`, raw nodes
+// that have no counterpart get default text.
 pub fn inject_raw_text<C: CustomNode + std::fmt::Debug>(
     source: &mut [Node<C>],
     hacked: &[Node<C>],
-) {
-    assert_eq!(
-        source.len(),
-        hacked.len(),
-        "Second parsing return different result in recover_space_hack"
-    );
-    for (source, hacked) in source.into_iter().zip(hacked) {
-        replace_node(source, hacked)
+) -> bool {
+    let mut matched = source.len() == hacked.len();
+    for (source, hacked) in source.iter_mut().zip(hacked) {
+        matched &= replace_node(source, hacked)
     }
+    if let Some(rest) = source.get_mut(hacked.len()..) {
+        inject_raw_text_default(rest)
+    }
+    matched
 }
 
 pub fn replace_node_default<C: CustomNode>(source: &mut Node<C>) {
@@ -59,13 +61,17 @@ pub fn replace_node_default<C: CustomNode>(source: &mut Node<C>) {
         Node::Block(_)
         | Node::Comment(_)
         | Node::Custom(_)
+        | Node::Incomplete(_)
         | Node::Text(_) => {}
     }
 }
 
-pub fn replace_node<C: CustomNode + std::fmt::Debug>(source: &mut Node<C>, hacked: &Node<C>) {
+pub fn replace_node<C: CustomNode + std::fmt::Debug>(
+    source: &mut Node<C>,
+    hacked: &Node<C>,
+) -> bool {
     match (source, hacked) {
-        (Node::RawText(source), Node::RawText(hacked)) => source.recover_space(&hacked),
+        (Node::RawText(source), Node::RawText(hacked)) => source.recover_space(hacked),
         (Node::Fragment(source), Node::Fragment(hacked)) => {
             inject_raw_text(&mut source.children, &hacked.children)
         }
@@ -76,12 +82,11 @@ pub fn replace_node<C: CustomNode + std::fmt::Debug>(source: &mut Node<C>, hacke
         (Node::Block(_), Node::Block(_))
         | (Node::Comment(_), Node::Comment(_))
         | (Node::Custom(_), Node::Custom(_))
-        | (Node::Text(_), Node::Text(_)) => {}
-        (source, hacked) => {
-            panic!(
-                "Mismatched node type in recover_space_hack {:?}, {:?}",
-                source, hacked
-            )
+        | (Node::Incomplete(_), Node::Incomplete(_))
+        | (Node::Text(_), Node::Text(_)) => true,
+        (source, _) => {
+            replace_node_default(source);
+            false
         }
     }
 }
@@ -148,7 +153,7 @@ impl MacroPattern {
 
         // skip any token before first '!'
         // to allow using macro with differnet paths.
-        while let Some(t) = stream.next() {
+        for t in stream.by_ref() {
             if let TokenTree::Punct(p) = t {
                 if p.as_char() == '!' {
                     break;
@@ -225,7 +230,7 @@ impl MacroPattern {
                 TokenStreamOperations::SkipUntil(expected) => 'compare: loop {
                     let needed = expected.clone().into_iter();
                     for expected in needed {
-                        let Some(t) = stream.next() else { return None };
+                        let t = stream.next()?;
 
                         if t.to_string() != expected.to_string() {
                             continue 'compare;
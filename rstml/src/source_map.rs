@@ -0,0 +1,156 @@
+//! Mapping from parsed nodes back to the original input.
+//!
+//! [`SourceMap`] is built in a separate pass over an already parsed tree, and
+//! stores byte offsets and line/column positions of every node, keyed by
+//! [`NodeId`]. Positions are taken from token spans, so they are only
+//! meaningful when spans carry location information: when the input was
+//! parsed from a string (`proc_macro2` fallback mode with `span-locations`),
+//! or inside a proc-macro on a nightly compiler. In other contexts every range
+//! is reported as `0..0`.
+//!
+//! ```rust
+//! use rstml::{parse2, source_map::SourceMap};
+//!
+//! let source = r#"<div><span>"hello"</span></div>"#;
+//! let nodes = parse2(source.parse().unwrap()).unwrap();
+//! let map = SourceMap::new(&nodes);
+//!
+//! // Preorder: div, span, text.
+//! assert_eq!(map.len(), 3);
+//! let span = map.iter().nth(1).unwrap();
+//! assert_eq!(&source[span.byte_range()], r#"<span>"hello"</span>"#);
+//! ```
+
+use std::ops::Range;
+
+use proc_macro2::{LineColumn, Span, TokenStream, TokenTree};
+use quote::ToTokens;
+
+use crate::node::{CustomNode, Node, NodeId, NodeType};
+
+/// Location of a single node in the original input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceMapEntry {
+    id: NodeId,
+    parent: Option<NodeId>,
+    node_type: NodeType,
+    byte_range: Range<usize>,
+    start: LineColumn,
+    end: LineColumn,
+}
+
+impl SourceMapEntry {
+    /// Id of the node this entry describes.
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+    /// Id of the parent element or fragment, `None` for top-level nodes.
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+    /// Type of the node.
+    pub fn node_type(&self) -> NodeType {
+        self.node_type.clone()
+    }
+    /// Byte offsets of the node (`start..end`) in the original input.
+    pub fn byte_range(&self) -> Range<usize> {
+        self.byte_range.clone()
+    }
+    /// Line and column of the first token of the node.
+    pub fn start(&self) -> LineColumn {
+        self.start
+    }
+    /// Line and column right after the last token of the node.
+    pub fn end(&self) -> LineColumn {
+        self.end
+    }
+}
+
+/// Table of node locations, see [module documentation](self).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    entries: Vec<SourceMapEntry>,
+}
+
+impl SourceMap {
+    /// Build source map for `nodes`, assigning [`NodeId`]s in preorder.
+    pub fn new<C: CustomNode>(nodes: &[Node<C>]) -> Self {
+        let mut map = Self::default();
+        map.visit_nodes(nodes, None);
+        map
+    }
+
+    fn visit_nodes<C: CustomNode>(&mut self, nodes: &[Node<C>], parent: Option<NodeId>) {
+        for node in nodes {
+            let id = NodeId(self.entries.len());
+            let (first, last) = boundary_spans(node.to_token_stream());
+            self.entries.push(SourceMapEntry {
+                id,
+                parent,
+                node_type: node.r#type(),
+                byte_range: first.byte_range().start..last.byte_range().end,
+                start: first.start(),
+                end: last.end(),
+            });
+            if let Some(children) = node.children() {
+                self.visit_nodes(children, Some(id));
+            }
+        }
+    }
+
+    /// Get entry of node with given `id`.
+    pub fn get(&self, id: NodeId) -> Option<&SourceMapEntry> {
+        self.entries.get(id.0)
+    }
+
+    /// Shortcut for `get(id).map(SourceMapEntry::byte_range)`.
+    pub fn byte_range(&self, id: NodeId) -> Option<Range<usize>> {
+        self.get(id).map(SourceMapEntry::byte_range)
+    }
+
+    /// Find the innermost node which contains `offset`.
+    pub fn node_at(&self, offset: usize) -> Option<NodeId> {
+        // Entries are in preorder, so the last match is the deepest one.
+        self.entries
+            .iter()
+            .rev()
+            .find(|e| e.byte_range.contains(&offset))
+            .map(|e| e.id)
+    }
+
+    /// Iterate over entries in preorder.
+    pub fn iter(&self) -> impl Iterator<Item = &SourceMapEntry> {
+        self.entries.iter()
+    }
+
+    /// Number of nodes in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the map has no nodes, e.g. it was built for empty input.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Returns spans of the first and the last token in stream.
+///
+/// Group delimiters are used instead of the whole group span, because joining
+/// spans is not supported on stable inside proc-macro.
+pub(crate) fn boundary_spans(tokens: TokenStream) -> (Span, Span) {
+    let mut iter = tokens.into_iter();
+    let Some(first) = iter.next() else {
+        return (Span::call_site(), Span::call_site());
+    };
+    let last = iter.last();
+    let start = match &first {
+        TokenTree::Group(g) => g.span_open(),
+        t => t.span(),
+    };
+    let end = match last.as_ref().unwrap_or(&first) {
+        TokenTree::Group(g) => g.span_close(),
+        t => t.span(),
+    };
+    (start, end)
+}
@@ -0,0 +1,181 @@
+//! Parsing of inline CSS in `style` attribute, available with
+//! `style-attribute` feature.
+//!
+//! Only splitting into declarations is done: property names and values are
+//! not checked, and `!important` is kept as part of the value. Semicolons and
+//! colons inside of quotes and parentheses (e.g. `url("a;b")`) are not treated
+//! as separators.
+//!
+//! ```rust
+//! use rstml::{node::{Node, NodeAttribute}, node};
+//!
+//! let Node::Element(element) = node!(<div style="color: red; margin: 0" />) else {
+//!     unreachable!()
+//! };
+//! let NodeAttribute::Attribute(style) = &element.attributes()[0] else {
+//!     unreachable!()
+//! };
+//! let declarations = style.style_declarations().unwrap().unwrap();
+//! let pairs: Vec<_> = declarations
+//!     .iter()
+//!     .map(|d| (d.property(), d.value()))
+//!     .collect();
+//! assert_eq!(pairs, [("color", "red"), ("margin", "0")]);
+//! assert_eq!(declarations[1].value_range(), 20..21);
+//! ```
+
+use std::ops::Range;
+
+use proc_macro2::Span;
+use syn::{Expr, ExprLit, Lit, LitStr};
+
+use crate::node::{class::literal_subspan, KeyedAttribute};
+
+/// Single `property: value` pair of inline CSS.
+#[derive(Clone, Debug)]
+pub struct StyleDeclaration {
+    property: String,
+    value: String,
+    property_range: Range<usize>,
+    value_range: Range<usize>,
+    property_span: Span,
+    value_span: Span,
+}
+
+impl StyleDeclaration {
+    pub fn property(&self) -> &str {
+        &self.property
+    }
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+    /// Byte offsets of property name in the value of string literal.
+    pub fn property_range(&self) -> Range<usize> {
+        self.property_range.clone()
+    }
+    /// Byte offsets of property value in the value of string literal.
+    pub fn value_range(&self) -> Range<usize> {
+        self.value_range.clone()
+    }
+    /// Span of property name inside of literal.
+    ///
+    /// Spans inside of literal are only available on nightly, or in
+    /// `proc_macro2` fallback mode with `span-locations`, and when literal has
+    /// no escapes. Otherwise span of the whole literal is returned.
+    pub fn property_span(&self) -> Span {
+        self.property_span
+    }
+    /// Span of property value inside of literal, see
+    /// [`property_span`](Self::property_span).
+    pub fn value_span(&self) -> Span {
+        self.value_span
+    }
+}
+
+/// Parse content of `lit` as a list of CSS declarations, in source order.
+///
+/// Empty declarations (`color: red;;`) are skipped. Returns error, if some
+/// declaration has no property name or value.
+pub fn parse_style(lit: &LitStr) -> syn::Result<Vec<StyleDeclaration>> {
+    let value = lit.value();
+    let span_of = |range: Range<usize>| literal_subspan(lit, &value, range);
+
+    let mut declarations = vec![];
+    for (segment, colon) in split_declarations(&value) {
+        let text = &value[segment.clone()];
+        if text.trim().is_empty() {
+            continue;
+        }
+        let Some(colon) = colon else {
+            return Err(syn::Error::new(
+                span_of(trim(&value, segment)),
+                "expected `property: value` declaration",
+            ));
+        };
+        let property_range = trim(&value, segment.start..colon);
+        let value_range = trim(&value, colon + 1..segment.end);
+        if property_range.is_empty() {
+            return Err(syn::Error::new(
+                span_of(trim(&value, segment)),
+                "missing property name",
+            ));
+        }
+        let property = value[property_range.clone()].to_string();
+        if value_range.is_empty() {
+            return Err(syn::Error::new(
+                span_of(property_range),
+                format!("missing value of property `{property}`"),
+            ));
+        }
+        declarations.push(StyleDeclaration {
+            property,
+            value: value[value_range.clone()].to_string(),
+            property_span: span_of(property_range.clone()),
+            value_span: span_of(value_range.clone()),
+            property_range,
+            value_range,
+        });
+    }
+    Ok(declarations)
+}
+
+impl KeyedAttribute {
+    /// Parse value of `style` attribute, see [`parse_style`].
+    ///
+    /// Returns `None` if attribute key is not `style`, or its value is not a
+    /// string literal.
+    pub fn style_declarations(&self) -> Option<syn::Result<Vec<StyleDeclaration>>> {
+        if self.key.to_string() != "style" {
+            return None;
+        }
+        match self.value()? {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(lit), ..
+            }) => Some(parse_style(lit)),
+            _ => None,
+        }
+    }
+}
+
+/// Split `css` by top-level semicolons, returns range of every segment along
+/// with position of its first top-level colon.
+fn split_declarations(css: &str) -> Vec<(Range<usize>, Option<usize>)> {
+    let mut segments = vec![];
+    let mut start = 0;
+    let mut colon = None;
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in css.char_indices() {
+        if let Some(q) = quote {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                _ if c == q => quote = None,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => quote = Some(c),
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            ':' if depth == 0 && colon.is_none() => colon = Some(i),
+            ';' if depth == 0 => {
+                segments.push((start..i, colon.take()));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    segments.push((start..css.len(), colon));
+    segments
+}
+
+/// Shrink `range` of `text` to exclude surrounding whitespace.
+fn trim(text: &str, range: Range<usize>) -> Range<usize> {
+    let part = &text[range.clone()];
+    let start = range.start + (part.len() - part.trim_start().len());
+    let end = range.end - (part.len() - part.trim_end().len());
+    start..end.max(start)
+}
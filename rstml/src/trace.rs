@@ -0,0 +1,21 @@
+//! Events of parsing steps, emitted with [`tracing`] only with `tracing`
+//! feature, and compiled out otherwise.
+//!
+//! [`tracing`]: https://docs.rs/tracing
+
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        ::tracing::trace!(target: "rstml", $($arg)*);
+    };
+}
+
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        ::tracing::debug!(target: "rstml", $($arg)*);
+    };
+}
+
+pub(crate) use debug;
+pub(crate) use trace;
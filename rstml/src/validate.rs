@@ -0,0 +1,1074 @@
+//! HTML5 validation of parsed trees, available with `html-validate` feature.
+//!
+//! [`validate`] walks an already parsed tree and reports:
+//! - elements that are not defined by HTML5 spec,
+//! - unknown attributes on known elements,
+//! - basic content-model violations, e.g. `<li>` outside of a list.
+//!
+//! Elements inside of `<svg>` and `<math>` are checked against SVG and
+//! MathML lists instead, according to their [`Namespace`], names in these
+//! namespaces are case-sensitive. Components (see
+//! [`ElementKind`](crate::node::ElementKind)), custom elements with dashed
+//! names, block names and namespaced attributes (`on:click`, `xlink:href`) are
+//! skipped. Parent requirements are checked only for html elements, and not
+//! for top-level nodes and children of components, because template can be
+//! inserted anywhere. Elements marked with [`Directive::Ignore`] are skipped
+//! along with their children.
+//!
+//! ```rust
+//! use rstml::{
+//!     tree,
+//!     validate::{validate, WarningKind},
+//! };
+//!
+//! let nodes = tree! { <ul><li foo="bar">"item"</li></ul> <li/> <blink/> };
+//! let warnings = validate(&nodes);
+//! assert_eq!(warnings.len(), 2);
+//! assert_eq!(
+//!     warnings[0].to_string(),
+//!     "unknown attribute `foo` on element `li`"
+//! );
+//! assert!(matches!(
+//!     warnings[1].kind(),
+//!     WarningKind::UnknownElement { .. }
+//! ));
+//! ```
+
+use std::fmt;
+
+use proc_macro2::Span;
+use proc_macro2_diagnostics::{Diagnostic, SpanDiagnosticExt};
+use syn::spanned::Spanned;
+
+use crate::node::{CustomNode, Directive, Namespace, Node, NodeAttribute, NodeElement, NodeName};
+
+/// Elements defined by HTML5 spec.
+const ELEMENTS: &[&str] = &[
+    "a",
+    "abbr",
+    "address",
+    "area",
+    "article",
+    "aside",
+    "audio",
+    "b",
+    "base",
+    "bdi",
+    "bdo",
+    "blockquote",
+    "body",
+    "br",
+    "button",
+    "canvas",
+    "caption",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "data",
+    "datalist",
+    "dd",
+    "del",
+    "details",
+    "dfn",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "embed",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "i",
+    "iframe",
+    "img",
+    "input",
+    "ins",
+    "kbd",
+    "label",
+    "legend",
+    "li",
+    "link",
+    "main",
+    "map",
+    "mark",
+    "math",
+    "menu",
+    "meta",
+    "meter",
+    "nav",
+    "noscript",
+    "object",
+    "ol",
+    "optgroup",
+    "option",
+    "output",
+    "p",
+    "picture",
+    "pre",
+    "progress",
+    "q",
+    "rp",
+    "rt",
+    "ruby",
+    "s",
+    "samp",
+    "script",
+    "search",
+    "section",
+    "select",
+    "slot",
+    "small",
+    "source",
+    "span",
+    "strong",
+    "style",
+    "sub",
+    "summary",
+    "sup",
+    "svg",
+    "table",
+    "tbody",
+    "td",
+    "template",
+    "textarea",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "title",
+    "tr",
+    "track",
+    "u",
+    "ul",
+    "var",
+    "video",
+    "wbr",
+];
+
+/// Elements defined by SVG spec.
+const SVG_ELEMENTS: &[&str] = &[
+    "a",
+    "animate",
+    "animateMotion",
+    "animateTransform",
+    "circle",
+    "clipPath",
+    "defs",
+    "desc",
+    "ellipse",
+    "feBlend",
+    "feColorMatrix",
+    "feComponentTransfer",
+    "feComposite",
+    "feConvolveMatrix",
+    "feDiffuseLighting",
+    "feDisplacementMap",
+    "feDistantLight",
+    "feDropShadow",
+    "feFlood",
+    "feFuncA",
+    "feFuncB",
+    "feFuncG",
+    "feFuncR",
+    "feGaussianBlur",
+    "feImage",
+    "feMerge",
+    "feMergeNode",
+    "feMorphology",
+    "feOffset",
+    "fePointLight",
+    "feSpecularLighting",
+    "feSpotLight",
+    "feTile",
+    "feTurbulence",
+    "filter",
+    "foreignObject",
+    "g",
+    "image",
+    "line",
+    "linearGradient",
+    "marker",
+    "mask",
+    "metadata",
+    "mpath",
+    "path",
+    "pattern",
+    "polygon",
+    "polyline",
+    "radialGradient",
+    "rect",
+    "script",
+    "set",
+    "stop",
+    "style",
+    "svg",
+    "switch",
+    "symbol",
+    "text",
+    "textPath",
+    "title",
+    "tspan",
+    "use",
+    "view",
+];
+
+/// Attributes allowed on SVG elements, element specific attributes are not
+/// distinguished.
+const SVG_ATTRIBUTES: &[&str] = &[
+    // Core and presentation attributes.
+    "id",
+    "class",
+    "style",
+    "lang",
+    "tabindex",
+    "xmlns",
+    "role",
+    "alignment-baseline",
+    "baseline-shift",
+    "clip",
+    "clip-path",
+    "clip-rule",
+    "color",
+    "color-interpolation",
+    "color-interpolation-filters",
+    "cursor",
+    "d",
+    "direction",
+    "display",
+    "dominant-baseline",
+    "fill",
+    "fill-opacity",
+    "fill-rule",
+    "filter",
+    "flood-color",
+    "flood-opacity",
+    "font-family",
+    "font-size",
+    "font-size-adjust",
+    "font-stretch",
+    "font-style",
+    "font-variant",
+    "font-weight",
+    "image-rendering",
+    "letter-spacing",
+    "lighting-color",
+    "marker-end",
+    "marker-mid",
+    "marker-start",
+    "mask",
+    "mask-type",
+    "opacity",
+    "overflow",
+    "paint-order",
+    "pointer-events",
+    "shape-rendering",
+    "stop-color",
+    "stop-opacity",
+    "stroke",
+    "stroke-dasharray",
+    "stroke-dashoffset",
+    "stroke-linecap",
+    "stroke-linejoin",
+    "stroke-miterlimit",
+    "stroke-opacity",
+    "stroke-width",
+    "text-anchor",
+    "text-decoration",
+    "text-overflow",
+    "text-rendering",
+    "transform",
+    "transform-origin",
+    "unicode-bidi",
+    "vector-effect",
+    "visibility",
+    "white-space",
+    "word-spacing",
+    "writing-mode",
+    // Geometry and element specific attributes.
+    "cx",
+    "cy",
+    "r",
+    "rx",
+    "ry",
+    "x",
+    "y",
+    "x1",
+    "y1",
+    "x2",
+    "y2",
+    "z",
+    "width",
+    "height",
+    "points",
+    "pathLength",
+    "viewBox",
+    "preserveAspectRatio",
+    "href",
+    "fx",
+    "fy",
+    "fr",
+    "offset",
+    "gradientUnits",
+    "gradientTransform",
+    "spreadMethod",
+    "patternUnits",
+    "patternContentUnits",
+    "patternTransform",
+    "clipPathUnits",
+    "maskUnits",
+    "maskContentUnits",
+    "markerUnits",
+    "markerWidth",
+    "markerHeight",
+    "refX",
+    "refY",
+    "orient",
+    "dx",
+    "dy",
+    "rotate",
+    "textLength",
+    "lengthAdjust",
+    "startOffset",
+    "method",
+    "spacing",
+    "side",
+    "path",
+    "filterUnits",
+    "primitiveUnits",
+    "in",
+    "in2",
+    "result",
+    "stdDeviation",
+    "edgeMode",
+    "mode",
+    "operator",
+    "k1",
+    "k2",
+    "k3",
+    "k4",
+    "values",
+    "type",
+    "tableValues",
+    "slope",
+    "intercept",
+    "amplitude",
+    "exponent",
+    "kernelMatrix",
+    "order",
+    "divisor",
+    "bias",
+    "targetX",
+    "targetY",
+    "preserveAlpha",
+    "kernelUnitLength",
+    "surfaceScale",
+    "diffuseConstant",
+    "specularConstant",
+    "specularExponent",
+    "scale",
+    "xChannelSelector",
+    "yChannelSelector",
+    "azimuth",
+    "elevation",
+    "pointsAtX",
+    "pointsAtY",
+    "pointsAtZ",
+    "limitingConeAngle",
+    "radius",
+    "baseFrequency",
+    "numOctaves",
+    "seed",
+    "stitchTiles",
+    "crossorigin",
+    "decoding",
+    "media",
+    "requiredExtensions",
+    "systemLanguage",
+    "version",
+    // Animation attributes.
+    "attributeName",
+    "begin",
+    "dur",
+    "end",
+    "min",
+    "max",
+    "restart",
+    "repeatCount",
+    "repeatDur",
+    "calcMode",
+    "keyTimes",
+    "keySplines",
+    "keyPoints",
+    "from",
+    "to",
+    "by",
+    "additive",
+    "accumulate",
+];
+
+/// Elements defined by MathML Core spec.
+const MATHML_ELEMENTS: &[&str] = &[
+    "math",
+    "annotation",
+    "maction",
+    "menclose",
+    "merror",
+    "mfrac",
+    "mi",
+    "mmultiscripts",
+    "mn",
+    "mo",
+    "mover",
+    "mpadded",
+    "mphantom",
+    "mprescripts",
+    "mroot",
+    "mrow",
+    "ms",
+    "mspace",
+    "msqrt",
+    "mstyle",
+    "msub",
+    "msubsup",
+    "msup",
+    "mtable",
+    "mtd",
+    "mtext",
+    "mtr",
+    "munder",
+    "munderover",
+    "none",
+    "semantics",
+];
+
+/// Attributes allowed on MathML elements, element specific attributes are not
+/// distinguished.
+const MATHML_ATTRIBUTES: &[&str] = &[
+    "id",
+    "class",
+    "style",
+    "dir",
+    "href",
+    "nonce",
+    "tabindex",
+    "autofocus",
+    "displaystyle",
+    "mathbackground",
+    "mathcolor",
+    "mathsize",
+    "mathvariant",
+    "scriptlevel",
+    "display",
+    "alttext",
+    "accent",
+    "accentunder",
+    "fence",
+    "form",
+    "largeop",
+    "lspace",
+    "rspace",
+    "maxsize",
+    "minsize",
+    "movablelimits",
+    "separator",
+    "stretchy",
+    "symmetric",
+    "linethickness",
+    "numalign",
+    "denomalign",
+    "bevelled",
+    "depth",
+    "height",
+    "width",
+    "voffset",
+    "lquote",
+    "rquote",
+    "columnalign",
+    "columnlines",
+    "columnspacing",
+    "columnspan",
+    "rowalign",
+    "rowlines",
+    "rowspacing",
+    "rowspan",
+    "frame",
+    "framespacing",
+    "notation",
+    "actiontype",
+    "selection",
+    "encoding",
+    "definitionURL",
+];
+
+/// Attributes allowed on every html element.
+const GLOBAL_ATTRIBUTES: &[&str] = &[
+    "accesskey",
+    "autocapitalize",
+    "autocorrect",
+    "autofocus",
+    "class",
+    "contenteditable",
+    "dir",
+    "draggable",
+    "enterkeyhint",
+    "hidden",
+    "id",
+    "inert",
+    "inputmode",
+    "is",
+    "itemid",
+    "itemprop",
+    "itemref",
+    "itemscope",
+    "itemtype",
+    "lang",
+    "nonce",
+    "part",
+    "popover",
+    "role",
+    "slot",
+    "spellcheck",
+    "style",
+    "tabindex",
+    "title",
+    "translate",
+    "writingsuggestions",
+    "xmlns",
+];
+
+/// Prefixes of attribute names allowed on every element.
+const GLOBAL_ATTRIBUTE_PREFIXES: &[&str] = &["data-", "aria-", "on"];
+
+/// Element specific attributes.
+const ELEMENT_ATTRIBUTES: &[(&str, &[&str])] = &[
+    (
+        "a",
+        &[
+            "href",
+            "target",
+            "download",
+            "ping",
+            "rel",
+            "hreflang",
+            "type",
+            "referrerpolicy",
+        ],
+    ),
+    (
+        "area",
+        &[
+            "alt",
+            "coords",
+            "shape",
+            "href",
+            "target",
+            "download",
+            "ping",
+            "rel",
+            "referrerpolicy",
+        ],
+    ),
+    (
+        "audio",
+        &[
+            "src",
+            "crossorigin",
+            "preload",
+            "autoplay",
+            "loop",
+            "muted",
+            "controls",
+        ],
+    ),
+    ("base", &["href", "target"]),
+    ("blockquote", &["cite"]),
+    (
+        "button",
+        &[
+            "command",
+            "commandfor",
+            "disabled",
+            "form",
+            "formaction",
+            "formenctype",
+            "formmethod",
+            "formnovalidate",
+            "formtarget",
+            "name",
+            "popovertarget",
+            "popovertargetaction",
+            "type",
+            "value",
+        ],
+    ),
+    ("canvas", &["width", "height"]),
+    ("col", &["span"]),
+    ("colgroup", &["span"]),
+    ("data", &["value"]),
+    ("del", &["cite", "datetime"]),
+    ("details", &["open", "name"]),
+    ("dialog", &["open"]),
+    ("embed", &["src", "type", "width", "height"]),
+    ("fieldset", &["disabled", "form", "name"]),
+    (
+        "form",
+        &[
+            "accept-charset",
+            "action",
+            "autocomplete",
+            "enctype",
+            "method",
+            "name",
+            "novalidate",
+            "rel",
+            "target",
+        ],
+    ),
+    (
+        "iframe",
+        &[
+            "src",
+            "srcdoc",
+            "name",
+            "sandbox",
+            "allow",
+            "allowfullscreen",
+            "width",
+            "height",
+            "referrerpolicy",
+            "loading",
+        ],
+    ),
+    (
+        "img",
+        &[
+            "alt",
+            "src",
+            "srcset",
+            "sizes",
+            "crossorigin",
+            "usemap",
+            "ismap",
+            "width",
+            "height",
+            "referrerpolicy",
+            "decoding",
+            "loading",
+            "fetchpriority",
+        ],
+    ),
+    (
+        "input",
+        &[
+            "accept",
+            "alpha",
+            "alt",
+            "autocomplete",
+            "checked",
+            "colorspace",
+            "dirname",
+            "disabled",
+            "form",
+            "formaction",
+            "formenctype",
+            "formmethod",
+            "formnovalidate",
+            "formtarget",
+            "height",
+            "list",
+            "max",
+            "maxlength",
+            "min",
+            "minlength",
+            "multiple",
+            "name",
+            "pattern",
+            "placeholder",
+            "popovertarget",
+            "popovertargetaction",
+            "readonly",
+            "required",
+            "size",
+            "src",
+            "step",
+            "type",
+            "value",
+            "width",
+        ],
+    ),
+    ("ins", &["cite", "datetime"]),
+    ("label", &["for"]),
+    ("li", &["value"]),
+    (
+        "link",
+        &[
+            "href",
+            "crossorigin",
+            "rel",
+            "as",
+            "media",
+            "hreflang",
+            "type",
+            "sizes",
+            "imagesrcset",
+            "imagesizes",
+            "referrerpolicy",
+            "integrity",
+            "blocking",
+            "color",
+            "disabled",
+            "fetchpriority",
+        ],
+    ),
+    ("map", &["name"]),
+    (
+        "meta",
+        &["name", "http-equiv", "content", "charset", "media"],
+    ),
+    ("meter", &["value", "min", "max", "low", "high", "optimum"]),
+    (
+        "object",
+        &["data", "type", "name", "form", "width", "height"],
+    ),
+    ("ol", &["reversed", "start", "type"]),
+    ("optgroup", &["disabled", "label"]),
+    ("option", &["disabled", "label", "selected", "value"]),
+    ("output", &["for", "form", "name"]),
+    ("progress", &["value", "max"]),
+    ("q", &["cite"]),
+    (
+        "script",
+        &[
+            "src",
+            "type",
+            "nomodule",
+            "async",
+            "defer",
+            "crossorigin",
+            "integrity",
+            "referrerpolicy",
+            "blocking",
+            "fetchpriority",
+        ],
+    ),
+    (
+        "select",
+        &[
+            "autocomplete",
+            "disabled",
+            "form",
+            "multiple",
+            "name",
+            "required",
+            "size",
+        ],
+    ),
+    ("slot", &["name"]),
+    (
+        "source",
+        &["type", "media", "src", "srcset", "sizes", "width", "height"],
+    ),
+    ("style", &["media", "blocking"]),
+    ("td", &["colspan", "rowspan", "headers"]),
+    (
+        "template",
+        &[
+            "shadowrootmode",
+            "shadowrootdelegatesfocus",
+            "shadowrootclonable",
+            "shadowrootserializable",
+        ],
+    ),
+    (
+        "textarea",
+        &[
+            "autocomplete",
+            "cols",
+            "dirname",
+            "disabled",
+            "form",
+            "maxlength",
+            "minlength",
+            "name",
+            "placeholder",
+            "readonly",
+            "required",
+            "rows",
+            "wrap",
+        ],
+    ),
+    ("th", &["colspan", "rowspan", "headers", "scope", "abbr"]),
+    ("time", &["datetime"]),
+    ("track", &["default", "kind", "label", "src", "srclang"]),
+    (
+        "video",
+        &[
+            "src",
+            "crossorigin",
+            "poster",
+            "preload",
+            "autoplay",
+            "playsinline",
+            "loop",
+            "muted",
+            "controls",
+            "width",
+            "height",
+        ],
+    ),
+];
+
+/// Elements that are only allowed inside of specific parents.
+const ALLOWED_PARENTS: &[(&str, &[&str])] = &[
+    ("li", &["ul", "ol", "menu"]),
+    ("dt", &["dl", "div"]),
+    ("dd", &["dl", "div"]),
+    ("tr", &["table", "thead", "tbody", "tfoot"]),
+    ("td", &["tr"]),
+    ("th", &["tr"]),
+    ("thead", &["table"]),
+    ("tbody", &["table"]),
+    ("tfoot", &["table"]),
+    ("caption", &["table"]),
+    ("colgroup", &["table"]),
+    ("col", &["colgroup", "table"]),
+    ("option", &["select", "datalist", "optgroup"]),
+    ("optgroup", &["select"]),
+    ("legend", &["fieldset"]),
+    ("figcaption", &["figure"]),
+    ("summary", &["details"]),
+    ("source", &["audio", "video", "picture"]),
+    ("track", &["audio", "video"]),
+    ("rt", &["ruby"]),
+    ("rp", &["ruby"]),
+];
+
+/// Kind of problem found by [`validate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WarningKind {
+    /// Element is not defined by spec of its namespace.
+    UnknownElement {
+        element: String,
+        namespace: Namespace,
+    },
+    /// Attribute is not allowed on this element.
+    UnknownAttribute { element: String, attribute: String },
+    /// Element is placed inside of parent that can't contain it.
+    InvalidParent {
+        element: String,
+        parent: String,
+        expected: &'static [&'static str],
+    },
+}
+
+/// Single warning produced by [`validate`].
+#[derive(Clone, Debug)]
+pub struct ValidationWarning {
+    span: Span,
+    kind: WarningKind,
+}
+
+impl ValidationWarning {
+    /// Span of element name or attribute key.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+    pub fn kind(&self) -> &WarningKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for ValidationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            WarningKind::UnknownElement { element, namespace } => {
+                let namespace = match namespace {
+                    Namespace::Html => "html",
+                    Namespace::Svg => "svg",
+                    Namespace::MathMl => "mathml",
+                };
+                write!(f, "unknown {namespace} element `{element}`")
+            }
+            WarningKind::UnknownAttribute { element, attribute } => {
+                write!(f, "unknown attribute `{attribute}` on element `{element}`")
+            }
+            WarningKind::InvalidParent {
+                element,
+                parent,
+                expected,
+            } => {
+                let expected = expected
+                    .iter()
+                    .map(|e| format!("`{e}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(
+                    f,
+                    "element `{element}` is not allowed inside of `{parent}`, expected one of: \
+                     {expected}"
+                )
+            }
+        }
+    }
+}
+
+impl From<ValidationWarning> for Diagnostic {
+    fn from(warning: ValidationWarning) -> Self {
+        warning.span.warning(warning.to_string())
+    }
+}
+
+/// Check `nodes` against HTML5 spec, see [module documentation](self).
+///
+/// Warnings are returned in document order.
+pub fn validate<C: CustomNode>(nodes: &[Node<C>]) -> Vec<ValidationWarning> {
+    let mut warnings = vec![];
+    validate_nodes(nodes, Parent::Unknown, &mut warnings);
+    warnings
+}
+
+/// Closest element that contains validated nodes.
+#[derive(Clone, Copy)]
+enum Parent<'a> {
+    /// Top-level or inside of component, anything is allowed.
+    Unknown,
+    Element(&'a str),
+}
+
+fn validate_nodes<C: CustomNode>(
+    nodes: &[Node<C>],
+    parent: Parent,
+    warnings: &mut Vec<ValidationWarning>,
+) {
+    for node in nodes {
+        match node {
+            Node::Element(element) if element.directives.contains(&Directive::Ignore) => {}
+            Node::Element(element) => validate_element(element, parent, warnings),
+            // Fragments are transparent for content model.
+            Node::Fragment(fragment) => validate_nodes(&fragment.children, parent, warnings),
+            _ => {}
+        }
+    }
+}
+
+fn validate_element<C: CustomNode>(
+    element: &NodeElement<C>,
+    parent: Parent,
+    warnings: &mut Vec<ValidationWarning>,
+) {
+    let Some(name) = element_name(element) else {
+        validate_nodes(&element.children, Parent::Unknown, warnings);
+        return;
+    };
+    let namespace = element.namespace;
+    let known_elements = match namespace {
+        Namespace::Html => ELEMENTS,
+        Namespace::Svg => SVG_ELEMENTS,
+        Namespace::MathMl => MATHML_ELEMENTS,
+    };
+    if !known_elements.contains(&name.as_str()) {
+        warnings.push(ValidationWarning {
+            span: element.name().span(),
+            kind: WarningKind::UnknownElement {
+                element: name,
+                namespace,
+            },
+        });
+        validate_nodes(&element.children, Parent::Unknown, warnings);
+        return;
+    }
+
+    if let (Namespace::Html, Parent::Element(parent)) = (namespace, parent) {
+        let expected = ALLOWED_PARENTS
+            .iter()
+            .find(|(child, _)| *child == name)
+            .map(|(_, parents)| *parents);
+        if let Some(expected) = expected.filter(|e| !e.contains(&parent)) {
+            warnings.push(ValidationWarning {
+                span: element.name().span(),
+                kind: WarningKind::InvalidParent {
+                    element: name.clone(),
+                    parent: parent.to_string(),
+                    expected,
+                },
+            });
+        }
+    }
+
+    for attribute in element.all_attributes() {
+        let NodeAttribute::Attribute(attribute) = &*attribute else {
+            continue;
+        };
+        if attribute.key.is_block() {
+            continue;
+        }
+        let mut key = attribute.key.to_string();
+        if namespace == Namespace::Html {
+            key.make_ascii_lowercase();
+        }
+        if key.contains(':') || is_known_attribute(namespace, &name, &key) {
+            continue;
+        }
+        warnings.push(ValidationWarning {
+            span: attribute.key.span(),
+            kind: WarningKind::UnknownAttribute {
+                element: name.clone(),
+                attribute: key,
+            },
+        });
+    }
+
+    validate_nodes(&element.children, Parent::Element(&name), warnings);
+}
+
+/// Name of element, if it should be validated, html names are lowercased.
+fn element_name<C: CustomNode>(element: &NodeElement<C>) -> Option<String> {
+    if element.is_component() {
+        return None;
+    }
+    match element.name() {
+        NodeName::Path(path) => path.path.get_ident().map(|ident| {
+            let mut name = ident.to_string();
+            if element.namespace == Namespace::Html {
+                name.make_ascii_lowercase();
+            }
+            name
+        }),
+        // Dashed names are custom elements, other punctuated names and blocks
+        // are not html.
+        NodeName::Punctuated(_) | NodeName::Block(_) => None,
+    }
+}
+
+fn is_known_attribute(namespace: Namespace, element: &str, key: &str) -> bool {
+    let has_global_prefix = GLOBAL_ATTRIBUTE_PREFIXES
+        .iter()
+        .any(|prefix| key.len() > prefix.len() && key.starts_with(prefix));
+    has_global_prefix
+        || match namespace {
+            Namespace::Html => {
+                GLOBAL_ATTRIBUTES.contains(&key)
+                    || ELEMENT_ATTRIBUTES
+                        .iter()
+                        .find(|(e, _)| *e == element)
+                        .is_some_and(|(_, attributes)| attributes.contains(&key))
+            }
+            Namespace::Svg => SVG_ATTRIBUTES.contains(&key),
+            Namespace::MathMl => MATHML_ATTRIBUTES.contains(&key),
+        }
+}
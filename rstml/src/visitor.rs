@@ -2,7 +2,7 @@ use std::marker::PhantomData;
 
 use super::node::*;
 use crate::{
-    atoms::{CloseTag, OpenTag},
+    atoms::{CloseTag, OpenTag, PartialOpenTag},
     Infallible,
 };
 
@@ -53,6 +53,9 @@ pub trait Visitor<Custom> {
     fn visit_fragment(&mut self, _node: &mut NodeFragment<Custom>) -> bool {
         true
     }
+    fn visit_incomplete(&mut self, _node: &mut PartialOpenTag) -> bool {
+        true
+    }
 
     // Visit atoms
     fn visit_rust_code(&mut self, _code: RustCode) -> bool {
@@ -253,6 +256,7 @@ where
             Node::Text(t) => self.visit_text_node(t),
             Node::RawText(r) => self.visit_raw_node(r),
             Node::Custom(c) => self.visit_custom(c),
+            Node::Incomplete(i) => self.visit_incomplete(i),
         }
     }
     fn visit_block(&mut self, node: &mut NodeBlock) -> bool {
@@ -314,6 +318,17 @@ where
         true
     }
 
+    fn visit_incomplete(&mut self, node: &mut PartialOpenTag) -> bool {
+        visit_inner!(self.visitor.visit_incomplete(node));
+
+        try_visit!(self.visit_node_name(&mut node.name));
+
+        for attribute in node.attributes.iter_mut() {
+            try_visit!(self.visit_attribute(attribute))
+        }
+        true
+    }
+
     fn visit_open_tag(&mut self, open_tag: &mut OpenTag) -> bool {
         visit_inner!(self.visitor.visit_open_tag(open_tag));
 
@@ -341,7 +356,7 @@ where
         visit_inner!(self.visitor.visit_keyed_attribute(attribute));
 
         match &mut attribute.possible_value {
-            KeyedAttributeValue::None => self.visit_attribute_flag(&mut attribute.key),
+            KeyedAttributeValue::Flag => self.visit_attribute_flag(&mut attribute.key),
             KeyedAttributeValue::Binding(b) => self.visit_attribute_binding(&mut attribute.key, b),
             KeyedAttributeValue::Value(v) => self.visit_attribute_value(&mut attribute.key, v),
         }
@@ -369,6 +384,7 @@ where
         match &mut value.value {
             KVAttributeValue::Expr(expr) => self.visit_rust_code(RustCode::Expr(expr)),
             KVAttributeValue::InvalidBraced(braced) => self.visit_invalid_block(braced),
+            KVAttributeValue::Incomplete => true,
         }
     }
 
@@ -1,11 +1,11 @@
-use quote::{quote, TokenStreamExt};
+use quote::{quote, ToTokens, TokenStreamExt};
 use rstml::{
     atoms::{self, OpenTag, OpenTagEnd},
-    node::{CustomNode, Node, NodeElement},
-    recoverable::{ParseRecoverable, Recoverable},
+    node::{CustomNode, Namespace, Node, NodeElement},
+    recoverable::{ParseRecoverable, Recoverable, RecoverableContext},
     Parser, ParserConfig,
 };
-use syn::{parse_quote, Expr, Token};
+use syn::{parse::ParseStream, parse_quote, Expr, Token};
 
 #[derive(Debug, syn_derive::ToTokens)]
 struct If {
@@ -96,3 +96,50 @@ fn custom_node_using_config() {
 
     assert_eq!(actual.condition, parse_quote!(just && an || expression));
 }
+
+/// `@`, that records parser state at its position.
+#[derive(Debug)]
+struct Probe {
+    token: Token![@],
+    namespace: Namespace,
+    errors_before: usize,
+}
+impl ParseRecoverable for Probe {
+    fn parse_recoverable(parser: &mut RecoverableContext, input: ParseStream) -> Option<Self> {
+        let token = parser.parse_simple(input)?;
+        Some(Self {
+            token,
+            namespace: parser.namespace(),
+            errors_before: parser.diagnostics().len(),
+        })
+    }
+}
+impl ToTokens for Probe {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        self.token.to_tokens(tokens)
+    }
+}
+impl CustomNode for Probe {
+    fn peek_element(input: ParseStream) -> bool {
+        input.peek(Token![@])
+    }
+}
+
+#[test]
+fn custom_node_context() {
+    let (nodes, errors) = Parser::new(ParserConfig::new().custom_node::<Probe>())
+        .parse_recoverable(quote! {
+            <div x=></div>
+            <svg>@</svg>
+        })
+        .split_vec();
+    assert_eq!(errors.len(), 1);
+    let Node::Element(svg) = &nodes[1] else {
+        panic!()
+    };
+    let Node::Custom(probe) = &svg.children[0] else {
+        panic!()
+    };
+    assert_eq!(probe.namespace, Namespace::Svg);
+    assert_eq!(probe.errors_before, 1);
+}
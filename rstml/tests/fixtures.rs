@@ -0,0 +1,109 @@
+//! Conformance fixtures.
+//!
+//! Every `tests/fixtures/valid/*.rsx` template should parse without
+//! diagnostics into the tree from `.tree` file next to it (rendered with
+//! [`Node::to_debug_tree`]). Every `tests/fixtures/invalid/*.rsx` template
+//! should report error codes from `.errors` file, one per line, and recover
+//! into the tree from `.tree` file.
+//!
+//! Run with `RSTML_BLESS=1` to overwrite expected files with actual output.
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use proc_macro2::TokenStream;
+use proc_macro2_diagnostics::{Diagnostic, Level};
+use rstml::{node::Node, Infallible, Parser, ParserConfig, ParserError};
+
+fn parser() -> Parser<Infallible> {
+    let void_elements: HashSet<_> = [
+        "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source",
+        "track", "wbr",
+    ]
+    .into();
+    Parser::new(
+        ParserConfig::new()
+            .recover_block(true)
+            .always_self_closed_elements(void_elements)
+            .raw_text_elements(["script", "style"].into()),
+    )
+}
+
+/// Templates of fixture directory, sorted by name.
+fn templates(dir: &str) -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(dir);
+    let mut templates: Vec<_> = fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("failed to read {}: {e}", dir.display()))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rsx"))
+        .collect();
+    templates.sort();
+    assert!(!templates.is_empty(), "no fixtures in {}", dir.display());
+    templates
+}
+
+/// Code of diagnostic, `-` for errors without code (e.g. reported by `syn`).
+/// Warnings without code are skipped.
+fn code(diagnostic: &Diagnostic) -> Option<&'static str> {
+    ParserError::code_of(diagnostic).or((diagnostic.level() == Level::Error).then_some("-"))
+}
+
+/// Compare `actual` output with expected file, or overwrite it in bless mode.
+fn check(path: &Path, extension: &str, actual: &str, failures: &mut Vec<String>) {
+    let expected_path = path.with_extension(extension);
+    if std::env::var_os("RSTML_BLESS").is_some() {
+        fs::write(&expected_path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&expected_path).unwrap_or_default();
+    if expected != actual {
+        failures.push(format!(
+            "{}:\n--- expected\n{expected}--- actual\n{actual}",
+            expected_path.display()
+        ));
+    }
+}
+
+/// Parse every template of `dir`, and return rendered tree and error codes.
+fn run(dir: &str, mut f: impl FnMut(&Path, String, Vec<&'static str>, &mut Vec<String>)) {
+    let parser = parser();
+    let mut failures = vec![];
+    for path in templates(dir) {
+        let source = fs::read_to_string(&path).unwrap();
+        let tokens = TokenStream::from_str(&source)
+            .unwrap_or_else(|e| panic!("failed to tokenize {}: {e}", path.display()));
+        let (nodes, diagnostics) = parser.parse_recoverable(tokens).split_vec();
+        let tree = nodes.iter().map(Node::to_debug_tree).collect();
+        let codes = diagnostics.iter().filter_map(code).collect();
+        f(&path, tree, codes, &mut failures);
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn test_valid_fixtures() {
+    run("valid", |path, tree, codes, failures| {
+        if !codes.is_empty() {
+            failures.push(format!("{}: unexpected errors {codes:?}", path.display()));
+        }
+        check(path, "tree", &tree, failures);
+    });
+}
+
+#[test]
+fn test_invalid_fixtures() {
+    run("invalid", |path, tree, codes, failures| {
+        if codes.is_empty() {
+            failures.push(format!("{}: no errors reported", path.display()));
+        }
+        let codes: String = codes.iter().map(|code| format!("{code}\n")).collect();
+        check(path, "errors", &codes, failures);
+        check(path, "tree", &tree, failures);
+    });
+}
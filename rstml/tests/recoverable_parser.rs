@@ -2,13 +2,23 @@ use std::{convert::TryFrom, str::FromStr};
 
 use eyre::Result;
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{quote, ToTokens};
 use rstml::{
-    node::{Node, NodeAttribute, NodeBlock},
+    atoms::{OpenTag, PartialOpenTag},
+    node::{
+        AttributeValueExpr, KVAttributeValue, KeyedAttributeValue, Node, NodeAttribute, NodeBlock,
+    },
+    recoverable::Recoverable,
     Parser, ParserConfig,
 };
 use syn::Block;
 
+/// Message of diagnostic, without the error code note.
+fn message(error: impl Into<syn::Error>) -> String {
+    let error = error.into().to_string();
+    error.lines().next().unwrap_or_default().to_string()
+}
+
 #[test]
 fn test_recover_incorrect_closing_tags() {
     let stream = quote!(<div><open></close><foo></foo></div>);
@@ -77,6 +87,44 @@ fn test_parse_invalid_attr_block() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_parse_invalid_attr_value_block() -> Result<()> {
+    let tokens = TokenStream::from_str("<foo a={x.} b=1 />").unwrap();
+
+    let config = ParserConfig::new();
+    let (nodes, errors) = Parser::new(config)
+        .parse_recoverable(tokens.clone())
+        .split_vec();
+    // Invalid block is reported once, and next attribute is kept untouched.
+    assert_eq!(errors.len(), 1);
+    let Node::Element(f) = &nodes[0] else {
+        panic!("expected element")
+    };
+    let [NodeAttribute::Attribute(b)] = f.attributes() else {
+        panic!("expected single attribute")
+    };
+    assert_eq!(b.key.to_string(), "b");
+
+    let config = ParserConfig::new().recover_block(true);
+    let (nodes, errors) = Parser::new(config).parse_recoverable(tokens).split_vec();
+    assert_eq!(errors.len(), 1);
+    let Node::Element(f) = &nodes[0] else {
+        panic!("expected element")
+    };
+    let NodeAttribute::Attribute(a) = &f.attributes()[0] else {
+        panic!("expected attribute")
+    };
+    let KeyedAttributeValue::Value(AttributeValueExpr {
+        value: KVAttributeValue::InvalidBraced(_),
+        ..
+    }) = &a.possible_value
+    else {
+        panic!("expected invalid block value")
+    };
+    assert_eq!(f.attributes().len(), 2);
+    Ok(())
+}
+
 #[test]
 fn test_parse_closed_tag_without_open() -> Result<()> {
     let tokens = TokenStream::from_str("</foo>").unwrap();
@@ -84,11 +132,8 @@ fn test_parse_closed_tag_without_open() -> Result<()> {
     let (nodes, errors) = Parser::new(config).parse_recoverable(tokens).split_vec();
 
     assert!(!errors.is_empty());
-
-    let Node::Element(f) = &nodes[0] else {
-        panic!("expected element")
-    };
-    assert_eq!(f.open_tag.name.to_string(), "foo");
+    // Close tag is skipped.
+    assert!(nodes.is_empty());
     Ok(())
 }
 
@@ -113,3 +158,582 @@ fn test_parse_open_tag_without_close() -> Result<()> {
 }
 
 // TODO: keyed attribute
+
+#[test]
+fn test_unparsed_group_tokens_do_not_panic() {
+    // Inputs found by fuzzing, each of them ended in a panic.
+    let inputs = [
+        "<a const (x = 1)>",
+        "<a b(, )>",
+        "{ [ {x} move ;] }",
+        "+ { -1 [ -1 (x) \"s\" ] }",
+    ];
+    for input in inputs {
+        let tokens = TokenStream::from_str(input).unwrap();
+        let config = ParserConfig::new().recover_block(true);
+        let _ = Parser::new(config).parse_recoverable(tokens.clone());
+        assert!(rstml::parse2(tokens).is_err(), "{input}");
+    }
+}
+
+#[test]
+fn test_parenthesized_pattern_in_binding() {
+    let tokens = TokenStream::from_str("<div foo(#[a] (x))/>").unwrap();
+    assert!(rstml::parse2(tokens).is_ok());
+}
+
+#[test]
+fn test_unparsed_group_tokens_keep_nodes() {
+    let tokens = TokenStream::from_str("<a b(, )></a><c/>").unwrap();
+    let config = ParserConfig::new().recover_block(true);
+    let (nodes, errors) = Parser::new(config).parse_recoverable(tokens).split_vec();
+    assert!(!errors.is_empty());
+    assert_eq!(nodes.len(), 2);
+}
+
+#[test]
+fn test_invalid_attribute_key_error_span() {
+    let source = "<div a data-></div>";
+    let tokens = TokenStream::from_str(source).unwrap();
+    let error = rstml::parse2(tokens).unwrap_err();
+    // Points to the end of tag, instead of call site.
+    assert_eq!(error.span().byte_range(), 12..13);
+    assert_eq!(error.to_string(), "expected punctuated node name");
+}
+
+#[test]
+fn test_attribute_value_bounded_by_tag_end() {
+    let tokens = TokenStream::from_str(r#"<div a=x b={y > 1} c=1 d=e>"z"</div>"#).unwrap();
+    let nodes = rstml::parse2(tokens).unwrap();
+    let Node::Element(div) = &nodes[0] else {
+        panic!("expected element")
+    };
+    let keys: Vec<_> = div
+        .attributes()
+        .iter()
+        .map(|a| a.to_token_stream().to_string())
+        .collect();
+    assert_eq!(keys, ["a = x", "b = { y > 1 }", "c = 1", "d = e"]);
+    assert_eq!(div.children.len(), 1);
+}
+
+#[test]
+fn test_parse_partial_open_tag() -> Result<()> {
+    let tag = syn::parse2::<Recoverable<PartialOpenTag>>(quote! { <div class="x" id={y} })?.inner();
+    assert!(!tag.is_complete());
+    assert_eq!(tag.name.to_string(), "div");
+    assert_eq!(tag.attributes.len(), 2);
+    assert!(OpenTag::try_from(tag).is_err());
+
+    let tag = syn::parse2::<Recoverable<PartialOpenTag>>(quote! { <input checked/> })?.inner();
+    let tag = OpenTag::try_from(tag).unwrap();
+    assert!(tag.is_self_closed());
+
+    assert!(syn::parse2::<Recoverable<OpenTag>>(quote! { <div class="x" }).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_recover_incomplete_input() {
+    let config = ParserConfig::new().recover_incomplete(true);
+    let parser = Parser::new(config);
+
+    let (nodes, errors) = parser.parse_recoverable(quote!(<ul><li cla)).split_vec();
+    assert!(!errors.is_empty());
+    let Node::Element(ul) = &nodes[0] else {
+        panic!("expected element")
+    };
+    assert!(ul.close_tag.is_none());
+    let Node::Incomplete(li) = &ul.children[0] else {
+        panic!("expected incomplete node")
+    };
+    assert_eq!(li.name.to_string(), "li");
+    assert_eq!(li.attributes[0].to_token_stream().to_string(), "cla");
+
+    let (nodes, errors) = parser.parse_recoverable(quote!(<div class=>)).split_vec();
+    assert_eq!(errors.len(), 2);
+    let Node::Element(div) = &nodes[0] else {
+        panic!("expected element")
+    };
+    let NodeAttribute::Attribute(attribute) = &div.attributes()[0] else {
+        panic!("expected attribute")
+    };
+    assert!(matches!(
+        &attribute.possible_value,
+        KeyedAttributeValue::Value(AttributeValueExpr {
+            value: KVAttributeValue::Incomplete,
+            ..
+        })
+    ));
+
+    // Without the flag, unfinished open tag is dropped.
+    let (nodes, _) = Parser::new(ParserConfig::new())
+        .parse_recoverable(quote!(<ul><li cla))
+        .split_vec();
+    let Node::Element(ul) = &nodes[0] else {
+        panic!("expected element")
+    };
+    assert!(ul.children.is_empty());
+}
+
+#[test]
+fn test_dangling_attribute_eq() {
+    let (nodes, errors) = Parser::new(ParserConfig::new())
+        .parse_recoverable(quote!(<div id="a" class=><span/></div>))
+        .split_vec();
+    let errors: Vec<_> = errors.into_iter().map(message).collect();
+    assert_eq!(errors, ["expected attribute value after `=`"]);
+    let Node::Element(div) = &nodes[0] else {
+        panic!("expected element")
+    };
+    assert_eq!(div.attributes().len(), 1);
+    assert_eq!(div.children.len(), 1);
+}
+
+#[test]
+fn test_on_element_closed() {
+    let closed = std::rc::Rc::new(std::cell::RefCell::new(vec![]));
+    let log = closed.clone();
+    let config = ParserConfig::new()
+        .always_self_closed_elements(["br"].into())
+        .on_element_closed(move |element| {
+            let name = element.name().to_string();
+            log.borrow_mut().push(name.clone());
+            if name == "bad" {
+                return Err(syn::Error::new_spanned(element.name(), "bad element"));
+            }
+            // Rewrite: drop all children of `erase`.
+            if name == "erase" {
+                element.children.clear();
+            }
+            Ok(())
+        });
+    let tokens = quote!(<div><br><erase>"a"<b/></erase><bad/></div>);
+    let (nodes, diagnostics) = Parser::new(config).parse_recoverable(tokens).split_vec();
+
+    assert_eq!(*closed.borrow(), ["br", "b", "erase", "bad", "div"]);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        syn::Error::from(diagnostics[0].clone()).to_string(),
+        "bad element"
+    );
+    // Element with error is kept.
+    assert_eq!(
+        nodes[0].to_token_stream().to_string(),
+        "< div > < br > < erase > < / erase > < bad / > < / div >"
+    );
+}
+
+#[test]
+fn test_children_parser() {
+    let config = ParserConfig::new().children_parser("markdown", |open_tag, input| {
+        assert_eq!(open_tag.name.to_string(), "markdown");
+        // Everything, including other close tags, belongs to the DSL.
+        let tokens: TokenStream = input.parse()?;
+        if tokens.to_string().contains("error") {
+            return Err(syn::Error::new_spanned(tokens, "invalid markdown"));
+        }
+        let text = tokens.to_string();
+        Ok(vec![Node::Text(syn::parse_quote!(#text))])
+    });
+    let parser = Parser::new(config);
+
+    let tokens = quote!(<div><markdown> Title </p> *a* </markdown><p>"ok"</p></div>);
+    let nodes = parser.parse_simple(tokens).unwrap();
+    let Node::Element(div) = &nodes[0] else {
+        panic!("expected element")
+    };
+    assert_eq!(div.children.len(), 2);
+    let Node::Element(markdown) = &div.children[0] else {
+        panic!("expected element")
+    };
+    let [Node::Text(text)] = &markdown.children[..] else {
+        panic!("expected single text child")
+    };
+    assert_eq!(text.value_string(), "Title < / p > * a *");
+
+    let tokens = quote!(<markdown> error </markdown>);
+    let (nodes, diagnostics) = parser.parse_recoverable(tokens).split_vec();
+    assert_eq!(diagnostics.len(), 1);
+    let Node::Element(markdown) = &nodes[0] else {
+        panic!("expected element")
+    };
+    assert!(matches!(&markdown.children[..], [Node::RawText(_)]));
+}
+
+#[test]
+fn test_error_codes() {
+    use proc_macro2::Span;
+    use rstml::ParserError;
+
+    let (_, errors) = Parser::new(ParserConfig::new().reject_flag_attributes(true))
+        .parse_recoverable(quote!(<div hidden><span x=></div> <a>))
+        .split_vec();
+    let codes: Vec<_> = errors.iter().map(ParserError::code_of).collect();
+    assert_eq!(
+        codes,
+        [
+            Some("E010"),
+            Some("E009"),
+            Some("E002"),
+            Some("E001"),
+            Some("E001")
+        ]
+    );
+
+    // Every error is recognized from its message.
+    let all = [
+        ParserError::UnclosedTag {
+            name: "div".into(),
+            suggested_fix: "</div>".into(),
+        },
+        ParserError::MissingTagEnd,
+        ParserError::FlagAttribute("x".into()),
+        ParserError::InvalidExpression("unexpected end of input".into()),
+        ParserError::AmbiguousAttributeValue,
+        ParserError::TopLevelNodeType("element".into()),
+        ParserError::TopLevelNodeCount {
+            found: 2,
+            expected: 1,
+        },
+        ParserError::ExpectedClassName,
+    ];
+    for error in all {
+        let code = error.code();
+        let diagnostic = error.clone().spanned(Span::call_site());
+        assert_eq!(ParserError::code_of(&diagnostic), Some(code));
+        assert_eq!(
+            ParserError::code_of_error(&error.error(Span::call_site())),
+            Some(code)
+        );
+    }
+    let user_error = syn::Error::new(Span::call_site(), "bad element");
+    assert_eq!(ParserError::code_of_error(&user_error), None);
+}
+
+#[test]
+fn test_unclosed_tag_suggested_fix() {
+    use std::{cell::RefCell, rc::Rc};
+
+    use rstml::ParserError;
+
+    let fixes = Rc::new(RefCell::new(vec![]));
+    let config = ParserConfig::new().error_message({
+        let fixes = fixes.clone();
+        move |error| {
+            fixes
+                .borrow_mut()
+                .extend(error.suggested_fix().map(String::from));
+            None
+        }
+    });
+    let (_, errors) = Parser::new(config)
+        .parse_recoverable(quote!(<div><Foo<T>>"a"))
+        .split_vec();
+    let errors: Vec<_> = errors.into_iter().map(message).collect();
+    assert_eq!(
+        errors,
+        [
+            "open tag has no corresponding close tag, expected `</Foo<T>>`",
+            "open tag has no corresponding close tag, expected `</div>`",
+        ]
+    );
+    assert_eq!(*fixes.borrow(), ["</Foo<T>>", "</div>"]);
+    assert_eq!(ParserError::MissingTagEnd.suggested_fix(), None);
+}
+
+#[test]
+fn test_element_name_check() {
+    use rstml::ParserError;
+
+    let known = ["div", "span"];
+    let config = ParserConfig::new().element_name_check(move |name| {
+        let name = name.to_string();
+        if known.contains(&name.as_str()) {
+            Ok(())
+        } else {
+            Err("unknown element".into())
+        }
+    });
+    let (nodes, errors) = Parser::new(config)
+        .parse_recoverable(TokenStream::from_str(r#"<div><spam>"a"</spam><span/></div>"#).unwrap())
+        .split_vec();
+    // Element is still parsed with its children.
+    assert_eq!(nodes[0].children().unwrap().len(), 2);
+    let errors: Vec<_> = errors
+        .iter()
+        .map(|e| (ParserError::code_of(e), syn::Error::from(e.clone())))
+        .map(|(code, e)| (code, message(e.clone()), e.span().start().column))
+        .collect();
+    assert_eq!(
+        errors,
+        [(
+            Some("E024"),
+            "invalid element name `spam`: unknown element".to_string(),
+            6
+        )]
+    );
+}
+
+#[test]
+fn test_attribute_name_check() {
+    use rstml::ParserError;
+
+    // Only `href` is allowed on `a`, and components accept any props.
+    let config = ParserConfig::new()
+        .tag_sugar(true)
+        .attribute_name_check(|element, key| {
+            if element.to_string() == "a" && key.to_string() != "href" {
+                return Err(format!("`a` doesn't accept `{key}`"));
+            }
+            Ok(())
+        });
+    let (nodes, errors) = Parser::new(config)
+        .parse_recoverable(quote!(<a.link href="/" {..rest} src="x"><Link src="y"/></a>))
+        .split_vec();
+    assert_eq!(nodes[0].children().unwrap().len(), 1);
+    let errors: Vec<_> = errors
+        .iter()
+        .map(|e| (ParserError::code_of(e), message(e.clone())))
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            (
+                Some("E025"),
+                "invalid attribute `class` of element `a`: `a` doesn't accept `class`".to_string()
+            ),
+            (
+                Some("E025"),
+                "invalid attribute `src` of element `a`: `a` doesn't accept `src`".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_close_tag_attributes() {
+    use rstml::ParserError;
+
+    let tokens = TokenStream::from_str(r#"<div>"a"</div class="x" hidden><span/>"#).unwrap();
+    let (nodes, errors) = Parser::new(ParserConfig::new())
+        .parse_recoverable(tokens)
+        .split_vec();
+    // Close tag is still parsed, so the next element is a sibling.
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(nodes[0].children().unwrap().len(), 1);
+    let errors: Vec<_> = errors
+        .iter()
+        .map(|e| (ParserError::code_of(e), syn::Error::from(e.clone())))
+        .map(|(code, e)| (code, message(e.clone()), e.span().start().column))
+        .collect();
+    assert_eq!(
+        errors,
+        [(
+            Some("E028"),
+            "close tags cannot have attributes".to_string(),
+            14
+        )]
+    );
+}
+
+#[test]
+fn test_missing_tag_end_span() {
+    use rstml::ParserError;
+
+    let tokens = TokenStream::from_str(r#""a" <div class="x""#).unwrap();
+    let (_, errors) = Parser::new(ParserConfig::new())
+        .parse_recoverable(tokens)
+        .split_vec();
+    let error = errors
+        .into_iter()
+        .find(|e| ParserError::code_of(e) == Some("E004"))
+        .unwrap();
+    assert_eq!(syn::Error::from(error).span().start().column, 4);
+}
+
+#[test]
+fn test_fragment_restrictions() {
+    use rstml::ParserError;
+
+    let tokens = quote!(<><div><></></div><>"a"</></>);
+    let codes = |config: ParserConfig| {
+        let (nodes, errors) = Parser::new(config)
+            .parse_recoverable(tokens.clone())
+            .split_vec();
+        assert_eq!(nodes[0].children().unwrap().len(), 2);
+        errors
+            .iter()
+            .filter_map(ParserError::code_of)
+            .collect::<Vec<_>>()
+    };
+    assert!(codes(ParserConfig::new()).is_empty());
+    assert_eq!(
+        codes(ParserConfig::new().reject_nested_fragments(true)),
+        ["E026", "E026"]
+    );
+    assert_eq!(
+        codes(ParserConfig::new().reject_empty_fragments(true)),
+        ["E027"]
+    );
+}
+
+#[test]
+fn test_name_policy() {
+    use rstml::{NamePolicy, ParserError};
+
+    let tokens = TokenStream::from_str("<div><_x/><my-é é-data=1/><é/></div>").unwrap();
+    let errors = |policy| {
+        let (nodes, errors) = Parser::new(ParserConfig::new().name_policy(policy))
+            .parse_recoverable(tokens.clone())
+            .split_vec();
+        assert_eq!(nodes[0].children().unwrap().len(), 3);
+        errors
+            .iter()
+            .filter(|e| ParserError::code_of(e) == Some("E030"))
+            .map(|e| syn::Error::from(e.clone()).span().start().column)
+            .collect::<Vec<_>>()
+    };
+    assert!(errors(NamePolicy::Permissive).is_empty());
+    assert!(errors(NamePolicy::Xml).is_empty());
+    assert_eq!(errors(NamePolicy::Html), [6, 27]);
+}
+
+#[test]
+fn test_reject_empty_blocks() {
+    use rstml::ParserError;
+
+    let tokens = quote!(<div a={} b={x.}>{}<p>{x}</p>{y.}</div>);
+    let (nodes, errors) = Parser::new(
+        ParserConfig::new()
+            .reject_empty_blocks(true)
+            .recover_block(true),
+    )
+    .parse_recoverable(tokens)
+    .split_vec();
+    let codes: Vec<_> = errors.iter().map(ParserError::code_of).collect();
+    assert_eq!(codes[0], Some("E032"));
+    assert_eq!(codes[2], Some("E032"));
+    assert_eq!(codes.len(), 4);
+
+    let Node::Element(div) = &nodes[0] else {
+        panic!("expected element")
+    };
+    for attribute in div.attributes() {
+        let NodeAttribute::Attribute(attribute) = attribute else {
+            panic!("expected keyed attribute")
+        };
+        assert!(matches!(
+            attribute.possible_value,
+            KeyedAttributeValue::Value(AttributeValueExpr {
+                value: KVAttributeValue::InvalidBraced(_),
+                ..
+            })
+        ));
+    }
+    let blocks: Vec<_> = div
+        .children
+        .iter()
+        .map(|child| match child {
+            Node::Block(NodeBlock::Invalid(block)) => Some(block.body.to_string()),
+            Node::Block(NodeBlock::ValidBlock(_)) => None,
+            _ => Some("element".into()),
+        })
+        .collect();
+    assert_eq!(
+        blocks,
+        [Some("".into()), Some("element".into()), Some("y .".into())]
+    );
+}
+
+#[test]
+fn test_text_literals() {
+    use rstml::{ParserError, TextLiterals};
+
+    let tokens = quote!(<p>"a" 1 'c' 2.5 word <script>let x = 1;</script></p>);
+    let errors = |literals| {
+        let (nodes, errors) = Parser::new(
+            ParserConfig::new()
+                .text_literals(literals)
+                .raw_text_elements(["script"].into()),
+        )
+        .parse_recoverable(tokens.clone())
+        .split_vec();
+        assert_eq!(nodes[0].children().unwrap().len(), 3);
+        errors
+            .into_iter()
+            .filter(|e| ParserError::code_of(e) == Some("E031"))
+            .map(message)
+            .collect::<Vec<_>>()
+    };
+    assert!(errors(TextLiterals::Any).is_empty());
+    assert_eq!(
+        errors(TextLiterals::Strings),
+        [
+            r#"literal `1` should be quoted to be used as text, e.g. `"1"`"#,
+            r#"literal `'c'` should be quoted to be used as text, e.g. `"c"`"#,
+            r#"literal `2.5` should be quoted to be used as text, e.g. `"2.5"`"#,
+        ]
+    );
+}
+
+#[test]
+fn test_skip_stray_close_tags() {
+    let tokens = quote!(<a/></div><b>"x"</b></>);
+    let (nodes, errors) = Parser::new(ParserConfig::new())
+        .parse_recoverable(tokens.clone())
+        .split_vec();
+    let tree: String = nodes.iter().map(Node::to_debug_tree).collect();
+    assert_eq!(tree, "a\nb\n  \"x\"\n");
+    let errors: Vec<_> = errors.into_iter().map(message).collect();
+    assert_eq!(
+        errors,
+        ["close tag was parsed while waiting for open tag"; 2]
+    );
+
+    // Lazy parser reports skipped tags along with the next node, or at the end.
+    let results: Vec<_> = Parser::new(ParserConfig::new())
+        .parse_iter(tokens)
+        .map(|result| result.is_ok())
+        .collect();
+    assert_eq!(results, [true, false, false]);
+}
+
+#[test]
+fn test_custom_error_messages() {
+    use rstml::ParserError;
+
+    let config = ParserConfig::new()
+        .number_of_top_level_nodes(1)
+        .error_message(|error| match error {
+            ParserError::UnclosedTag { .. } => Some("component must be closed".into()),
+            ParserError::TopLevelNodeCount { found, .. } => {
+                Some(format!("template must have one root, found {found}"))
+            }
+            _ => None,
+        });
+    let (_, errors) = Parser::new(config)
+        .parse_recoverable(quote!(<a></b> <Card>))
+        .split_vec();
+    // Codes don't depend on messages.
+    let codes: Vec<_> = errors.iter().map(ParserError::code_of).collect();
+    assert_eq!(codes, [Some("E002"), Some("E001"), Some("E018")]);
+    let errors: Vec<_> = errors.into_iter().map(message).collect();
+    assert_eq!(
+        errors,
+        [
+            "wrong close tag found",
+            "component must be closed",
+            "template must have one root, found 2",
+        ]
+    );
+
+    // Even if message of one error looks like another one.
+    let config =
+        ParserConfig::new().element_name_check(|_| Err("wrong close tag found".to_string()));
+    let (_, errors) = Parser::new(config)
+        .parse_recoverable(quote!(<a/>))
+        .split_vec();
+    let codes: Vec<_> = errors.iter().map(ParserError::code_of).collect();
+    assert_eq!(codes, [Some("E024")]);
+}
@@ -0,0 +1,203 @@
+//! Property tests of `parse -> ToTokens -> parse` round trip.
+//!
+//! Random trees are rendered to source, parsed, rendered back with
+//! [`ToTokens`] and parsed again. Both parsed trees should be equal, and should
+//! have the structure of the generated tree.
+
+use std::str::FromStr;
+
+use proc_macro2::TokenStream;
+use proptest::prelude::*;
+use quote::ToTokens;
+use rstml::{node::Node, Parser, ParserConfig};
+
+/// Generated attribute.
+#[derive(Clone, Debug)]
+enum Attr {
+    Str(String, String),
+    Int(String, u32),
+    Flag(String),
+    Block(String),
+}
+
+/// Generated node.
+#[derive(Clone, Debug)]
+enum Tree {
+    Element {
+        name: String,
+        attributes: Vec<Attr>,
+        children: Vec<Tree>,
+        self_closing: bool,
+    },
+    Fragment(Vec<Tree>),
+    Text(String),
+    Block(String),
+    Comment(String),
+}
+
+impl Tree {
+    /// Source of node, like the one user would write in macro.
+    fn source(&self, out: &mut String) {
+        match self {
+            Tree::Element {
+                name,
+                attributes,
+                children,
+                self_closing,
+            } => {
+                out.push('<');
+                out.push_str(name);
+                for attribute in attributes {
+                    out.push(' ');
+                    out.push_str(&match attribute {
+                        Attr::Str(key, value) => format!("{key}={value:?}"),
+                        Attr::Int(key, value) => format!("{key}={value}"),
+                        Attr::Flag(key) => key.clone(),
+                        Attr::Block(ident) => format!("{{..{ident}}}"),
+                    });
+                }
+                if *self_closing {
+                    out.push_str("/>");
+                    return;
+                }
+                out.push('>');
+                children.iter().for_each(|child| child.source(out));
+                out.push_str(&format!("</{name}>"));
+            }
+            Tree::Fragment(children) => {
+                out.push_str("<>");
+                children.iter().for_each(|child| child.source(out));
+                out.push_str("</>");
+            }
+            Tree::Text(text) => out.push_str(&format!("{text:?}")),
+            Tree::Block(ident) => out.push_str(&format!("{{{ident} + 1}}")),
+            Tree::Comment(text) => out.push_str(&format!("<!-- {text:?} -->")),
+        }
+    }
+
+    /// Expected output of [`Node::to_debug_tree`].
+    fn debug_tree(&self, depth: usize, out: &mut String) {
+        let indent = "  ".repeat(depth);
+        let children = match self {
+            Tree::Element {
+                name,
+                attributes,
+                children,
+                ..
+            } => {
+                out.push_str(&format!("{indent}{name}"));
+                if !attributes.is_empty() {
+                    let attributes: Vec<_> = attributes
+                        .iter()
+                        .map(|attribute| match attribute {
+                            Attr::Str(key, value) => format!("{key}={value:?}"),
+                            Attr::Int(key, value) => format!("{key}={value}"),
+                            Attr::Flag(key) => key.clone(),
+                            Attr::Block(ident) => format!("{{ .. {ident} }}"),
+                        })
+                        .collect();
+                    out.push_str(&format!(" [{}]", attributes.join(" ")));
+                }
+                out.push('\n');
+                children.as_slice()
+            }
+            Tree::Fragment(children) => {
+                out.push_str(&format!("{indent}<>\n"));
+                children.as_slice()
+            }
+            Tree::Text(text) => {
+                out.push_str(&format!("{indent}{text:?}\n"));
+                &[]
+            }
+            Tree::Block(ident) => {
+                out.push_str(&format!("{indent}{{ {ident} + 1 }}\n"));
+                &[]
+            }
+            Tree::Comment(text) => {
+                out.push_str(&format!("{indent}<!-- {text:?} -->\n"));
+                &[]
+            }
+        };
+        children
+            .iter()
+            .for_each(|child| child.debug_tree(depth + 1, out));
+    }
+}
+
+/// Identifiers that are never keywords.
+fn ident() -> impl Strategy<Value = String> {
+    "x[a-z0-9_]{0,5}"
+}
+
+/// Element names, including dashed ones, like `x-el`.
+fn element_name() -> impl Strategy<Value = String> {
+    prop_oneof![ident(), "x[a-z]{0,3}-[a-z]{1,4}", "x[a-z]{0,3}:[a-z]{1,4}"]
+}
+
+fn attribute() -> impl Strategy<Value = Attr> {
+    prop_oneof![
+        (element_name(), "[a-z0-9 ]{0,6}").prop_map(|(k, v)| Attr::Str(k, v)),
+        (element_name(), any::<u32>()).prop_map(|(k, v)| Attr::Int(k, v)),
+        element_name().prop_map(Attr::Flag),
+        ident().prop_map(Attr::Block),
+    ]
+}
+
+fn tree() -> impl Strategy<Value = Tree> {
+    let leaf = prop_oneof![
+        "[a-zA-Z0-9 ,.!]{0,10}".prop_map(Tree::Text),
+        ident().prop_map(Tree::Block),
+        "[a-z ]{0,8}".prop_map(Tree::Comment),
+        (element_name(), prop::collection::vec(attribute(), 0..3)).prop_map(
+            |(name, attributes)| Tree::Element {
+                name,
+                attributes,
+                children: vec![],
+                self_closing: true,
+            }
+        ),
+    ];
+    leaf.prop_recursive(4, 32, 4, |inner| {
+        prop_oneof![
+            (
+                element_name(),
+                prop::collection::vec(attribute(), 0..3),
+                prop::collection::vec(inner.clone(), 0..4),
+            )
+                .prop_map(|(name, attributes, children)| Tree::Element {
+                    name,
+                    attributes,
+                    children,
+                    self_closing: false,
+                }),
+            prop::collection::vec(inner, 0..4).prop_map(Tree::Fragment),
+        ]
+    })
+}
+
+fn parse(tokens: TokenStream) -> Result<Vec<Node>, TestCaseError> {
+    Parser::new(ParserConfig::new())
+        .parse_simple(tokens)
+        .map_err(|e| TestCaseError::fail(e.to_string()))
+}
+
+proptest! {
+    #[test]
+    fn test_roundtrip(trees in prop::collection::vec(tree(), 1..4)) {
+        let mut source = String::new();
+        let mut expected = String::new();
+        for tree in &trees {
+            tree.source(&mut source);
+            tree.debug_tree(0, &mut expected);
+        }
+
+        let nodes = parse(TokenStream::from_str(&source).unwrap())?;
+        let actual: String = nodes.iter().map(Node::to_debug_tree).collect();
+        prop_assert_eq!(actual, expected);
+
+        let mut tokens = TokenStream::new();
+        nodes.iter().for_each(|node| node.to_tokens(&mut tokens));
+        let reparsed = parse(tokens)?;
+        prop_assert_eq!(reparsed, nodes);
+    }
+}
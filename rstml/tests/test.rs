@@ -5,19 +5,21 @@ use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
 use rstml::{
     node::{
-        CustomNode, KVAttributeValue, KeyedAttribute, KeyedAttributeValue, Node, NodeAttribute,
-        NodeElement, NodeName, NodeType,
+        AttributeValueExpr, ClassValue, CustomNode, ElementKind, KVAttributeValue, KeyedAttribute,
+        KeyedAttributeValue, Namespace, Node, NodeAttribute, NodeBlock, NodeElement, NodeName,
+        NodeText, NodeTree, NodeType,
     },
     parse2,
     recoverable::{ParseRecoverable, RecoverableContext},
-    Parser, ParserConfig,
+    source_map::SourceMap,
+    ComponentRules, Parser, ParserConfig, ParserError,
 };
 use syn::{
     bracketed,
-    parse::ParseStream,
+    parse::{ParseStream, Parser as _},
     parse_quote,
     token::{Bracket, Colon},
-    Block, LifetimeParam, Pat, PatType, Token, TypeParam,
+    Block, LifetimeParam, Pat, PatType, TypeParam,
 };
 
 #[test]
@@ -48,6 +50,50 @@ fn test_single_element_with_attributes() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_typed_literal_values() -> Result<()> {
+    let tokens = quote! {
+        <foo a=-1 b=0x10 c=-0.5 d=true e="2" f=-x>" 3 "</foo>
+    };
+    let nodes = parse2(tokens)?;
+
+    let a = get_element_attribute(&nodes, 0, 0);
+    assert_eq!(a.value_literal_string(), Some("-1".into()));
+    assert_eq!(a.value_int(), Some(-1));
+    assert_eq!(a.value_float(), Some(-1.0));
+    assert_eq!(get_element_attribute(&nodes, 0, 1).value_int(), Some(16));
+    let c = get_element_attribute(&nodes, 0, 2);
+    assert_eq!(c.value_int(), None);
+    assert_eq!(c.value_float(), Some(-0.5));
+    assert_eq!(get_element_attribute(&nodes, 0, 3).value_bool(), Some(true));
+    assert_eq!(get_element_attribute(&nodes, 0, 4).value_int(), None);
+    assert_eq!(
+        get_element_attribute(&nodes, 0, 5).value_literal_string(),
+        None
+    );
+
+    let Node::Text(text) = get_element_child(&nodes, 0, 0) else {
+        panic!("expected text")
+    };
+    assert_eq!(text.value_int(), Some(3));
+    assert_eq!(text.value_float(), Some(3.0));
+
+    let nodes = parse2(quote! { <p>42</p><p>-1</p><p>0.5</p><p>1 + 1</p><p>x</p> })?;
+    let raw = |i| match get_element_child(&nodes, i, 0) {
+        Node::RawText(raw) => raw,
+        _ => panic!("expected raw text"),
+    };
+    assert_eq!(raw(0).value_int(), Some(42));
+    assert_eq!(raw(1).value_int(), Some(-1));
+    assert_eq!(raw(1).value_float(), Some(-1.0));
+    assert_eq!(raw(2).value_int(), None);
+    assert_eq!(raw(2).value_float(), Some(0.5));
+    assert_eq!(raw(3).value_int(), None);
+    assert_eq!(raw(4).value_float(), None);
+
+    Ok(())
+}
+
 #[test]
 fn test_single_element_with_text() -> Result<()> {
     let tokens = quote! {
@@ -64,6 +110,33 @@ fn test_single_element_with_text() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_merge_adjacent_text() -> Result<()> {
+    let tokens = quote! {
+        "Hello " "world" <p>"a" "b" {c} "d"</p>
+    };
+
+    let config = ParserConfig::new()
+        .merge_adjacent_text(true)
+        .number_of_top_level_nodes(2);
+    let nodes = Parser::new(config).parse_simple(tokens.clone())?;
+    let Node::Text(text) = &nodes[0] else {
+        panic!("expected text")
+    };
+    assert_eq!(text.value_string(), "Hello world");
+    let element = get_element(&nodes, 1);
+    assert_eq!(element.children.len(), 3);
+    let Node::Text(text) = &element.children[0] else {
+        panic!("expected text")
+    };
+    assert_eq!(text.value_string(), "ab");
+
+    let nodes = parse2(tokens)?;
+    assert_eq!(nodes.len(), 3);
+
+    Ok(())
+}
+
 #[test]
 fn test_single_element_with_unquoted_text_simple() -> Result<()> {
     let tokens = quote! {
@@ -82,6 +155,58 @@ fn test_single_element_with_unquoted_text_simple() -> Result<()> {
     Ok(())
 }
 
+#[cfg(feature = "rawtext-stable-hack")]
+#[test]
+fn test_unquoted_text_recovered_from_source_text() -> Result<()> {
+    let tokens = quote! {
+        <foo> bar  baz </foo>
+    };
+
+    let nodes = Parser::new(ParserConfig::new())
+        .parse_with_source_text(tokens, "<foo> bar  baz </foo>")
+        .into_result()?;
+    let Node::RawText(child) = get_element_child(&nodes, 0, 0) else {
+        panic!("expected child")
+    };
+
+    assert_eq!(child.to_token_stream_string(), "bar baz");
+    assert_eq!(child.to_string_best(), " bar  baz ");
+    Ok(())
+}
+
+#[cfg(feature = "rawtext-stable-hack")]
+#[test]
+fn test_parse_source_macro() -> Result<()> {
+    let nodes = rstml::parse_source!("<foo> bar  baz </foo>").into_result()?;
+    let Node::RawText(child) = get_element_child(&nodes, 0, 0) else {
+        panic!("expected child")
+    };
+    assert_eq!(child.to_string_best(), " bar  baz ");
+
+    // Input that can't be tokenized is reported.
+    let result = rstml::parse_source!("<foo>\"</foo>");
+    assert!(matches!(result, rstml::ParsingResult::Failed(_)));
+    Ok(())
+}
+
+#[cfg(feature = "rawtext-stable-hack")]
+#[test]
+fn test_mismatched_source_text() {
+    let parser = Parser::new(ParserConfig::new());
+    for source_text in ["<foo> bar </foo><bar/>", "<foo>{bar}</foo>", "<foo"] {
+        let (nodes, diagnostics) = parser
+            .parse_with_source_text(quote! { <foo> bar </foo> }, source_text)
+            .split_vec();
+        assert_eq!(nodes.len(), 1);
+        let codes: Vec<_> = diagnostics.iter().map(ParserError::code_of).collect();
+        if source_text == "<foo" {
+            assert_eq!(codes, [None]);
+        } else {
+            assert_eq!(codes, [Some("E033")]);
+        }
+    }
+}
+
 #[test]
 fn test_css_selector_unquoted_text() -> Result<()> {
     let tokens = quote! {
@@ -297,7 +422,7 @@ fn test_reserved_keyword_attributes() -> Result<()> {
     };
     let nodes = parse2(tokens)?;
     let element = get_element(&nodes, 0);
-    let Some(NodeAttribute::Attribute(attribute)) = element.attributes().get(0) else {
+    let Some(NodeAttribute::Attribute(attribute)) = element.attributes().first() else {
         panic!("expected attribute")
     };
 
@@ -407,6 +532,30 @@ fn test_dashed_attribute_name_integers_not_supported_at_beginning() {
     let _ = parse2(tokens).unwrap();
 }
 
+#[test]
+fn test_numeric_and_punctuated_element_names() -> Result<()> {
+    let tokens = TokenStream::from_str(r#"<1a></1a> <-custom data-x="y"/> <x-1b></x-1b>"#).unwrap();
+    let nodes = parse2(tokens)?;
+    let names: Vec<_> = (0..3)
+        .map(|i| get_element(&nodes, i).name().to_string())
+        .collect();
+    assert_eq!(names, ["1a", "-custom", "x-1b"]);
+    assert_eq!(
+        get_element_attribute(&nodes, 1, 0).key.to_string(),
+        "data-x"
+    );
+
+    // Generated tokens share the same span, so they are joined as well.
+    let nodes = parse2(quote! { <-custom></-custom> })?;
+    assert_eq!(get_element(&nodes, 0).name().to_string(), "-custom");
+
+    // Parts separated by whitespace are not joined.
+    let tokens = TokenStream::from_str("<- custom/>").unwrap();
+    assert!(parse2(tokens).is_err());
+
+    Ok(())
+}
+
 #[test]
 fn test_dashed_attribute_name_with_long_integer_suffixes() -> Result<()> {
     let tokens = quote! {
@@ -540,6 +689,34 @@ fn test_block_as_attribute() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_block_as_attribute_key() -> Result<()> {
+    let tokens = quote! {
+        <div {key}={value} {attribute} {"data-x"}="y" />
+    };
+
+    let nodes = parse2(tokens)?;
+    let element = get_element(&nodes, 0);
+    assert_eq!(element.attributes().len(), 3);
+
+    let attribute = get_element_attribute(&nodes, 0, 0);
+    let NodeName::Block(key) = &attribute.key else {
+        panic!("expected block key")
+    };
+    assert_eq!(key.to_token_stream().to_string(), "{ key }");
+    assert_eq!(
+        attribute.value().unwrap().to_token_stream().to_string(),
+        "{ value }"
+    );
+    assert!(matches!(element.attributes()[1], NodeAttribute::Block(_)));
+    assert_eq!(
+        get_element_attribute(&nodes, 0, 2).value_literal_string(),
+        Some("y".into())
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_number_of_top_level_nodes() -> Result<()> {
     let tokens = quote! {
@@ -634,6 +811,38 @@ fn test_transform_block_none() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_block_delimiters() -> Result<()> {
+    use proc_macro2::Delimiter;
+
+    let tokens = quote! {
+        <style>"p { color: red; }"</style>
+        <p class=[class] [attribute]>[value] {braces}</p>
+    };
+
+    let config = ParserConfig::new().block_delimiters([Delimiter::Bracket]);
+    let nodes = Parser::new(config).parse_simple(tokens)?;
+    let Node::Block(block) = get_element_child(&nodes, 1, 0) else {
+        panic!("expected block")
+    };
+    assert_eq!(block.to_token_stream().to_string(), "{ value }");
+    let Node::RawText(text) = get_element_child(&nodes, 1, 1) else {
+        panic!("expected raw text")
+    };
+    assert_eq!(text.to_token_stream_string(), "{ braces }");
+
+    let element = get_element(&nodes, 1);
+    assert_eq!(element.attributes().len(), 2);
+    assert!(matches!(element.attributes()[1], NodeAttribute::Block(_)));
+    let attribute = get_element_attribute(&nodes, 1, 0);
+    assert_eq!(
+        attribute.value().unwrap().to_token_stream().to_string(),
+        "{ class }"
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_doctype() -> Result<()> {
     let tokens = quote! {
@@ -643,7 +852,7 @@ fn test_doctype() -> Result<()> {
     };
 
     let nodes = parse2(tokens)?;
-    let Some(Node::Doctype(doctype)) = nodes.get(0) else {
+    let Some(Node::Doctype(doctype)) = nodes.first() else {
         panic!("expected doctype")
     };
 
@@ -661,7 +870,7 @@ fn test_doctype_empty() -> Result<()> {
     };
 
     let nodes = parse2(tokens)?;
-    let Some(Node::Doctype(doctype)) = nodes.get(0) else {
+    let Some(Node::Doctype(doctype)) = nodes.first() else {
         panic!("expected doctype")
     };
 
@@ -681,7 +890,7 @@ fn test_comment() -> Result<()> {
     };
 
     let nodes = parse2(tokens)?;
-    let Some(Node::Comment(comment1)) = nodes.get(0) else {
+    let Some(Node::Comment(comment1)) = nodes.first() else {
         panic!("expected comment")
     };
     let Node::Comment(comment2) = get_element_child(&nodes, 1, 0) else {
@@ -694,6 +903,42 @@ fn test_comment() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_doc_comments() -> Result<()> {
+    let tokens = TokenStream::from_str(
+        r#"
+        /// Greeting
+        <div>
+            // removed by tokenizer
+            Hello /* also removed */ world
+            //! inner
+            {name}
+        </div>
+        "#,
+    )
+    .unwrap();
+
+    let nodes = parse2(tokens)?;
+    let Some(Node::Comment(comment)) = nodes.first() else {
+        panic!("expected comment")
+    };
+    assert_eq!(comment.value.value(), " Greeting");
+    let Node::Comment(inner) = get_element_child(&nodes, 1, 1) else {
+        panic!("expected comment")
+    };
+    assert_eq!(inner.value.value(), " inner");
+    assert_eq!(nodes[1].children().unwrap().len(), 3);
+
+    // Quoted doc comments are parsed the same way.
+    let nodes = parse2(quote! {
+        /// Greeting
+        <div />
+    })?;
+    assert!(matches!(&nodes[0], Node::Comment(c) if c.value.value() == " Greeting"));
+
+    Ok(())
+}
+
 #[test]
 fn test_fragment() -> Result<()> {
     let tokens = quote! {
@@ -703,7 +948,7 @@ fn test_fragment() -> Result<()> {
     };
 
     let nodes = parse2(tokens)?;
-    let Some(Node::Fragment(fragment)) = nodes.get(0) else {
+    let Some(Node::Fragment(fragment)) = nodes.first() else {
         panic!("expected fragment")
     };
 
@@ -783,7 +1028,7 @@ fn test_generics_closed_not_match() -> Result<()> {
         <foo<Bar>> </foo<Baz>>
     };
     let e = parse2(tokens).unwrap_err();
-    assert_eq!(e.to_string(), "close tag generics missmatch");
+    assert_eq!(message(e), "close tag generics missmatch");
     Ok(())
 }
 
@@ -942,6 +1187,57 @@ fn test_custom_wildcard() -> Result<()> {
         .is_wildcard());
     Ok(())
 }
+
+#[test]
+fn test_case_insensitive_close_tags() -> Result<()> {
+    let config = ParserConfig::new().case_insensitive_close_tags(true);
+    let parser = Parser::new(config);
+
+    let nodes = parser.parse_simple(quote! { <DIV><my-Tag></MY-tag></div> })?;
+    assert_eq!(get_element(&nodes, 0).name().to_string(), "DIV");
+
+    assert!(parser.parse_simple(quote! { <Foo></foo> }).is_err());
+    assert!(parser.parse_simple(quote! { <a::b></A::B> }).is_err());
+    assert!(parse2(quote! { <DIV></div> }).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_element_kind() -> Result<()> {
+    let tokens = quote! {
+        <div/> <Button/> <ui::button/> <my-button/> <{name}/>
+    };
+
+    let kinds = |config: ParserConfig| -> Result<Vec<ElementKind>> {
+        let nodes = Parser::new(config).parse_simple(tokens.clone())?;
+        Ok((0..nodes.len())
+            .map(|ix| get_element(&nodes, ix).kind)
+            .collect())
+    };
+    use ElementKind::{Component, HtmlElement};
+    assert_eq!(
+        kinds(ParserConfig::new())?,
+        [HtmlElement, Component, Component, HtmlElement, HtmlElement]
+    );
+
+    let rules = ComponentRules {
+        uppercase_first_letter: false,
+        path_qualified: false,
+        contains_dash: true,
+    };
+    assert_eq!(
+        kinds(ParserConfig::new().component_rules(rules))?,
+        [
+            HtmlElement,
+            HtmlElement,
+            HtmlElement,
+            Component,
+            HtmlElement
+        ]
+    );
+    Ok(())
+}
+
 #[test]
 fn test_single_element_with_different_attributes() -> Result<()> {
     let tokens = quote! {
@@ -995,13 +1291,26 @@ fn test_invalid_blocks() -> Result<()> {
     assert_eq!(diagnostics.len(), 1);
     let dbg_diag = format!("{:?}", diagnostics[0]);
     assert!(dbg_diag.contains("unexpected end of input, expected identifier or integer"));
-    // same should not work if recover_block = false
+    // children blocks are kept as invalid even if recover_block = false
     let config = ParserConfig::new();
     let (nodes, diagnostics) = Parser::new(config).parse_recoverable(tokens).split_vec();
-    let node = get_element(&nodes, 0);
-    assert!(node.children.is_empty());
-    // TODO: Cleanup errors
-    assert!(diagnostics.len() > 1);
+    let Node::Block(NodeBlock::Invalid(block)) = get_element_child(&nodes, 0, 0) else {
+        panic!("expected invalid block")
+    };
+    assert_eq!(block.to_token_stream().to_string(), "{ block . }");
+    assert_eq!(diagnostics.len(), 1);
+
+    // siblings of the broken block and its parent are kept
+    let tokens = quote! {
+        <div>{x.}<i/></div><p/>
+    };
+    let (nodes, diagnostics) = Parser::new(ParserConfig::new())
+        .parse_recoverable(tokens)
+        .split_vec();
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(get_element(&nodes, 0).children.len(), 2);
+    assert_eq!(get_element(&nodes, 1).name().to_string(), "p");
+    assert_eq!(diagnostics.len(), 1);
     Ok(())
 }
 
@@ -1057,6 +1366,120 @@ fn test_consecutive_puncts_in_name() {
     assert_eq!(name.to_string(), "a--::..d");
 }
 
+#[test]
+fn test_deeply_nested_elements() -> Result<()> {
+    let depth = 1000;
+    let source = format!(
+        "{}\"text\"{}",
+        "<div>".repeat(depth),
+        "</div>".repeat(depth)
+    );
+    let nodes = parse2(TokenStream::from_str(&source).unwrap())?;
+
+    let mut node = &nodes[0];
+    for _ in 0..depth {
+        let Node::Element(element) = node else {
+            panic!("expected element")
+        };
+        assert!(element.close_tag.is_some());
+        node = &element.children[0];
+    }
+    assert!(matches!(node, Node::Text(_)));
+
+    Ok(())
+}
+
+#[test]
+fn test_max_depth() -> Result<()> {
+    let tokens = quote! {
+        <div><>"text"<br/></></div>
+    };
+    let config = ParserConfig::new().max_depth(3);
+    assert!(Parser::new(config).parse_simple(tokens.clone()).is_ok());
+
+    let config = ParserConfig::new().max_depth(2);
+    let err = Parser::new(config).parse_simple(tokens).unwrap_err();
+    assert_eq!(message(err), "maximum element nesting depth exceeded");
+
+    // Too deep elements are dropped, but their ancestors and siblings are kept.
+    let tokens = quote! {
+        <div><p><span><b/></span><i/></p>"b"</div><br/>
+    };
+    let config = ParserConfig::new().max_depth(2);
+    let (nodes, diagnostics) = Parser::new(config).parse_recoverable(tokens).split_vec();
+    let codes: Vec<_> = diagnostics.iter().map(ParserError::code_of).collect();
+    assert_eq!(codes, [Some("E008"), Some("E008")]);
+    let tree: String = nodes.iter().map(Node::to_debug_tree).collect();
+    assert_eq!(tree, "div\n  p\n  \"b\"\nbr\n");
+
+    // Too deep top-level nodes are skipped, and parsing continues with their
+    // siblings.
+    let tokens = quote! { <a/>"b"<c><d/></c> };
+    let parser = Parser::new(ParserConfig::new().max_depth(0));
+    let (nodes, diagnostics) = parser.parse_recoverable(tokens.clone()).split_vec();
+    let codes: Vec<_> = diagnostics.iter().map(ParserError::code_of).collect();
+    assert_eq!(codes, [Some("E008"), Some("E008")]);
+    let tree: String = nodes.iter().map(Node::to_debug_tree).collect();
+    assert_eq!(tree, "\"b\"\n");
+    let items: Vec<_> = parser.parse_iter(tokens).collect();
+    assert!(matches!(items[..], [Err(_), Ok(Node::Text(_)), Err(_)]));
+    assert_eq!(
+        message(items[2].clone().unwrap_err()),
+        "maximum element nesting depth exceeded"
+    );
+    // Input that doesn't fit in the first chunk of iterator.
+    let tokens: TokenStream = "<li/>".repeat(100).parse().unwrap();
+    let messages: Vec<_> = parser
+        .parse_iter(tokens)
+        .map(|item| message(item.unwrap_err()))
+        .collect();
+    assert_eq!(
+        messages,
+        vec!["maximum element nesting depth exceeded"; 100]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_source_map() -> Result<()> {
+    let source = "<div class=\"a\">\n    <br/>\n    {value}\n</div>\n<>\"text\"</>";
+    let nodes = parse2(TokenStream::from_str(source).unwrap())?;
+    let map = SourceMap::new(&nodes);
+
+    let ranges: Vec<_> = map
+        .iter()
+        .map(|e| (e.node_type(), &source[e.byte_range()]))
+        .collect();
+    assert_eq!(
+        ranges,
+        [
+            (
+                NodeType::Element,
+                "<div class=\"a\">\n    <br/>\n    {value}\n</div>"
+            ),
+            (NodeType::Element, "<br/>"),
+            (NodeType::Block, "{value}"),
+            (NodeType::Fragment, "<>\"text\"</>"),
+            (NodeType::Text, "\"text\""),
+        ]
+    );
+
+    let block = map.iter().nth(2).unwrap();
+    assert_eq!(block.parent().map(|p| p.index()), Some(0));
+    assert_eq!((block.start().line, block.start().column), (3, 4));
+    assert_eq!((block.end().line, block.end().column), (3, 11));
+
+    let offset = source.find("value").unwrap();
+    assert_eq!(map.node_at(offset), Some(block.id()));
+    assert_eq!(
+        map.node_at(source.find("text").unwrap()).unwrap().index(),
+        4
+    );
+
+    Ok(())
+}
+
 fn get_element<C: CustomNode>(nodes: &[Node<C>], element_index: usize) -> &NodeElement<C> {
     let Some(Node::Element(element)) = nodes.get(element_index) else {
         panic!("expected element")
@@ -1090,3 +1513,2220 @@ fn get_element_child<C: CustomNode>(
     };
     element.children.get(child_index).expect("child")
 }
+
+/// Message of diagnostic, without the error code note.
+fn message(error: impl Into<syn::Error>) -> String {
+    let error = error.into().to_string();
+    error.lines().next().unwrap_or_default().to_string()
+}
+
+#[test]
+fn test_parse_iter() -> Result<()> {
+    let source: String = (0..100)
+        .map(|i| format!(r#"<div id={i}><span>"text"</span>{{ value }}</div><!-- "c" -->"#))
+        .collect();
+    let tokens = TokenStream::from_str(&source).unwrap();
+    let parser = Parser::new(ParserConfig::new());
+
+    let nodes = parser.parse_simple(tokens.clone())?;
+    let streamed = parser.parse_iter(tokens).collect::<syn::Result<Vec<_>>>()?;
+    assert_eq!(streamed.len(), 200);
+    assert_eq!(
+        streamed
+            .iter()
+            .map(|n| n.to_token_stream().to_string())
+            .collect::<Vec<_>>(),
+        nodes
+            .iter()
+            .map(|n| n.to_token_stream().to_string())
+            .collect::<Vec<_>>()
+    );
+
+    let tokens = quote! { <a/> <b></c> <d/> };
+    let items: Vec<_> = parser.parse_iter(tokens).collect();
+    assert_eq!(items.len(), 3);
+    assert!(items[0].is_ok());
+    assert!(items[1].is_err());
+    assert!(items[2].is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_iter_merge_text() -> Result<()> {
+    let parser = Parser::new(
+        ParserConfig::new()
+            .merge_adjacent_text(true)
+            .number_of_top_level_nodes(3),
+    );
+    let tokens = quote! { "a" "b" "c" "d" "e" "f" <br/> "g" "h" };
+    let nodes = parser.parse_simple(tokens.clone())?;
+    let streamed = parser.parse_iter(tokens).collect::<syn::Result<Vec<_>>>()?;
+    assert_eq!(streamed, nodes);
+    assert_eq!(
+        streamed.iter().map(Node::to_debug_tree).collect::<String>(),
+        "\"abcdef\"\nbr\n\"gh\"\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_parse_iter_hooks() {
+    let closed = std::rc::Rc::new(std::cell::Cell::new(0));
+    let counter = closed.clone();
+    let parser = Parser::new(ParserConfig::new().on_element_closed(move |element| {
+        counter.set(counter.get() + 1);
+        if element.name().to_string() == "bad" {
+            return Err(syn::Error::new_spanned(element.name(), "bad element"));
+        }
+        Ok(())
+    }));
+    let items: String = (0..40).map(|i| format!("<li>{i}</li>")).collect();
+    let source = format!("<ul>{items}</ul><bad/><p/>");
+    let tokens = TokenStream::from_str(&source).unwrap();
+
+    let nodes = parser.parse_recoverable(tokens.clone()).split_vec().0;
+    assert_eq!(closed.replace(0), 43);
+    let streamed: Vec<_> = parser.parse_iter(tokens).collect();
+    assert_eq!(closed.get(), 43);
+    assert!(streamed[1].is_err());
+    assert_eq!(streamed[0].as_ref().unwrap(), &nodes[0]);
+    assert_eq!(streamed[2].as_ref().unwrap(), &nodes[2]);
+}
+
+#[test]
+fn test_parse_iter_is_lazy_after_errors() {
+    let closed = std::rc::Rc::new(std::cell::Cell::new(0));
+    let counter = closed.clone();
+    let parser = Parser::new(ParserConfig::new().on_element_closed(move |_| {
+        counter.set(counter.get() + 1);
+        Ok(())
+    }));
+    // Stray close tag, element with error, and element with warning.
+    let items: String = (0..200).map(|i| format!("<li>{i}</li>")).collect();
+    let source = format!("</div><a></a b><p a=b>c></p><i/>{items}");
+    let tokens = TokenStream::from_str(&source).unwrap();
+
+    let mut iter = parser.parse_iter(tokens.clone());
+    let first: Vec<_> = iter.by_ref().take(5).collect();
+    assert_eq!(first.iter().filter(|item| item.is_err()).count(), 1);
+    assert!(
+        closed.get() < 20,
+        "whole input was parsed: {}",
+        closed.get()
+    );
+    let rest: Vec<_> = iter.collect();
+    assert_eq!(rest.len(), 198);
+    assert!(rest.iter().all(Result::is_ok));
+
+    // Without hooks, chunk that fits the node is not parsed again.
+    let calls = std::rc::Rc::new(std::cell::Cell::new(0));
+    let counter = calls.clone();
+    let parser = Parser::new(ParserConfig::new().children_parser("md", move |_, input| {
+        counter.set(counter.get() + 1);
+        input.parse::<TokenStream>()?;
+        Ok(vec![])
+    }));
+    let source = format!("<md>x</md>{items}");
+    let tokens = TokenStream::from_str(&source).unwrap();
+    assert_eq!(parser.parse_iter(tokens).count(), 201);
+    assert_eq!(calls.get(), 1);
+}
+
+#[test]
+fn test_tree_macro() -> Result<()> {
+    let text = "interpolated";
+    let nodes = rstml::tree! {
+        <div class="item">#text</div>
+        <br/>
+    };
+    let expected = parse2(quote! { <div class="item">"interpolated"</div><br/> })?;
+    assert_eq!(
+        nodes
+            .iter()
+            .map(|n| n.to_token_stream().to_string())
+            .collect::<Vec<_>>(),
+        expected
+            .iter()
+            .map(|n| n.to_token_stream().to_string())
+            .collect::<Vec<_>>()
+    );
+
+    let config = ParserConfig::new().always_self_closed_elements(["br"].into());
+    let Node::Element(element) = rstml::node!(config = config; <br>) else {
+        panic!("expected element")
+    };
+    assert_eq!(element.name().to_string(), "br");
+
+    Ok(())
+}
+
+#[test]
+fn test_node_eq_ignores_spans() -> Result<()> {
+    let source = r#"<div a-b="x" {block}><>raw text</>{ value + 1 }<!-- "c" --></div>"#;
+    let parsed = parse2(TokenStream::from_str(source).unwrap())?;
+    let quoted = parse2(quote! {
+        <div a-b="x" {block}><>raw text</>{ value + 1 }<!-- "c" --></div>
+    })?;
+    assert_eq!(parsed, quoted);
+    assert_eq!(Node::diff_all(&parsed, &quoted), None);
+
+    let other = parse2(quote! { <div a-b-c="x" {block}><>raw text</>{ value + 1 }</div> })?;
+    assert_ne!(parsed, other);
+    let diff = Node::diff_all(&parsed, &other).unwrap();
+    assert_eq!(diff.path, "/0/div/@a-b");
+
+    let other = parse2(quote! { <div a-b="x" {block}><>raw</>{ value + 1 }</div> })?;
+    assert_eq!(Node::diff_all(&parsed, &other).unwrap().path, "/0/div/0/0");
+
+    let other = parse2(quote! { <div a-b="x" {block}><>raw text</>{ value + 1 }</div> })?;
+    let diff = Node::diff_all(&parsed, &other).unwrap();
+    assert_eq!(
+        diff.to_string(),
+        r#"/0/div/2: `< ! - - "c" - - >` != nothing"#
+    );
+
+    let diff = parse2(quote! { <br/> })?[0]
+        .diff(&parse2(quote! { <br></br> })?[0])
+        .unwrap();
+    assert_eq!(diff.path, "/0/br");
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_attributes() -> Result<()> {
+    let attributes = rstml::parse_attributes.parse2(quote! {
+        class="x" id={y} data-value=1 checked {spread}
+    })?;
+    let attributes: Vec<_> = attributes
+        .iter()
+        .map(|a| a.to_token_stream().to_string())
+        .collect();
+    assert_eq!(
+        attributes,
+        [
+            "class = \"x\"",
+            "id = { y }",
+            "data - value = 1",
+            "checked",
+            "{ spread }"
+        ]
+    );
+
+    // Parsing stops before end of tag.
+    let parser = |input: ParseStream| {
+        let attributes = rstml::parse_attributes(input)?;
+        input.parse::<rstml::atoms::OpenTagEnd>()?;
+        Ok(attributes)
+    };
+    assert_eq!(parser.parse2(quote! { a=1 b /> })?.len(), 2);
+
+    assert!(rstml::parse_attributes.parse2(quote! { a= }).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_element_namespace() -> Result<()> {
+    let tokens = quote! {
+        <div>
+            <svg><image href="a"><title>"t"</title></image><foreignObject><img></foreignObject></svg>
+            <math><mi>"x"</mi></math>
+            <img>
+        </div>
+    };
+    let config = ParserConfig::new().always_self_closed_elements(["img", "image"].into());
+    let nodes = Parser::new(config).parse_simple(tokens)?;
+
+    let namespaces: Vec<_> = nodes[0]
+        .clone()
+        .flatten()
+        .into_iter()
+        .filter_map(|node| match node {
+            Node::Element(e) => Some((e.name().to_string(), e.namespace)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        namespaces,
+        [
+            ("div", Namespace::Html),
+            ("svg", Namespace::Svg),
+            // Not self-closed in svg.
+            ("image", Namespace::Svg),
+            ("title", Namespace::Svg),
+            ("foreignObject", Namespace::Svg),
+            ("img", Namespace::Html),
+            ("math", Namespace::MathMl),
+            ("mi", Namespace::MathMl),
+            ("img", Namespace::Html),
+        ]
+        .map(|(name, namespace)| (name.to_string(), namespace))
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "html-validate")]
+#[test]
+fn test_html_validate() -> Result<()> {
+    use rstml::validate::{validate, WarningKind};
+
+    let tokens = quote! {
+        <table><td colspan=2 /></table>
+        <div data-id="1" onclick="f()" on:click={f} hreff="x">
+            <MyList><li /></MyList>
+            <my-element foo="bar" />
+            <svg><circle r=1 stroke-width=2 foo=3 /><foreignObject><p /></foreignObject></svg>
+            <math><mfrac><mi>"x"</mi><mn>"2"</mn></mfrac><p /></math>
+            <marquee />
+        </div>
+        <li />
+    };
+    let nodes = parse2(tokens)?;
+    let kinds: Vec<_> = validate(&nodes)
+        .into_iter()
+        .map(|w| w.kind().clone())
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            WarningKind::InvalidParent {
+                element: "td".into(),
+                parent: "table".into(),
+                expected: &["tr"],
+            },
+            WarningKind::UnknownAttribute {
+                element: "div".into(),
+                attribute: "hreff".into(),
+            },
+            WarningKind::UnknownAttribute {
+                element: "circle".into(),
+                attribute: "foo".into(),
+            },
+            WarningKind::UnknownElement {
+                element: "p".into(),
+                namespace: Namespace::MathMl,
+            },
+            WarningKind::UnknownElement {
+                element: "marquee".into(),
+                namespace: Namespace::Html,
+            },
+        ]
+    );
+
+    // Special attributes are validated too.
+    let config = ParserConfig::new().special_attributes(["hreff"].into());
+    let nodes = Parser::new(config).parse_simple(quote! { <a hreff="x" /> })?;
+    let kinds: Vec<_> = validate(&nodes)
+        .into_iter()
+        .map(|w| w.kind().clone())
+        .collect();
+    assert_eq!(
+        kinds,
+        [WarningKind::UnknownAttribute {
+            element: "a".into(),
+            attribute: "hreff".into(),
+        }]
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "style-attribute")]
+#[test]
+fn test_style_declarations() -> Result<()> {
+    let tokens = TokenStream::from_str(
+        r#"<div style=" color: red ; background: url('a;b') ;; margin:0 !important" />"#,
+    )
+    .unwrap();
+    let nodes = parse2(tokens)?;
+    let Node::Element(element) = &nodes[0] else {
+        panic!("expected element")
+    };
+    let NodeAttribute::Attribute(style) = &element.attributes()[0] else {
+        panic!("expected attribute")
+    };
+    let declarations = style.style_declarations().unwrap()?;
+    let pairs: Vec<_> = declarations
+        .iter()
+        .map(|d| (d.property(), d.value()))
+        .collect();
+    assert_eq!(
+        pairs,
+        [
+            ("color", "red"),
+            ("background", "url('a;b')"),
+            ("margin", "0 !important"),
+        ]
+    );
+    assert_eq!(declarations[0].property_range(), 1..6);
+    assert_eq!(
+        declarations[1].value_span().source_text().as_deref(),
+        Some("url('a;b')")
+    );
+
+    let nodes = parse2(quote! { <div style="color: red; margin" title="a" /> })?;
+    let Node::Element(element) = &nodes[0] else {
+        panic!("expected element")
+    };
+    let attributes: Vec<_> = element
+        .attributes()
+        .iter()
+        .map(|a| match a {
+            NodeAttribute::Attribute(a) => a.style_declarations().map(|d| d.is_ok()),
+            NodeAttribute::Block(_) => None,
+        })
+        .collect();
+    assert_eq!(attributes, [Some(false), None]);
+
+    Ok(())
+}
+
+#[test]
+fn test_class_names() -> Result<()> {
+    let tokens = TokenStream::from_str(
+        r#"<div class=" a  b-c a " /> <div class={("a", flag.then_some("b"))} /> <div class />"#,
+    )
+    .unwrap();
+    let nodes = parse2(tokens)?;
+    let classes: Vec<_> = nodes
+        .iter()
+        .map(|node| {
+            let Node::Element(element) = node else {
+                panic!("expected element")
+            };
+            let NodeAttribute::Attribute(class) = &element.attributes()[0] else {
+                panic!("expected attribute")
+            };
+            class.class_names()
+        })
+        .collect();
+
+    let Some(ClassValue::Names(names)) = &classes[0] else {
+        panic!("expected class names")
+    };
+    let names: Vec<_> = names
+        .iter()
+        .map(|n| (n.name(), n.range(), n.span().source_text().unwrap()))
+        .collect();
+    assert_eq!(
+        names,
+        [
+            ("a", 1..2, "a".to_string()),
+            ("b-c", 4..7, "b-c".to_string()),
+            ("a", 8..9, "a".to_string()),
+        ]
+    );
+    let Some(ClassValue::Opaque(expr)) = &classes[1] else {
+        panic!("expected opaque value")
+    };
+    assert_eq!(
+        expr.to_token_stream().to_string(),
+        r#"{ ("a" , flag . then_some ("b")) }"#
+    );
+    assert!(classes[2].is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_node_ids() -> Result<()> {
+    let mut nodes = parse2(quote! {
+        <div><span>"a"</span><>"b"</></div>
+        <p />
+    })?;
+    let types: Vec<_> = Node::preorder(&nodes)
+        .map(|(id, node)| (id.index(), node.r#type()))
+        .collect();
+    assert_eq!(
+        types,
+        [
+            (0, NodeType::Element),
+            (1, NodeType::Element),
+            (2, NodeType::Text),
+            (3, NodeType::Fragment),
+            (4, NodeType::Text),
+            (5, NodeType::Element),
+        ]
+    );
+    // Same order as in source map.
+    let map = SourceMap::new(&nodes);
+    assert!(
+        Node::preorder(&nodes).all(|(id, node)| map.get(id).unwrap().node_type() == node.r#type())
+    );
+
+    // Analyze, then transform.
+    let texts: Vec<_> = Node::preorder(&nodes)
+        .filter(|(_, node)| matches!(node, Node::Text(_)))
+        .map(|(id, _)| id)
+        .collect();
+    for id in texts {
+        let Some(Node::Text(text)) = Node::get_by_id_mut(&mut nodes, id) else {
+            panic!("expected text")
+        };
+        text.value = parse_quote!("changed");
+    }
+    let detached = nodes[1].clone();
+    assert_eq!(NodeTree::find_id(&nodes, &detached), None);
+    assert_eq!(
+        NodeTree::find_id(&nodes, &nodes[1]).map(|id| id.index()),
+        Some(5)
+    );
+    let (text_id, _) = Node::preorder(&nodes).nth(4).unwrap();
+    assert_eq!(
+        Node::get_by_id(&nodes, text_id)
+            .unwrap()
+            .to_token_stream()
+            .to_string(),
+        "\"changed\""
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_node_tree_index() -> Result<()> {
+    let nodes = parse2(quote! {
+        <ul><li>"a"</li><li /></ul>
+        <p />
+    })?;
+    let tree = NodeTree::index(&nodes);
+    let ids: Vec<_> = Node::preorder(&nodes).map(|(id, _)| id).collect();
+    let [ul, li_a, text, li, p] = ids[..] else {
+        panic!("expected 5 nodes")
+    };
+    assert_eq!(tree.len(), 5);
+    assert_eq!(tree.roots(), [ul, p]);
+    assert_eq!(tree.parent_of(text), Some(li_a));
+    assert_eq!(tree.parent_of(ul), None);
+    assert_eq!(tree.ancestors(text).collect::<Vec<_>>(), [li_a, ul]);
+    assert_eq!(tree.children_of(ul), [li_a, li]);
+    assert_eq!(tree.children_of(text), []);
+    assert_eq!(tree.previous_sibling(li), Some(li_a));
+    assert_eq!(tree.previous_sibling(li_a), None);
+    assert_eq!(tree.next_sibling(ul), Some(p));
+    assert_eq!(tree.next_sibling(p), None);
+    assert_eq!(tree.sibling_index(li), Some(1));
+    assert_eq!(tree.depth(text), Some(2));
+    assert_eq!(tree.depth(p), Some(0));
+
+    Ok(())
+}
+
+#[test]
+fn test_entity_segments() -> Result<()> {
+    use rstml::node::{Entity, EntityKind, TextSegment};
+
+    let segments = TextSegment::split("a &amp;b &#x1F600;&#38; & c; &foo;");
+    assert_eq!(
+        segments
+            .iter()
+            .map(|s| match s {
+                TextSegment::Text(t) => t.clone(),
+                TextSegment::Entity(e) => format!("[{}]", e.source()),
+            })
+            .collect::<String>(),
+        "a [&amp;]b [&#x1F600;][&#38;] & c; [&foo;]"
+    );
+    let TextSegment::Entity(entity) = &segments[1] else {
+        panic!("expected entity")
+    };
+    assert_eq!(entity.kind(), &EntityKind::Named("amp".into()));
+    assert_eq!(TextSegment::decode(&segments), "a &b 😀& & c; &foo;");
+    assert_eq!(
+        Entity::decode_str("&#xD800; &#99999999999;"),
+        "&#xD800; &#99999999999;"
+    );
+
+    // Source text is available.
+    let tokens = TokenStream::from_str("<p>Tom &amp; Jerry &copy; </p>").unwrap();
+    let nodes = parse2(tokens)?;
+    let Node::RawText(text) = &nodes[0].children().unwrap()[0] else {
+        panic!("expected raw text")
+    };
+    assert_eq!(text.to_string_decoded(), "Tom & Jerry © ");
+
+    // Only token stream is available.
+    let nodes = parse2(quote! { <p>Tom &amp; Jerry & co; &#38;</p> })?;
+    let Node::RawText(text) = &nodes[0].children().unwrap()[0] else {
+        panic!("expected raw text")
+    };
+    let entities: Vec<_> = text
+        .entity_segments()
+        .into_iter()
+        .filter_map(|s| match s {
+            TextSegment::Entity(e) => e.decoded(),
+            TextSegment::Text(_) => None,
+        })
+        .collect();
+    assert_eq!(entities, ['&', '&']);
+
+    Ok(())
+}
+
+#[test]
+fn test_interpolate_text() -> Result<()> {
+    let config = ParserConfig::new().interpolate_text(true);
+    let nodes = Parser::new(config.clone()).parse_simple(quote! {
+        <p>"Hello {name}, {{literal}} {items.len() + 1}!"</p>
+    })?;
+    let children: Vec<_> = nodes[0]
+        .children()
+        .unwrap()
+        .iter()
+        .map(|child| match child {
+            Node::Text(text) => format!("text:{}", text.value_string()),
+            Node::Block(block) => format!("block:{}", block.to_token_stream()),
+            _ => panic!("unexpected node"),
+        })
+        .collect();
+    assert_eq!(
+        children,
+        [
+            "text:Hello ",
+            "block:{ name }",
+            "text:, {literal} ",
+            "block:{ items . len () + 1 }",
+            "text:!",
+        ]
+    );
+
+    // Invalid segments are reported, and text is kept as is.
+    let (nodes, diagnostics) = Parser::new(config.clone())
+        .parse_recoverable(quote! { <p>"a {} b"</p><p>"unmatched {"</p><p>"{1 +}"</p> })
+        .split_vec();
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.level() == proc_macro2_diagnostics::Level::Error)
+        .count();
+    assert_eq!(errors, 3);
+    assert!(nodes
+        .iter()
+        .all(|node| matches!(node.children().unwrap()[..], [Node::Text(_)])));
+
+    // Disabled by default, and not applied to top-level text.
+    let nodes = parse2(quote! { <p>"{name}"</p> })?;
+    assert!(matches!(nodes[0].children().unwrap()[..], [Node::Text(_)]));
+    let nodes = Parser::new(config).parse_simple(quote! { "{name}" })?;
+    assert!(matches!(nodes[..], [Node::Text(_)]));
+    Ok(())
+}
+
+#[test]
+fn test_debug_tree() -> Result<()> {
+    let template = Parser::new(ParserConfig::new())
+        .parse_template(quote! {
+            <!DOCTYPE html>
+            <ul id={id} {..rest} on:click(e)>
+                <li>"a"</li>
+                <>{b}<br/></>
+            </ul>
+        })
+        .into_result()?;
+    assert_eq!(
+        template.to_debug_tree(),
+        [
+            "<!DOCTYPE html>",
+            "ul [id={ id } { .. rest } on:click(e)]",
+            "  li",
+            "    \"a\"",
+            "  <>",
+            "    { b }",
+            "    br",
+            "",
+        ]
+        .join("\n")
+    );
+    Ok(())
+}
+
+#[test]
+fn test_format_tokens() -> Result<()> {
+    let config = ParserConfig::new().interpolate_text(true);
+    let nodes = Parser::new(config).parse_simple(quote! {
+        <p>"Hello {name}, {{literal}} " {count + 1} "!"</p>
+    })?;
+    let tokens = Node::format_tokens(nodes[0].children().unwrap()).unwrap();
+    assert_eq!(
+        tokens.to_string(),
+        r#":: std :: format ! ("Hello {}, {{literal}} {}!" , { name } , { count + 1 })"#
+    );
+
+    assert!(Node::format_tokens(&nodes).is_none());
+    assert_eq!(
+        Node::<rstml::Infallible>::format_tokens(&[])
+            .unwrap()
+            .to_string(),
+        r#":: std :: format ! ("")"#
+    );
+    Ok(())
+}
+
+#[test]
+fn test_tag_sugar() -> Result<()> {
+    let config = ParserConfig::new().tag_sugar(true);
+    let tokens = TokenStream::from_str(
+        r#"<div #app.container.dark-mode title="x">
+            <span.label/>
+        </div>"#,
+    )
+    .unwrap();
+    let nodes = Parser::new(config.clone()).parse_simple(tokens)?;
+    let Node::Element(element) = &nodes[0] else {
+        panic!("expected element")
+    };
+    assert_eq!(element.name().to_string(), "div");
+    let attributes: Vec<_> = element
+        .attributes()
+        .iter()
+        .map(|a| match a {
+            NodeAttribute::Attribute(a) => (
+                a.key.to_string(),
+                a.value_literal_string().unwrap(),
+                a.is_sugar(),
+            ),
+            _ => panic!("expected keyed attribute"),
+        })
+        .collect();
+    assert_eq!(
+        attributes,
+        [
+            ("id".into(), "app".into(), true),
+            ("class".into(), "container dark-mode".into(), true),
+            ("title".into(), "x".into(), false),
+        ]
+    );
+    // Shorthands are converted to tokens as written.
+    let NodeAttribute::Attribute(class) = &element.attributes()[1] else {
+        unreachable!()
+    };
+    assert_eq!(
+        class.to_token_stream().to_string(),
+        ". container . dark - mode"
+    );
+    let Node::Element(span) = &element.children[0] else {
+        panic!("expected element")
+    };
+    assert_eq!(span.name().to_string(), "span");
+    assert_eq!(class_of(span), "label");
+
+    // Invalid shorthands are reported.
+    let (_, diagnostics) = Parser::new(config)
+        .parse_recoverable(TokenStream::from_str("<div #a #b {x}/>").unwrap())
+        .split_vec();
+    assert_eq!(diagnostics.len(), 1);
+
+    // Disabled by default.
+    let nodes = parse2(quote! { <div.app/> })?;
+    let Node::Element(element) = &nodes[0] else {
+        panic!("expected element")
+    };
+    assert_eq!(element.name().to_string(), "div.app");
+    assert!(element.attributes().is_empty());
+    Ok(())
+}
+
+fn class_of<C: CustomNode>(element: &NodeElement<C>) -> String {
+    match &element.attributes()[0] {
+        NodeAttribute::Attribute(a) if a.key.to_string() == "class" => {
+            a.value_literal_string().unwrap()
+        }
+        _ => panic!("expected class attribute"),
+    }
+}
+
+#[test]
+fn test_flag_attributes() -> Result<()> {
+    let tokens = quote! { <input disabled checked=false hidden=true value="x" /> };
+    let nodes = parse2(tokens.clone())?;
+    let Node::Element(element) = &nodes[0] else {
+        panic!("expected element")
+    };
+    let attributes: Vec<_> = element
+        .attributes()
+        .iter()
+        .map(|a| match a {
+            NodeAttribute::Attribute(a) => (a.is_flag(), a.as_bool()),
+            _ => panic!("expected keyed attribute"),
+        })
+        .collect();
+    assert_eq!(
+        attributes,
+        [
+            (true, Some(true)),
+            (false, Some(false)),
+            (false, Some(true)),
+            (false, None)
+        ]
+    );
+
+    let (nodes, diagnostics) = Parser::new(ParserConfig::new().reject_flag_attributes(true))
+        .parse_recoverable(tokens)
+        .split_vec();
+    assert_eq!(nodes.len(), 1);
+    let errors: Vec<_> = diagnostics.into_iter().map(message).collect();
+    assert_eq!(errors, ["expected value for attribute `disabled`"]);
+    Ok(())
+}
+
+#[test]
+fn test_attribute_value_parsing() -> Result<()> {
+    use rstml::AttributeValueParsing;
+
+    let value_of = |nodes: &[Node]| {
+        let Node::Element(element) = &nodes[0] else {
+            panic!("expected element")
+        };
+        let NodeAttribute::Attribute(attribute) = &element.attributes()[0] else {
+            panic!("expected attribute")
+        };
+        attribute.value().unwrap().to_token_stream().to_string()
+    };
+    let tokens = quote! { <div cond=a > b></div> };
+
+    // Conservative by default, and comparison is reported.
+    let (nodes, diagnostics) = Parser::new(ParserConfig::new())
+        .parse_recoverable(tokens.clone())
+        .split_vec();
+    assert_eq!(value_of(&nodes), "a");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].level(),
+        proc_macro2_diagnostics::Level::Warning
+    );
+
+    let greedy = ParserConfig::new().attribute_value_parsing(AttributeValueParsing::Greedy);
+    let nodes = Parser::new(greedy.clone()).parse_simple(tokens)?;
+    assert_eq!(value_of(&nodes), "a > b");
+    let nodes = Parser::new(greedy.clone()).parse_simple(quote! { <input value=x/> })?;
+    assert_eq!(value_of(&nodes), "x");
+
+    let nodes = Parser::new(greedy).parse_simple(quote! { <div attr=x>"a > b"</div> })?;
+    assert_eq!(value_of(&nodes), "x");
+    assert_eq!(nodes[0].children().unwrap().len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_unquoted_values() -> Result<()> {
+    let tokens = TokenStream::from_str(
+        "<div area=1/2 margin=10px-auto-0 sum=1/2+x n=5 block={1/2} ratio=1.5em/2/>",
+    )
+    .unwrap();
+    let nodes =
+        Parser::new(ParserConfig::new().unquoted_values(true)).parse_simple(tokens.clone())?;
+    assert_eq!(
+        nodes[0].to_debug_tree(),
+        "div [area=\"1/2\" margin=\"10px-auto-0\" sum=1 / 2 + x n=5 block={ 1 / 2 } \
+         ratio=\"1.5em/2\"]\n"
+    );
+    let Node::Element(element) = &nodes[0] else {
+        panic!("expected element")
+    };
+    let NodeAttribute::Attribute(area) = &element.attributes()[0] else {
+        panic!("expected attribute")
+    };
+    let span = syn::spanned::Spanned::span(area.value().unwrap());
+    assert_eq!((span.start().column, span.end().column), (10, 13));
+
+    // Numbers with units are kept with their units, and values with
+    // whitespace between parts are expressions.
+    let units = TokenStream::from_str(
+        "<div w=10px h=1.5em p=50% c=calc-50%-10px n=10u8 sum=1 / 2 diff=1- 2 />",
+    )
+    .unwrap();
+    let nodes = Parser::new(ParserConfig::new().unquoted_values(true)).parse_simple(units)?;
+    assert_eq!(
+        nodes[0].to_debug_tree(),
+        "div [w=\"10px\" h=\"1.5em\" p=\"50%\" c=calc - 50 % - 10px n=10u8 sum=1 / 2 diff=1 - \
+         2]\n"
+    );
+    let Node::Element(element) = &nodes[0] else {
+        panic!("expected element")
+    };
+    let NodeAttribute::Attribute(width) = &element.attributes()[0] else {
+        panic!("expected attribute")
+    };
+    assert_eq!(width.value_literal_string().as_deref(), Some("10px"));
+
+    // Values are expressions by default.
+    let nodes = Parser::new(ParserConfig::new()).parse_simple(tokens)?;
+    assert!(nodes[0]
+        .to_debug_tree()
+        .starts_with("div [area=1 / 2 margin=10px - auto - 0 "));
+    Ok(())
+}
+
+#[test]
+fn test_parsed_template() -> Result<()> {
+    let parser = Parser::new(ParserConfig::new());
+    let template = parser
+        .parse_template(quote! { <div>"a"</div> })
+        .into_result()?;
+    let Node::Element(element) = template.single_root()? else {
+        panic!("expected element")
+    };
+    assert_eq!(element.name().to_string(), "div");
+
+    let template = parser
+        .parse_template(quote! { <!-- "a" --> <div/> "b" <br/> })
+        .into_result()?;
+    assert_eq!(template.len(), 4);
+    let elements: Vec<_> = template
+        .roots_of_type(NodeType::Element)
+        .map(|node| node.to_token_stream().to_string())
+        .collect();
+    assert_eq!(elements, ["< div / >", "< br / >"]);
+    let error = template.single_root().unwrap_err();
+    assert_eq!(ParserError::code_of_error(&error), Some("E018"));
+    assert_eq!(
+        template.to_token_stream().to_string(),
+        r#"< ! - - "a" - - > < div / > "b" < br / >"#
+    );
+    assert_eq!(template.into_nodes().len(), 4);
+    Ok(())
+}
+
+#[test]
+fn test_template_config_summary() -> Result<()> {
+    let config = ParserConfig::new()
+        .recover_block(true)
+        .always_self_closed_elements(["br"].into());
+    let template = Parser::new(config.clone())
+        .parse_template(quote! { <p>"a"<br></p> })
+        .into_result()?;
+    let summary = template.config.as_deref().unwrap();
+    assert_eq!(summary, &config.summary());
+    assert!(summary.recover_block && !summary.flat_tree);
+    assert!(summary.always_self_closed_elements.contains("br"));
+
+    // Templates created from nodes don't know their config.
+    let template = rstml::node::ParsedTemplate::new(template.into_nodes());
+    assert!(template.config.is_none());
+    Ok(())
+}
+
+#[test]
+fn test_config_summary_has_every_option() {
+    // Names of top-level fields in pretty debug output.
+    fn fields(debug: String) -> Vec<String> {
+        debug
+            .lines()
+            .filter_map(|line| line.strip_prefix("    "))
+            .filter(|line| !line.starts_with(' '))
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+    const CALLBACKS: &[&str] = &[
+        "element_close_wildcard",
+        "error_message",
+        "element_name_check",
+        "attribute_name_check",
+        "on_element_closed",
+    ];
+    let config = ParserConfig::new().max_depth(3).reject_empty_blocks(true);
+    let summary = fields(format!("{:#?}", config.summary()));
+    for field in fields(format!("{config:#?}")) {
+        assert!(
+            CALLBACKS.contains(&field.as_str()) || summary.contains(&field),
+            "`{field}` of ParserConfig is missing in ConfigSummary"
+        );
+    }
+    assert_eq!(config.summary().max_depth, Some(3));
+    assert!(config.summary().reject_empty_blocks);
+}
+
+#[test]
+fn test_element_builder() -> Result<()> {
+    let source = TokenStream::from_str("site <ui::Icon/>").unwrap();
+    let mut tokens = source.into_iter();
+    let site = tokens.next().unwrap().span();
+    let children = parse2(tokens.collect())?;
+
+    let element = NodeElement::<rstml::Infallible>::builder("my-wrapper")
+        .attribute("class", "outer")
+        .flag("hidden")
+        .children(children)
+        .span(site)
+        .build();
+    assert_eq!(
+        Node::Element(element.clone()).to_debug_tree(),
+        "my-wrapper [class=\"outer\" hidden]\n  ui::Icon\n"
+    );
+    assert_eq!(element.kind, ElementKind::HtmlElement);
+    let Node::Element(icon) = &element.children[0] else {
+        panic!("expected element")
+    };
+    assert_eq!(icon.kind, ElementKind::Component);
+
+    // Synthesized tokens point to the chosen span, children keep their own.
+    use syn::spanned::Spanned;
+    let NodeAttribute::Attribute(hidden) = &element.attributes()[1] else {
+        panic!("expected keyed attribute")
+    };
+    for span in [
+        element.open_tag.token_lt.span,
+        element.name().span(),
+        hidden.key.span(),
+        element.close_tag.as_ref().unwrap().span(),
+    ] {
+        assert_eq!(span.start().column, 0);
+    }
+    assert_eq!(element.children[0].span().start().column, 5);
+
+    let element = NodeElement::<rstml::Infallible>::builder("label")
+        .child(
+            NodeElement::builder("input")
+                .attribute("value", quote!(x + 1))
+                .self_closed(true)
+                .build(),
+        )
+        .child(NodeText {
+            value: parse_quote!("text"),
+        })
+        .build();
+    assert_eq!(
+        element.to_token_stream().to_string(),
+        quote! { <label><input value=x + 1/>"text"</label> }.to_string()
+    );
+
+    // Built elements are equal to parsed ones.
+    let built = NodeElement::builder("div")
+        .attribute("a", "1")
+        .self_closed(true)
+        .build();
+    let parsed = rstml::node!(<div a="1"/>);
+    assert_eq!(parsed.diff(&Node::Element(built.clone())), None);
+    assert_eq!(parsed, Node::Element(built));
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "invalid value of attribute `a`")]
+fn test_element_builder_invalid_value() {
+    let builder = NodeElement::<rstml::Infallible>::builder("div").attribute("a", quote!(=));
+    // Value is checked only when element is built.
+    builder.build();
+}
+
+#[test]
+fn test_element_builder_try_build() {
+    let error = NodeElement::<rstml::Infallible>::builder("div")
+        .attribute("a", quote!(=))
+        .try_build()
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .starts_with("invalid value of attribute `a`"));
+
+    let error = NodeElement::<rstml::Infallible>::builder("div")
+        .flag("a b")
+        .try_build()
+        .unwrap_err();
+    assert!(error.to_string().starts_with("invalid name `a b`"));
+    let error = NodeElement::<rstml::Infallible>::builder("")
+        .try_build()
+        .unwrap_err();
+    assert!(error.to_string().starts_with("invalid name ``"));
+
+    let element = NodeElement::<rstml::Infallible>::builder("div")
+        .flag("hidden")
+        .try_build()
+        .expect("valid element");
+    assert_eq!(element.name().to_string(), "div");
+}
+
+#[test]
+fn test_name_validity() {
+    use rstml::{
+        node::{NameError, NameKind},
+        NamePolicy,
+    };
+
+    for (name, element, attribute) in [
+        ("div", true, true),
+        ("ui::Button", true, true),
+        ("data-user_id", true, true),
+        ("1a", true, false),
+        ("-x", true, false),
+        ("on:click", true, true),
+        ("type", true, true),
+        ("a b", false, false),
+        ("a=", false, false),
+        ("", false, false),
+    ] {
+        assert_eq!(
+            NodeName::is_valid(name, NameKind::Element),
+            element,
+            "{name}"
+        );
+        assert_eq!(
+            NodeName::is_valid(name, NameKind::Attribute),
+            attribute,
+            "{name}"
+        );
+        // Same names are accepted by parser.
+        let tokens = TokenStream::from_str(&format!("<{name}/>")).unwrap();
+        let parsed = parse2(tokens).ok().and_then(|nodes| match &nodes[..] {
+            [Node::Element(e)] => Some(e.name().to_string()),
+            _ => None,
+        });
+        assert_eq!(parsed.as_deref() == Some(name), element, "{name}");
+    }
+    assert_eq!(
+        NodeName::validity("\"a", NameKind::Element),
+        Err(NameError::Tokenize("\"a".into()))
+    );
+
+    let error = NodeName::validity_with_policy("é-x", NameKind::Attribute, NamePolicy::Xml);
+    assert!(error.is_ok());
+    let error = NodeName::validity_with_policy("x-é", NameKind::Element, NamePolicy::Html);
+    assert!(error.is_ok());
+    let error = NodeName::validity_with_policy("é", NameKind::Element, NamePolicy::Html);
+    assert_eq!(
+        error.unwrap_err().to_string(),
+        "character `é` in name `é` is not allowed by HTML rules"
+    );
+}
+
+#[test]
+fn test_attribute_value_generics() -> Result<()> {
+    let parse = |tokens: TokenStream| {
+        let (nodes, diagnostics) = Parser::new(ParserConfig::new())
+            .parse_recoverable(tokens)
+            .split_vec();
+        let Node::Element(element) = &nodes[0] else {
+            panic!("expected element")
+        };
+        let values: Vec<_> = element
+            .attributes()
+            .iter()
+            .map(|attribute| {
+                let NodeAttribute::Attribute(attribute) = attribute else {
+                    panic!("expected attribute")
+                };
+                attribute.value().unwrap().to_token_stream().to_string()
+            })
+            .collect();
+        let codes: Vec<_> = diagnostics
+            .iter()
+            .filter_map(ParserError::code_of)
+            .collect();
+        (values, codes, nodes.len())
+    };
+
+    // Any tokens are allowed in braces.
+    let (values, diagnostics, nodes) = parse(quote! {
+        <div on:click={|e| if e.x > 3 { a >> 2 } else { <T as Tr>::X }} w={a >= b}></div><br/>
+    });
+    assert_eq!(
+        values,
+        [
+            "{ | e | if e . x > 3 { a >> 2 } else { < T as Tr > :: X } }",
+            "{ a >= b }"
+        ]
+    );
+    assert_eq!((diagnostics.len(), nodes), (0, 2));
+
+    // `>` that closes generics is not the end of tag.
+    let (values, diagnostics, nodes) = parse(quote! {
+        <div
+            a=Vec::<u8>::new()
+            b=Vec::<Vec<u8>>::new()
+            c=HashMap::<K, V>::new()
+            d=|e: Event<Click>| e.id
+            e=<T as Tr>::X
+            f=(a > b)
+        ></div><br/>
+    });
+    assert_eq!(
+        values,
+        [
+            "Vec :: < u8 > :: new ()",
+            "Vec :: < Vec < u8 > > :: new ()",
+            "HashMap :: < K , V > :: new ()",
+            "| e : Event < Click > | e . id",
+            "< T as Tr > :: X",
+            "(a > b)"
+        ]
+    );
+    assert_eq!((diagnostics.len(), nodes), (0, 2));
+
+    // Comparison is still ambiguous.
+    let (values, diagnostics, _) = parse(quote! { <div x=a >= b></div> });
+    assert_eq!(values, ["a"]);
+    assert_eq!(diagnostics, ["E012"]);
+    Ok(())
+}
+
+#[test]
+fn test_element_self_closed() -> Result<()> {
+    let config = ParserConfig::new().always_self_closed_elements(["br"].into());
+    let nodes = Parser::new(config).parse_simple(quote! { <div/><div></div><br><br/> })?;
+    let closing: Vec<_> = nodes
+        .iter()
+        .map(|node| match node {
+            Node::Element(e) => (e.is_self_closed(), e.has_close_tag()),
+            _ => panic!("expected element"),
+        })
+        .collect();
+    assert_eq!(
+        closing,
+        [(true, false), (false, true), (false, false), (true, false)]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_attribute_positions() -> Result<()> {
+    let config = ParserConfig::new().tag_sugar(true);
+    let nodes = Parser::new(config).parse_simple(quote! { <div.a b {..c} key=1 d></div> })?;
+    let Node::Element(mut element) = nodes.into_iter().next().unwrap() else {
+        panic!("expected element")
+    };
+    let positions: Vec<_> = element.attributes().iter().map(|a| a.position()).collect();
+    assert_eq!(positions, [Some(0), Some(1), None, Some(3), Some(4)]);
+
+    // Move `key` first, and drop block attributes.
+    let key = element.attributes_mut().remove(3);
+    element.insert_attribute(0, key);
+    element.retain_attributes(|a| matches!(a, NodeAttribute::Attribute(_)));
+    let attributes: Vec<_> = element
+        .attributes()
+        .iter()
+        .map(|a| (a.to_token_stream().to_string(), a.position()))
+        .collect();
+    assert_eq!(
+        attributes,
+        [
+            ("key = 1".to_string(), Some(3)),
+            (". a".to_string(), Some(0)),
+            ("b".to_string(), Some(1)),
+            ("d".to_string(), Some(4)),
+        ]
+    );
+
+    // Positions are not compared.
+    let reordered = parse2(quote! { <div key=1 b d></div> })?;
+    let Node::Element(reordered) = &reordered[0] else {
+        panic!("expected element")
+    };
+    assert_eq!(element.attributes()[0], reordered.attributes()[0]);
+    assert_ne!(element.attributes()[0].position(), Some(0));
+    Ok(())
+}
+
+#[test]
+fn test_element_mutators() -> Result<()> {
+    let nodes = Parser::new(ParserConfig::new())
+        .parse_simple(quote! { <ul class="a" key=1><li>"a"</li><li>"b"</li></ul> })?;
+    let Node::Element(mut element) = nodes.into_iter().next().unwrap() else {
+        panic!("expected element")
+    };
+
+    let key = element.remove_attribute("key").unwrap();
+    assert_eq!(key.value_literal_string(), Some("1".to_string()));
+    assert!(element.remove_attribute("key").is_none());
+
+    let replacement = Parser::new(ParserConfig::new()).parse_simple(quote! { <li>"c"</li> "d" })?;
+    assert!(element.replace_child(2, replacement.clone()).is_none());
+    assert_eq!(element.children.len(), 2);
+    let replaced = element.replace_child(1, replacement).unwrap();
+    assert_eq!(
+        replaced.to_token_stream().to_string(),
+        r#"< li > "b" < / li >"#
+    );
+
+    let wrapper = element.wrap_children(parse_quote!(Wrapper));
+    assert!(wrapper.is_component());
+    assert_eq!(wrapper.children.len(), 3);
+    assert_eq!(
+        element.to_token_stream().to_string(),
+        r#"< ul class = "a" > < Wrapper > < li > "a" < / li > < li > "c" < / li > "d" < / Wrapper > < / ul >"#
+    );
+    Ok(())
+}
+
+#[test]
+fn test_special_attributes() -> Result<()> {
+    let config = ParserConfig::new().special_attributes(["key", "ref"].into());
+    let tokens = quote! { <ul><li class="a" key={id} ref={node} {..rest}></li></ul> };
+    let nodes = Parser::new(config).parse_simple(tokens.clone())?;
+    let element = match &nodes[0].children().unwrap()[0] {
+        Node::Element(element) => element,
+        _ => panic!("expected element"),
+    };
+    assert_eq!(element.attributes().len(), 2);
+    let keys: Vec<_> = element
+        .special_attributes
+        .iter()
+        .map(|a| a.key.to_string())
+        .collect();
+    assert_eq!(keys, ["key", "ref"]);
+    assert!(element.special_attribute("key").unwrap().value().is_some());
+    assert!(element.special_attribute("class").is_none());
+    // Special attributes are emitted in source order.
+    assert_eq!(nodes[0].to_token_stream().to_string(), tokens.to_string());
+
+    // Passes over the tree see special attributes too.
+    assert_eq!(element.all_attributes().len(), 4);
+    assert_eq!(
+        nodes[0].to_debug_tree(),
+        "ul\n  li [class=\"a\" key={ id } ref={ node } { .. rest }]\n"
+    );
+    let attributes: Vec<_> = rstml::events::events(&nodes)
+        .into_iter()
+        .filter_map(|event| match event {
+            rstml::events::Event::Attribute(a) => Some(a.to_token_stream().to_string()),
+            rstml::events::Event::SpecialAttribute(a) => Some(format!("special {}", a.key)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        attributes,
+        ["class = \"a\"", "special key", "special ref", "{ .. rest }"]
+    );
+    let other = parse2(quote! { <ul><li class="a" key={other} ref={node} {..rest}></li></ul> })?;
+    assert_eq!(
+        nodes[0].diff(&other[0]).unwrap().to_string(),
+        "/0/ul/0/li/@key: `key = { id }` != `key = { other }`"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_include_resolver() -> Result<()> {
+    use rstml::include::IncludeResolver;
+
+    let parser = Parser::new(ParserConfig::new());
+    let mut nodes = parser.parse_simple(quote! {
+        <main><include src="page"/><include src="missing"/><include/></main>
+    })?;
+    let diagnostics = IncludeResolver::new(|src| match src {
+        "page" => Ok(r#"<h1>"a"</h1><include src="footer"/>"#.into()),
+        "footer" => Ok(r#"<footer/>"#.into()),
+        "loop" => Ok(r#"<include src="loop"/>"#.into()),
+        _ => Err("not found".into()),
+    })
+    .resolve(&parser, &mut nodes);
+    assert_eq!(
+        nodes[0].to_debug_tree(),
+        "main\n  h1\n    \"a\"\n  footer\n"
+    );
+    let messages: Vec<_> = diagnostics
+        .into_iter()
+        .filter(|d| d.level() == proc_macro2_diagnostics::Level::Error)
+        .map(|d| syn::Error::from(d).to_string())
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "failed to include `missing`: not found",
+            "`include` should have `src` attribute with string literal",
+        ]
+    );
+
+    let mut nodes = parser.parse_simple(quote! { <include src="loop"/> })?;
+    let diagnostics = IncludeResolver::new(|_| Ok(r#"<include src="loop"/>"#.into()))
+        .max_depth(3)
+        .resolve(&parser, &mut nodes);
+    assert!(nodes.is_empty());
+    let last = diagnostics.last().unwrap().clone();
+    assert_eq!(
+        syn::Error::from(last).to_string(),
+        "`include` is nested deeper than 3 levels"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_reparse() -> Result<()> {
+    let parser = Parser::new(ParserConfig::new());
+    let parse = |source: &str| parser.parse_simple(TokenStream::from_str(source).unwrap());
+    let tree = |nodes: &[Node]| nodes.iter().map(Node::to_debug_tree).collect::<String>();
+
+    let old_source = r#"<a/> "t" <b>"x"</b> <c/>"#;
+    let edits = [
+        // Changed text inside of `b`.
+        (14..15, r#"<a/> "t" <b>"yz"</b> <c/>"#, 1),
+        // Inserted node at the end.
+        (24..24, r#"<a/> "t" <b>"x"</b> <c/><d/>"#, 4),
+        // Text before the edit is parsed again.
+        (9..9, r#"<a/> "t" "u" <b>"x"</b> <c/>"#, 1),
+    ];
+    for (changed, new_source, reused) in edits {
+        let old = parse(old_source)?;
+        let kept: Vec<_> = old[..reused].to_vec();
+        let new = parser.reparse(old, new_source, changed).into_result()?;
+        assert_eq!(tree(&new), tree(&parse(new_source)?));
+        assert_eq!(&new[..reused], &kept[..]);
+    }
+
+    // Children of single root before the edit are reused, and only elements
+    // after the edit are closed again.
+    let closed = std::rc::Rc::new(std::cell::RefCell::new(vec![]));
+    let names = closed.clone();
+    let parser = Parser::new(ParserConfig::new().on_element_closed(move |element| {
+        names.borrow_mut().push(element.name().to_string());
+        Ok(())
+    }));
+    let old_source = r#"<ul><li>"a"</li><li><b/>"b"</li><li>"c"</li></ul>"#;
+    let edits = [
+        // Changed text inside of the second `li`.
+        (
+            25..26,
+            r#"<ul><li>"a"</li><li><b/>"x"</li><li>"c"</li></ul>"#,
+            &["li", "li", "ul"][..],
+        ),
+        // Inserted element after the last `li`.
+        (
+            44..44,
+            r#"<ul><li>"a"</li><li><b/>"b"</li><li>"c"</li><p/></ul>"#,
+            &["p", "ul"],
+        ),
+        // Close tag of root is changed.
+        (
+            46..48,
+            r#"<ul><li>"a"</li><li><b/>"b"</li><li>"c"</li></ol>"#,
+            &["li", "b", "li", "li", "ul"],
+        ),
+    ];
+    for (changed, new_source, expected_closed) in edits {
+        let old = parser
+            .parse_simple(TokenStream::from_str(old_source).unwrap())
+            .unwrap();
+        closed.borrow_mut().clear();
+        let (new, _) = parser.reparse(old, new_source, changed).split_vec();
+        assert_eq!(&*closed.borrow(), expected_closed);
+        let (expected, _) = parser
+            .parse_recoverable(TokenStream::from_str(new_source).unwrap())
+            .split_vec();
+        assert_eq!(tree(&new), tree(&expected));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_interned_names() -> Result<()> {
+    let nodes = parse2(quote! { <div class="a"><div class="b" id="c"/></div><span class="d"/> })?;
+    let outer = get_element(&nodes, 0);
+    let Node::Element(inner) = &outer.children[0] else {
+        panic!("expected element")
+    };
+    let span = get_element(&nodes, 1);
+
+    let (outer_name, inner_name) = (outer.interned_name.as_ref(), inner.interned_name.as_ref());
+    assert!(rstml::node::Name::ptr_eq(
+        outer_name.unwrap(),
+        inner_name.unwrap()
+    ));
+    assert_eq!(span.interned_name.as_ref().unwrap(), "span");
+
+    let keys: Vec<_> = [outer, inner, span]
+        .iter()
+        .flat_map(|element| element.attributes())
+        .filter_map(|attribute| match attribute {
+            NodeAttribute::Attribute(attribute) => attribute.interned_key.clone(),
+            NodeAttribute::Block(_) => None,
+        })
+        .collect();
+    assert_eq!(keys, ["class", "class", "id", "class"]);
+    assert!(rstml::node::Name::ptr_eq(&keys[0], &keys[3]));
+
+    // Hash is consistent with equality.
+    let names: std::collections::HashSet<_> = [outer.name(), inner.name(), span.name()].into();
+    assert_eq!(names.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_parse_arena() -> Result<()> {
+    let tokens = quote! { <div><p>"a"</p><p>"b"</p></div> <br/> };
+    let mut arena = Parser::new(ParserConfig::new())
+        .parse_arena(tokens)
+        .into_result()?;
+    assert_eq!(arena.len(), 6);
+    let div = arena.roots()[0];
+    let (first, second) = (arena.children(div)[0], arena.children(div)[1]);
+    assert_eq!(arena.parent(arena.children(first)[0]), Some(first));
+    assert!(arena.get(div).children().unwrap().is_empty());
+
+    // Mutation in place, and moving node between parents.
+    if let Node::Element(p) = arena.get_mut(first) {
+        p.open_tag.name = parse_quote!(span);
+    }
+    arena.detach(second);
+    let br = arena.roots()[1];
+    arena.append_child(br, rstml::node!(<i/>));
+    assert_eq!(arena.parent(second), None);
+
+    let nodes = arena.into_nodes();
+    assert_eq!(
+        nodes.iter().map(Node::to_debug_tree).collect::<String>(),
+        "div\n  span\n    \"a\"\nbr\n  i\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_node_metadata() -> Result<()> {
+    use rstml::node::{MetadataKey, NodeMetadata};
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Depth(usize);
+    #[derive(Debug, PartialEq)]
+    struct Dynamic;
+
+    let nodes = parse2(quote! { <div id={id}><p>{text}</p></div> })?;
+    let tree = NodeTree::index(&nodes);
+    let mut metadata = NodeMetadata::new();
+    for (id, node) in Node::preorder(&nodes) {
+        for &child in tree.children_of(id) {
+            let depth = metadata.get_or_default::<Depth>(id).0;
+            *metadata.get_or_default::<Depth>(child) = Depth(depth + 1);
+        }
+        if let Node::Block(_) = node {
+            metadata.insert(id, Dynamic);
+        }
+    }
+    let (div, _) = Node::preorder(&nodes).next().unwrap();
+    metadata.insert(MetadataKey::attribute(div, 0), Dynamic);
+
+    let depths: Vec<_> = metadata.iter::<Depth>().map(|(_, d)| d.0).collect();
+    assert_eq!(depths, [0, 1, 2]);
+    let dynamic: Vec<_> = metadata.iter::<Dynamic>().map(|(key, _)| key).collect();
+    assert_eq!(dynamic.len(), 2);
+    let (text, _) = Node::preorder(&nodes).nth(2).unwrap();
+    assert_eq!(
+        dynamic,
+        [MetadataKey::Node(text), MetadataKey::attribute(div, 0)]
+    );
+
+    assert_eq!(metadata.insert(div, Depth(5)), Some(Depth(0)));
+    metadata.remove_node(div);
+    assert!(!metadata.contains::<Depth>(div));
+    assert!(!metadata.contains::<Dynamic>(MetadataKey::attribute(div, 0)));
+    assert_eq!(metadata.len(), 3);
+    Ok(())
+}
+
+#[test]
+fn test_is_static() -> Result<()> {
+    let nodes = parse2(quote! {
+        <!DOCTYPE html>
+        <div id="main" width=10 hidden><!-- "c" --><>"text" raw text</></div>
+        <div><p>{value}</p></div>
+        <div {..rest}></div>
+        <div class={class}></div>
+        <input bind:value(x)/>
+        <Button/>
+    })?;
+    let flags: Vec<_> = nodes.iter().map(Node::is_static).collect();
+    assert_eq!(flags, [true, true, false, false, false, false, false]);
+    Ok(())
+}
+
+#[test]
+fn test_raw_blocks() -> Result<()> {
+    let tokens = quote! { <div {..rest} value={ a, b }>{ x, y } {z}</div> };
+    let parser = Parser::new(ParserConfig::new().raw_blocks(true));
+    let nodes = parser.parse_simple(tokens.clone())?;
+    let element = get_element(&nodes, 0);
+
+    let Some(NodeAttribute::Block(NodeBlock::Invalid(rest))) = element.attributes().first() else {
+        panic!("expected raw attribute block")
+    };
+    assert_eq!(rest.body.to_string(), ".. rest");
+    let value = get_element_attribute(&nodes, 0, 1);
+    let KeyedAttributeValue::Value(AttributeValueExpr {
+        value: KVAttributeValue::InvalidBraced(value),
+        ..
+    }) = &value.possible_value
+    else {
+        panic!("expected raw attribute value")
+    };
+    assert_eq!(value.body.to_string(), "a , b");
+    let blocks: Vec<_> = element
+        .children
+        .iter()
+        .map(|child| match child {
+            Node::Block(NodeBlock::Invalid(block)) => block.body.to_string(),
+            _ => panic!("expected raw block"),
+        })
+        .collect();
+    assert_eq!(blocks, ["x , y", "z"]);
+    assert_eq!(nodes[0].to_token_stream().to_string(), tokens.to_string());
+    Ok(())
+}
+
+#[test]
+fn test_raw_text_string_literal() -> Result<()> {
+    let config = ParserConfig::new().raw_text_elements(["script", "style"].into());
+    let parser = Parser::new(config);
+    let nodes = parser.parse_simple(quote! {
+        <script>r#"let font = 'Open Sans';"#</script>
+        <style>p { color: red; }</style>
+    })?;
+    let Node::Text(text) = get_element_child(&nodes, 0, 0) else {
+        panic!("expected text")
+    };
+    assert_eq!(text.value_string(), "let font = 'Open Sans';");
+    assert!(matches!(get_element_child(&nodes, 1, 0), Node::RawText(_)));
+
+    let (_, errors) = parser
+        .parse_str("<div>\n  <style>p { font-family: 'Open Sans'; }</style>\n</div>")
+        .split_vec();
+    assert_eq!(
+        errors.iter().map(ParserError::code_of).collect::<Vec<_>>(),
+        [Some("E029")]
+    );
+    let error: syn::Error = errors[0].clone().into();
+    assert!(error
+        .to_string()
+        .starts_with("failed to tokenize template at 2:"));
+    Ok(())
+}
+
+#[test]
+fn test_node_parse_with() -> Result<()> {
+    let config = ParserConfig::new().always_self_closed_elements(["br"].into());
+    let parser = |input: ParseStream| {
+        let node = Node::parse_with(&config)(input)?;
+        input.parse::<syn::Token![,]>()?;
+        let rest: syn::Ident = input.parse()?;
+        Ok((node, rest))
+    };
+    let (node, rest) = parser.parse2(quote! { <br>, rest })?;
+    assert_eq!(node.to_debug_tree(), "br\n");
+    assert_eq!(rest, "rest");
+
+    let error = Node::parse_with(&config)
+        .parse2(quote! { <div></span> })
+        .unwrap_err();
+    assert_eq!(ParserError::code_of_error(&error), Some("E002"));
+    Ok(())
+}
+
+#[test]
+fn test_parse_on_worker_threads() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<ParserError>();
+    assert_send_sync::<SourceMap>();
+    assert_send_sync::<rstml::node::Name>();
+    assert_send_sync::<rstml::node::NameInterner>();
+    assert_send_sync::<rstml::node::NodeId>();
+    assert_send_sync::<rstml::arena::NodeRef>();
+    assert_send_sync::<NodeType>();
+    assert_send_sync::<ElementKind>();
+    assert_send_sync::<Namespace>();
+    assert_send_sync::<rstml::node::Directive>();
+    #[cfg(feature = "dom")]
+    assert_send_sync::<rstml::dom::DomNode>();
+
+    // Every thread tokenizes and parses its own template, only strings are
+    // sent back.
+    let sources = ["<div>\"a\"</div>", "<p><br/></p>", "<ul><li/></ul>"];
+    let trees: Vec<String> = std::thread::scope(|scope| {
+        let handles: Vec<_> = sources
+            .iter()
+            .map(|source| {
+                scope.spawn(move || {
+                    let parser = Parser::new(ParserConfig::new());
+                    let nodes = parser
+                        .parse_simple(TokenStream::from_str(source).unwrap())
+                        .unwrap();
+                    nodes[0].to_debug_tree()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert_eq!(trees, ["div\n  \"a\"\n", "p\n  br\n", "ul\n  li\n"]);
+}
+
+#[test]
+fn test_strict_self_closing() -> Result<()> {
+    let config = ParserConfig::new()
+        .always_self_closed_elements(["br"].into())
+        .strict_self_closing(true);
+    let parser = Parser::new(config);
+    parser.parse_simple(quote! { <div><br/><svg><br></br></svg></div> })?;
+
+    let (nodes, diagnostics) = parser
+        .parse_recoverable(quote! { <div><br>"a"</div> })
+        .split_vec();
+    assert_eq!(nodes[0].children().unwrap().len(), 2);
+    let errors: Vec<_> = diagnostics
+        .iter()
+        .map(|d| (ParserError::code_of(d), message(d.clone())))
+        .filter(|(code, _)| code.is_some())
+        .collect();
+    assert_eq!(
+        errors,
+        [(
+            Some("E023"),
+            "void element `br` should be self-closed: `<br/>`".to_string()
+        )]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_preserve_tokens() -> Result<()> {
+    let config = ParserConfig::new()
+        .preserve_tokens(true)
+        .on_element_closed(|element| {
+            element.children.clear();
+            Ok(())
+        });
+    let tokens = quote! { <div><p>"a"</p><>"b"</></div> };
+    let nodes = Parser::new(config).parse_simple(tokens.clone())?;
+    assert_eq!(nodes[0].tokens().to_string(), tokens.to_string());
+    assert_eq!(nodes[0].to_token_stream().to_string(), "< div > < / div >");
+
+    // Without the flag, tokens are produced from nodes.
+    let nodes = parse2(quote! { <div><p>"a"</p><>"b"</></div> })?;
+    assert_eq!(nodes[0].tokens().to_string(), tokens.to_string());
+    let Node::Element(div) = &nodes[0] else {
+        panic!("expected element")
+    };
+    assert!(div.source_tokens.get().is_none());
+    assert_eq!(div.children[1].tokens().to_string(), r#"< > "b" < / >"#);
+    Ok(())
+}
+
+#[cfg(feature = "format")]
+#[test]
+fn test_format() -> Result<()> {
+    use rstml::format::{format, FormatOptions, SelfClosing};
+
+    let source = r#"<><input type="text" value={ value+1 } on:input=move |e| set(e)/>
+        <span></span>  <ul><li class="a"  hidden>"one"</li></ul></>"#;
+    let nodes = parse2(TokenStream::from_str(source).unwrap())?;
+    let options = FormatOptions {
+        indent: 2,
+        max_width: 40,
+        self_closing: SelfClosing::Always,
+    };
+    assert_eq!(
+        format(&nodes, source, &options),
+        r#"<>
+  <input
+    type="text"
+    value={ value+1 }
+    on:input=move |e| set(e)
+  />
+  <span />
+  <ul>
+    <li class="a" hidden>"one"</li>
+  </ul>
+</>"#
+    );
+
+    let options = FormatOptions {
+        self_closing: SelfClosing::Never,
+        ..Default::default()
+    };
+    let source = "<div/><br>";
+    let config = ParserConfig::new().always_self_closed_elements(["br"].into());
+    let nodes = Parser::new(config).parse_simple(TokenStream::from_str(source).unwrap())?;
+    assert_eq!(format(&nodes, source, &options), "<div></div>\n<br>");
+
+    // Generics of close tags are kept, so output can be parsed again.
+    let source = r#"<foo<T>>"x"</foo<T>><bar<U: Clone>/>"#;
+    let nodes = parse2(TokenStream::from_str(source).unwrap())?;
+    let formatted = format(&nodes, source, &options);
+    assert_eq!(
+        formatted,
+        "<foo<T>>\"x\"</foo<T>>\n<bar<U: Clone>></bar<U: Clone>>"
+    );
+    let reparsed = parse2(TokenStream::from_str(&formatted).unwrap())?;
+    assert_eq!(reparsed[0], nodes[0]);
+
+    let source = r#"<li key=1 class="a"/>"#;
+    let config = ParserConfig::new().special_attributes(["key"].into());
+    let nodes = Parser::new(config).parse_simple(TokenStream::from_str(source).unwrap())?;
+    assert_eq!(
+        format(&nodes, source, &FormatOptions::default()),
+        r#"<li key=1 class="a" />"#
+    );
+
+    // Comments and doctypes are written as in source, continuation lines of
+    // blocks are indented to the level of block.
+    let source = r#"<!DOCTYPE html>
+<!--  "c"  -->
+<div>
+        {
+            let x = 1;
+
+            x
+        }
+</div>"#;
+    let nodes = parse2(TokenStream::from_str(source).unwrap())?;
+    assert_eq!(
+        format(&nodes, source, &FormatOptions::default()),
+        r#"<!DOCTYPE html>
+<!-- "c" -->
+<div>
+    {
+        let x = 1;
+
+        x
+    }
+</div>"#
+    );
+    Ok(())
+}
+
+#[cfg(feature = "lint")]
+#[test]
+fn test_lint() -> Result<()> {
+    use proc_macro2_diagnostics::Diagnostic;
+    use rstml::lint::{lint, Linter, Rule};
+    use syn::spanned::Spanned;
+
+    // Warnings are formatted with level prefix.
+    let assert_messages = |diagnostics: Vec<Diagnostic>, expected: &[&str]| {
+        assert_eq!(diagnostics.len(), expected.len());
+        for (diagnostic, expected) in diagnostics.into_iter().zip(expected) {
+            let message = syn::Error::from(diagnostic).to_string();
+            assert!(message.contains(expected), "{message}");
+        }
+    };
+    let nodes = parse2(quote! {
+        <div class="a" class="b">
+            <img src="a.png" {..rest} />
+            <img src="b.png" />
+            <Button />
+            <svg><linearGradient /></svg>
+            <sPan />
+        </div>
+        { return }
+        <p />
+    })?;
+    assert_messages(
+        lint(&nodes),
+        &[
+            "unreachable node after block that never completes",
+            "duplicate attribute `class`",
+            "`img` element should have `alt` attribute",
+            "html element name `sPan` should be lowercase: `span`",
+        ],
+    );
+
+    // Special attributes are checked too.
+    let config = ParserConfig::new().special_attributes(["key"].into());
+    let special = Parser::new(config).parse_simple(quote! { <li key=1 class="a" key=2 /> })?;
+    assert_messages(lint(&special), &["duplicate attribute `key`"]);
+
+    // Custom rules.
+    struct NoDiv;
+    impl Rule for NoDiv {
+        fn check_node(&self, node: &Node, diagnostics: &mut Vec<Diagnostic>) {
+            if let Node::Element(e) = node {
+                if e.name().to_string() == "div" {
+                    diagnostics.push(Diagnostic::spanned(
+                        e.name().span(),
+                        proc_macro2_diagnostics::Level::Error,
+                        "div is not allowed",
+                    ));
+                }
+            }
+        }
+    }
+    let diagnostics = Linter::new().rule(NoDiv).lint(&nodes);
+    assert_messages(diagnostics, &["div is not allowed"]);
+    Ok(())
+}
+
+#[cfg(feature = "dom")]
+#[test]
+fn test_dom_conversion() -> Result<()> {
+    use rstml::dom::{DomAttribute, DomElement, DomNode};
+
+    let nodes = parse2(quote! {
+        <!DOCTYPE html>
+        <><!-- "note" --><input data-id=1 disabled/></>
+    })?;
+    let dom = DomNode::from_nodes(&nodes)?;
+    assert_eq!(
+        dom,
+        [
+            DomNode::Doctype("html".into()),
+            DomNode::Comment("note".into()),
+            DomNode::Element(DomElement {
+                name: "input".into(),
+                attributes: vec![
+                    DomAttribute {
+                        name: "data-id".into(),
+                        value: Some("1".into()),
+                    },
+                    DomAttribute {
+                        name: "disabled".into(),
+                        value: None,
+                    },
+                ],
+                children: vec![],
+            }),
+        ]
+    );
+    // Round trip keeps DOM the same.
+    let nodes: Vec<Node> = DomNode::to_nodes(&dom)?;
+    assert_eq!(DomNode::from_nodes(&nodes)?, dom);
+    let node = Node::<rstml::Infallible>::try_from(&DomNode::Text("a \"b\"".into()))?;
+    assert_eq!(node.to_token_stream().to_string(), r#""a \"b\"""#);
+
+    // Special attributes are kept at their positions.
+    let config = ParserConfig::new().special_attributes(["key"].into());
+    let nodes = Parser::new(config).parse_simple(quote! { <li class="a" key="b" /> })?;
+    let [DomNode::Element(element)] = &DomNode::from_nodes(&nodes)?[..] else {
+        panic!("expected element")
+    };
+    let names: Vec<_> = element.attributes.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(names, ["class", "key"]);
+
+    // Dynamic content can't be converted.
+    let nodes = parse2(quote! { <div class=name>{value}</div> })?;
+    assert!(DomNode::from_nodes(&nodes).is_err());
+    let element = DomNode::Element(DomElement {
+        name: "1div".into(),
+        ..Default::default()
+    });
+    assert!(DomNode::to_nodes::<rstml::Infallible>(&[element]).is_err());
+    Ok(())
+}
+
+#[cfg(feature = "html-import")]
+#[test]
+fn test_html_import() -> Result<()> {
+    use rstml::{
+        dom::DomNode,
+        import::{html_to_nodes, html_to_tokens, parse_html},
+    };
+
+    let html = r#"<!doctype html>
+        <!-- main -->
+        <DIV Class='card' hidden>
+            <p>Hello <b>world</b>!<p>Second
+            <img src=a.png alt="A &lt; B"><br>
+            <svg viewBox="0 0 1 1"></svg>
+            <script>if (a < b) {}</script>
+        </span></div>"#;
+    let tokens = html_to_tokens(html)?;
+    assert_eq!(
+        tokens.to_string(),
+        concat!(
+            r#"< ! DOCTYPE html > < ! - - "main" - - > "#,
+            r#"< div class = "card" hidden > "#,
+            r#"< p > "Hello " < b > "world" < / b > "!" < / p > "#,
+            r#"< p > "Second" < img src = "a.png" alt = "A < B" / > < br / > "#,
+            r#"< svg viewBox = "0 0 1 1" / > "#,
+            r#"< script > "if (a < b) {}" < / script > < / p > < / div >"#
+        )
+    );
+
+    // Unclosed tags and stray `<` are accepted.
+    let dom = parse_html("a < b <i>c");
+    assert_eq!(dom.len(), 2);
+    assert_eq!(dom[0], DomNode::Text("a < b ".into()));
+
+    // Abruptly closed comments are empty.
+    let dom = parse_html("<!-->a<!--->b<!---->");
+    assert_eq!(
+        dom,
+        [
+            DomNode::Comment("".into()),
+            DomNode::Text("a".into()),
+            DomNode::Comment("".into()),
+            DomNode::Text("b".into()),
+            DomNode::Comment("".into()),
+        ]
+    );
+
+    // Names, that are not valid in rsx, are reported.
+    let error = html_to_nodes::<rstml::Infallible>("<a @click=go>").unwrap_err();
+    assert!(error.to_string().contains("`@click`"));
+    Ok(())
+}
+
+#[test]
+fn test_directive_comments() -> Result<()> {
+    use rstml::node::Directive;
+
+    let tokens = quote! {
+        <!-- "rstml-ignore" --> <!-- "rstml-hydrate" --> <div></div>
+        <!-- "rstml-raw" --> <code>if a < b {}</code>
+        <!-- "rstml-ignore" --> "text" <p></p>
+        <!-- "note" --> <span/>
+    };
+    let config = ParserConfig::new().directive_prefix("rstml-");
+    let nodes = Parser::new(config).parse_simple(tokens.clone())?;
+    let elements: Vec<_> = nodes
+        .iter()
+        .filter_map(|node| match node {
+            Node::Element(element) => Some(element),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        elements[0].directives,
+        [Directive::Ignore, Directive::Custom("hydrate".into())]
+    );
+    assert_eq!(elements[1].directives, [Directive::Raw]);
+    assert!(matches!(elements[1].children[..], [Node::RawText(_)]));
+    // Directive is applied only to the next node.
+    assert!(elements[2].directives.is_empty());
+    assert!(elements[3].directives.is_empty());
+
+    // Directive that is the last child is not applied to the next sibling of
+    // its parent.
+    let last_child = quote! {
+        <div><!-- "rstml-raw" --></div><pre>a b</pre>
+    };
+    let config = ParserConfig::new().directive_prefix("rstml-");
+    let nodes = Parser::new(config).parse_simple(last_child)?;
+    assert!(get_element(&nodes, 1).directives.is_empty());
+
+    // Without prefix, directive comments are plain comments.
+    let nodes = Parser::new(ParserConfig::new()).parse_recoverable(tokens);
+    let (nodes, _) = nodes.split_vec();
+    let Node::Element(div) = &nodes[2] else {
+        panic!("expected element")
+    };
+    assert!(div.directives.is_empty());
+    Ok(())
+}
+
+#[cfg(feature = "html-validate")]
+#[test]
+fn test_directive_ignore_validation() -> Result<()> {
+    let tokens = quote! {
+        <!-- "rstml-ignore" --> <blink><marquee/></blink>
+        <blink/>
+    };
+    let nodes = Parser::new(ParserConfig::new().directive_prefix("rstml-")).parse_simple(tokens)?;
+    assert_eq!(rstml::validate::validate(&nodes).len(), 1);
+    Ok(())
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_tracing_events() -> Result<()> {
+    use std::sync::{Arc, Mutex};
+
+    use tracing::{
+        field::{Field, Visit},
+        span, Event, Metadata, Subscriber,
+    };
+
+    /// Collects messages of events, with their fields.
+    #[derive(Default)]
+    struct Collector(Arc<Mutex<Vec<String>>>);
+
+    struct Message(String);
+
+    impl Visit for Message {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            match field.name() {
+                "message" => self.0.insert_str(0, &format!("{value:?}")),
+                name => self.0.push_str(&format!(" {name}={value:?}")),
+            }
+        }
+    }
+
+    impl Subscriber for Collector {
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            metadata.target() == "rstml"
+        }
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+        fn event(&self, event: &Event<'_>) {
+            let mut message = Message(String::new());
+            event.record(&mut message);
+            self.0.lock().unwrap().push(message.0);
+        }
+        fn enter(&self, _: &span::Id) {}
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    let collector = Collector::default();
+    let events = collector.0.clone();
+    tracing::subscriber::with_default(collector, || {
+        Parser::new(ParserConfig::new()).parse_recoverable(quote! { <div id="a">"x"</span> })
+    });
+    let events = events.lock().unwrap();
+    let events: Vec<_> = events.iter().map(String::as_str).collect();
+    assert_eq!(
+        events[..5],
+        [
+            "parse started",
+            "attribute key=id",
+            "open tag name=\"div\" attributes=1 self_closed=false namespace=Html",
+            "node type=NodeType::Text",
+            "close tag name=span",
+        ]
+    );
+    assert!(events[5].starts_with("diagnostic reported"));
+    assert_eq!(events.last(), Some(&"parse finished nodes=1 diagnostics=1"));
+    Ok(())
+}
+
+#[cfg(feature = "codegen")]
+#[test]
+fn test_codegen_custom_backend() -> Result<()> {
+    use rstml::codegen::{nodes_to_exprs, Backend};
+    use syn::{parse_quote, Expr};
+
+    // Components are created with their own builder, and fragments are vectors.
+    struct Dsl;
+    impl Backend for Dsl {
+        fn element_new(&self, element: &NodeElement<rstml::Infallible>) -> Expr {
+            match element.name() {
+                NodeName::Path(path) if element.is_component() => parse_quote!(#path::builder()),
+                name => {
+                    let name = name.to_string();
+                    parse_quote!(html::el(#name))
+                }
+            }
+        }
+        fn fragment(&self, children: Vec<Expr>) -> Expr {
+            parse_quote!(vec![#(#children),*])
+        }
+    }
+
+    let nodes = parse2(quote! {
+        <!-- "skipped" -->
+        <><Button {..props} bind:value(x)/><p>"a"</p></>
+    })?;
+    let exprs = nodes_to_exprs(&Dsl, &nodes);
+    assert_eq!(exprs.len(), 1);
+    assert_eq!(
+        exprs[0].to_token_stream().to_string(),
+        quote! {
+            vec![Button::builder().attrs({..props}), html::el("p").child("a")]
+        }
+        .to_string()
+    );
+
+    // Special attributes are generated at their positions.
+    let config = ParserConfig::new().special_attributes(["key"].into());
+    let nodes = Parser::new(config).parse_simple(quote! { <li class="a" key={id} hidden/> })?;
+    assert_eq!(
+        nodes_to_exprs(&Dsl, &nodes)[0]
+            .to_token_stream()
+            .to_string(),
+        quote! {
+            html::el("li").attr("class", "a").attr("key", {id}).attr("hidden", true)
+        }
+        .to_string()
+    );
+    Ok(())
+}
+
+#[cfg(feature = "analysis")]
+#[test]
+fn test_referenced_idents() -> Result<()> {
+    use rstml::analysis::referenced_idents;
+
+    let config = ParserConfig::new().special_attributes(["key"].into());
+    let nodes = Parser::new(config).parse_simple(quote! {
+        <div key={id} class=format!("{}-{}", prefix, size) {..props}>
+            {
+                let total = count * 2;
+                match total { 0 => None, n => Some(n + offset) }
+            }
+            {items.iter().map(|item| item.len()).sum::<usize>()}
+            {count}
+        </div>
+    })?;
+    let idents: Vec<_> = referenced_idents(&nodes)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        idents,
+        ["prefix", "size", "props", "id", "count", "offset", "items"]
+    );
+    Ok(())
+}
+
+#[cfg(feature = "analysis")]
+#[test]
+fn test_skeleton() -> Result<()> {
+    use rstml::analysis::{HoleKind, Skeleton};
+
+    let config = ParserConfig::new().special_attributes(["key"].into());
+    let nodes = Parser::new(config).parse_simple(quote! {
+        <ul key={id} hidden=false>
+            <li width=10 {..rest}>"a < b" <>"c"<br/></> {value}</li>
+            <svg><rect/></svg>
+            <Item/>
+            <script>r#"a < b"#</script>
+        </ul>
+    })?;
+    let skeleton = Skeleton::new(&nodes);
+    assert_eq!(
+        skeleton.html,
+        "<ul><li width=\"10\">a &lt; bc<br><!></li><svg><rect/></svg><!><script>a < \
+         b</script></ul>"
+    );
+    let holes: Vec<_> = skeleton
+        .holes
+        .iter()
+        .map(|hole| {
+            let kind = match &hole.kind {
+                HoleKind::Node(node) => node.to_token_stream().to_string(),
+                HoleKind::Attribute(attribute) => attribute.key.to_string(),
+                HoleKind::Spread(block) => block.to_token_stream().to_string(),
+            };
+            (hole.path.clone(), kind)
+        })
+        .collect();
+    assert_eq!(
+        holes,
+        [
+            (vec![0], "key".to_string()),
+            (vec![0, 0], "{ .. rest }".to_string()),
+            (vec![0, 0, 2], "{ value }".to_string()),
+            (vec![0, 2], "< Item / >".to_string()),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_events() -> Result<()> {
+    use rstml::events::{events, Event};
+
+    let tokens = TokenStream::from_str(r#"<ul a=1 b><>"x"<li/></></ul>{y}"#).unwrap();
+    let nodes = parse2(tokens)?;
+    let events = events(&nodes);
+    let kinds: Vec<_> = events
+        .iter()
+        .map(|event| match event {
+            Event::ElementStart(e) => format!("<{}", e.name()),
+            Event::Attribute(a) => a.to_token_stream().to_string(),
+            Event::AttributesEnd(_) => ">".into(),
+            Event::ElementEnd(e) => format!("/{}", e.name()),
+            Event::Text(t) => t.value_string(),
+            Event::Block(_) => "{}".into(),
+            event => panic!("unexpected {event:?}"),
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        ["<ul", "a = 1", "b", ">", "x", "<li", ">", "/li", "/ul", "{}"]
+    );
+    let columns: Vec<_> = events
+        .iter()
+        .map(|event| event.span().start().column)
+        .collect();
+    assert_eq!(columns, [0, 4, 8, 9, 12, 15, 18, 18, 23, 28]);
+
+    Ok(())
+}